@@ -1,7 +1,8 @@
+use crate::cnf::NORMAL_FETCH_SIZE;
 use crate::err::Error;
 use crate::key::change;
 use crate::key::debug::Sprintable;
-use crate::kvs::Transaction;
+use crate::kvs::{KeyDecode, Transaction};
 use crate::vs::VersionStamp;
 use std::str;
 
@@ -84,3 +85,54 @@ pub async fn gc_range(tx: &Transaction, ns: &str, db: &str, vt: VersionStamp) ->
 	// Ok all good
 	Ok(())
 }
+
+// gc_tb deletes the change feed entries of the given table that become stale at the given timestamp.
+#[instrument(level = "trace", target = "surrealdb::core::cfs", skip(tx))]
+pub async fn gc_tb(tx: &Transaction, ts: u64, ns: &str, db: &str, tb: &str) -> Result<(), Error> {
+	// Fetch the database and table definitions
+	let db_def = tx.get_db(ns, db).await?;
+	let tb_def = tx.get_tb(ns, db, tb).await?;
+	// Get the database changefeed expiration
+	let db_cf_expiry = db_def.changefeed.map(|v| v.expiry.as_secs()).unwrap_or_default();
+	// Get the table changefeed expiration
+	let tb_cf_expiry = tb_def.changefeed.map(|v| v.expiry.as_secs()).unwrap_or_default();
+	// The entries are shared with the database changefeed, so keep the longer expiration
+	let cf_expiry = db_cf_expiry.max(tb_cf_expiry);
+	// Ignore this table if the expiry is greater
+	if ts < cf_expiry {
+		return Ok(());
+	}
+	// Calculate the watermark expiry window
+	let watermark_ts = ts - cf_expiry;
+	// Calculate the watermark versionstamp
+	let watermark_vs =
+		tx.lock().await.get_versionstamp_from_timestamp(watermark_ts, ns, db).await?;
+	// If a versionstamp exists, then garbage collect
+	let Some(watermark_vs) = watermark_vs else {
+		return Ok(());
+	};
+	// Calculate the range
+	let beg = change::prefix_ts(ns, db, VersionStamp::ZERO)?;
+	let end = change::prefix_ts(ns, db, watermark_vs)?;
+	// Trace for debugging
+	trace!(
+		"Performing garbage collection on {ns}:{db}:{tb} for watermark {watermark_vs:?}, between {} and {}",
+		beg.sprint(),
+		end.sprint()
+	);
+	// The entries of all tables are interleaved, so only delete the entries of this table
+	let mut next = Some(beg..end);
+	while let Some(rng) = next {
+		let res = tx.batch_keys(rng, *NORMAL_FETCH_SIZE, None).await?;
+		next = res.next;
+		for k in res.result.into_iter() {
+			if change::Cf::decode(&k)?.tb == tb {
+				tx.del(k).await?;
+			}
+		}
+		// Yield execution
+		yield_now!();
+	}
+	// Ok all good
+	Ok(())
+}
@@ -14,6 +14,9 @@ pub const ID_CHARS: [char; 36] = [
 /// Specifies the names of parameters which can not be specified in a query
 pub const PROTECTED_PARAM_NAMES: &[&str] = &["access", "auth", "token", "session"];
 
+/// The placeholder which is returned in place of a sensitive field or a secret value
+pub const REDACTED: &str = "[REDACTED]";
+
 /// The memory usage threshold before tasks are forced to exit (default: 0 bytes)
 pub static MEMORY_THRESHOLD: LazyLock<usize> =
 	lazy_env_parse!(bytes, "SURREAL_MEMORY_THRESHOLD", usize, 0);
@@ -27,6 +30,13 @@ pub static MAX_CONCURRENT_TASKS: LazyLock<usize> =
 pub static MAX_COMPUTATION_DEPTH: LazyLock<u32> =
 	lazy_env_parse!("SURREAL_MAX_COMPUTATION_DEPTH", u32, 120);
 
+/// Specifies how many levels of nested records will be fetched before returning record ids (default: 10)
+pub static MAX_FETCH_DEPTH: LazyLock<usize> = lazy_env_parse!("SURREAL_MAX_FETCH_DEPTH", usize, 10);
+
+/// The maximum number of queries which can be kept in the query log of each connection (default: 1000)
+pub static MAX_QUERY_LOG_SIZE: LazyLock<usize> =
+	lazy_env_parse!("SURREAL_MAX_QUERY_LOG_SIZE", usize, 1000);
+
 /// Specifies how deep the parser will parse nested objects and arrays (default: 100)
 pub static MAX_OBJECT_PARSING_DEPTH: LazyLock<u32> =
 	lazy_env_parse!("SURREAL_MAX_OBJECT_PARSING_DEPTH", u32, 100);
@@ -75,6 +85,21 @@ pub static INDEXING_BATCH_SIZE: LazyLock<u32> =
 pub static MAX_ORDER_LIMIT_PRIORITY_QUEUE_SIZE: LazyLock<u32> =
 	lazy_env_parse!("SURREAL_MAX_ORDER_LIMIT_PRIORITY_QUEUE_SIZE", u32, 1000);
 
+/// The maximum number of changes which are replayed when starting a live query (default: 1000)
+pub static MAX_LIVE_REPLAY_CHANGES: LazyLock<usize> =
+	lazy_env_parse!("SURREAL_MAX_LIVE_REPLAY_CHANGES", usize, 1000);
+
+/// The maximum number of records a conditional count can scan when its condition can not use an index (default: 10000)
+pub static COUNT_SCAN_LIMIT: LazyLock<usize> =
+	lazy_env_parse!("SURREAL_COUNT_SCAN_LIMIT", usize, 10000);
+
+/// How long a read waits for the database to reach a requested minimum versionstamp, in milliseconds (default: 1000)
+pub static MIN_VERSION_TIMEOUT: LazyLock<u64> =
+	lazy_env_parse!("SURREAL_MIN_VERSION_TIMEOUT", u64, 1000);
+
+/// How far back in time, in seconds, a query can read with the `as_of` option, where 0 allows all retained history (default: 0)
+pub static AS_OF_RETENTION: LazyLock<u64> = lazy_env_parse!("SURREAL_AS_OF_RETENTION", u64, 0);
+
 /// The maximum stack size of the JavaScript function runtime (default: 256 KiB)
 pub static SCRIPTING_MAX_STACK_SIZE: LazyLock<usize> =
 	lazy_env_parse!("SURREAL_SCRIPTING_MAX_STACK_SIZE", usize, 256 * 1024);
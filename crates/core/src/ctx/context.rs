@@ -18,6 +18,9 @@ use crate::kvs::Transaction;
 use crate::mem::ALLOC;
 use crate::sql::value::Value;
 use async_channel::Sender;
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::{self, Debug};
@@ -70,6 +73,10 @@ pub struct MutableContext {
 	isolated: bool,
 	// A map of bucket connections
 	buckets: Option<Arc<BucketConnections>>,
+	// An optional seeded random number generator
+	rng: Option<Arc<Mutex<StdRng>>>,
+	// An optional limit on the depth of recursive idioms
+	max_depth: Option<u32>,
 }
 
 impl Default for MutableContext {
@@ -120,6 +127,8 @@ impl MutableContext {
 			transaction: None,
 			isolated: false,
 			buckets: None,
+			rng: None,
+			max_depth: None,
 		}
 	}
 
@@ -145,6 +154,8 @@ impl MutableContext {
 			isolated: false,
 			parent: Some(parent.clone()),
 			buckets: parent.buckets.clone(),
+			rng: parent.rng.clone(),
+			max_depth: parent.max_depth,
 		}
 	}
 
@@ -172,6 +183,8 @@ impl MutableContext {
 			isolated: true,
 			parent: Some(parent.clone()),
 			buckets: parent.buckets.clone(),
+			rng: parent.rng.clone(),
+			max_depth: parent.max_depth,
 		}
 	}
 
@@ -199,6 +212,8 @@ impl MutableContext {
 			isolated: false,
 			parent: None,
 			buckets: from.buckets.clone(),
+			rng: from.rng.clone(),
+			max_depth: from.max_depth,
 		}
 	}
 
@@ -234,6 +249,8 @@ impl MutableContext {
 			transaction: None,
 			isolated: false,
 			buckets: Some(buckets),
+			rng: None,
+			max_depth: None,
 		};
 		if let Some(timeout) = time_out {
 			ctx.add_timeout(timeout)?;
@@ -457,6 +474,34 @@ impl MutableContext {
 		)
 	}
 
+	//
+	// Random number generation
+	//
+
+	/// Seed the random number generator for this context
+	pub(crate) fn add_seed(&mut self, seed: u64) {
+		self.rng = Some(Arc::new(Mutex::new(StdRng::seed_from_u64(seed))));
+	}
+
+	/// Get the seeded random number generator, if any
+	pub(crate) fn get_rng(&self) -> Option<Arc<Mutex<StdRng>>> {
+		self.rng.clone()
+	}
+
+	//
+	// Recursion depth
+	//
+
+	/// Limit the depth of recursive idioms for this context
+	pub(crate) fn set_max_depth(&mut self, depth: u32) {
+		self.max_depth = Some(depth);
+	}
+
+	/// Get the limit on the depth of recursive idioms, if any
+	pub(crate) fn max_depth(&self) -> Option<u32> {
+		self.max_depth
+	}
+
 	//
 	// Capabilities
 	//
@@ -493,6 +493,25 @@ impl<T: Hash + Eq + PartialEq + fmt::Debug + fmt::Display> Targets<T> {
 	}
 }
 
+impl<T: Hash + Eq + PartialEq + Clone> Targets<T> {
+	/// Returns the targets which are contained in both sets of targets
+	pub(crate) fn intersection(&self, other: &Self) -> Self {
+		match (self, other) {
+			(Self::None, _) | (_, Self::None) => Self::None,
+			(Self::All, v) | (v, Self::All) => v.clone(),
+			(Self::Some(a), Self::Some(b)) => Self::Some(a.intersection(b).cloned().collect()),
+		}
+	}
+	/// Returns the targets which are contained in either set of targets
+	pub(crate) fn union(&self, other: &Self) -> Self {
+		match (self, other) {
+			(Self::All, _) | (_, Self::All) => Self::All,
+			(Self::None, v) | (v, Self::None) => v.clone(),
+			(Self::Some(a), Self::Some(b)) => Self::Some(a.union(b).cloned().collect()),
+		}
+	}
+}
+
 impl<T: Target + Hash + Eq + PartialEq + fmt::Display> fmt::Display for Targets<T> {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
@@ -507,7 +526,7 @@ impl<T: Target + Hash + Eq + PartialEq + fmt::Display> fmt::Display for Targets<
 	}
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub struct Capabilities {
 	scripting: bool,
@@ -604,6 +623,32 @@ impl Capabilities {
 		}
 	}
 
+	/// Narrows these capabilities by another set of capabilities. The
+	/// resulting capabilities are never more permissive than either.
+	pub fn narrow(&self, other: &Capabilities) -> Self {
+		Self {
+			scripting: self.scripting && other.scripting,
+			guest_access: self.guest_access && other.guest_access,
+			live_query_notifications: self.live_query_notifications
+				&& other.live_query_notifications,
+
+			allow_funcs: self.allow_funcs.intersection(&other.allow_funcs),
+			deny_funcs: self.deny_funcs.union(&other.deny_funcs),
+			allow_net: self.allow_net.intersection(&other.allow_net),
+			deny_net: self.deny_net.union(&other.deny_net),
+			allow_rpc: self.allow_rpc.intersection(&other.allow_rpc),
+			deny_rpc: self.deny_rpc.union(&other.deny_rpc),
+			allow_http: self.allow_http.intersection(&other.allow_http),
+			deny_http: self.deny_http.union(&other.deny_http),
+			allow_experimental: self.allow_experimental.intersection(&other.allow_experimental),
+			deny_experimental: self.deny_experimental.union(&other.deny_experimental),
+			allow_arbitrary_query: self
+				.allow_arbitrary_query
+				.intersection(&other.allow_arbitrary_query),
+			deny_arbitrary_query: self.deny_arbitrary_query.union(&other.deny_arbitrary_query),
+		}
+	}
+
 	pub fn with_scripting(mut self, scripting: bool) -> Self {
 		self.scripting = scripting;
 		self
@@ -1224,4 +1269,32 @@ mod tests {
 			assert!(!caps.allows_query(&ArbitraryQueryTarget::from_str("system").unwrap()));
 		}
 	}
+
+	#[test]
+	fn test_narrow_capabilities() {
+		let caps = Capabilities::default()
+			.with_scripting(true)
+			.with_network_targets(Targets::<NetTarget>::All)
+			.without_functions(Targets::<FuncTarget>::Some(
+				[FuncTarget::from_str("crypto::*").unwrap()].into(),
+			));
+		// Narrowing can only ever remove capabilities
+		let narrowed = caps.narrow(
+			&Capabilities::all()
+				.with_scripting(false)
+				.with_network_targets(Targets::<NetTarget>::None)
+				.with_experimental(Targets::<ExperimentalTarget>::All)
+				.without_functions(Targets::<FuncTarget>::Some(
+					[FuncTarget::from_str("http::*").unwrap()].into(),
+				)),
+		);
+		assert!(!narrowed.allows_scripting());
+		assert!(!narrowed.allows_network_target(&NetTarget::from_str("example.com").unwrap()));
+		assert!(!narrowed.allows_function_name("crypto::md5"));
+		assert!(!narrowed.allows_function_name("http::get"));
+		assert!(narrowed.allows_function_name("string::len"));
+		// Narrowing can never add capabilities
+		let narrowed = Capabilities::none().narrow(&Capabilities::all());
+		assert_eq!(narrowed, Capabilities::none());
+	}
 }
@@ -1,7 +1,9 @@
 use crate::ctx::reason::Reason;
 use crate::ctx::Context;
+use crate::ctx::MutableContext;
 use crate::dbs::response::Response;
 use crate::dbs::Force;
+use crate::dbs::Notification;
 use crate::dbs::Options;
 use crate::dbs::QueryType;
 use crate::err::Error;
@@ -19,8 +21,10 @@ use crate::sql::value::Value;
 use crate::sql::Base;
 use crate::sql::ControlFlow;
 use crate::sql::FlowResult;
+use async_channel::{Receiver, Sender};
 use futures::{Stream, StreamExt};
 use reblessive::TreeStack;
+use std::collections::BTreeMap;
 use std::pin::{pin, Pin};
 use std::sync::Arc;
 use std::time::Duration;
@@ -38,6 +42,8 @@ pub struct Executor {
 	results: Vec<Response>,
 	opt: Options,
 	ctx: Context,
+	fail_fast: bool,
+	responses: Option<Sender<Response>>,
 }
 
 impl Executor {
@@ -47,6 +53,20 @@ impl Executor {
 			results: Vec::new(),
 			opt,
 			ctx,
+			fail_fast: false,
+			responses: None,
+		}
+	}
+
+	/// Sends the responses which are complete, if they are being streamed
+	async fn flush(&mut self) {
+		if let Some(chn) = &self.responses {
+			for res in self.results.drain(..) {
+				// The receiver may have stopped listening
+				if chn.send(res).await.is_err() {
+					break;
+				}
+			}
 		}
 	}
 
@@ -264,6 +284,7 @@ impl Executor {
 					time: Duration::ZERO,
 					result: Err(Error::QueryNotExecuted),
 					query_type: QueryType::Other,
+					statement: Some(stmt.kind()),
 				});
 			}
 
@@ -321,6 +342,7 @@ impl Executor {
 							Reason::Canceled => Error::QueryCancelled,
 						}),
 						query_type: QueryType::Other,
+						statement: Some(stmt.kind()),
 					});
 				}
 
@@ -339,6 +361,7 @@ impl Executor {
 				Statement::Kill(_) => QueryType::Kill,
 				_ => QueryType::Other,
 			};
+			let statement = stmt.kind();
 
 			let before = Instant::now();
 			let value = match stmt {
@@ -359,6 +382,7 @@ impl Executor {
 									.to_string(),
 						}),
 						query_type: QueryType::Other,
+						statement: Some(statement),
 					});
 
 					self.opt.sender = None;
@@ -374,6 +398,7 @@ impl Executor {
 							time: Duration::ZERO,
 							result: Err(Error::QueryNotExecuted),
 							query_type: QueryType::Other,
+							statement: Some(stmt.kind()),
 						});
 					}
 
@@ -467,6 +492,7 @@ impl Executor {
 								time: before.elapsed(),
 								result: Err(*e),
 								query_type,
+								statement: Some(statement),
 							});
 
 							let _ = txn.cancel().await;
@@ -484,6 +510,7 @@ impl Executor {
 									time: Duration::ZERO,
 									result: Err(Error::QueryNotExecuted),
 									query_type: QueryType::Other,
+									statement: Some(stmt.kind()),
 								});
 							}
 
@@ -507,6 +534,7 @@ impl Executor {
 				time: before.elapsed(),
 				result: value,
 				query_type,
+				statement: Some(statement),
 			});
 		}
 
@@ -547,58 +575,435 @@ impl Executor {
 	where
 		S: Stream<Item = Result<Statement, Error>>,
 	{
+		Executor::new(ctx, opt).execute_statements(kvs, stream).await
+	}
+
+	/// Executes all statements, but stops executing any further statements
+	/// once a statement has failed, so that the remaining statements are
+	/// returned as not executed instead.
+	#[instrument(level = "debug", name = "executor", target = "surrealdb::core::dbs", skip_all)]
+	pub async fn execute_fail_fast(
+		kvs: &Datastore,
+		ctx: Context,
+		opt: Options,
+		qry: Query,
+	) -> Result<Vec<Response>, Error> {
+		let stream = futures::stream::iter(qry.into_iter().map(Ok));
+		let mut this = Executor::new(ctx, opt);
+		this.fail_fast = true;
+		this.execute_statements(kvs, stream).await
+	}
+
+	/// Executes all statements, sending the response of each statement
+	/// to the channel as soon as it completes, instead of returning them.
+	/// The responses of a transaction are sent once the transaction ends.
+	#[instrument(level = "debug", name = "executor", target = "surrealdb::core::dbs", skip_all)]
+	pub async fn execute_streamed(
+		kvs: &Datastore,
+		ctx: Context,
+		opt: Options,
+		qry: Query,
+		fail_fast: bool,
+		chn: Sender<Response>,
+	) -> Result<(), Error> {
+		let stream = futures::stream::iter(qry.into_iter().map(Ok));
 		let mut this = Executor::new(ctx, opt);
+		this.fail_fast = fail_fast;
+		this.responses = Some(chn.clone());
+		for res in this.execute_statements(kvs, stream).await? {
+			if chn.send(res).await.is_err() {
+				break;
+			}
+		}
+		Ok(())
+	}
+
+	async fn execute_statements<S>(
+		mut self,
+		kvs: &Datastore,
+		stream: S,
+	) -> Result<Vec<Response>, Error>
+	where
+		S: Stream<Item = Result<Statement, Error>>,
+	{
 		let mut stream = pin!(stream);
+		// Whether a statement has failed in fail-fast mode
+		let mut failed = false;
+		// Whether a skipped statement is within a transaction block
+		let mut skipped_block = false;
 
 		while let Some(stmt) = stream.next().await {
 			yield_now!();
 			let stmt = match stmt {
 				Ok(x) => x,
 				Err(e) => {
-					this.results.push(Response {
+					self.results.push(Response {
 						time: Duration::ZERO,
 						result: Err(e),
 						query_type: QueryType::Other,
+						statement: None,
 					});
 
-					return Ok(this.results);
+					return Ok(self.results);
 				}
 			};
 
+			// Skip all remaining statements once a statement has failed
+			if failed {
+				match stmt {
+					Statement::Option(_) => {}
+					Statement::Begin(_) => skipped_block = true,
+					Statement::Cancel(_) | Statement::Commit(_) if skipped_block => {
+						skipped_block = false
+					}
+					stmt => self.results.push(Response {
+						time: Duration::ZERO,
+						result: Err(Error::QueryNotExecutedFailFast),
+						query_type: (&stmt).into(),
+						statement: Some(stmt.kind()),
+					}),
+				}
+				continue;
+			}
+
+			let start = self.results.len();
+			let statement = stmt.kind();
+
 			match stmt {
-				Statement::Option(stmt) => this.execute_option_statement(stmt)?,
+				Statement::Option(stmt) => self.execute_option_statement(stmt)?,
 				// handle option here because it doesn't produce a result.
 				Statement::Begin(_) => {
-					if let Err(e) = this.execute_begin_statement(kvs, stream.as_mut()).await {
-						this.results.push(Response {
+					if let Err(e) = self.execute_begin_statement(kvs, stream.as_mut()).await {
+						self.results.push(Response {
 							time: Duration::ZERO,
 							result: Err(e),
 							query_type: QueryType::Other,
+							statement: Some(statement),
 						});
 
-						return Ok(this.results);
+						return Ok(self.results);
 					}
 				}
 				stmt => {
 					let query_type: QueryType = (&stmt).into();
 
 					let now = Instant::now();
-					let result = this.execute_bare_statement(kvs, stmt).await;
-					this.results.push(Response {
+					let result = self.execute_bare_statement(kvs, stmt).await;
+					self.results.push(Response {
 						time: now.elapsed(),
 						result,
 						query_type,
+						statement: Some(statement),
+					});
+				}
+			}
+
+			failed = self.fail_fast && self.results[start..].iter().any(|r| r.result.is_err());
+			// Send the responses of this statement
+			self.flush().await;
+		}
+		Ok(self.results)
+	}
+
+	/// Executes all statements within a single read-only transaction,
+	/// so that every statement sees the same consistent snapshot.
+	#[instrument(level = "debug", name = "executor", target = "surrealdb::core::dbs", skip_all)]
+	pub async fn execute_snapshot(
+		kvs: &Datastore,
+		ctx: Context,
+		opt: Options,
+		qry: Query,
+	) -> Result<Vec<Response>, Error> {
+		// Reject any statement which could write, or manage transactions
+		if qry.iter().any(|stmt| {
+			stmt.writeable()
+				|| matches!(stmt, Statement::Begin(_) | Statement::Cancel(_) | Statement::Commit(_))
+		}) {
+			return Err(Error::TxReadonly);
+		}
+
+		let mut this = Executor::new(ctx, opt);
+		let txn = Arc::new(kvs.transaction(TransactionType::Read, LockType::Optimistic).await?);
+
+		let res: Result<(), Error> = async {
+			for stmt in qry {
+				yield_now!();
+				let stmt = match stmt {
+					Statement::Option(stmt) => {
+						this.execute_option_statement(stmt)?;
+						continue;
+					}
+					stmt => stmt,
+				};
+				let query_type: QueryType = (&stmt).into();
+				let statement = stmt.kind();
+				let now = Instant::now();
+				let result = this.execute_shared_statement(&txn, stmt).await?;
+				this.results.push(Response {
+					time: now.elapsed(),
+					result,
+					query_type,
+					statement: Some(statement),
+				});
+			}
+			Ok(())
+		}
+		.await;
+
+		// A read-only transaction has nothing to commit
+		let _ = txn.cancel().await;
+
+		res?;
+
+		Ok(this.results)
+	}
+
+	/// Executes all statements within a single write transaction, stopping at the
+	/// first failed statement. The transaction is committed only if every statement
+	/// succeeded, and is always cancelled in a dry run.
+	#[instrument(level = "debug", name = "executor", target = "surrealdb::core::dbs", skip_all)]
+	pub async fn execute_migration(
+		kvs: &Datastore,
+		ctx: Context,
+		opt: Options,
+		qry: Query,
+		dry_run: bool,
+	) -> Result<Vec<Response>, Error> {
+		// Reject any statement which manages transactions
+		if qry.iter().any(|stmt| {
+			matches!(stmt, Statement::Begin(_) | Statement::Cancel(_) | Statement::Commit(_))
+		}) {
+			return Err(Error::QueryNotExecutedDetail {
+				message: "Transaction statements are not allowed in a migration".to_string(),
+			});
+		}
+
+		let mut this = Executor::new(ctx, opt);
+		let txn = Arc::new(kvs.transaction(TransactionType::Write, LockType::Optimistic).await?);
+
+		// Create a sender for this transaction only if the context allows for notifications.
+		let receiver = this.ctx.has_notifications().then(|| {
+			let (send, recv) = async_channel::unbounded();
+			this.opt.sender = Some(send);
+			recv
+		});
+
+		let mut failed = false;
+		let res: Result<(), Error> = async {
+			for stmt in qry {
+				yield_now!();
+				let stmt = match stmt {
+					Statement::Option(stmt) => {
+						this.execute_option_statement(stmt)?;
+						continue;
+					}
+					stmt => stmt,
+				};
+				let query_type: QueryType = (&stmt).into();
+				let statement = stmt.kind();
+				// Statements after a failed statement are not executed
+				if failed {
+					this.results.push(Response {
+						time: Duration::ZERO,
+						result: Err(Error::QueryNotExecuted),
+						query_type,
+						statement: Some(statement),
 					});
+					continue;
 				}
+				let now = Instant::now();
+				let result = this.execute_shared_statement(&txn, stmt).await?;
+				failed = result.is_err();
+				this.results.push(Response {
+					time: now.elapsed(),
+					result,
+					query_type,
+					statement: Some(statement),
+				});
 			}
+			Ok(())
+		}
+		.await;
+
+		// The executor itself failed, so cancel the transaction
+		if let Err(e) = res {
+			let _ = txn.cancel().await;
+			return Err(e);
 		}
+
+		this.opt.sender = None;
+
+		// Roll back everything if a statement failed, or if this is a dry run
+		if failed || dry_run {
+			let _ = txn.cancel().await;
+			return Ok(this.results);
+		}
+
+		// failed to commit
+		if let Err(e) = this.commit_shared_transaction(&txn, receiver).await {
+			for res in &mut this.results {
+				res.query_type = QueryType::Other;
+				res.result = Err(Error::QueryNotExecutedDetail {
+					message: e.to_string(),
+				});
+			}
+		}
+
 		Ok(this.results)
 	}
+
+	/// Executes every statement once for each set of variables, within a single write
+	/// transaction. The responses of each set are returned up to the first failed set,
+	/// and the transaction is committed only if every statement succeeded.
+	#[instrument(level = "debug", name = "executor", target = "surrealdb::core::dbs", skip_all)]
+	pub async fn execute_batch(
+		kvs: &Datastore,
+		ctx: Context,
+		opt: Options,
+		qry: Query,
+		sets: Vec<BTreeMap<String, Value>>,
+	) -> Result<Vec<Vec<Response>>, Error> {
+		// Reject any statement which manages transactions
+		if qry.iter().any(|stmt| {
+			matches!(stmt, Statement::Begin(_) | Statement::Cancel(_) | Statement::Commit(_))
+		}) {
+			return Err(Error::QueryNotExecutedDetail {
+				message: "Transaction statements are not allowed in a batch".to_string(),
+			});
+		}
+
+		let mut this = Executor::new(ctx, opt);
+		let txn = Arc::new(kvs.transaction(TransactionType::Write, LockType::Optimistic).await?);
+
+		// Create a sender for this transaction only if the context allows for notifications.
+		let receiver = this.ctx.has_notifications().then(|| {
+			let (send, recv) = async_channel::unbounded();
+			this.opt.sender = Some(send);
+			recv
+		});
+
+		// Each set starts from the same context and options, so that nothing
+		// which was bound or changed by a previous set leaks into the next set
+		let (ctx, opt) = (this.ctx.clone(), this.opt.clone());
+
+		let mut out = Vec::with_capacity(sets.len());
+		let mut failed = false;
+		let res: Result<(), Error> = async {
+			for vars in sets {
+				// Store the variables of this set in a fresh child context
+				let mut child = MutableContext::new(&ctx);
+				child.add_values(vars);
+				this.ctx = child.freeze();
+				this.opt = opt.clone();
+				for stmt in qry.iter().cloned() {
+					yield_now!();
+					let stmt = match stmt {
+						Statement::Option(stmt) => {
+							this.execute_option_statement(stmt)?;
+							continue;
+						}
+						stmt => stmt,
+					};
+					let query_type: QueryType = (&stmt).into();
+					let statement = stmt.kind();
+					let now = Instant::now();
+					let result = this.execute_shared_statement(&txn, stmt).await?;
+					failed = result.is_err();
+					this.results.push(Response {
+						time: now.elapsed(),
+						result,
+						query_type,
+						statement: Some(statement),
+					});
+					if failed {
+						break;
+					}
+				}
+				out.push(std::mem::take(&mut this.results));
+				if failed {
+					break;
+				}
+			}
+			Ok(())
+		}
+		.await;
+
+		// The executor itself failed, so cancel the transaction
+		if let Err(e) = res {
+			let _ = txn.cancel().await;
+			return Err(e);
+		}
+
+		this.opt.sender = None;
+
+		// Roll back every set if a statement failed
+		if failed {
+			let _ = txn.cancel().await;
+			return Ok(out);
+		}
+
+		this.commit_shared_transaction(&txn, receiver).await?;
+
+		Ok(out)
+	}
+
+	/// Executes a statement within a transaction which is shared by several statements,
+	/// returning the outcome of the statement, or an error if the executor itself failed.
+	async fn execute_shared_statement(
+		&mut self,
+		txn: &Arc<Transaction>,
+		stmt: Statement,
+	) -> Result<Result<Value, Error>, Error> {
+		Ok(match self.ctx.done(true)? {
+			Some(Reason::Timedout) => Err(Error::QueryTimedout),
+			Some(Reason::Canceled) => Err(Error::QueryCancelled),
+			None => match stmt {
+				Statement::Use(stmt) => self.execute_use_statement(stmt).map(|_| Value::None),
+				stmt => match self.execute_transaction_statement(txn.clone(), stmt).await {
+					Ok(value) | Err(ControlFlow::Return(value)) => Ok(value),
+					Err(ControlFlow::Break) | Err(ControlFlow::Continue) => {
+						Err(Error::InvalidControlFlow)
+					}
+					Err(ControlFlow::Err(e)) => Err(*e),
+				},
+			},
+		})
+	}
+
+	/// Commits a transaction which is shared by several statements, and then sends
+	/// any notifications which were held until the commit.
+	async fn commit_shared_transaction(
+		&mut self,
+		txn: &Arc<Transaction>,
+		receiver: Option<Receiver<Notification>>,
+	) -> Result<(), Error> {
+		let mut lock = txn.lock().await;
+		if let Err(e) = lock.complete_changes(false).await {
+			let _ = lock.cancel().await;
+			return Err(e);
+		}
+		lock.commit().await?;
+		// flush notifications.
+		if let Some(recv) = receiver {
+			if let Some(sink) = self.ctx.notifications() {
+				spawn(async move {
+					while let Ok(x) = recv.recv().await {
+						if sink.send(x).await.is_err() {
+							break;
+						}
+					}
+				});
+			}
+		}
+		Ok(())
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::{dbs::Session, iam::Role, kvs::Datastore};
+	use std::collections::BTreeMap;
+
+	use crate::{dbs::Session, iam::Role, kvs::Datastore, sql::Value};
 
 	#[tokio::test]
 	async fn check_execute_option_permissions() {
@@ -708,4 +1113,129 @@ mod tests {
 			);
 		}
 	}
+
+	#[tokio::test]
+	async fn check_execute_snapshot() {
+		let ds = Datastore::new("memory").await.unwrap();
+		let ses = Session::owner().with_ns("NS").with_db("DB");
+		ds.execute("CREATE person:1; CREATE person:2", &ses, None).await.unwrap();
+		// All statements read from the same snapshot
+		let qry = crate::syn::parse("SELECT * FROM person; SELECT count() FROM person GROUP ALL")
+			.unwrap();
+		let res = ds.process_snapshot(qry, &ses, None).await.unwrap();
+		assert_eq!(res.len(), 2);
+		assert_eq!(
+			res[0].result.as_ref().unwrap().to_string(),
+			"[{ id: person:1 }, { id: person:2 }]"
+		);
+		assert_eq!(res[1].result.as_ref().unwrap().to_string(), "[{ count: 2 }]");
+		// Any statement which could write is rejected
+		let qry = crate::syn::parse("SELECT * FROM person; CREATE person:3").unwrap();
+		let res = ds.process_snapshot(qry, &ses, None).await;
+		assert!(matches!(res, Err(crate::err::Error::TxReadonly)), "{res:?}");
+		// Nothing was written
+		let res = ds.execute("SELECT * FROM person:3", &ses, None).await.unwrap();
+		assert_eq!(res[0].result.as_ref().unwrap().to_string(), "[]");
+	}
+
+	#[tokio::test]
+	async fn check_execute_migration() {
+		let ds = Datastore::new("memory").await.unwrap();
+		let ses = Session::owner().with_ns("NS").with_db("DB");
+		let sql = "DEFINE TABLE person SCHEMAFULL; DEFINE FIELD name ON person TYPE string; CREATE person:1 SET name = 'a'";
+		// Nothing is written in a dry run
+		let qry = crate::syn::parse(sql).unwrap();
+		let res = ds.process_migration(qry, &ses, None, true).await.unwrap();
+		assert_eq!(res.len(), 3);
+		assert!(res.iter().all(|res| res.result.is_ok()));
+		let res = ds.execute("INFO FOR DB", &ses, None).await.unwrap();
+		assert!(!res[0].result.as_ref().unwrap().to_string().contains("person"));
+		// Everything is rolled back when a statement fails
+		let qry =
+			crate::syn::parse(&format!("{sql}; CREATE person:2 SET name = 1; CREATE person:3"))
+				.unwrap();
+		let res = ds.process_migration(qry, &ses, None, false).await.unwrap();
+		assert_eq!(res.len(), 5);
+		assert!(res[..3].iter().all(|res| res.result.is_ok()));
+		assert!(res[3].result.is_err());
+		assert!(matches!(res[4].result, Err(crate::err::Error::QueryNotExecuted)));
+		let res = ds.execute("INFO FOR DB", &ses, None).await.unwrap();
+		assert!(!res[0].result.as_ref().unwrap().to_string().contains("person"));
+		// Everything is committed when every statement succeeds
+		let qry = crate::syn::parse(sql).unwrap();
+		let res = ds.process_migration(qry, &ses, None, false).await.unwrap();
+		assert!(res.iter().all(|res| res.result.is_ok()));
+		let res = ds.execute("SELECT VALUE name FROM person", &ses, None).await.unwrap();
+		assert_eq!(res[0].result.as_ref().unwrap().to_string(), "['a']");
+		// Statements which manage transactions are rejected
+		let qry = crate::syn::parse("BEGIN; CREATE person:4; COMMIT").unwrap();
+		let err = ds.process_migration(qry, &ses, None, false).await.unwrap_err();
+		assert!(err.to_string().contains("not allowed in a migration"), "{err}");
+	}
+
+	#[tokio::test]
+	async fn check_execute_batch() {
+		let ds = Datastore::new("memory").await.unwrap();
+		let ses = Session::owner().with_ns("NS").with_db("DB");
+		let sql = "CREATE ONLY type::thing('person', $id) RETURN VALUE id";
+		let set = |id: i64| BTreeMap::from([("id".to_string(), Value::from(id))]);
+		// The statements are executed once for each set of variables
+		let qry = crate::syn::parse(sql).unwrap();
+		let res = ds.process_batch(qry, &ses, None, vec![set(1), set(2)]).await.unwrap();
+		assert_eq!(res.len(), 2);
+		assert_eq!(res[0][0].result.as_ref().unwrap().to_string(), "person:1");
+		assert_eq!(res[1][0].result.as_ref().unwrap().to_string(), "person:2");
+		// Every set is rolled back when a set fails
+		let qry = crate::syn::parse(sql).unwrap();
+		let res = ds.process_batch(qry, &ses, None, vec![set(3), set(1), set(4)]).await.unwrap();
+		assert_eq!(res.len(), 2);
+		assert!(res[1][0].result.is_err());
+		let res = ds.execute("SELECT VALUE id FROM person", &ses, None).await.unwrap();
+		assert_eq!(res[0].result.as_ref().unwrap().to_string(), "[person:1, person:2]");
+		// Nothing bound by a previous set leaks into the next set
+		let sql = "RETURN [$name, $note, $tmp]; LET $tmp = $name";
+		let qry = crate::syn::parse(sql).unwrap();
+		let first = BTreeMap::from([
+			("name".to_string(), Value::from("a")),
+			("note".to_string(), Value::from("x")),
+		]);
+		let second = BTreeMap::from([("name".to_string(), Value::from("b"))]);
+		let res = ds.process_batch(qry, &ses, None, vec![first, second]).await.unwrap();
+		assert_eq!(res[0][0].result.as_ref().unwrap().to_string(), "['a', 'x', NONE]");
+		assert_eq!(res[1][0].result.as_ref().unwrap().to_string(), "['b', NONE, NONE]");
+		// Transaction statements are rejected
+		let qry = crate::syn::parse("BEGIN; CREATE person:5; COMMIT").unwrap();
+		let err = ds.process_batch(qry, &ses, None, vec![set(5)]).await.unwrap_err();
+		assert!(err.to_string().contains("not allowed in a batch"), "{err}");
+	}
+
+	#[tokio::test]
+	async fn check_execute_fail_fast() {
+		let ds = Datastore::new("memory").await.unwrap();
+		let ses = Session::owner().with_ns("NS").with_db("DB");
+		let sql = "CREATE person:1; CREATE person:1; CREATE person:2; BEGIN; CREATE person:3; COMMIT; CREATE person:4";
+		// Statements after a failed statement are not executed
+		let qry = crate::syn::parse(sql).unwrap();
+		let res = ds.process_fail_fast(qry, &ses, None).await.unwrap();
+		assert_eq!(res.len(), 5);
+		assert!(res[0].result.is_ok());
+		assert!(matches!(res[1].result, Err(crate::err::Error::RecordExists { .. })));
+		for res in &res[2..] {
+			assert!(matches!(res.result, Err(crate::err::Error::QueryNotExecutedFailFast)));
+		}
+		let res = ds.execute("SELECT VALUE id FROM person", &ses, None).await.unwrap();
+		assert_eq!(res[0].result.as_ref().unwrap().to_string(), "[person:1]");
+		// Statements after a failed statement are executed by default
+		let qry = crate::syn::parse(sql).unwrap();
+		let res = ds.process(qry, &ses, None).await.unwrap();
+		assert_eq!(res.len(), 5);
+		assert!(res[0].result.is_err());
+		assert!(res[1].result.is_err());
+		assert!(res[2..].iter().all(|res| res.result.is_ok()));
+		let res = ds.execute("SELECT VALUE id FROM person", &ses, None).await.unwrap();
+		assert_eq!(
+			res[0].result.as_ref().unwrap().to_string(),
+			"[person:1, person:2, person:3, person:4]"
+		);
+	}
 }
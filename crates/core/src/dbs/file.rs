@@ -1,6 +1,7 @@
 use crate::cnf::EXTERNAL_SORTING_BUFFER_LIMIT;
 use crate::dbs::plan::Explanation;
 use crate::err::Error;
+use crate::fnc::util::string::collation::Collation;
 use crate::sql::order::Ordering;
 use crate::sql::Value;
 use ext_sort::{ExternalChunk, ExternalSorter, ExternalSorterBuilder, LimitedBufferBuilder};
@@ -10,6 +11,7 @@ use revision::Revisioned;
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Take, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::{fs, io, mem};
 use tempfile::{Builder, TempDir};
 #[cfg(not(target_family = "wasm"))]
@@ -21,6 +23,7 @@ pub(super) struct FileCollector {
 	writer: Option<FileWriter>,
 	reader: Option<FileReader>,
 	orders: Option<Ordering>,
+	collation: Option<Arc<Collation>>,
 	paging: FilePaging,
 }
 
@@ -32,13 +35,14 @@ impl FileCollector {
 
 	const USIZE_SIZE: usize = mem::size_of::<usize>();
 
-	pub(super) fn new(temp_dir: &Path) -> Result<Self, Error> {
+	pub(super) fn new(temp_dir: &Path, collation: Option<Arc<Collation>>) -> Result<Self, Error> {
 		let dir = Builder::new().prefix("SURREAL").tempdir_in(temp_dir)?;
 		Ok(Self {
 			len: 0,
 			writer: Some(FileWriter::new(&dir)?),
 			reader: None,
 			orders: None,
+			collation,
 			paging: Default::default(),
 			dir,
 		})
@@ -146,6 +150,7 @@ impl FileCollector {
 			}
 			Ordering::Order(orders) => {
 				let sort_dir = self.dir.path().join(Self::SORT_DIRECTORY_NAME);
+				let collation = self.collation.clone();
 
 				let f = move || {
 					fs::create_dir(&sort_dir)?;
@@ -163,7 +168,8 @@ impl FileCollector {
 						))
 						.build()?;
 
-					let sorted = sorter.sort_by(reader, |a, b| orders.compare(a, b))?;
+					let sorted = sorter
+						.sort_by(reader, |a, b| orders.compare(a, b, collation.as_deref()))?;
 					let iter = sorted.map(Result::unwrap);
 					let r: Vec<Value> = iter.skip(start as usize).take(num as usize).collect();
 					Ok(r)
@@ -361,13 +361,7 @@ impl Iterator {
 		// Process the query START clause
 		self.setup_start(stk, &cancel_ctx, opt, stm).await?;
 		// Prepare the results with possible optimisations on groups
-		self.results = self.results.prepare(
-			#[cfg(storage)]
-			ctx,
-			stm,
-			self.start,
-			self.limit,
-		)?;
+		self.results = self.results.prepare(ctx, stm, self.start, self.limit)?;
 		// Extract the expected behaviour depending on the presence of EXPLAIN with or without FULL
 		let mut plan = Plan::new(ctx, stm, &self.entries, &self.results);
 		// Check if we actually need to process and iterate over the results
@@ -3,7 +3,7 @@ use crate::dbs::Notification;
 use crate::err::Error;
 use crate::iam::{Action, Auth, ResourceKind};
 use crate::sql::statements::define::{DefineIndexStatement, DefineTableStatement};
-use crate::sql::Base;
+use crate::sql::{Base, Idiom};
 use async_channel::Sender;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -44,6 +44,8 @@ pub struct Options {
 	pub(crate) futures: Futures,
 	/// The data version as nanosecond timestamp
 	pub(crate) version: Option<u64>,
+	/// The sensitive fields which are revealed, if sensitive fields are masked
+	pub(crate) reveal: Option<Arc<[Idiom]>>,
 	/// The channel over which we send notifications
 	pub(crate) sender: Option<Sender<Notification>>,
 }
@@ -88,6 +90,7 @@ impl Options {
 			sender: None,
 			auth: Arc::new(Auth::default()),
 			version: None,
+			reveal: None,
 		}
 	}
 
@@ -217,6 +220,13 @@ impl Options {
 		self
 	}
 
+	/// Specify which sensitive fields are revealed, masking
+	/// all other sensitive fields unless this is `None`
+	pub fn with_reveal(mut self, reveal: Option<Arc<[Idiom]>>) -> Self {
+		self.reveal = reveal;
+		self
+	}
+
 	// --------------------------------------------------
 
 	/// Create a new Options object for a subquery
@@ -228,6 +238,7 @@ impl Options {
 			db: self.db.clone(),
 			force: self.force.clone(),
 			perms: self.perms,
+			reveal: self.reveal.clone(),
 			..*self
 		}
 	}
@@ -241,6 +252,7 @@ impl Options {
 			db: self.db.clone(),
 			force: self.force.clone(),
 			perms,
+			reveal: self.reveal.clone(),
 			..*self
 		}
 	}
@@ -253,6 +265,7 @@ impl Options {
 			ns: self.ns.clone(),
 			db: self.db.clone(),
 			force,
+			reveal: self.reveal.clone(),
 			..*self
 		}
 	}
@@ -266,6 +279,7 @@ impl Options {
 			db: self.db.clone(),
 			force: self.force.clone(),
 			strict,
+			reveal: self.reveal.clone(),
 			..*self
 		}
 	}
@@ -279,6 +293,7 @@ impl Options {
 			db: self.db.clone(),
 			force: self.force.clone(),
 			import,
+			reveal: self.reveal.clone(),
 			..*self
 		}
 	}
@@ -298,6 +313,7 @@ impl Options {
 					false => Futures::Disabled,
 				},
 			},
+			reveal: self.reveal.clone(),
 			..*self
 		}
 	}
@@ -310,6 +326,7 @@ impl Options {
 			db: self.db.clone(),
 			force: self.force.clone(),
 			sender: Some(sender),
+			reveal: self.reveal.clone(),
 			..*self
 		}
 	}
@@ -339,6 +356,7 @@ impl Options {
 			db: self.db.clone(),
 			force: self.force.clone(),
 			dive: self.dive - cost as u32,
+			reveal: self.reveal.clone(),
 			..*self
 		})
 	}
@@ -369,6 +387,15 @@ impl Options {
 		Ok((self.ns()?, self.db()?))
 	}
 
+	/// Check whether a sensitive field is masked for this request
+	#[inline(always)]
+	pub fn is_masked(&self, field: &Idiom) -> bool {
+		match &self.reveal {
+			Some(reveal) => !reveal.iter().any(|r| field.starts_with(r)),
+			None => false,
+		}
+	}
+
 	/// Check whether this request supports realtime queries
 	#[inline(always)]
 	pub fn realtime(&self) -> Result<(), Error> {
@@ -48,6 +48,8 @@ pub struct Response {
 	pub result: Result<CoreValue, Error>,
 	// Record the query type in case processing the response is necessary (such as tracking live queries).
 	pub query_type: QueryType,
+	// Record the kind of statement which produced this response, if the statement could be parsed.
+	pub statement: Option<&'static str>,
 }
 
 impl Response {
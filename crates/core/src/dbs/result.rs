@@ -7,6 +7,7 @@ use crate::dbs::plan::Explanation;
 use crate::dbs::store::{MemoryCollector, MemoryOrdered, MemoryOrderedLimit, MemoryRandom};
 use crate::dbs::{Options, Statement};
 use crate::err::Error;
+use crate::fnc::util::string::collation::Collation;
 use crate::idx::planner::RecordStrategy;
 use crate::sql::order::Ordering;
 use crate::sql::Value;
@@ -26,7 +27,7 @@ pub(super) enum Results {
 impl Results {
 	pub(super) fn prepare(
 		&mut self,
-		#[cfg(storage)] ctx: &Context,
+		ctx: &Context,
 		stm: &Statement<'_>,
 		start: Option<u32>,
 		limit: Option<u32>,
@@ -34,10 +35,17 @@ impl Results {
 		if stm.expr().is_some() && stm.group().is_some() {
 			return Ok(Self::Groups(GroupsCollector::new(stm)));
 		}
+		// Strings are collated using the locale of the session
+		let collation = match stm.order() {
+			Some(Ordering::Order(orders)) if orders.iter().any(|o| o.collate) => {
+				Collation::from_ctx(ctx)
+			}
+			_ => None,
+		};
 		#[cfg(storage)]
 		if stm.tempfiles() {
 			if let Some(temp_dir) = ctx.temporary_directory() {
-				return Ok(Self::File(Box::new(FileCollector::new(temp_dir)?)));
+				return Ok(Self::File(Box::new(FileCollector::new(temp_dir, collation)?)));
 			}
 		}
 		if let Some(ordering) = stm.order() {
@@ -50,10 +58,11 @@ impl Results {
 							return Ok(Self::MemoryOrderedLimit(MemoryOrderedLimit::new(
 								limit as usize,
 								orders.clone(),
+								collation,
 							)));
 						}
 					}
-					Ok(Self::MemoryOrdered(MemoryOrdered::new(orders.clone(), None)))
+					Ok(Self::MemoryOrdered(MemoryOrdered::new(orders.clone(), collation, None)))
 				}
 			};
 		}
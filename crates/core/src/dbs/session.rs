@@ -1,9 +1,14 @@
 use crate::ctx::MutableContext;
+use crate::dbs::Capabilities;
+use crate::err::Error;
 use crate::iam::Auth;
 use crate::iam::{Level, Role};
 use crate::sql::value::Value;
-use chrono::Utc;
+use crate::sql::Idiom;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
 use std::collections::BTreeMap;
+use std::fmt;
 use std::sync::Arc;
 
 /// Specifies the current session information when processing a query.
@@ -32,6 +37,27 @@ pub struct Session {
 	pub rd: Option<Value>,
 	/// The current expiration time of the session
 	pub exp: Option<i64>,
+	/// The current timezone of the session, as an offset or a region name
+	pub(crate) tz: Option<String>,
+	/// The current locale of the session
+	///
+	/// This affects the case mapping of the `string::lowercase` and
+	/// `string::uppercase` functions, and the collation of strings when
+	/// ordering with `COLLATE`, or with the `array::sort_lexical` and
+	/// `array::sort_natural_lexical` functions.
+	pub(crate) lc: Option<String>,
+	/// The capabilities by which the datastore capabilities are narrowed
+	pub(crate) caps: Option<Arc<Capabilities>>,
+	/// The seed for deterministic random number generation
+	pub(crate) seed: Option<u64>,
+	/// The maximum depth of recursive idioms
+	pub(crate) max_depth: Option<u32>,
+	/// Whether FUTURE values are left uncomputed
+	pub(crate) skip_futures: bool,
+	/// The timestamp at which all records are read
+	pub(crate) as_of: Option<u64>,
+	/// The sensitive fields which are revealed, as all other sensitive fields are masked
+	pub(crate) reveal: Vec<Idiom>,
 	/// The parameters set
 	pub parameters: BTreeMap<String, Value>,
 }
@@ -55,6 +81,48 @@ impl Session {
 		self
 	}
 
+	/// Set the locale used for case mapping in the session
+	pub fn with_lc(mut self, lc: &str) -> Session {
+		self.lc = Some(lc.to_owned());
+		self
+	}
+
+	/// Set the timezone for the session
+	pub fn with_tz(mut self, tz: &str) -> Session {
+		self.tz = Some(tz.to_owned());
+		self
+	}
+
+	/// Narrow the datastore capabilities for the session
+	pub fn with_caps(mut self, caps: Capabilities) -> Session {
+		self.caps = Some(Arc::new(caps));
+		self
+	}
+
+	/// Set the seed for deterministic random number generation in the session
+	pub fn with_seed(mut self, seed: u64) -> Session {
+		self.seed = Some(seed);
+		self
+	}
+
+	/// Set the maximum depth of recursive idioms in the session
+	pub fn with_max_depth(mut self, max_depth: u32) -> Session {
+		self.max_depth = Some(max_depth);
+		self
+	}
+
+	/// Set whether FUTURE values are left uncomputed in the session
+	pub fn with_skip_futures(mut self, skip_futures: bool) -> Session {
+		self.skip_futures = skip_futures;
+		self
+	}
+
+	/// Set the timestamp at which all records are read in the session
+	pub fn with_as_of(mut self, as_of: u64) -> Session {
+		self.as_of = Some(as_of);
+		self
+	}
+
 	// Set the realtime functionality of the session
 	pub fn with_rt(mut self, rt: bool) -> Session {
 		self.rt = rt;
@@ -99,6 +167,8 @@ impl Session {
 			"or".to_string() => self.or.clone().into(),
 			"rd".to_string() => self.rd.clone().into(),
 			"tk".to_string() => self.tk.clone().into(),
+			"tz".to_string() => self.tz.clone().into(),
+			"lc".to_string() => self.lc.clone().into(),
 		});
 
 		vec![("access", access), ("auth", auth), ("token", token), ("session", session)]
@@ -109,6 +179,18 @@ impl Session {
 		let vars = self.values().into_iter();
 
 		ctx.add_values(vars);
+		// Narrow the capabilities for this execution
+		if let Some(caps) = &self.caps {
+			ctx.add_capabilities(Arc::new(ctx.get_capabilities().narrow(caps)));
+		}
+		// Seed the random number generator for this execution
+		if let Some(seed) = self.seed {
+			ctx.add_seed(seed);
+		}
+		// Limit the depth of recursive idioms for this execution
+		if let Some(depth) = self.max_depth {
+			ctx.set_max_depth(depth);
+		}
 	}
 
 	/// Create a system session for a given level and role
@@ -148,6 +230,14 @@ impl Session {
 			tk: None,
 			rd: Some(rid),
 			exp: None,
+			tz: None,
+			lc: None,
+			caps: None,
+			seed: None,
+			max_depth: None,
+			skip_futures: false,
+			as_of: None,
+			reveal: Vec::new(),
 			parameters: Default::default(),
 		}
 	}
@@ -167,3 +257,90 @@ impl Session {
 		Session::for_level(Level::Root, Role::Viewer)
 	}
 }
+
+/// A timezone which is set on a session
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Timezone {
+	/// A fixed offset from UTC, such as `+05:30`
+	Fixed(FixedOffset),
+	/// A region in the IANA timezone database, such as `America/New_York`
+	Region(Tz),
+}
+
+impl Timezone {
+	/// Retrieves the offset from UTC of this timezone at the given instant,
+	/// taking into account any daylight saving time for a region
+	pub fn offset_at(&self, dt: &DateTime<Utc>) -> FixedOffset {
+		match self {
+			Self::Fixed(v) => *v,
+			Self::Region(v) => v.offset_from_utc_datetime(&dt.naive_utc()).fix(),
+		}
+	}
+
+	/// Converts a date and time in this timezone into an instant, taking the
+	/// earliest instant if the date and time occurs more than once
+	pub fn from_local(&self, dt: &NaiveDateTime) -> Option<DateTime<Utc>> {
+		match self {
+			Self::Fixed(v) => v.from_local_datetime(dt).earliest().map(|v| v.with_timezone(&Utc)),
+			Self::Region(v) => v.from_local_datetime(dt).earliest().map(|v| v.with_timezone(&Utc)),
+		}
+	}
+}
+
+impl fmt::Display for Timezone {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Fixed(v) => write!(f, "{v}"),
+			Self::Region(v) => write!(f, "{}", v.name()),
+		}
+	}
+}
+
+/// Parses a timezone identifier into a timezone
+///
+/// Accepts `UTC`, `Z`, a `+HH:MM` / `-HH:MM` offset, or a region name such as `America/New_York`.
+pub fn parse_timezone(tz: &str) -> Result<Timezone, Error> {
+	match tz {
+		"UTC" | "utc" | "Z" | "z" => Ok(Timezone::Fixed(FixedOffset::east_opt(0).unwrap())),
+		v => match v.parse::<FixedOffset>() {
+			Ok(v) => Ok(Timezone::Fixed(v)),
+			Err(_) => v
+				.parse::<Tz>()
+				.map(Timezone::Region)
+				.map_err(|_| Error::InvalidTimezone(v.to_owned())),
+		},
+	}
+}
+
+/// Parses a locale identifier into its canonical form
+///
+/// Accepts a BCP 47 language tag, such as `en`, `tr-TR`, or `zh-Hant-TW`.
+pub fn parse_locale(lc: &str) -> Result<String, Error> {
+	let err = || Error::InvalidLocale(lc.to_owned());
+	let mut parts = lc.split(['-', '_']);
+	// The language subtag is required
+	let language = parts.next().ok_or_else(err)?;
+	if !matches!(language.len(), 2..=3 | 5..=8)
+		|| !language.chars().all(|c| c.is_ascii_alphabetic())
+	{
+		return Err(err());
+	}
+	let mut out = language.to_ascii_lowercase();
+	for part in parts {
+		if !(1..=8).contains(&part.len()) || !part.chars().all(|c| c.is_ascii_alphanumeric()) {
+			return Err(err());
+		}
+		out.push('-');
+		match part.len() {
+			// Script subtags are titlecased
+			4 if part.chars().all(|c| c.is_ascii_alphabetic()) => {
+				out.push_str(&part[..1].to_ascii_uppercase());
+				out.push_str(&part[1..].to_ascii_lowercase());
+			}
+			// Region subtags are uppercased
+			2 => out.push_str(&part.to_ascii_uppercase()),
+			_ => out.push_str(&part.to_ascii_lowercase()),
+		}
+	}
+	Ok(out)
+}
@@ -1,6 +1,7 @@
 use crate::dbs::plan::Explanation;
 #[cfg(not(target_family = "wasm"))]
 use crate::err::Error;
+use crate::fnc::util::string::collation::Collation;
 use crate::sql::order::OrderList;
 use crate::sql::value::Value;
 use std::cmp::{Ordering, Reverse};
@@ -185,10 +186,16 @@ pub(in crate::dbs) struct MemoryOrdered {
 	result: Option<Vec<Value>>,
 	/// The order specification
 	orders: OrderList,
+	/// The collation of the session locale
+	collation: Option<Arc<Collation>>,
 }
 
 impl MemoryOrdered {
-	pub(in crate::dbs) fn new(orders: OrderList, batch_size: Option<usize>) -> Self {
+	pub(in crate::dbs) fn new(
+		orders: OrderList,
+		collation: Option<Arc<Collation>>,
+		batch_size: Option<usize>,
+	) -> Self {
 		let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
 		Self {
 			batch_size,
@@ -197,6 +204,7 @@ impl MemoryOrdered {
 			batch: Vec::with_capacity(batch_size),
 			result: None,
 			orders,
+			collation,
 		}
 	}
 
@@ -232,7 +240,9 @@ impl MemoryOrdered {
 			}
 			let mut ordered = mem::take(&mut self.ordered);
 			let mut values = mem::take(&mut self.values);
-			ordered.sort_unstable_by(|a, b| self.orders.compare(&values[*a], &values[*b]));
+			let collation = self.collation.as_deref();
+			ordered
+				.sort_unstable_by(|a, b| self.orders.compare(&values[*a], &values[*b], collation));
 			let res = MemoryRandom::ordered_values_to_vec(&mut values, &ordered);
 			self.result = Some(res);
 		}
@@ -247,8 +257,12 @@ impl MemoryOrdered {
 			let mut ordered = mem::take(&mut self.ordered);
 			let mut values = mem::take(&mut self.values);
 			let orders = self.orders.clone();
+			let collation = self.collation.clone();
 			let result = spawn_blocking(move || {
-				ordered.par_sort_unstable_by(|a, b| orders.compare(&values[*a], &values[*b]));
+				let collation = collation.as_deref();
+				ordered.par_sort_unstable_by(|a, b| {
+					orders.compare(&values[*a], &values[*b], collation)
+				});
 				MemoryRandom::ordered_values_to_vec(&mut values, &ordered)
 			})
 			.await
@@ -276,6 +290,7 @@ impl MemoryOrdered {
 pub(super) struct OrderedValue {
 	value: Value,
 	orders: Arc<OrderList>,
+	collation: Option<Arc<Collation>>,
 }
 impl PartialOrd<Self> for OrderedValue {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -287,7 +302,7 @@ impl Eq for OrderedValue {}
 
 impl Ord for OrderedValue {
 	fn cmp(&self, other: &Self) -> Ordering {
-		self.orders.compare(&other.value, &self.value)
+		self.orders.compare(&other.value, &self.value, self.collation.as_deref())
 	}
 }
 
@@ -304,16 +319,19 @@ pub(super) struct MemoryOrderedLimit {
 	limit: usize,
 	/// The order specification
 	orders: Arc<OrderList>,
+	/// The collation of the session locale
+	collation: Option<Arc<Collation>>,
 	/// The finalized result
 	result: Option<Vec<Value>>,
 }
 
 impl MemoryOrderedLimit {
-	pub(super) fn new(limit: usize, orders: OrderList) -> Self {
+	pub(super) fn new(limit: usize, orders: OrderList, collation: Option<Arc<Collation>>) -> Self {
 		Self {
 			heap: BinaryHeap::with_capacity(limit + 1),
 			limit,
 			orders: Arc::new(orders),
+			collation,
 			result: None,
 		}
 	}
@@ -324,11 +342,12 @@ impl MemoryOrderedLimit {
 			// if smaller that the top of this min-heap in order to
 			// prevent unnecessary push/pop and Arc::clone.
 			if let Some(top) = self.heap.peek() {
-				let cmp = self.orders.compare(&value, &top.0.value);
+				let cmp = self.orders.compare(&value, &top.0.value, self.collation.as_deref());
 				if cmp == Ordering::Less {
 					self.heap.push(Reverse(OrderedValue {
 						value,
 						orders: self.orders.clone(),
+						collation: self.collation.clone(),
 					}));
 					self.heap.pop();
 				}
@@ -338,6 +357,7 @@ impl MemoryOrderedLimit {
 			self.heap.push(Reverse(OrderedValue {
 				value,
 				orders: self.orders.clone(),
+				collation: self.collation.clone(),
 			}));
 		}
 	}
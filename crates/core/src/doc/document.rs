@@ -1,3 +1,4 @@
+use crate::cnf::REDACTED;
 use crate::ctx::Context;
 use crate::ctx::MutableContext;
 use crate::dbs::Options;
@@ -291,6 +292,10 @@ impl Document {
 	/// reduced so that these reduced documents are used
 	/// instead of their non-reduced versions.
 	///
+	/// Any sensitive fields which are not revealed are also
+	/// masked in the reduced documents, so that they can not
+	/// be read through computed fields or conditions.
+	///
 	/// If there is no requirement to reduce a document
 	/// based on the permissions, then this function will
 	/// not have any performance impact by cloning the
@@ -307,11 +312,17 @@ impl Document {
 			return Ok(false);
 		}
 		// Are permissions being skipped?
-		if !opt.check_perms(Action::View)? {
+		let perms = opt.check_perms(Action::View)?;
+		// Are sensitive fields never being masked?
+		if !perms && opt.reveal.is_none() {
 			return Ok(false);
 		}
 		// Fetch the fields for the table
 		let fds = self.fd(ctx, opt).await?;
+		// Are there no sensitive fields to be masked?
+		if !perms && !fds.iter().any(|fd| fd.sensitive && opt.is_masked(&fd.name)) {
+			return Ok(false);
+		}
 		// Fetch the targets to process
 		let targets = match permitted {
 			Permitted::Initial => vec![(&self.initial, &mut self.initial_reduced)],
@@ -332,8 +343,12 @@ impl Document {
 			for fd in fds.iter() {
 				// Loop over each field in document
 				for k in out.each(&fd.name).iter() {
-					// Process the field permissions
-					match &fd.permissions.select {
+					// Process the field permissions, unless they are skipped
+					let select = match perms {
+						true => &fd.permissions.select,
+						false => &Permission::Full,
+					};
+					match select {
 						Permission::Full => (),
 						Permission::None => out.cut(k),
 						Permission::Specific(e) => {
@@ -356,6 +371,10 @@ impl Document {
 							}
 						}
 					}
+					// Mask the field if it is sensitive and not revealed
+					if fd.sensitive && opt.is_masked(&fd.name) && !out.pick(k).is_none() {
+						out.put(k, Value::from(REDACTED));
+					}
 				}
 			}
 			// Update the permitted document
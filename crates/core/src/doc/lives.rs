@@ -110,8 +110,12 @@ impl Document {
 			// We need to create a new options which we will
 			// use for processing this LIVE query statement.
 			// This ensures that we are using the auth data
-			// of the user who created the LIVE query.
-			let lqopt = opt.new_with_perms(true).with_auth(Arc::from(auth));
+			// of the user who created the LIVE query, and
+			// that all sensitive fields are masked.
+			let lqopt = opt
+				.new_with_perms(true)
+				.with_auth(Arc::from(auth))
+				.with_reveal(Some(Arc::from([])));
 			// First of all, let's check to see if the WHERE
 			// clause of the LIVE query is matched by this
 			// document. If it is then we can continue.
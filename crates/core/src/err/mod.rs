@@ -262,6 +262,14 @@ pub enum Error {
 	#[error("Invalid timeout: {0:?} seconds")]
 	InvalidTimeout(u64),
 
+	/// Invalid timezone
+	#[error("Invalid timezone: {0:?}")]
+	InvalidTimezone(String),
+
+	/// Invalid locale
+	#[error("Invalid locale: {0:?}")]
+	InvalidLocale(String),
+
 	/// Invalid timeout
 	#[error("Invalid control flow statement, break or continue statement found outside of loop.")]
 	InvalidControlFlow,
@@ -288,6 +296,10 @@ pub enum Error {
 		message: String,
 	},
 
+	/// The query did not execute, because a previous statement has failed
+	#[error("The query was not executed because a previous statement failed")]
+	QueryNotExecutedFailFast,
+
 	/// The permissions do not allow for changing to the specified namespace
 	#[error("You don't have permission to change to the {ns} namespace")]
 	NsNotAllowed {
@@ -1254,6 +1266,12 @@ pub enum Error {
 		limit: u32,
 	},
 
+	/// The recursion depth limit of the request was exceeded
+	#[error("Exceeded the recursion depth limit of {limit} for this request.")]
+	RecursionDepthExceeded {
+		limit: u32,
+	},
+
 	/// Found an unexpected value in a range
 	#[error("Tried to use a `@` repeat recurse symbol, while not recursing.")]
 	RepeatRecurseNotRecursing,
@@ -3,6 +3,7 @@ use crate::ctx::Context;
 use crate::dbs::Options;
 use crate::doc::CursorDoc;
 use crate::err::Error;
+use crate::fnc::util::string::collation::Collation;
 use crate::sql::array::Array;
 use crate::sql::array::Clump;
 use crate::sql::array::Combine;
@@ -692,25 +693,39 @@ pub fn sort_natural(
 }
 
 pub fn sort_lexical(
+	ctx: &Context,
 	(mut array, Optional(order)): (Array, Optional<Value>),
 ) -> Result<Value, Error> {
+	// Strings are collated using the locale of the session
+	let collation = Collation::from_ctx(ctx);
+	let cmp = |a: &Value, b: &Value| match &collation {
+		Some(c) => a.collated_cmp(b, c, false),
+		None => a.lexical_cmp(b),
+	};
 	if sort_as_asc(&order) {
-		array.sort_unstable_by(|a, b| a.lexical_cmp(b).unwrap_or(Ordering::Equal));
+		array.sort_unstable_by(|a, b| cmp(a, b).unwrap_or(Ordering::Equal));
 		Ok(array.into())
 	} else {
-		array.sort_unstable_by(|a, b| b.lexical_cmp(a).unwrap_or(Ordering::Equal));
+		array.sort_unstable_by(|a, b| cmp(b, a).unwrap_or(Ordering::Equal));
 		Ok(array.into())
 	}
 }
 
 pub fn sort_natural_lexical(
+	ctx: &Context,
 	(mut array, Optional(order)): (Array, Optional<Value>),
 ) -> Result<Value, Error> {
+	// Strings are collated using the locale of the session
+	let collation = Collation::from_ctx(ctx);
+	let cmp = |a: &Value, b: &Value| match &collation {
+		Some(c) => a.collated_cmp(b, c, true),
+		None => a.natural_lexical_cmp(b),
+	};
 	if sort_as_asc(&order) {
-		array.sort_unstable_by(|a, b| a.natural_lexical_cmp(b).unwrap_or(Ordering::Equal));
+		array.sort_unstable_by(|a, b| cmp(a, b).unwrap_or(Ordering::Equal));
 		Ok(array.into())
 	} else {
-		array.sort_unstable_by(|a, b| b.natural_lexical_cmp(a).unwrap_or(Ordering::Equal));
+		array.sort_unstable_by(|a, b| cmp(b, a).unwrap_or(Ordering::Equal));
 		Ok(array.into())
 	}
 }
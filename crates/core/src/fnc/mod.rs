@@ -185,8 +185,8 @@ pub fn synchronous(
 		"array::slice" => array::slice,
 		"array::sort" => array::sort,
 		"array::sort_natural" => array::sort_natural,
-		"array::sort_lexical" => array::sort_lexical,
-		"array::sort_natural_lexical" => array::sort_natural_lexical,
+		"array::sort_lexical" => array::sort_lexical(ctx),
+		"array::sort_natural_lexical" => array::sort_natural_lexical(ctx),
 		"array::swap" => array::swap,
 		"array::transpose" => array::transpose,
 		"array::union" => array::union,
@@ -305,19 +305,19 @@ pub fn synchronous(
 		"parse::url::query" => parse::url::query,
 		"parse::url::scheme" => parse::url::scheme,
 		//
-		"rand" => rand::rand,
-		"rand::bool" => rand::bool,
-		"rand::duration" => rand::duration,
-		"rand::enum" => rand::r#enum,
-		"rand::float" => rand::float,
-		"rand::guid" => rand::guid,
-		"rand::int" => rand::int,
-		"rand::string" => rand::string,
-		"rand::time" => rand::time,
-		"rand::ulid" => rand::ulid,
-		"rand::uuid::v4" => rand::uuid::v4,
-		"rand::uuid::v7" => rand::uuid::v7,
-		"rand::uuid" => rand::uuid,
+		"rand" => rand::rand(ctx),
+		"rand::bool" => rand::bool(ctx),
+		"rand::duration" => rand::duration(ctx),
+		"rand::enum" => rand::r#enum(ctx),
+		"rand::float" => rand::float(ctx),
+		"rand::guid" => rand::guid(ctx),
+		"rand::int" => rand::int(ctx),
+		"rand::string" => rand::string(ctx),
+		"rand::time" => rand::time(ctx),
+		"rand::ulid" => rand::ulid(ctx),
+		"rand::uuid::v4" => rand::uuid::v4(ctx),
+		"rand::uuid::v7" => rand::uuid::v7(ctx),
+		"rand::uuid" => rand::uuid(ctx),
 		//
 		"record::id" => record::id,
 		"record::table" => record::tb,
@@ -337,7 +337,7 @@ pub fn synchronous(
 		"string::ends_with" => string::ends_with,
 		"string::join" => string::join,
 		"string::len" => string::len,
-		"string::lowercase" => string::lowercase,
+		"string::lowercase" => string::lowercase(ctx),
 		"string::matches" => string::matches,
 		"string::repeat" => string::repeat,
 		"string::replace" => string::replace,
@@ -347,7 +347,7 @@ pub fn synchronous(
 		"string::split" => string::split,
 		"string::starts_with" => string::starts_with,
 		"string::trim" => string::trim,
-		"string::uppercase" => string::uppercase,
+		"string::uppercase" => string::uppercase(ctx),
 		"string::words" => string::words,
 		//
 		"string::distance::damerau_levenshtein" => string::distance::damerau_levenshtein,
@@ -396,27 +396,27 @@ pub fn synchronous(
 		"string::semver::set::patch" => string::semver::set::patch,
 		//
 		"time::ceil" => time::ceil,
-		"time::day" => time::day,
+		"time::day" => time::day(ctx),
 		"time::floor" => time::floor,
-		"time::format" => time::format,
-		"time::group" => time::group,
-		"time::hour" => time::hour,
+		"time::format" => time::format(ctx),
+		"time::group" => time::group(ctx),
+		"time::hour" => time::hour(ctx),
 		"time::max" => time::max,
 		"time::min" => time::min,
-		"time::minute" => time::minute,
-		"time::month" => time::month,
+		"time::minute" => time::minute(ctx),
+		"time::month" => time::month(ctx),
 		"time::nano" => time::nano,
 		"time::micros" => time::micros,
 		"time::millis" => time::millis,
 		"time::now" => time::now,
 		"time::round" => time::round,
-		"time::second" => time::second,
-		"time::timezone" => time::timezone,
+		"time::second" => time::second(ctx),
+		"time::timezone" => time::timezone(ctx),
 		"time::unix" => time::unix,
-		"time::wday" => time::wday,
-		"time::week" => time::week,
-		"time::yday" => time::yday,
-		"time::year" => time::year,
+		"time::wday" => time::wday(ctx),
+		"time::week" => time::week(ctx),
+		"time::yday" => time::yday(ctx),
+		"time::year" => time::year(ctx),
 		"time::from::nanos" => time::from::nanos,
 		"time::from::micros" => time::from::micros,
 		"time::from::millis" => time::from::millis,
@@ -424,7 +424,7 @@ pub fn synchronous(
 		"time::from::ulid" => time::from::ulid,
 		"time::from::unix" => time::from::unix,
 		"time::from::uuid" => time::from::uuid,
-		"time::is::leap_year" => time::is::leap_year,
+		"time::is::leap_year" => time::is::leap_year(ctx),
 		//
 		"type::array" => r#type::array,
 		"type::bool" => r#type::bool,
@@ -661,8 +661,8 @@ pub async fn idiom(
 				"some" => array::any((stk, ctx, Some(opt), doc)).await,
 				"sort" => array::sort,
 				"sort_natural" => array::sort_natural,
-				"sort_lexical" => array::sort_lexical,
-				"sort_natural_lexical" => array::sort_natural_lexical,
+				"sort_lexical" => array::sort_lexical(ctx),
+				"sort_natural_lexical" => array::sort_natural_lexical(ctx),
 				"swap" => array::swap,
 				"transpose" => array::transpose,
 				"union" => array::union,
@@ -808,7 +808,7 @@ pub async fn idiom(
 				"ends_with" => string::ends_with,
 				"join" => string::join,
 				"len" => string::len,
-				"lowercase" => string::lowercase,
+				"lowercase" => string::lowercase(ctx),
 				"matches" => string::matches,
 				"repeat" => string::repeat,
 				"replace" => string::replace,
@@ -818,7 +818,7 @@ pub async fn idiom(
 				"split" => string::split,
 				"starts_with" => string::starts_with,
 				"trim" => string::trim,
-				"uppercase" => string::uppercase,
+				"uppercase" => string::uppercase(ctx),
 				"words" => string::words,
 				"distance_damerau_levenshtein" => string::distance::damerau_levenshtein,
 				"distance_hamming" => string::distance::hamming,
@@ -870,24 +870,24 @@ pub async fn idiom(
 				"no such method found for the datetime type",
 				//
 				"ceil" => time::ceil,
-				"day" => time::day,
+				"day" => time::day(ctx),
 				"floor" => time::floor,
-				"format" => time::format,
-				"group" => time::group,
-				"hour" => time::hour,
-				"is_leap_year" => time::is::leap_year,
+				"format" => time::format(ctx),
+				"group" => time::group(ctx),
+				"hour" => time::hour(ctx),
+				"is_leap_year" => time::is::leap_year(ctx),
 				"micros" => time::micros,
 				"millis" => time::millis,
-				"minute" => time::minute,
-				"month" => time::month,
+				"minute" => time::minute(ctx),
+				"month" => time::month(ctx),
 				"nano" => time::nano,
 				"round" => time::round,
-				"second" => time::second,
+				"second" => time::second(ctx),
 				"unix" => time::unix,
-				"wday" => time::wday,
-				"week" => time::week,
-				"yday" => time::yday,
-				"year" => time::year,
+				"wday" => time::wday(ctx),
+				"week" => time::week(ctx),
+				"yday" => time::yday(ctx),
+				"year" => time::year(ctx),
 			)
 		}
 		Value::File(_) => {
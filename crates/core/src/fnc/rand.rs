@@ -1,33 +1,44 @@
 use crate::cnf::ID_CHARS;
+use crate::ctx::Context;
 use crate::err::Error;
-use crate::sql::uuid::Uuid;
 use crate::sql::value::Value;
 use crate::sql::{Datetime, Duration, Number};
 use chrono::{TimeZone, Utc};
 use nanoid::nanoid;
 use rand::distributions::{Alphanumeric, DistString};
-use rand::prelude::IteratorRandom;
-use rand::Rng;
+use rand::prelude::{IteratorRandom, SliceRandom};
+use rand::{Rng, RngCore};
 use ulid::Ulid;
 
 use super::args::{Any, Args, Arity, FromArg, Optional};
 
-pub fn rand(_: ()) -> Result<Value, Error> {
-	Ok(rand::random::<f64>().into())
+/// Runs the function with the seeded random number generator
+/// of the context, or with the thread-local generator otherwise
+fn with_rng<T>(ctx: &Context, f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+	match ctx.get_rng() {
+		Some(rng) => f(&mut *rng.lock()),
+		None => f(&mut rand::thread_rng()),
+	}
+}
+
+pub fn rand(ctx: &Context, _: ()) -> Result<Value, Error> {
+	Ok(with_rng(ctx, |rng| rng.gen::<f64>()).into())
 }
 
-pub fn bool(_: ()) -> Result<Value, Error> {
-	Ok(rand::random::<bool>().into())
+pub fn bool(ctx: &Context, _: ()) -> Result<Value, Error> {
+	Ok(with_rng(ctx, |rng| rng.gen::<bool>()).into())
 }
 
-pub fn r#enum(Any(mut args): Any) -> Result<Value, Error> {
+pub fn r#enum(ctx: &Context, Any(mut args): Any) -> Result<Value, Error> {
 	Ok(match args.len() {
 		0 => Value::None,
 		1 => match args.remove(0) {
-			Value::Array(v) => v.into_iter().choose(&mut rand::thread_rng()).unwrap_or(Value::None),
+			Value::Array(v) => {
+				with_rng(ctx, |rng| v.into_iter().choose(rng)).unwrap_or(Value::None)
+			}
 			v => v,
 		},
-		_ => args.into_iter().choose(&mut rand::thread_rng()).unwrap(),
+		_ => with_rng(ctx, |rng| args.into_iter().choose(rng)).unwrap(),
 	})
 }
 
@@ -68,20 +79,23 @@ impl<T: FromArg> FromArg for NoneOrRange<T> {
 // TODO (Delskayn): Switching of min and max if min > max is also inconsistent with rest of
 // functions and the range type. The functions should either return NONE or an error if the lowerbound
 // of the ranges here are larger then the upperbound.
-pub fn float((NoneOrRange(range),): (NoneOrRange<f64>,)) -> Result<Value, Error> {
-	let v = if let Some((min, max)) = range {
-		if max < min {
-			rand::thread_rng().gen_range(max..=min)
+pub fn float(ctx: &Context, (NoneOrRange(range),): (NoneOrRange<f64>,)) -> Result<Value, Error> {
+	let v = with_rng(ctx, |rng| {
+		if let Some((min, max)) = range {
+			if max < min {
+				rng.gen_range(max..=min)
+			} else {
+				rng.gen_range(min..=max)
+			}
 		} else {
-			rand::thread_rng().gen_range(min..=max)
+			rng.gen::<f64>()
 		}
-	} else {
-		rand::random::<f64>()
-	};
+	});
 	Ok(Value::from(v))
 }
 
 pub fn guid(
+	ctx: &Context,
 	(Optional(arg1), Optional(arg2)): (Optional<i64>, Optional<i64>),
 ) -> Result<Value, Error> {
 	// Set a reasonable maximum length
@@ -104,7 +118,7 @@ pub fn guid(
 			});
 		}
 
-		rand::thread_rng().gen_range((lower as usize)..=(upper as usize))
+		with_rng(ctx, |rng| rng.gen_range((lower as usize)..=(upper as usize)))
 	} else {
 		if lower > LIMIT {
 			return Err(Error::InvalidArguments {
@@ -116,23 +130,33 @@ pub fn guid(
 	};
 
 	// Generate the random guid
-	Ok(nanoid!(len, &ID_CHARS).into())
+	let guid = match ctx.get_rng() {
+		Some(rng) => {
+			let mut rng = rng.lock();
+			(0..len).filter_map(|_| ID_CHARS.choose(&mut *rng)).collect::<String>()
+		}
+		None => nanoid!(len, &ID_CHARS),
+	};
+	Ok(guid.into())
 }
 
-pub fn int((NoneOrRange(range),): (NoneOrRange<i64>,)) -> Result<Value, Error> {
-	Ok(if let Some((min, max)) = range {
-		if max < min {
-			rand::thread_rng().gen_range(max..=min)
+pub fn int(ctx: &Context, (NoneOrRange(range),): (NoneOrRange<i64>,)) -> Result<Value, Error> {
+	Ok(with_rng(ctx, |rng| {
+		if let Some((min, max)) = range {
+			if max < min {
+				rng.gen_range(max..=min)
+			} else {
+				rng.gen_range(min..=max)
+			}
 		} else {
-			rand::thread_rng().gen_range(min..=max)
+			rng.gen::<i64>()
 		}
-	} else {
-		rand::random::<i64>()
-	}
+	})
 	.into())
 }
 
 pub fn string(
+	ctx: &Context,
 	(Optional(arg1), Optional(arg2)): (Optional<i64>, Optional<i64>),
 ) -> Result<Value, Error> {
 	// Set a reasonable maximum length
@@ -154,7 +178,7 @@ pub fn string(
 			});
 		}
 
-		rand::thread_rng().gen_range((lower as usize)..=(upper as usize))
+		with_rng(ctx, |rng| rng.gen_range((lower as usize)..=(upper as usize)))
 	} else {
 		if lower > LIMIT {
 			return Err(Error::InvalidArguments {
@@ -165,17 +189,17 @@ pub fn string(
 		lower as usize
 	};
 	// Generate the random string
-	Ok(Alphanumeric.sample_string(&mut rand::thread_rng(), len).into())
+	Ok(with_rng(ctx, |rng| Alphanumeric.sample_string(rng, len)).into())
 }
 
-pub fn duration((dur1, dur2): (Duration, Duration)) -> Result<Value, Error> {
+pub fn duration(ctx: &Context, (dur1, dur2): (Duration, Duration)) -> Result<Value, Error> {
 	// Sort from low to high
 	let (from, to) = match dur2 > dur1 {
 		true => (dur1, dur2),
 		false => (dur2, dur1),
 	};
 
-	let rand = rand::thread_rng().gen_range(from.as_nanos()..=to.as_nanos());
+	let rand = with_rng(ctx, |rng| rng.gen_range(from.as_nanos()..=to.as_nanos()));
 
 	let nanos = (rand % 1_000_000_000) as u32;
 
@@ -187,7 +211,7 @@ pub fn duration((dur1, dur2): (Duration, Duration)) -> Result<Value, Error> {
 	Ok(Value::Duration(Duration::new(secs, nanos)))
 }
 
-pub fn time((NoneOrRange(range),): (NoneOrRange<Value>,)) -> Result<Value, Error> {
+pub fn time(ctx: &Context, (NoneOrRange(range),): (NoneOrRange<Value>,)) -> Result<Value, Error> {
 	// Process the arguments
 	let range = match range {
 		None => None,
@@ -232,7 +256,7 @@ pub fn time((NoneOrRange(range),): (NoneOrRange<Value>,)) -> Result<Value, Error
 	};
 	// Generate the random time, try up to 5 times
 	for _ in 0..5 {
-		let val = rand::thread_rng().gen_range(min..=max);
+		let val = with_rng(ctx, |rng| rng.gen_range(min..=max));
 		if let Some(v) = Utc.timestamp_opt(val, 0).earliest() {
 			return Ok(v.into());
 		}
@@ -241,7 +265,7 @@ pub fn time((NoneOrRange(range),): (NoneOrRange<Value>,)) -> Result<Value, Error
 	Err(fail!("Expected a valid datetime, but were unable to generate one"))
 }
 
-pub fn ulid((Optional(timestamp),): (Optional<Datetime>,)) -> Result<Value, Error> {
+pub fn ulid(ctx: &Context, (Optional(timestamp),): (Optional<Datetime>,)) -> Result<Value, Error> {
 	let ulid = match timestamp {
 		Some(timestamp) => {
 			#[cfg(target_family = "wasm")]
@@ -254,47 +278,53 @@ pub fn ulid((Optional(timestamp),): (Optional<Datetime>,)) -> Result<Value, Erro
 				});
 			}
 
-			Ulid::from_datetime(timestamp.0.into())
+			match ctx.get_rng() {
+				Some(rng) => Ulid::from_parts(unix_millis(timestamp.0), rng.lock().gen()),
+				None => Ulid::from_datetime(timestamp.0.into()),
+			}
 		}
-		None => Ulid::new(),
+		None => match ctx.get_rng() {
+			Some(rng) => Ulid::from_parts(unix_millis(Utc::now()), rng.lock().gen()),
+			None => Ulid::new(),
+		},
 	};
 
 	Ok(ulid.to_string().into())
 }
 
-pub fn uuid((Optional(timestamp),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	let uuid = match timestamp {
-		Some(timestamp) => {
-			#[cfg(target_family = "wasm")]
-			if timestamp.0 < chrono::DateTime::UNIX_EPOCH {
-				return Err(Error::InvalidArguments {
-					name: String::from("rand::ulid"),
-					message: format!(
-						"To generate a ULID from a datetime, it must be a time beyond UNIX epoch."
-					),
-				});
-			}
+pub fn uuid(ctx: &Context, (Optional(timestamp),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	uuid::v7(ctx, (Optional(timestamp),))
+}
 
-			Uuid::new_v7_from_datetime(timestamp)
-		}
-		None => Uuid::new(),
-	};
-	Ok(uuid.into())
+/// Returns the milliseconds since the UNIX epoch, clamped at the epoch
+fn unix_millis(datetime: chrono::DateTime<Utc>) -> u64 {
+	datetime.timestamp_millis().max(0) as u64
 }
 
 pub mod uuid {
 
+	use super::unix_millis;
+	use crate::ctx::Context;
 	use crate::err::Error;
 	use crate::fnc::args::Optional;
 	use crate::sql::uuid::Uuid;
 	use crate::sql::value::Value;
 	use crate::sql::Datetime;
+	use chrono::Utc;
+	use rand::Rng;
 
-	pub fn v4(_: ()) -> Result<Value, Error> {
-		Ok(Uuid::new_v4().into())
+	pub fn v4(ctx: &Context, _: ()) -> Result<Value, Error> {
+		let uuid = match ctx.get_rng() {
+			Some(rng) => Uuid(uuid::Builder::from_random_bytes(rng.lock().gen()).into_uuid()),
+			None => Uuid::new_v4(),
+		};
+		Ok(uuid.into())
 	}
 
-	pub fn v7((Optional(timestamp),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	pub fn v7(
+		ctx: &Context,
+		(Optional(timestamp),): (Optional<Datetime>,),
+	) -> Result<Value, Error> {
 		let uuid = match timestamp {
 			Some(timestamp) => {
 				#[cfg(target_family = "wasm")]
@@ -307,10 +337,21 @@ pub mod uuid {
 					});
 				}
 
-				Uuid::new_v7_from_datetime(timestamp)
+				match ctx.get_rng() {
+					Some(rng) => new_v7(unix_millis(timestamp.0), &rng.lock().gen()),
+					None => Uuid::new_v7_from_datetime(timestamp),
+				}
 			}
-			None => Uuid::new(),
+			None => match ctx.get_rng() {
+				Some(rng) => new_v7(unix_millis(Utc::now()), &rng.lock().gen()),
+				None => Uuid::new(),
+			},
 		};
 		Ok(uuid.into())
 	}
+
+	/// Builds a V7 UUID from a timestamp and the supplied random bytes
+	fn new_v7(millis: u64, bytes: &[u8; 10]) -> Uuid {
+		Uuid(uuid::Builder::from_unix_timestamp_millis(millis, bytes).into_uuid())
+	}
 }
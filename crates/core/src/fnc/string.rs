@@ -1,6 +1,8 @@
 use crate::cnf::GENERATION_ALLOCATION_LIMIT;
+use crate::ctx::Context;
 use crate::err::Error;
 use crate::fnc::util::string;
+use crate::sql::paths::LC;
 use crate::sql::value::Value;
 use crate::sql::Regex;
 
@@ -18,6 +20,15 @@ fn limit(name: &str, n: usize) -> Result<(), Error> {
 	}
 }
 
+/// Checks if the locale set on the current session uses Turkic casing rules,
+/// in which the dotted and dotless `i` are separate letters.
+fn turkic_casing(ctx: &Context) -> bool {
+	match ctx.value("session").unwrap_or(&Value::None).pick(LC.as_ref()) {
+		Value::Strand(v) => matches!(v.split('-').next(), Some("tr" | "az")),
+		_ => false,
+	}
+}
+
 pub fn concat(Any(args): Any) -> Result<Value, Error> {
 	let strings = args.into_iter().map(Value::as_string).collect::<Vec<_>>();
 	limit("string::concat", strings.iter().map(String::len).sum::<usize>())?;
@@ -58,8 +69,12 @@ pub fn len((string,): (String,)) -> Result<Value, Error> {
 	Ok(num.into())
 }
 
-pub fn lowercase((string,): (String,)) -> Result<Value, Error> {
-	Ok(string.to_lowercase().into())
+pub fn lowercase(ctx: &Context, (string,): (String,)) -> Result<Value, Error> {
+	Ok(match turkic_casing(ctx) {
+		true => string.replace('I', "ı").replace('İ', "i").to_lowercase(),
+		false => string.to_lowercase(),
+	}
+	.into())
 }
 
 pub fn repeat((val, num): (String, i64)) -> Result<Value, Error> {
@@ -173,8 +188,12 @@ pub fn trim((string,): (String,)) -> Result<Value, Error> {
 	Ok(string.trim().into())
 }
 
-pub fn uppercase((string,): (String,)) -> Result<Value, Error> {
-	Ok(string.to_uppercase().into())
+pub fn uppercase(ctx: &Context, (string,): (String,)) -> Result<Value, Error> {
+	Ok(match turkic_casing(ctx) {
+		true => string.replace('i', "İ").to_uppercase(),
+		false => string.to_uppercase(),
+	}
+	.into())
 }
 
 pub fn words((string,): (String,)) -> Result<Value, Error> {
@@ -1,12 +1,35 @@
+use crate::ctx::Context;
+use crate::dbs::{parse_timezone, Timezone};
 use crate::err::Error;
 use crate::sql::datetime::Datetime;
 use crate::sql::duration::Duration;
+use crate::sql::paths::TZ;
 use crate::sql::value::Value;
-use chrono::offset::TimeZone;
-use chrono::{DateTime, Datelike, DurationRound, Local, Timelike, Utc};
+use chrono::{
+	DateTime, Datelike, DurationRound, FixedOffset, Local, NaiveDate, Offset, Timelike, Utc,
+};
 
 use super::args::Optional;
 
+/// Retrieves the timezone set on the current session, if any
+fn session_timezone(ctx: &Context) -> Option<Timezone> {
+	match ctx.value("session").unwrap_or(&Value::None).pick(TZ.as_ref()) {
+		Value::Strand(v) => parse_timezone(v.as_str()).ok(),
+		_ => None,
+	}
+}
+
+/// Converts a datetime, or the current time if none is given, into the
+/// timezone set on the current session, or into UTC if none is set
+fn local(ctx: &Context, val: Option<Datetime>) -> DateTime<FixedOffset> {
+	let val = val.unwrap_or_default();
+	let offset = match session_timezone(ctx) {
+		Some(tz) => tz.offset_at(&val),
+		None => Utc.fix(),
+	};
+	val.with_timezone(&offset)
+}
+
 pub fn ceil((val, duration): (Datetime, Duration)) -> Result<Value, Error> {
 	match chrono::Duration::from_std(*duration) {
 		Ok(d) => {
@@ -41,11 +64,8 @@ pub fn ceil((val, duration): (Datetime, Duration)) -> Result<Value, Error> {
 	}
 }
 
-pub fn day((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.day().into(),
-		None => Datetime::default().day().into(),
-	})
+pub fn day(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).day().into())
 }
 
 pub fn floor((val, duration): (Datetime, Duration)) -> Result<Value, Error> {
@@ -70,54 +90,42 @@ pub fn floor((val, duration): (Datetime, Duration)) -> Result<Value, Error> {
 	}
 }
 
-pub fn format((val, format): (Datetime, String)) -> Result<Value, Error> {
-	Ok(val.format(&format).to_string().into())
+pub fn format(ctx: &Context, (val, format): (Datetime, String)) -> Result<Value, Error> {
+	Ok(local(ctx, Some(val)).format(&format).to_string().into())
 }
 
-pub fn group((val, group): (Datetime, String)) -> Result<Value, Error> {
-	match group.as_str() {
-		"year" => Ok(Utc
-			.with_ymd_and_hms(val.year(), 1, 1, 0,0,0)
-			.earliest()
-			.unwrap()
-			.into()),
-		"month" => Ok(Utc
-			.with_ymd_and_hms(val.year(), val.month(), 1, 0,0,0)
-			.earliest()
-			.unwrap()
-			.into()),
-		"day" => Ok(Utc
-			.with_ymd_and_hms(val.year(), val.month(), val.day(), 0,0,0)
-			.earliest()
-			.unwrap()
-			.into()),
-		"hour" => Ok(Utc
-			.with_ymd_and_hms(val.year(), val.month(), val.day(), val.hour(),0,0)
-			.earliest()
-			.unwrap()
-			.into()),
-		"minute" => Ok(Utc
-			.with_ymd_and_hms(val.year(), val.month(), val.day(), val.hour(), val.minute(),0)
-			.earliest()
-			.unwrap()
-			.into()),
-		"second" => Ok(Utc
-			.with_ymd_and_hms(val.year(), val.month(), val.day(), val.hour(), val.minute(), val.second())
-			.earliest()
-			.unwrap()
-			.into()),
-		_ => Err(Error::InvalidArguments {
+pub fn group(ctx: &Context, (val, group): (Datetime, String)) -> Result<Value, Error> {
+	// Group by the date and time in the session timezone
+	let tz = session_timezone(ctx).unwrap_or(Timezone::Fixed(Utc.fix()));
+	let v = val.with_timezone(&tz.offset_at(&val));
+	let date = |month, day| NaiveDate::from_ymd_opt(v.year(), month, day);
+	let res = match group.as_str() {
+		"year" => date(1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+		"month" => date(v.month(), 1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+		"day" => date(v.month(), v.day()).and_then(|d| d.and_hms_opt(0, 0, 0)),
+		"hour" => date(v.month(), v.day()).and_then(|d| d.and_hms_opt(v.hour(), 0, 0)),
+		"minute" => {
+			date(v.month(), v.day()).and_then(|d| d.and_hms_opt(v.hour(), v.minute(), 0))
+		}
+		"second" => date(v.month(), v.day())
+			.and_then(|d| d.and_hms_opt(v.hour(), v.minute(), v.second())),
+		_ => return Err(Error::InvalidArguments {
 			name: String::from("time::group"),
 			message: String::from("The second argument must be a string, and can be one of 'year', 'month', 'day', 'hour', 'minute', or 'second'."),
 		}),
+	};
+	// Convert the start of the group back into an instant
+	match res.and_then(|v| tz.from_local(&v)) {
+		Some(v) => Ok(v.into()),
+		None => Err(Error::InvalidArguments {
+			name: String::from("time::group"),
+			message: String::from("The start of the group does not exist in the session timezone."),
+		}),
 	}
 }
 
-pub fn hour((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.hour().into(),
-		None => Datetime::default().hour().into(),
-	})
+pub fn hour(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).hour().into())
 }
 
 pub fn max((array,): (Vec<Datetime>,)) -> Result<Value, Error> {
@@ -134,18 +142,12 @@ pub fn min((array,): (Vec<Datetime>,)) -> Result<Value, Error> {
 	})
 }
 
-pub fn minute((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.minute().into(),
-		None => Datetime::default().minute().into(),
-	})
+pub fn minute(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).minute().into())
 }
 
-pub fn month((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.month().into(),
-		None => Datetime::default().month().into(),
-	})
+pub fn month(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).month().into())
 }
 
 pub fn nano((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
@@ -170,6 +172,8 @@ pub fn micros((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
 }
 
 pub fn now(_: ()) -> Result<Value, Error> {
+	// The current instant is the same in every timezone, so the session
+	// timezone is applied when the datetime is formatted or decomposed
 	Ok(Datetime::default().into())
 }
 
@@ -195,15 +199,15 @@ pub fn round((val, duration): (Datetime, Duration)) -> Result<Value, Error> {
 	}
 }
 
-pub fn second((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.second().into(),
-		None => Datetime::default().second().into(),
-	})
+pub fn second(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).second().into())
 }
 
-pub fn timezone(_: ()) -> Result<Value, Error> {
-	Ok(Local::now().offset().to_string().into())
+pub fn timezone(ctx: &Context, _: ()) -> Result<Value, Error> {
+	Ok(match session_timezone(ctx) {
+		Some(tz) => tz.to_string().into(),
+		None => Local::now().offset().to_string().into(),
+	})
 }
 
 pub fn unix((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
@@ -213,44 +217,33 @@ pub fn unix((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
 	})
 }
 
-pub fn wday((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.weekday().number_from_monday().into(),
-		None => Datetime::default().weekday().number_from_monday().into(),
-	})
+pub fn wday(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).weekday().number_from_monday().into())
 }
 
-pub fn week((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.iso_week().week().into(),
-		None => Datetime::default().iso_week().week().into(),
-	})
+pub fn week(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).iso_week().week().into())
 }
 
-pub fn yday((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.ordinal().into(),
-		None => Datetime::default().ordinal().into(),
-	})
+pub fn yday(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).ordinal().into())
 }
 
-pub fn year((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-	Ok(match val {
-		Some(v) => v.year().into(),
-		None => Datetime::default().year().into(),
-	})
+pub fn year(ctx: &Context, (Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
+	Ok(local(ctx, val).year().into())
 }
 
 pub mod is {
+	use crate::ctx::Context;
 	use crate::err::Error;
 	use crate::fnc::args::Optional;
 	use crate::sql::{Datetime, Value};
 
-	pub fn leap_year((Optional(val),): (Optional<Datetime>,)) -> Result<Value, Error> {
-		Ok(match val {
-			Some(v) => v.naive_utc().date().leap_year().into(),
-			None => Datetime::default().naive_utc().date().leap_year().into(),
-		})
+	pub fn leap_year(
+		ctx: &Context,
+		(Optional(val),): (Optional<Datetime>,),
+	) -> Result<Value, Error> {
+		Ok(super::local(ctx, val).date_naive().leap_year().into())
 	}
 }
 
@@ -0,0 +1,74 @@
+use crate::ctx::Context;
+use crate::sql::paths::LC;
+use crate::sql::value::Value;
+use icu_collator::{Collator, CollatorOptions, Numeric};
+use icu_provider::DataLocale;
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+/// Compares strings using the collation rules of a locale
+pub struct Collation {
+	/// The collator used for lexical comparison
+	lexical: Collator,
+	/// The collator used for lexical comparison, with digits compared numerically
+	numeric: Collator,
+}
+
+impl Collation {
+	/// Creates the collation for a locale identifier, such as `sv` or `de-DE`
+	pub fn new(lc: &str) -> Option<Self> {
+		let locale = lc.parse::<DataLocale>().ok()?;
+		let mut options = CollatorOptions::new();
+		let lexical = Collator::try_new(&locale, options).ok()?;
+		options.numeric = Some(Numeric::On);
+		let numeric = Collator::try_new(&locale, options).ok()?;
+		Some(Self {
+			lexical,
+			numeric,
+		})
+	}
+
+	/// Fetches the collation for the locale set on the current session, if any
+	pub fn from_ctx(ctx: &Context) -> Option<Arc<Self>> {
+		match ctx.value("session").unwrap_or(&Value::None).pick(LC.as_ref()) {
+			Value::Strand(v) => Self::new(v.as_str()).map(Arc::new),
+			_ => None,
+		}
+	}
+
+	/// Compares two strings, comparing any digits numerically if requested
+	pub fn compare(&self, a: &str, b: &str, numeric: bool) -> Ordering {
+		match numeric {
+			true => self.numeric.compare(a, b),
+			false => self.lexical.compare(a, b),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn collation_follows_locale() {
+		// Swedish sorts `ä` after `z`
+		let sv = Collation::new("sv").unwrap();
+		assert_eq!(sv.compare("ä", "z", false), Ordering::Greater);
+		// German sorts `ä` alongside `a`
+		let de = Collation::new("de").unwrap();
+		assert_eq!(de.compare("ä", "z", false), Ordering::Less);
+		assert_eq!(de.compare("äb", "ac", false), Ordering::Less);
+	}
+
+	#[test]
+	fn collation_numeric() {
+		let en = Collation::new("en").unwrap();
+		assert_eq!(en.compare("item 10", "item 9", false), Ordering::Less);
+		assert_eq!(en.compare("item 10", "item 9", true), Ordering::Greater);
+	}
+
+	#[test]
+	fn collation_invalid_locale() {
+		assert!(Collation::new("not a locale").is_none());
+	}
+}
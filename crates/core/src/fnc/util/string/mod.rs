@@ -1,2 +1,3 @@
+pub mod collation;
 pub mod fuzzy;
 pub mod slug;
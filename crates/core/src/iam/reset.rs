@@ -11,6 +11,8 @@ pub fn reset(session: &mut Session) -> Result<(), Error> {
 	session.rd = None;
 	session.ns = None;
 	session.db = None;
+	session.tz = None;
+	session.lc = None;
 	session.parameters = BTreeMap::new();
 	Ok(())
 }
@@ -144,7 +144,8 @@ impl<'a> TreeBuilder<'a> {
 	}
 
 	async fn eval_order(&mut self) -> Result<(), Error> {
-		if let Some(o) = self.first_order {
+		// An index can not place null values first or last on request
+		if let Some(o) = self.first_order.filter(|o| o.nulls_first.is_none()) {
 			if let Node::IndexedField(id, irf) = self.resolve_idiom(&o.value).await? {
 				for (ixr, id_col) in &irf {
 					if *id_col == 0 {
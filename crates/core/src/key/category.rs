@@ -92,6 +92,8 @@ pub enum Category {
 	DatabaseConfig,
 	/// crate::key::database::sq             /*{ns}*{db}*sq{sq}
 	DatabaseSequence,
+	/// crate::key::database::wh             /*{ns}*{db}!wh{wh}
+	DatabaseWebhook,
 	///
 	/// ------------------------------
 	///
@@ -215,6 +217,7 @@ impl Display for Category {
 			Self::DatabaseVersionstamp => "DatabaseVersionstamp",
 			Self::DatabaseSequence => "DatabaseSequence",
 			Self::DatabaseConfig => "DatabaseConfig",
+			Self::DatabaseWebhook => "DatabaseWebhook",
 			Self::TableRoot => "TableRoot",
 			Self::TableEvent => "TableEvent",
 			Self::TableField => "TableField",
@@ -14,3 +14,4 @@ pub mod ti;
 pub mod ts;
 pub mod us;
 pub mod vs;
+pub mod wh;
@@ -39,6 +39,7 @@
 /// crate::key::database::us             /*{ns}*{db}!us{us}
 /// crate::key::database::vs             /*{ns}*{db}!vs
 /// crate::key::database::cg             /*{ns}*{db}!cg{ty}
+/// crate::key::database::wh             /*{ns}*{db}!wh{wh}
 ///
 /// crate::key::database::access::all    /*{ns}*{db}&{ac}
 /// crate::key::database::access::gr     /*{ns}*{db}&{ac}!gr{gr}
@@ -2,6 +2,10 @@ use crate::err::Error;
 use crate::kvs::Datastore;
 use crate::kvs::{LockType::*, TransactionType::*};
 use crate::vs::VersionStamp;
+#[cfg(not(target_family = "wasm"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(target_family = "wasm")]
+use wasmtimer::std::{SystemTime, UNIX_EPOCH};
 
 impl Datastore {
 	/// Saves the current timestamp for each database's current versionstamp.
@@ -38,6 +42,73 @@ impl Datastore {
 		Ok(vs)
 	}
 
+	/// Allocates the next versionstamp of a database, which is ordered
+	/// after every transaction already committed to the database.
+	///
+	/// This advances the versionstamp of the database, and so requires
+	/// a separate write transaction which is committed to the datastore.
+	#[instrument(level = "trace", target = "surrealdb::core::kvs::ds", skip(self))]
+	pub async fn next_versionstamp(&self, ns: &str, db: &str) -> Result<VersionStamp, Error> {
+		// Create a new transaction
+		let txn = self.transaction(Write, Optimistic).await?;
+		// Advance the versionstamp of the database
+		let key = crate::key::database::vs::new(ns, db);
+		let vs = catch!(txn, txn.lock().await.get_timestamp(key).await);
+		// Commit the changes
+		catch!(txn, txn.commit().await);
+		// Return the version
+		Ok(vs)
+	}
+
+	/// Fetches the latest versionstamp of a database, if one has been allocated.
+	#[instrument(level = "trace", target = "surrealdb::core::kvs::ds", skip(self))]
+	pub async fn current_versionstamp(
+		&self,
+		ns: &str,
+		db: &str,
+	) -> Result<Option<VersionStamp>, Error> {
+		// Create a new transaction
+		let txn = self.transaction(Read, Optimistic).await?;
+		// Fetch the versionstamp of the database
+		let key = crate::key::database::vs::new(ns, db);
+		let vs = catch!(txn, txn.get(key, None).await);
+		// Cancel the transaction
+		catch!(txn, txn.cancel().await);
+		// Return the version
+		Ok(vs.map(|v| VersionStamp::from_slice(&v)).transpose()?)
+	}
+
+	/// Saves the timestamp for the current versionstamp of a database, and
+	/// deletes the change feed entries of a table which have expired.
+	///
+	/// Unlike [`Datastore::changefeed_process`], this only touches the
+	/// change feed entries of the specified table.
+	#[instrument(level = "trace", target = "surrealdb::core::kvs::ds", skip(self))]
+	pub async fn changefeed_process_table(
+		&self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<(), Error> {
+		// Calculate the current system time
+		let ts = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_err(|e| {
+				Error::Internal(format!("Clock may have gone backwards: {:?}", e.duration()))
+			})?
+			.as_secs();
+		// Save the timestamp for the current versionstamp
+		let txn = self.transaction(Write, Optimistic).await?;
+		catch!(txn, txn.lock().await.set_timestamp_for_versionstamp(ts, ns, db).await);
+		catch!(txn, txn.commit().await);
+		// Perform the garbage collection
+		let txn = self.transaction(Write, Optimistic).await?;
+		catch!(txn, crate::cf::gc_tb(&txn, ts, ns, db, tb).await);
+		catch!(txn, txn.commit().await);
+		// Everything ok
+		Ok(())
+	}
+
 	/// Deletes all change feed entries that are older than the timestamp.
 	#[instrument(level = "trace", target = "surrealdb::core::kvs::ds", skip(self))]
 	pub(crate) async fn changefeed_cleanup(&self, ts: u64) -> Result<(), Error> {
@@ -4,7 +4,8 @@ use super::tx::Transaction;
 use super::version::Version;
 use crate::buc::BucketConnections;
 use crate::cf;
-use crate::ctx::MutableContext;
+use crate::cnf::PROTECTED_PARAM_NAMES;
+use crate::ctx::{Canceller, Context, MutableContext};
 #[cfg(feature = "jwks")]
 use crate::dbs::capabilities::NetTarget;
 use crate::dbs::capabilities::{
@@ -36,6 +37,7 @@ use bytes::{Bytes, BytesMut};
 use dashmap::DashMap;
 use futures::{Future, Stream};
 use reblessive::TreeStack;
+use std::collections::BTreeMap;
 use std::fmt;
 #[cfg(storage)]
 use std::path::PathBuf;
@@ -647,6 +649,8 @@ impl Datastore {
 		self.expire_nodes().await?;
 		// Remove archived nodes
 		self.remove_nodes().await?;
+		// Remove the temporary tables of inactive nodes
+		self.remove_orphaned_temp_tables().await?;
 		// Everything ok
 		Ok(())
 	}
@@ -921,29 +925,216 @@ impl Datastore {
 		sess: &Session,
 		vars: Variables,
 	) -> Result<Vec<Response>, Error> {
-		// Check if the session has expired
-		if sess.expired() {
-			return Err(Error::ExpiredSession);
+		// Prepare the context and options of the query
+		let (ctx, opt) = self.prepare_execution(sess, vars)?;
+		// Process all statements
+		Executor::execute(self, ctx, opt, ast).await
+	}
+
+	/// Execute a pre-parsed SQL query within a single read-only snapshot
+	///
+	/// All statements see the same consistent view of the data, and the
+	/// query is rejected if any of its statements could write.
+	///
+	/// ```rust,no_run
+	/// use surrealdb_core::kvs::Datastore;
+	/// use surrealdb_core::err::Error;
+	/// use surrealdb_core::dbs::Session;
+	/// use surrealdb_core::sql::parse;
+	///
+	/// #[tokio::main]
+	/// async fn main() -> Result<(), Error> {
+	///     let ds = Datastore::new("memory").await?;
+	///     let ses = Session::owner().with_ns("test").with_db("test");
+	///     let ast = parse("SELECT * FROM person; SELECT count() FROM person GROUP ALL;")?;
+	///     let res = ds.process_snapshot(ast, &ses, None).await?;
+	///     Ok(())
+	/// }
+	/// ```
+	#[instrument(level = "debug", target = "surrealdb::core::kvs::ds", skip_all)]
+	pub async fn process_snapshot(
+		&self,
+		ast: Query,
+		sess: &Session,
+		vars: Variables,
+	) -> Result<Vec<Response>, Error> {
+		// Prepare the context and options of the query
+		let (ctx, opt) = self.prepare_execution(sess, vars)?;
+		// Process all statements within a single snapshot
+		Executor::execute_snapshot(self, ctx, opt, ast).await
+	}
+
+	/// Execute a pre-parsed SQL query, stopping at the first failed statement
+	///
+	/// Any statements after a failed statement are not executed, and are
+	/// returned with an error, instead of being executed independently.
+	///
+	/// ```rust,no_run
+	/// use surrealdb_core::kvs::Datastore;
+	/// use surrealdb_core::err::Error;
+	/// use surrealdb_core::dbs::Session;
+	/// use surrealdb_core::sql::parse;
+	///
+	/// #[tokio::main]
+	/// async fn main() -> Result<(), Error> {
+	///     let ds = Datastore::new("memory").await?;
+	///     let ses = Session::owner().with_ns("test").with_db("test");
+	///     let ast = parse("CREATE person:tobie; CREATE person:jaime;")?;
+	///     let res = ds.process_fail_fast(ast, &ses, None).await?;
+	///     Ok(())
+	/// }
+	/// ```
+	#[instrument(level = "debug", target = "surrealdb::core::kvs::ds", skip_all)]
+	pub async fn process_fail_fast(
+		&self,
+		ast: Query,
+		sess: &Session,
+		vars: Variables,
+	) -> Result<Vec<Response>, Error> {
+		// Prepare the context and options of the query
+		let (ctx, opt) = self.prepare_execution(sess, vars)?;
+		// Process all statements until one fails
+		Executor::execute_fail_fast(self, ctx, opt, ast).await
+	}
+
+	/// Execute a pre-parsed SQL query as a migration within a single transaction
+	///
+	/// Any statements after a failed statement are not executed, and the whole
+	/// migration is rolled back. In a dry run the migration is always rolled back.
+	///
+	/// ```rust,no_run
+	/// use surrealdb_core::kvs::Datastore;
+	/// use surrealdb_core::err::Error;
+	/// use surrealdb_core::dbs::Session;
+	/// use surrealdb_core::sql::parse;
+	///
+	/// #[tokio::main]
+	/// async fn main() -> Result<(), Error> {
+	///     let ds = Datastore::new("memory").await?;
+	///     let ses = Session::owner().with_ns("test").with_db("test");
+	///     let ast = parse("DEFINE FIELD name ON person TYPE string; UPDATE person SET name = 'unknown';")?;
+	///     let res = ds.process_migration(ast, &ses, None, false).await?;
+	///     Ok(())
+	/// }
+	/// ```
+	#[instrument(level = "debug", target = "surrealdb::core::kvs::ds", skip_all)]
+	pub async fn process_migration(
+		&self,
+		ast: Query,
+		sess: &Session,
+		vars: Variables,
+		dry_run: bool,
+	) -> Result<Vec<Response>, Error> {
+		// Prepare the context and options of the query
+		let (ctx, opt) = self.prepare_execution(sess, vars)?;
+		// Process all statements within a single transaction
+		Executor::execute_migration(self, ctx, opt, ast, dry_run).await
+	}
+
+	/// Execute a pre-parsed SQL query once for each set of variables, within a single transaction
+	///
+	/// The responses of each set of variables are returned up to the first set
+	/// which failed, in which case the whole batch is rolled back.
+	///
+	/// ```rust,no_run
+	/// use std::collections::BTreeMap;
+	/// use surrealdb_core::kvs::Datastore;
+	/// use surrealdb_core::err::Error;
+	/// use surrealdb_core::dbs::Session;
+	/// use surrealdb_core::sql::parse;
+	///
+	/// #[tokio::main]
+	/// async fn main() -> Result<(), Error> {
+	///     let ds = Datastore::new("memory").await?;
+	///     let ses = Session::owner().with_ns("test").with_db("test");
+	///     let ast = parse("CREATE person SET name = $name;")?;
+	///     let sets = vec![
+	///         BTreeMap::from([("name".to_string(), "Tobie".into())]),
+	///         BTreeMap::from([("name".to_string(), "Jaime".into())]),
+	///     ];
+	///     let res = ds.process_batch(ast, &ses, None, sets).await?;
+	///     Ok(())
+	/// }
+	/// ```
+	#[instrument(level = "debug", target = "surrealdb::core::kvs::ds", skip_all)]
+	pub async fn process_batch(
+		&self,
+		ast: Query,
+		sess: &Session,
+		vars: Variables,
+		sets: Vec<BTreeMap<String, Value>>,
+	) -> Result<Vec<Vec<Response>>, Error> {
+		// Prepare the context and options of the query
+		let (ctx, opt) = self.prepare_execution(sess, vars)?;
+		// Check that no set of variables sets a protected variable
+		for key in sets.iter().flat_map(|v| v.keys()) {
+			if PROTECTED_PARAM_NAMES.contains(&key.as_str()) {
+				return Err(Error::InvalidParam {
+					name: key.to_owned(),
+				});
+			}
 		}
-		// Check if anonymous actors can execute queries when auth is enabled
-		// TODO(sgirones): Check this as part of the authorisation layer
-		self.check_anon(sess).map_err(|_| IamError::NotAllowed {
-			actor: "anonymous".to_string(),
-			action: "process".to_string(),
-			resource: "query".to_string(),
-		})?;
+		// Process all statements for each set of variables
+		Executor::execute_batch(self, ctx, opt, ast, sets).await
+	}
 
-		// Create a new query options
-		let opt = self.setup_options(sess);
+	/// Execute a pre-parsed SQL query, streaming the response of each statement
+	///
+	/// The response of each statement is sent to the channel as soon as the
+	/// statement completes, and the responses within a transaction are sent
+	/// once the transaction is committed or cancelled.
+	///
+	/// ```rust,no_run
+	/// use surrealdb_core::kvs::Datastore;
+	/// use surrealdb_core::err::Error;
+	/// use surrealdb_core::dbs::Session;
+	/// use surrealdb_core::sql::parse;
+	///
+	/// #[tokio::main]
+	/// async fn main() -> Result<(), Error> {
+	///     let ds = Datastore::new("memory").await?;
+	///     let ses = Session::owner().with_ns("test").with_db("test");
+	///     let ast = parse("CREATE person:tobie; SLEEP 1s; CREATE person:jaime;")?;
+	///     let (snd, rcv) = surrealdb_core::channel::unbounded();
+	///     ds.process_streamed(ast, &ses, None, false, snd).await?;
+	///     while let Ok(res) = rcv.try_recv() {
+	///         println!("{:?}", res.result);
+	///     }
+	///     Ok(())
+	/// }
+	/// ```
+	#[instrument(level = "debug", target = "surrealdb::core::kvs::ds", skip_all)]
+	pub async fn process_streamed(
+		&self,
+		ast: Query,
+		sess: &Session,
+		vars: Variables,
+		fail_fast: bool,
+		chn: Sender<Response>,
+	) -> Result<(), Error> {
+		// Prepare the context and options of the query
+		let (ctx, opt) = self.prepare_execution(sess, vars)?;
+		// Process all statements, sending each response as it completes
+		Executor::execute_streamed(self, ctx, opt, ast, fail_fast, chn).await
+	}
 
-		// Create a default context
-		let mut ctx = self.setup_ctx()?;
-		// Start an execution context
-		sess.context(&mut ctx);
-		// Store the query variables
-		vars.attach(&mut ctx)?;
-		// Process all statements
-		Executor::execute(self, ctx.freeze(), opt, ast).await
+	/// Prepares a pre-parsed SQL query for execution, which can be stopped with the returned [`Canceller`]
+	///
+	/// Once cancelled, no further statements are executed, and the transaction
+	/// of any statement which is still running is cancelled.
+	pub(crate) fn process_cancellable(
+		&self,
+		ast: Query,
+		sess: &Session,
+		vars: Variables,
+	) -> Result<(Canceller, impl Future<Output = Result<Vec<Response>, Error>> + '_), Error> {
+		// Prepare the context and options of the query
+		let (ctx, opt) = self.prepare_execution(sess, vars)?;
+		// Add cancellation to the context of the query
+		let mut ctx = MutableContext::new(&ctx);
+		let canceller = ctx.add_cancel();
+		// Process all statements, until the query is cancelled
+		Ok((canceller, Executor::execute(self, ctx.freeze(), opt, ast)))
 	}
 
 	/// Ensure a SQL [`Value`] is fully computed
@@ -1198,8 +1389,36 @@ impl Datastore {
 		Ok(())
 	}
 
+	/// Checks that the session can execute a query, and prepares the context and options to execute it with
+	fn prepare_execution(
+		&self,
+		sess: &Session,
+		vars: Variables,
+	) -> Result<(Context, Options), Error> {
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession);
+		}
+		// Check if anonymous actors can execute queries when auth is enabled
+		// TODO(sgirones): Check this as part of the authorisation layer
+		self.check_anon(sess).map_err(|_| IamError::NotAllowed {
+			actor: "anonymous".to_string(),
+			action: "process".to_string(),
+			resource: "query".to_string(),
+		})?;
+		// Create a new query options
+		let opt = self.setup_options(sess);
+		// Create a default context
+		let mut ctx = self.setup_ctx()?;
+		// Start an execution context
+		sess.context(&mut ctx);
+		// Store the query variables
+		vars.attach(&mut ctx)?;
+		Ok((ctx.freeze(), opt))
+	}
+
 	pub fn setup_options(&self, sess: &Session) -> Options {
-		Options::default()
+		let opt = Options::default()
 			.with_id(self.id)
 			.with_ns(sess.ns())
 			.with_db(sess.db())
@@ -1207,6 +1426,13 @@ impl Datastore {
 			.with_auth(sess.au.clone())
 			.with_strict(self.strict)
 			.with_auth_enabled(self.auth_enabled)
+			.with_version(sess.as_of)
+			.with_reveal(Some(Arc::from(sess.reveal.as_slice())));
+		// Leave FUTURE values uncomputed if requested
+		match sess.skip_futures {
+			true => opt.with_futures_never(),
+			false => opt,
+		}
 	}
 	pub fn setup_ctx(&self) -> Result<MutableContext, Error> {
 		let mut ctx = MutableContext::from_ds(
@@ -25,10 +25,12 @@ mod live;
 mod node;
 mod scanner;
 mod stash;
+mod temp;
 mod threadpool;
 mod tr;
 mod tx;
 mod version;
+mod webhook;
 
 mod fdb;
 mod indxdb;
@@ -52,6 +54,7 @@ pub use key::{KeyDecode, KeyDecodeOwned, KeyEncode};
 
 pub use ds::Datastore;
 pub use live::Live;
+pub(crate) use webhook::Webhook;
 pub use tr::{Check, LockType, TransactionType, Transactor};
 pub use tx::Transaction;
 
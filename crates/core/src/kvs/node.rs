@@ -294,6 +294,22 @@ impl Datastore {
 	/// cleaned up and removed.
 	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::node", skip(self))]
 	pub async fn delete_queries(&self, ids: Vec<uuid::Uuid>) -> Result<(), Error> {
+		self.delete_node_queries(self.id(), ids).await
+	}
+
+	/// Clean up the live queries which are registered on a node.
+	///
+	/// This function should be run when killing orphaned live queries.
+	///
+	/// This function clears up the live queries on the specified node, which
+	/// are specified by unique live query UUIDs. This is necessary when live
+	/// queries are no longer owned by any connection in the cluster.
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::node", skip(self))]
+	pub async fn delete_node_queries(
+		&self,
+		nd: uuid::Uuid,
+		ids: Vec<uuid::Uuid>,
+	) -> Result<(), Error> {
 		// Log the node deletion
 		trace!(target: TARGET, "Deleting live queries for a connection");
 		// Fetch expired nodes
@@ -301,13 +317,13 @@ impl Datastore {
 		// Loop over the live query unique ids
 		for id in ids.into_iter() {
 			// Get the key for this node live query
-			let nlq = crate::key::node::lq::new(self.id(), id);
+			let nlq = crate::key::node::lq::new(nd, id);
 			// Fetch the LIVE meta data node entry
 			if let Some(val) = catch!(txn, txn.get(nlq, None).await) {
 				// Decode the data for this live query
 				let lq: Live = revision::from_slice(&val)?;
 				// Get the key for this node live query
-				let nlq = crate::key::node::lq::new(self.id(), id);
+				let nlq = crate::key::node::lq::new(nd, id);
 				// Get the key for this table live query
 				let tlq = crate::key::table::lq::new(&lq.ns, &lq.db, &lq.tb, id);
 				// Delete the table live query
@@ -321,4 +337,82 @@ impl Datastore {
 		// All ok
 		Ok(())
 	}
+
+	/// Lists the live queries which are registered on every node in the cluster.
+	///
+	/// This function should be run when checking for orphaned live queries.
+	///
+	/// This function scans all of the live queries on each node in the cluster,
+	/// and returns the node on which each live query is registered, its unique
+	/// live query UUID, the namespace, database, and table on which the live
+	/// query is defined, and the live query definition, if it still exists.
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::node", skip(self))]
+	#[expect(clippy::type_complexity)]
+	pub async fn cluster_queries(
+		&self,
+	) -> Result<Vec<(Node, uuid::Uuid, Live, Option<LiveStatement>)>, Error> {
+		// Log the live query listing
+		trace!(target: TARGET, "Listing live queries for the cluster");
+		// Open a readonly transaction
+		let txn = self.transaction(Read, Optimistic).await?;
+		let nds = catch!(txn, txn.all_nodes().await);
+		let mut out = Vec::new();
+		// Loop over the nodes in the cluster
+		for nd in nds.iter() {
+			let beg = crate::key::node::lq::prefix(nd.id)?;
+			let end = crate::key::node::lq::suffix(nd.id)?;
+			let mut next = Some(beg..end);
+			// Scan the live queries for this node
+			while let Some(rng) = next {
+				// Fetch the next batch of keys and values
+				let max = *NORMAL_FETCH_SIZE;
+				let res = catch!(txn, txn.batch_keys_vals(rng, max, None).await);
+				next = res.next;
+				for (k, v) in res.result.iter() {
+					// Decode the data for this live query
+					let val: Live = revision::from_slice(v)?;
+					// Get the key for this node live query
+					let nlq = catch!(txn, crate::key::node::lq::Lq::decode(k));
+					// Get the key for this table live query
+					let tlq = crate::key::table::lq::new(&val.ns, &val.db, &val.tb, nlq.lq);
+					// Fetch the definition of the live query
+					let stm = match catch!(txn, txn.get(tlq, None).await) {
+						Some(v) => Some(revision::from_slice::<LiveStatement>(&v)?),
+						None => None,
+					};
+					// Store the live query
+					out.push((nd.clone(), nlq.lq, val, stm));
+				}
+				// Pause and yield execution
+				yield_now!();
+			}
+		}
+		// Cancel the read transaction
+		txn.cancel().await?;
+		// All ok
+		Ok(out)
+	}
+
+	/// Checks if a live query is still registered on this node.
+	///
+	/// This function should be run when a WebSocket checks a live query.
+	///
+	/// This function checks whether the live query, which is specified by
+	/// a unique live query UUID, has not yet been killed or cleaned up on
+	/// the current node.
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::node", skip(self))]
+	pub async fn has_query(&self, id: uuid::Uuid) -> Result<bool, Error> {
+		// Log the live query check
+		trace!(target: TARGET, "Checking a live query for a connection");
+		// Fetch the live query
+		let txn = self.transaction(Read, Optimistic).await?;
+		// Get the key for this node live query
+		let nlq = crate::key::node::lq::new(self.id(), id);
+		// Check if the LIVE meta data node entry exists
+		let res = catch!(txn, txn.exists(nlq, None).await);
+		// Cancel the read transaction
+		txn.cancel().await?;
+		// All ok
+		Ok(res)
+	}
 }
@@ -0,0 +1,137 @@
+use crate::dbs::Session;
+use crate::err::Error;
+use crate::kvs::Datastore;
+use crate::kvs::{LockType::*, TransactionType::*};
+use crate::sql::Table;
+
+const TARGET: &str = "surrealdb::core::kvs::temp";
+
+/// The comment which marks a table as a temporary table of a connection
+const TEMP_TABLE_COMMENT: &str = "Temporary table, removed when the connection closes";
+
+impl Datastore {
+	/// Generates the unique comment which marks a temporary table defined
+	/// on a connection to this node, so that the table is only ever removed
+	/// if it has not since been removed and defined again by someone else.
+	pub fn temp_table_marker(&self) -> String {
+		format!("{TEMP_TABLE_COMMENT} (node {}, id {})", self.id(), uuid::Uuid::new_v4())
+	}
+
+	/// Removes a temporary table which was defined on a connection.
+	///
+	/// This function should be run when a connection is reset or closed.
+	///
+	/// This function removes the table with the session which defined it,
+	/// but only if the table is still marked with the comment with which
+	/// it was defined, so that a table which has since been defined again
+	/// is never removed.
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::temp", skip(self, sess))]
+	pub async fn remove_temp_table(
+		&self,
+		sess: &Session,
+		tb: &str,
+		marker: &str,
+	) -> Result<(), Error> {
+		// Get the namespace and database of the table
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Check that the table is still this temporary table
+		let txn = self.transaction(Read, Optimistic).await?;
+		let res = txn.get_tb(ns, db, tb).await;
+		txn.cancel().await?;
+		match res {
+			Ok(v) if v.comment.as_ref().is_some_and(|v| v.as_str() == marker) => (),
+			Ok(_)
+			| Err(Error::TbNotFound {
+				..
+			}) => return Ok(()),
+			Err(e) => return Err(e),
+		}
+		// The session may have expired since the table was defined
+		let mut sess = sess.clone();
+		sess.exp = None;
+		// Remove the table with the session which defined it
+		trace!(target: TARGET, "Removing temporary table {ns}/{db}/{tb}");
+		let sql = format!("REMOVE TABLE IF EXISTS {}", Table::from(tb));
+		for res in self.execute(&sql, &sess, None).await? {
+			res.result?;
+		}
+		Ok(())
+	}
+
+	/// Removes the temporary tables which were left behind by connections.
+	///
+	/// This function should be run at server or database startup.
+	///
+	/// This function removes the temporary tables which were defined on
+	/// connections to nodes which are no longer active in the cluster, and
+	/// so could not be removed when those connections were closed, such as
+	/// when a node was stopped without shutting down gracefully.
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::temp", skip(self))]
+	pub async fn remove_orphaned_temp_tables(&self) -> Result<(), Error> {
+		// Log when this method is run
+		trace!(target: TARGET, "Removing orphaned temporary tables");
+		// Find the temporary tables of nodes which are no longer active
+		let mut orphaned = Vec::new();
+		{
+			let txn = self.transaction(Read, Optimistic).await?;
+			let nds = catch!(txn, txn.all_nodes().await);
+			let nss = catch!(txn, txn.all_ns().await);
+			for ns in nss.iter() {
+				let dbs = catch!(txn, txn.all_db(&ns.name).await);
+				for db in dbs.iter() {
+					let tbs = catch!(txn, txn.all_tb(&ns.name, &db.name, None).await);
+					for tb in tbs.iter() {
+						// Check that the table is marked as a temporary table
+						let Some(nd) = tb.comment.as_ref().and_then(|v| Self::temp_table_node(v))
+						else {
+							continue;
+						};
+						// Check that the node which defined the table is no longer active
+						if nds.iter().any(|n| n.id == nd && n.is_active() && n.id != self.id()) {
+							continue;
+						}
+						orphaned.push((ns.name.clone(), db.name.clone(), tb.name.to_raw()));
+					}
+				}
+			}
+			txn.cancel().await?;
+		}
+		// Remove the orphaned temporary tables
+		for (ns, db, tb) in orphaned {
+			trace!(target: TARGET, "Removing orphaned temporary table {ns}/{db}/{tb}");
+			let sess = Session::owner().with_ns(&ns).with_db(&db);
+			let sql = format!("REMOVE TABLE IF EXISTS {}", Table::from(tb));
+			for res in self.execute(&sql, &sess, None).await? {
+				res.result?;
+			}
+		}
+		// All ok
+		Ok(())
+	}
+
+	/// Retrieves the node which defined a temporary table from its comment
+	fn temp_table_node(comment: &str) -> Option<uuid::Uuid> {
+		let rest = comment.strip_prefix(TEMP_TABLE_COMMENT)?.strip_prefix(" (node ")?;
+		let (nd, _) = rest.split_once(", id ")?;
+		uuid::Uuid::parse_str(nd).ok()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn temp_table_marker_contains_the_node() {
+		let ds = Datastore::new("memory").await.unwrap();
+		let marker = ds.temp_table_marker();
+		assert!(marker.starts_with(TEMP_TABLE_COMMENT));
+		assert_eq!(Datastore::temp_table_node(&marker), Some(ds.id()));
+		// Each marker is unique
+		assert_ne!(marker, ds.temp_table_marker());
+		// Other comments are not temporary table markers
+		assert_eq!(Datastore::temp_table_node(TEMP_TABLE_COMMENT), None);
+		assert_eq!(Datastore::temp_table_node("A table"), None);
+	}
+}
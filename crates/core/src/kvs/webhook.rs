@@ -0,0 +1,317 @@
+use crate::cf::TableMutation;
+use crate::dbs::Session;
+use crate::err::Error;
+use crate::fnc::args::Optional;
+use crate::kvs::Datastore;
+use crate::kvs::{LockType::*, TransactionType::*};
+use crate::sql::statements::show::ShowSince;
+use crate::sql::statements::SelectStatement;
+use crate::sql::{Datetime, Field, Fields, Param, Subquery, Value, Values};
+use chrono::Utc;
+use revision::revisioned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TARGET: &str = "surrealdb::core::kvs::webhook";
+
+/// The maximum number of change sets which are read for a webhook at once
+const WEBHOOK_BATCH_SIZE: u32 = 100;
+
+/// How long a webhook URL has to respond before the delivery fails
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait before retrying the first failed delivery of a webhook
+const WEBHOOK_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The longest time to wait before retrying a failed delivery of a webhook
+const WEBHOOK_MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+#[revisioned(revision = 1)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Webhook {
+	/// The unique id of this webhook
+	pub id: Uuid,
+	/// The URL to which the changes are posted
+	pub url: String,
+	/// The table whose changes are delivered
+	pub tb: String,
+	/// The condition which a change must match to be delivered
+	pub cond: Option<Value>,
+	/// The versionstamp from which the changes are yet to be delivered
+	pub vs: u64,
+	/// The number of consecutive deliveries which have failed
+	pub failures: u32,
+	/// When a failed delivery is next retried
+	pub retry_at: Option<Datetime>,
+	/// The error of the last failed delivery
+	pub error: Option<String>,
+}
+
+impl Webhook {
+	/// Create a new webhook, delivering the changes from a versionstamp
+	pub fn new(url: String, tb: String, cond: Option<Value>, vs: u64) -> Self {
+		Self {
+			id: Uuid::new_v4(),
+			url,
+			tb,
+			cond,
+			vs,
+			failures: 0,
+			retry_at: None,
+			error: None,
+		}
+	}
+	/// Check if a failed delivery is waiting to be retried
+	fn is_waiting(&self) -> bool {
+		self.retry_at.as_ref().is_some_and(|v| v.0 > Utc::now())
+	}
+	/// Record a failed delivery, backing off exponentially before it is retried
+	fn failed(&mut self, err: Error) {
+		let wait = WEBHOOK_RETRY_BACKOFF
+			.saturating_mul(1 << self.failures.min(16))
+			.min(WEBHOOK_MAX_BACKOFF);
+		self.failures = self.failures.saturating_add(1);
+		self.retry_at = chrono::Duration::from_std(wait).ok().map(|v| (Utc::now() + v).into());
+		self.error = Some(err.to_string());
+	}
+	/// Record a successful delivery of the changes up to a versionstamp
+	fn delivered(&mut self, vs: u64) {
+		self.vs = vs + 1;
+		self.failures = 0;
+		self.retry_at = None;
+		self.error = None;
+	}
+}
+
+impl Datastore {
+	/// Stores a webhook which delivers the changes of a table
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::webhook", skip(self))]
+	pub(crate) async fn define_webhook(
+		&self,
+		ns: &str,
+		db: &str,
+		wh: &Webhook,
+	) -> Result<(), Error> {
+		let txn = self.transaction(Write, Optimistic).await?;
+		let key = crate::key::database::wh::new(ns, db, wh.id);
+		catch!(txn, txn.set(key, revision::to_vec(wh)?, None).await);
+		catch!(txn, txn.commit().await);
+		Ok(())
+	}
+
+	/// Retrieves the webhooks of a database
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::webhook", skip(self))]
+	pub(crate) async fn all_webhooks(&self, ns: &str, db: &str) -> Result<Vec<Webhook>, Error> {
+		let txn = self.transaction(Read, Optimistic).await?;
+		let beg = crate::key::database::wh::prefix(ns, db)?;
+		let end = crate::key::database::wh::suffix(ns, db)?;
+		let res = catch!(txn, txn.getr(beg..end, None).await);
+		txn.cancel().await?;
+		res.iter().map(|(_, v)| revision::from_slice(v).map_err(Error::from)).collect()
+	}
+
+	/// Removes a webhook, returning whether the webhook existed
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::webhook", skip(self))]
+	pub(crate) async fn remove_webhook(&self, ns: &str, db: &str, id: Uuid) -> Result<bool, Error> {
+		let txn = self.transaction(Write, Optimistic).await?;
+		let key = crate::key::database::wh::new(ns, db, id);
+		let exists = catch!(txn, txn.exists(key.clone(), None).await);
+		if exists {
+			catch!(txn, txn.del(key).await);
+		}
+		catch!(txn, txn.commit().await);
+		Ok(exists)
+	}
+
+	/// Delivers the changes of the tables with webhooks to the webhook URLs.
+	///
+	/// This function should be run periodically by a background task.
+	///
+	/// The changes are read from the changefeed of each table after they
+	/// have been committed, so a webhook never delays or fails a write. The
+	/// versionstamp of the delivered changes is stored with each webhook,
+	/// so every change is delivered at least once, and a failed delivery is
+	/// retried with an exponential backoff until it succeeds, or until the
+	/// changes are no longer retained by the changefeed.
+	#[instrument(err, level = "trace", target = "surrealdb::core::kvs::webhook", skip(self))]
+	pub async fn webhook_process(&self) -> Result<(), Error> {
+		// Find the webhooks of every database
+		let mut hooks = Vec::new();
+		{
+			let txn = self.transaction(Read, Optimistic).await?;
+			let nss = catch!(txn, txn.all_ns().await);
+			for ns in nss.iter() {
+				let dbs = catch!(txn, txn.all_db(&ns.name).await);
+				for db in dbs.iter() {
+					let beg = crate::key::database::wh::prefix(&ns.name, &db.name)?;
+					let end = crate::key::database::wh::suffix(&ns.name, &db.name)?;
+					for (_, v) in catch!(txn, txn.getr(beg..end, None).await) {
+						let wh: Webhook =
+							catch!(txn, revision::from_slice(&v).map_err(Error::from));
+						hooks.push((ns.name.clone(), db.name.clone(), wh));
+					}
+				}
+			}
+			txn.cancel().await?;
+		}
+		// Deliver the changes of each webhook
+		for (ns, db, wh) in hooks {
+			// Wait until a failed delivery is due to be retried
+			if wh.is_waiting() {
+				continue;
+			}
+			if let Err(e) = self.webhook_deliver(&ns, &db, wh).await {
+				error!(target: TARGET, "Error delivering a webhook in {ns}/{db}: {e}");
+			}
+		}
+		// All ok
+		Ok(())
+	}
+
+	/// Delivers the pending changes of a webhook, in the order in which they were committed
+	async fn webhook_deliver(&self, ns: &str, db: &str, mut wh: Webhook) -> Result<(), Error> {
+		// Read the changes which have not yet been delivered
+		let txn = self.transaction(Read, Optimistic).await?;
+		let since = ShowSince::Versionstamp(wh.vs);
+		let res =
+			crate::cf::read(&txn, ns, db, Some(&wh.tb), since, Some(WEBHOOK_BATCH_SIZE)).await;
+		txn.cancel().await?;
+		let res = res?;
+		if res.is_empty() {
+			return Ok(());
+		}
+		// Deliver each change set in turn
+		let prev = wh.clone();
+		for cs in res {
+			let vs = cs.0.into_u64_lossy();
+			// Collect the changes which match the condition
+			let mut changes = Vec::new();
+			let mut res = Ok(());
+			for mutation in cs.1 .0.into_iter().flat_map(|v| v.1) {
+				match self.webhook_matches(ns, db, &wh, &mutation).await {
+					Ok(true) => changes.push(mutation.into_value()),
+					Ok(false) => (),
+					Err(e) => {
+						res = Err(e);
+						break;
+					}
+				}
+			}
+			// Post the changes to the webhook URL
+			if res.is_ok() && !changes.is_empty() {
+				trace!(target: TARGET, "Delivering webhook {} at versionstamp {vs}", wh.id);
+				res = self.webhook_post(&wh, vs, changes).await;
+			}
+			// Retry the change set later if it could not be delivered
+			if let Err(e) = res {
+				wh.failed(e);
+				break;
+			}
+			wh.delivered(vs);
+		}
+		// Store the progress of the webhook, unless it has since been changed or removed
+		let txn = self.transaction(Write, Optimistic).await?;
+		let key = crate::key::database::wh::new(ns, db, wh.id);
+		let chk = revision::to_vec(&prev)?;
+		catch!(txn, txn.putc(key, revision::to_vec(&wh)?, Some(chk)).await);
+		catch!(txn, txn.commit().await);
+		Ok(())
+	}
+
+	/// Checks whether a change matches the condition of a webhook
+	async fn webhook_matches(
+		&self,
+		ns: &str,
+		db: &str,
+		wh: &Webhook,
+		mutation: &TableMutation,
+	) -> Result<bool, Error> {
+		// Get the event and the record of the change
+		let (event, value) = match mutation {
+			TableMutation::Set(_, v) | TableMutation::SetWithDiff(_, v, _) => ("UPDATE", v.clone()),
+			TableMutation::DelWithOriginal(_, v) => ("DELETE", v.clone()),
+			TableMutation::Del(id) => (
+				"DELETE",
+				Value::from(map! {
+					"id".to_string() => Value::Thing(id.clone()),
+				}),
+			),
+			TableMutation::Def(_) => return Ok(false),
+		};
+		// Every change matches when there is no condition
+		let Some(cond) = &wh.cond else {
+			return Ok(true);
+		};
+		// Compute the condition against the record of the change
+		let stm = SelectStatement {
+			expr: Fields(
+				vec![Field::Single {
+					expr: cond.clone(),
+					alias: None,
+				}],
+				true,
+			),
+			what: Values(vec![Value::Param(Param::from("value"))]),
+			only: true,
+			..Default::default()
+		};
+		let vars = BTreeMap::from([
+			("event".to_string(), Value::from(event)),
+			("value".to_string(), value),
+		]);
+		let sess = Session::owner().with_ns(ns).with_db(db);
+		let val = Value::Subquery(Box::new(Subquery::Select(stm)));
+		Ok(self.evaluate(&val, &sess, Some(vars)).await?.is_truthy())
+	}
+
+	/// Posts a change set to the webhook URL
+	async fn webhook_post(&self, wh: &Webhook, vs: u64, changes: Vec<Value>) -> Result<(), Error> {
+		// Check the URL against the network capabilities, with a timeout
+		let mut ctx = self.setup_ctx()?;
+		ctx.add_timeout(WEBHOOK_TIMEOUT)?;
+		let ctx = ctx.freeze();
+		// Post the changes
+		let body = Value::from(map! {
+			"webhook".to_string() => Value::Uuid(wh.id.into()),
+			"table".to_string() => Value::from(wh.tb.as_str()),
+			"versionstamp".to_string() => Value::from(vs),
+			"changes".to_string() => Value::from(changes),
+		});
+		let url = Value::from(wh.url.as_str());
+		crate::fnc::http::post(&ctx, (url, Optional(Some(body)), Optional(None))).await?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn failed_deliveries_back_off() {
+		let mut wh = Webhook::new("http://localhost".into(), "test".into(), None, 1);
+		assert!(!wh.is_waiting());
+		wh.failed(Error::Http("500 Internal Server Error".into()));
+		assert_eq!(wh.failures, 1);
+		assert!(wh.is_waiting());
+		assert_eq!(
+			wh.error.as_deref(),
+			Some("There was an error processing a remote HTTP request: 500 Internal Server Error")
+		);
+		// The backoff is capped
+		for _ in 0..32 {
+			wh.failed(Error::Http("500 Internal Server Error".into()));
+		}
+		let wait = wh.retry_at.as_ref().unwrap().0 - Utc::now();
+		assert!(wait <= chrono::Duration::from_std(WEBHOOK_MAX_BACKOFF).unwrap());
+		// A successful delivery resets the backoff
+		wh.delivered(10);
+		assert_eq!(wh.vs, 11);
+		assert_eq!(wh.failures, 0);
+		assert!(!wh.is_waiting());
+		assert_eq!(wh.error, None);
+	}
+}
@@ -1,17 +1,23 @@
 #[cfg(not(target_family = "wasm"))]
 use crate::gql::SchemaCache;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Semaphore;
 use uuid::Uuid;
 
 use super::Data;
 use super::Method;
+use super::QueryLog;
+use super::QueryLogEntry;
 use super::RpcError;
 use super::RpcProtocolV1;
 use super::RpcProtocolV2;
+use super::ScanCursor;
 use crate::dbs::Session;
 use crate::kvs::Datastore;
 use crate::sql::Array;
+use crate::sql::Query;
+use crate::sql::Value;
 
 #[expect(async_fn_in_trait)]
 pub trait RpcContext {
@@ -37,6 +43,41 @@ pub trait RpcContext {
 	fn handle_live(&self, _lqid: &Uuid) -> impl std::future::Future<Output = ()> + Send {
 		async { unimplemented!("handle_live function must be implemented if LQ_SUPPORT = true") }
 	}
+	/// Attaches a client-chosen token to the notifications of a live query
+	fn handle_live_token(
+		&self,
+		_lqid: &Uuid,
+		_token: String,
+	) -> impl std::future::Future<Output = ()> + Send {
+		async {
+			unimplemented!("handle_live_token function must be implemented if LQ_SUPPORT = true")
+		}
+	}
+	/// Coalesces the notifications of a live query which are made within a debounce or throttle window
+	fn handle_live_window(
+		&self,
+		_lqid: &Uuid,
+		_window: Duration,
+		_throttle: bool,
+		_diff: bool,
+	) -> impl std::future::Future<Output = ()> + Send {
+		async {
+			unimplemented!("handle_live_window function must be implemented if LQ_SUPPORT = true")
+		}
+	}
+	/// Registers a live query whose notifications are replaced by a recomputed aggregate value
+	fn handle_live_aggregate(
+		&self,
+		_lqid: &Uuid,
+		_query: Query,
+		_debounce: Duration,
+	) -> impl std::future::Future<Output = ()> + Send {
+		async {
+			unimplemented!(
+				"handle_live_aggregate function must be implemented if LQ_SUPPORT = true"
+			)
+		}
+	}
 	/// Handles the execution of a KILL statement
 	fn handle_kill(&self, _lqid: &Uuid) -> impl std::future::Future<Output = ()> + Send {
 		async { unimplemented!("handle_kill function must be implemented if LQ_SUPPORT = true") }
@@ -45,6 +86,177 @@ pub trait RpcContext {
 	fn cleanup_lqs(&self) -> impl std::future::Future<Output = ()> + Send {
 		async { unimplemented!("cleanup_lqs function must be implemented if LQ_SUPPORT = true") }
 	}
+	/// Checks if a live query is still active on this connection
+	fn has_live_query(&self, _lqid: &Uuid) -> impl std::future::Future<Output = bool> + Send {
+		async { false }
+	}
+	/// Checks if a live query on this node is owned by a connected session, either
+	/// because a connection is tracking it, or because the session which started it
+	/// is still connected, but has not yet started tracking it
+	fn has_live_session(
+		&self,
+		_lqid: &Uuid,
+		_session: Option<Uuid>,
+	) -> impl std::future::Future<Output = bool> + Send {
+		async {
+			unimplemented!("has_live_session function must be implemented if LQ_SUPPORT = true")
+		}
+	}
+	/// Counts the live queries which are active on this connection
+	fn live_query_count(&self) -> impl std::future::Future<Output = usize> + Send {
+		async { 0 }
+	}
+	/// The maximum number of live queries which can be active on this connection
+	fn max_live_queries(&self) -> Option<usize> {
+		None
+	}
+	/// Estimates the number of messages buffered for delivery on this connection
+	fn buffered_messages(&self) -> usize {
+		0
+	}
+
+	// ------------------------------
+	// Prepared statements
+	// ------------------------------
+
+	/// Prepared statements are disabled by default
+	const PREPARED_SUPPORT: bool = false;
+
+	/// Stores a prepared query, returning the handle used to execute it
+	fn store_prepared(&self, _query: Query) -> impl std::future::Future<Output = Uuid> + Send {
+		async {
+			unimplemented!("store_prepared function must be implemented if PREPARED_SUPPORT = true")
+		}
+	}
+	/// Counts the prepared queries which are stored on this connection
+	fn prepared_count(&self) -> impl std::future::Future<Output = usize> + Send {
+		async { 0 }
+	}
+	/// Retrieves a prepared query, if the handle exists and has not expired
+	fn get_prepared(&self, _id: &Uuid) -> impl std::future::Future<Output = Option<Query>> + Send {
+		async {
+			unimplemented!("get_prepared function must be implemented if PREPARED_SUPPORT = true")
+		}
+	}
+
+	// ------------------------------
+	// Table scans
+	// ------------------------------
+
+	/// Table scans are disabled by default
+	const SCAN_SUPPORT: bool = false;
+
+	/// Stores the cursor of a table scan, returning the handle used to continue it
+	fn open_scan(&self, _cursor: ScanCursor) -> impl std::future::Future<Output = Uuid> + Send {
+		async { unimplemented!("open_scan function must be implemented if SCAN_SUPPORT = true") }
+	}
+	/// Retrieves the cursor of a table scan, if the handle exists and has not expired
+	fn get_scan(&self, _id: &Uuid) -> impl std::future::Future<Output = Option<ScanCursor>> + Send {
+		async { unimplemented!("get_scan function must be implemented if SCAN_SUPPORT = true") }
+	}
+	/// Moves the cursor of a table scan, keeping the scan open
+	fn update_scan(
+		&self,
+		_id: &Uuid,
+		_cursor: ScanCursor,
+	) -> impl std::future::Future<Output = ()> + Send {
+		async { unimplemented!("update_scan function must be implemented if SCAN_SUPPORT = true") }
+	}
+	/// Closes a table scan, returning whether the handle existed
+	fn close_scan(&self, _id: &Uuid) -> impl std::future::Future<Output = bool> + Send {
+		async { unimplemented!("close_scan function must be implemented if SCAN_SUPPORT = true") }
+	}
+
+	// ------------------------------
+	// Query log
+	// ------------------------------
+
+	/// The query log is disabled by default
+	const QUERY_LOG_SUPPORT: bool = false;
+
+	/// Starts recording the queries executed on this connection, replacing any existing log
+	fn start_query_log(&self, _log: QueryLog) -> impl std::future::Future<Output = ()> + Send {
+		async {
+			unimplemented!(
+				"start_query_log function must be implemented if QUERY_LOG_SUPPORT = true"
+			)
+		}
+	}
+	/// Stops recording the queries executed on this connection, returning whether a log existed
+	fn stop_query_log(&self) -> impl std::future::Future<Output = bool> + Send {
+		async {
+			unimplemented!(
+				"stop_query_log function must be implemented if QUERY_LOG_SUPPORT = true"
+			)
+		}
+	}
+	/// Checks if the queries executed on this connection are being recorded
+	fn is_logging_queries(&self) -> impl std::future::Future<Output = bool> + Send {
+		async { false }
+	}
+	/// Records a query in the query log of this connection, if it has been started
+	fn log_query(&self, _entry: QueryLogEntry) -> impl std::future::Future<Output = ()> + Send {
+		async {
+			unimplemented!("log_query function must be implemented if QUERY_LOG_SUPPORT = true")
+		}
+	}
+	/// Retrieves the queries recorded on this connection, optionally clearing the log
+	fn query_log(
+		&self,
+		_clear: bool,
+	) -> impl std::future::Future<Output = Option<Vec<QueryLogEntry>>> + Send {
+		async {
+			unimplemented!("query_log function must be implemented if QUERY_LOG_SUPPORT = true")
+		}
+	}
+
+	// ------------------------------
+	// Temporary tables
+	// ------------------------------
+
+	/// Temporary tables are disabled by default
+	///
+	/// A temporary table is a normal table which is removed with its connection, and so it
+	/// is not isolated from other sessions which have access to the same database.
+	const TEMP_TABLE_SUPPORT: bool = false;
+
+	/// Tracks a table which is removed when this connection is reset or closed, with the
+	/// session which defined it, and the comment with which it was marked when defined
+	fn add_temp_table(
+		&self,
+		_sess: Arc<Session>,
+		_tb: String,
+		_marker: String,
+	) -> impl std::future::Future<Output = ()> + Send {
+		async {
+			unimplemented!(
+				"add_temp_table function must be implemented if TEMP_TABLE_SUPPORT = true"
+			)
+		}
+	}
+	/// Removes the temporary tables which were defined on this connection
+	fn cleanup_temp_tables(&self) -> impl std::future::Future<Output = ()> + Send {
+		async {}
+	}
+
+	// ------------------------------
+	// Streamed queries
+	// ------------------------------
+
+	/// Streamed queries are disabled by default
+	const STREAM_SUPPORT: bool = false;
+
+	/// Sends the response of a single statement of a streamed query to the client
+	fn send_stream(
+		&self,
+		_token: &str,
+		_index: usize,
+		_response: Value,
+	) -> impl std::future::Future<Output = ()> + Send {
+		async {
+			unimplemented!("send_stream function must be implemented if STREAM_SUPPORT = true")
+		}
+	}
 
 	// ------------------------------
 	// GraphQL
@@ -60,6 +272,15 @@ pub trait RpcContext {
 		unimplemented!("graphql_schema_cache function must be implemented if GQL_SUPPORT = true")
 	}
 
+	// ------------------------------
+	// Shutdown
+	// ------------------------------
+
+	/// Checks if the server is shutting down, in which case new method calls are rejected
+	fn is_shutting_down(&self) -> bool {
+		false
+	}
+
 	// ------------------------------
 	// Method execution
 	// ------------------------------
@@ -75,6 +296,11 @@ pub trait RpcContext {
 		Self: RpcProtocolV1,
 		Self: RpcProtocolV2,
 	{
+		// Don't start processing if we are gracefully shutting down
+		if self.is_shutting_down() {
+			return Err(RpcError::ShuttingDown);
+		}
+		// Execute the method on the requested protocol
 		match version {
 			Some(1) => RpcProtocolV1::execute(self, method, params).await,
 			Some(2) => RpcProtocolV2::execute(self, method, params).await,
@@ -82,3 +308,59 @@ pub trait RpcContext {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicBool, Ordering};
+
+	struct Context {
+		kvs: Datastore,
+		lock: Arc<Semaphore>,
+		session: Arc<Session>,
+		shutting_down: AtomicBool,
+	}
+
+	impl RpcContext for Context {
+		fn kvs(&self) -> &Datastore {
+			&self.kvs
+		}
+		fn lock(&self) -> Arc<Semaphore> {
+			self.lock.clone()
+		}
+		fn session(&self) -> Arc<Session> {
+			self.session.clone()
+		}
+		fn set_session(&self, _session: Arc<Session>) {}
+		fn version_data(&self) -> Data {
+			Value::from("test").into()
+		}
+		fn is_shutting_down(&self) -> bool {
+			self.shutting_down.load(Ordering::Relaxed)
+		}
+	}
+
+	impl RpcProtocolV1 for Context {}
+	impl RpcProtocolV2 for Context {}
+
+	#[tokio::test]
+	async fn methods_are_rejected_while_shutting_down() {
+		let ctx = Context {
+			kvs: Datastore::new("memory").await.unwrap(),
+			lock: Arc::new(Semaphore::new(1)),
+			session: Arc::new(Session::owner()),
+			shutting_down: AtomicBool::new(false),
+		};
+		// Methods are processed until a shutdown starts
+		for version in [None, Some(1), Some(2)] {
+			let res = RpcContext::execute(&ctx, version, Method::Ping, Array::new()).await;
+			assert!(res.is_ok(), "{res:?}");
+		}
+		// Methods on every protocol are rejected once a shutdown starts
+		ctx.shutting_down.store(true, Ordering::Relaxed);
+		for version in [None, Some(1), Some(2)] {
+			let res = RpcContext::execute(&ctx, version, Method::Ping, Array::new()).await;
+			assert!(matches!(res, Err(RpcError::ShuttingDown)), "{res:?}");
+		}
+	}
+}
@@ -23,6 +23,66 @@ pub enum RpcError {
 	BadLQConfig,
 	#[error("A GraphQL request was made, but GraphQL is not supported by the context")]
 	BadGQLConfig,
+	#[error(
+		"A prepared statement was used, but prepared statements are not supported by the context"
+	)]
+	BadPreparedConfig,
+	#[error("The prepared statement '{0}' does not exist or has expired")]
+	PreparedNotFound(uuid::Uuid),
+	#[error("The batch was rolled back, as the parameters at index {0} failed: {1}")]
+	BatchFailed(usize, String),
+	#[error("A table scan was opened, but table scans are not supported by the context")]
+	BadScanConfig,
+	#[error("The table scan '{0}' does not exist or has expired")]
+	ScanNotFound(uuid::Uuid),
+	#[error("The query log was used, but the query log is not supported by the context")]
+	BadQueryLogConfig,
+	#[error("The query log has not been started on this connection")]
+	QueryLogNotStarted,
+	#[error(
+		"A temporary table was defined, but temporary tables are not supported by the context"
+	)]
+	BadTempTableConfig,
+	#[error("A streamed query was made, but streamed queries are not supported by the context")]
+	BadStreamConfig,
+	#[error("The edge at index {0} must be an object with `from`, `kind`, and `to` fields")]
+	InvalidEdge(usize),
+	#[error("The hop at index {0} must be an object with an `edge` table, and an optional `direction` of `in` or `out` and `cond`")]
+	InvalidHop(usize),
+	#[error("The traversal exceeds the maximum of {0} hops")]
+	TooManyHops(usize),
+	#[error("The pipeline stage at index {0} is invalid, as {1}")]
+	InvalidStage(usize, &'static str),
+	#[error("The session has reached the maximum of {0} live queries")]
+	TooManyLiveQueries(usize),
+	#[error("The query at index {0} must be a string containing only read-only statements")]
+	InvalidRaceQuery(usize),
+	#[error("The server is gracefully shutting down")]
+	ShuttingDown,
+	#[error("The result exceeds the maximum of {0} results")]
+	TooManyResults(usize),
+	#[error("Expected {0} result, but found {1}")]
+	UnexpectedResultCount(&'static str, usize),
+	#[error("The record at index {0} does not have an id")]
+	MissingRecordId(usize),
+	#[error("The result contains a non-finite number, which can not be represented")]
+	NonFiniteNumber,
+	#[error("The database did not reach the versionstamp {0} in time")]
+	VersionNotReached(u64),
+	#[error("The datetime {0} is outside of the history retained by the database")]
+	HistoryNotRetained(String),
+	#[error("The record {0} has changed since it was read")]
+	RecordChanged(String),
+	#[error("The condition can not use an index on the table {0}, which has more than {1} records, so counting requires the `allow_scan` option")]
+	UnindexedCount(String, usize),
+	#[error("The table {0} is not a view")]
+	NotAView(String),
+	#[error("The named subquery {0} references itself through the other named subqueries")]
+	CyclicSubquery(String),
+	#[error("The query references ${0}, which is not defined by a named subquery or variable")]
+	UndefinedParam(String),
+	#[error("The table {0} does not have a changefeed, which is required to deliver webhooks")]
+	NoChangefeed(String),
 	#[error("Error: {0}")]
 	Thrown(String),
 }
@@ -37,6 +97,19 @@ impl From<err::Error> for RpcError {
 	}
 }
 
+impl RpcError {
+	/// Returns the JSON-RPC error code which is reported for this error
+	pub(crate) fn code(&self) -> i64 {
+		match self {
+			RpcError::ParseError => -32700,
+			RpcError::InvalidRequest => -32600,
+			RpcError::MethodNotFound => -32601,
+			RpcError::InvalidParams => -32602,
+			_ => -32000,
+		}
+	}
+}
+
 impl From<&str> for RpcError {
 	fn from(e: &str) -> Self {
 		RpcError::Thrown(e.to_string())
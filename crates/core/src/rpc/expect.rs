@@ -0,0 +1,44 @@
+use crate::sql::Value;
+
+use super::RpcError;
+
+/// How many results a client expects a method to return
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Expect {
+	/// Fails the request unless exactly one result is returned
+	One,
+	/// Fails the request if no results are returned
+	AtLeastOne,
+	/// Allows any number of results, including none
+	Many,
+}
+
+impl Expect {
+	pub(crate) fn process_options(opts: Value) -> Result<Self, RpcError> {
+		match opts {
+			Value::Strand(v) => match v.as_str() {
+				"one" => Ok(Self::One),
+				"at_least_one" => Ok(Self::AtLeastOne),
+				"many" => Ok(Self::Many),
+				_ => Err(RpcError::InvalidParams),
+			},
+			_ => Err(RpcError::InvalidParams),
+		}
+	}
+
+	/// Checks that the number of results meets this expectation
+	pub(crate) fn check(self, v: &Value) -> Result<(), RpcError> {
+		let count = match v {
+			Value::Array(v) => v.len(),
+			Value::None => 0,
+			_ => 1,
+		};
+		match self {
+			Self::One if count != 1 => Err(RpcError::UnexpectedResultCount("exactly one", count)),
+			Self::AtLeastOne if count == 0 => {
+				Err(RpcError::UnexpectedResultCount("at least one", count))
+			}
+			_ => Ok(()),
+		}
+	}
+}
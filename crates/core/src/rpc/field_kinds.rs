@@ -0,0 +1,119 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+	dbs::Session,
+	err::Error,
+	iam::{Action, ResourceKind},
+	kvs::{Datastore, LockType::Optimistic, TransactionType::Read},
+	sql::{Object, Part, Permission, Value},
+};
+
+use super::sensitive;
+
+/// Describes the type of each top-level field of the selected records, preferring the declared types
+pub(crate) async fn describe(
+	kvs: &Datastore,
+	sess: &Session,
+	what: &Value,
+	res: &Value,
+) -> Result<BTreeMap<String, String>, Error> {
+	let declared = declared(kvs, sess, what).await?;
+	let mut types = infer(res);
+	for (k, v) in types.iter_mut() {
+		if let Some(kind) = declared.get(k) {
+			v.clone_from(kind);
+		}
+	}
+	Ok(types)
+}
+
+/// Returns the declared type of each top-level field of the selected table
+async fn declared(
+	kvs: &Datastore,
+	sess: &Session,
+	what: &Value,
+) -> Result<BTreeMap<String, String>, Error> {
+	// Declared types are only used when a single table is selected
+	let tables = sensitive::tables(what);
+	let [tb] = tables.as_slice() else {
+		return Ok(BTreeMap::new());
+	};
+	// Get the NS and DB
+	let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+	let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+	// Users who can not view the field definitions only see the
+	// fields which they are always permitted to select
+	let all = (!kvs.is_auth_enabled() && sess.au.is_anon())
+		|| sess.au.is_allowed(Action::View, &ResourceKind::Field.on_db(ns, db)).is_ok();
+	// Fetch the field definitions
+	let txn = kvs.transaction(Read, Optimistic).await?;
+	let fds = txn.all_tb_fields(ns, db, tb, None).await;
+	txn.cancel().await?;
+	// Describe the type of each top-level field
+	let mut out = BTreeMap::new();
+	for fd in fds?.iter() {
+		if let ([Part::Field(name)], Some(kind)) = (fd.name.0.as_slice(), &fd.kind) {
+			if all || fd.permissions.select == Permission::Full {
+				out.insert(name.to_raw(), kind.to_string());
+			}
+		}
+	}
+	Ok(out)
+}
+
+/// Infers the type of each top-level field from the values of the returned records
+pub(crate) fn infer(res: &Value) -> BTreeMap<String, String> {
+	let records: Vec<&Object> = match res {
+		Value::Array(v) => v
+			.iter()
+			.filter_map(|v| match v {
+				Value::Object(v) => Some(v),
+				_ => None,
+			})
+			.collect(),
+		Value::Object(v) => vec![v],
+		_ => vec![],
+	};
+	// Collect the kinds of value seen for each field
+	let mut kinds: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+	for record in records.iter() {
+		for (k, v) in record.iter() {
+			kinds.entry(k.clone()).or_default().insert(kind_of(v));
+		}
+	}
+	kinds
+		.into_iter()
+		.map(|(k, mut v)| {
+			// A field which is missing from some records is optional
+			let optional = v.remove("none") || records.iter().any(|r| !r.contains_key(&k));
+			let kind = v.into_iter().collect::<Vec<_>>().join(" | ");
+			let kind = match (optional, kind.is_empty()) {
+				(_, true) => "none".to_string(),
+				(true, false) => format!("option<{kind}>"),
+				(false, false) => kind,
+			};
+			(k, kind)
+		})
+		.collect()
+}
+
+/// Returns the result alongside the type of each of its fields
+pub(crate) fn with_types(res: Value, types: BTreeMap<String, String>) -> Value {
+	Value::from(map! {
+		"result".to_string() => res,
+		"types".to_string() => into_value(types),
+	})
+}
+
+/// Converts the type of each field into an object which can be returned
+pub(crate) fn into_value(types: BTreeMap<String, String>) -> Value {
+	Value::from(types.into_iter().map(|(k, v)| (k, Value::from(v))).collect::<BTreeMap<_, _>>())
+}
+
+/// Describes the kind of a single value, including the table of a record id
+fn kind_of(v: &Value) -> String {
+	match v {
+		Value::Thing(v) => format!("record<{}>", v.tb),
+		v => v.kindof().to_string(),
+	}
+}
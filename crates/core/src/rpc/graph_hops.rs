@@ -0,0 +1,72 @@
+use crate::{
+	dbs::Capabilities,
+	sql::{
+		graph::{GraphSubject, GraphSubjects},
+		Array, Dir, Graph, Part, Table, Value,
+	},
+	syn::condition_with_capabilities,
+};
+
+use super::RpcError;
+
+/// The maximum number of hops which can be traversed by a single request
+pub(crate) const MAX_HOPS: usize = 8;
+
+/// Converts the hops into the graph parts of an idiom which traverses them in order
+///
+/// Each hop follows an edge table in a direction, and then on to the records at the
+/// other end of the edges, which are filtered by the condition of the hop.
+pub(crate) fn parts(hops: Array, capabilities: &Capabilities) -> Result<Vec<Part>, RpcError> {
+	if hops.is_empty() {
+		return Err(RpcError::InvalidParams);
+	}
+	if hops.len() > MAX_HOPS {
+		return Err(RpcError::TooManyHops(MAX_HOPS));
+	}
+	let mut parts = Vec::with_capacity(hops.len() * 2);
+	for (i, hop) in hops.into_iter().enumerate() {
+		let Value::Object(mut hop) = hop else {
+			return Err(RpcError::InvalidHop(i));
+		};
+		// Process the edge table
+		let edge = match hop.remove("edge") {
+			Some(Value::Strand(v)) if valid_edge(v.as_str()) => Table(v.0),
+			Some(Value::Table(v)) if valid_edge(v.as_str()) => v,
+			_ => return Err(RpcError::InvalidHop(i)),
+		};
+		// Process the direction, following outgoing edges by default
+		let dir = match hop.remove("direction") {
+			Some(Value::Strand(v)) if v.as_str() == "out" => Dir::Out,
+			Some(Value::Strand(v)) if v.as_str() == "in" => Dir::In,
+			None => Dir::Out,
+			_ => return Err(RpcError::InvalidHop(i)),
+		};
+		// Process the condition on the reached records
+		let cond = match hop.remove("cond") {
+			Some(Value::Strand(v)) => Some(condition_with_capabilities(v.as_str(), capabilities)?),
+			None => None,
+			_ => return Err(RpcError::InvalidHop(i)),
+		};
+		// Any other fields are a mistake in the hop
+		if !hop.is_empty() {
+			return Err(RpcError::InvalidHop(i));
+		}
+		parts.push(Part::Graph(Graph {
+			dir: dir.clone(),
+			what: GraphSubjects(vec![GraphSubject::Table(edge)]),
+			..Default::default()
+		}));
+		parts.push(Part::Graph(Graph {
+			dir,
+			what: GraphSubjects(vec![]),
+			cond,
+			..Default::default()
+		}));
+	}
+	Ok(parts)
+}
+
+/// Checks that the name of an edge table is a plain identifier
+fn valid_edge(name: &str) -> bool {
+	!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
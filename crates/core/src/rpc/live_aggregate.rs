@@ -0,0 +1,135 @@
+use std::time;
+
+use crate::{
+	dbs::Capabilities,
+	sql::{
+		statements::{OutputStatement, SelectStatement},
+		Cond, Duration, Expression, Field, Fields, Function, Groups, Idiom, Number, Operator, Part,
+		Query, Statement, Subquery, Value,
+	},
+	syn::{condition_with_capabilities, idiom_with_capabilities},
+};
+
+use super::RpcError;
+
+/// The longest period over which the changes of a live query can be debounced or throttled
+pub(crate) const MAX_LIVE_DEBOUNCE: time::Duration = time::Duration::from_secs(60);
+
+/// The aggregate functions which can be computed by a live aggregate
+#[derive(Clone, Debug)]
+pub(crate) enum AggregateFunction {
+	/// Counts the matching records
+	Count,
+	/// Sums a field of the matching records
+	Sum(Idiom),
+}
+
+/// Options for the `live_aggregate` method
+#[derive(Clone, Debug)]
+pub(crate) struct LiveAggregate {
+	/// - One of: `"count"` or `"sum"`, where `"sum"` requires a `field` to sum
+	pub function: AggregateFunction,
+	/// - A string, containing an expression for a `WHERE` clause
+	/// - Records which stop matching the condition are removed from the aggregate
+	pub cond: Option<Cond>,
+	/// - A duration, stating how long to wait for further changes before recomputing
+	/// - All of the changes within this period are coalesced into a single notification
+	pub debounce: time::Duration,
+}
+
+impl LiveAggregate {
+	pub(crate) fn process_options(
+		opts: Value,
+		capabilities: &Capabilities,
+	) -> Result<Self, RpcError> {
+		if let Value::Object(mut obj) = opts {
+			// Process "field" option
+			let field = match obj.remove("field") {
+				Some(Value::Strand(v)) => Some(idiom_with_capabilities(v.as_str(), capabilities)?),
+				None => None,
+				_ => return Err(RpcError::InvalidParams),
+			};
+
+			// Process "function" option
+			let function = match (obj.remove("function"), field) {
+				(Some(Value::Strand(v)), None) if v.as_str() == "count" => AggregateFunction::Count,
+				(Some(Value::Strand(v)), Some(field)) if v.as_str() == "sum" => {
+					AggregateFunction::Sum(field)
+				}
+				_ => return Err(RpcError::InvalidParams),
+			};
+
+			// Process "cond" option
+			let cond = match obj.remove("cond") {
+				Some(Value::Strand(v)) => {
+					Some(condition_with_capabilities(v.as_str(), capabilities)?)
+				}
+				None => None,
+				_ => return Err(RpcError::InvalidParams),
+			};
+
+			// Process "debounce" option
+			let debounce = match obj.remove("debounce") {
+				Some(Value::Duration(v)) => v.0,
+				Some(Value::Strand(v)) => {
+					Duration::try_from(v.as_str()).map_err(|_| RpcError::InvalidParams)?.0
+				}
+				None => time::Duration::ZERO,
+				_ => return Err(RpcError::InvalidParams),
+			};
+			if debounce > MAX_LIVE_DEBOUNCE {
+				return Err(RpcError::InvalidParams);
+			}
+
+			Ok(Self {
+				function,
+				cond,
+				debounce,
+			})
+		} else {
+			Err(RpcError::InvalidParams)
+		}
+	}
+
+	/// Builds the query which computes the current aggregate value over the table
+	pub(crate) fn query(&self, what: Value) -> Query {
+		Query::from(vec![Statement::Output(OutputStatement {
+			what: self.value(what),
+			fetch: None,
+		})])
+	}
+
+	/// Builds the expression which computes the aggregate value,
+	/// returning zero when no records match
+	pub(crate) fn value(&self, what: Value) -> Value {
+		let expr = match &self.function {
+			AggregateFunction::Count => Function::Normal("count".to_string(), vec![]),
+			AggregateFunction::Sum(field) => {
+				Function::Normal("math::sum".to_string(), vec![Value::Idiom(field.clone())])
+			}
+		};
+		let select = SelectStatement {
+			expr: Fields(
+				vec![Field::Single {
+					expr: expr.into(),
+					alias: Some(Idiom(vec![Part::from("value")])),
+				}],
+				false,
+			),
+			what: vec![what].into(),
+			cond: self.cond.clone(),
+			group: Some(Groups(vec![])),
+			..Default::default()
+		};
+		let value = Value::Idiom(Idiom(vec![
+			Part::Start(Value::Subquery(Box::new(Subquery::Select(select)))),
+			Part::Index(Number::Int(0)),
+			Part::from("value"),
+		]));
+		Value::Expression(Box::new(Expression::Binary {
+			l: value,
+			o: Operator::Nco,
+			r: Value::from(0),
+		}))
+	}
+}
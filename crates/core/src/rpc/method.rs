@@ -4,6 +4,7 @@ pub enum Method {
 	Unknown,
 	Ping,
 	Info,
+	Footprint,
 	Use,
 	Signup,
 	Signin,
@@ -12,22 +13,70 @@ pub enum Method {
 	Reset,
 	Kill,
 	Live,
+	IsLive,
+	LiveStats,
+	LiveAggregate,
+	KillOrphans,
 	Set,
 	Unset,
+	Vars,
 	Select,
+	ExistsMany,
 	Insert,
 	Create,
 	Upsert,
+	Swap,
+	UpsertSplit,
 	Update,
+	Append,
 	Merge,
 	Patch,
 	Delete,
 	Version,
 	Query,
+	Race,
 	Relate,
 	Run,
 	GraphQL,
+	Batch,
 	InsertRelation,
+	Timezone,
+	Locale,
+	Duplicate,
+	Move,
+	Aggregate,
+	Count,
+	Changed,
+	Diff,
+	Eval,
+	CheckPermission,
+	Prepare,
+	ExecutePrepared,
+	ExecutePreparedBatch,
+	ScanOpen,
+	ScanNext,
+	ScanClose,
+	KeyRange,
+	QueryLog,
+	QueryLogStart,
+	QueryLogStop,
+	Webhooks,
+	WebhookRegister,
+	WebhookRemove,
+	BulkRelate,
+	Edges,
+	Traverse,
+	Pipeline,
+	Search,
+	FieldTypes,
+	View,
+	Maintain,
+	TempTable,
+	Migrate,
+	SchemaHash,
+	ExportSchema,
+	Indexes,
+	Permissions,
 }
 
 impl Method {
@@ -55,6 +104,7 @@ impl Method {
 		match s.as_ref() {
 			"ping" => Self::Ping,
 			"info" => Self::Info,
+			"footprint" => Self::Footprint,
 			"use" => Self::Use,
 			"signup" => Self::Signup,
 			"signin" => Self::Signin,
@@ -63,22 +113,70 @@ impl Method {
 			"reset" => Self::Reset,
 			"kill" => Self::Kill,
 			"live" => Self::Live,
+			"is_live" => Self::IsLive,
+			"live_stats" => Self::LiveStats,
+			"live_aggregate" => Self::LiveAggregate,
+			"kill_orphans" => Self::KillOrphans,
 			"set" | "let" => Self::Set,
 			"unset" => Self::Unset,
+			"vars" => Self::Vars,
 			"select" => Self::Select,
+			"exists_many" => Self::ExistsMany,
 			"insert" => Self::Insert,
 			"create" => Self::Create,
 			"upsert" => Self::Upsert,
+			"swap" => Self::Swap,
+			"upsert_split" => Self::UpsertSplit,
 			"update" => Self::Update,
+			"append" => Self::Append,
 			"merge" => Self::Merge,
 			"patch" => Self::Patch,
 			"delete" => Self::Delete,
 			"version" => Self::Version,
 			"query" => Self::Query,
+			"race" => Self::Race,
 			"relate" => Self::Relate,
 			"run" => Self::Run,
 			"graphql" => Self::GraphQL,
+			"batch" => Self::Batch,
 			"insert_relation" => Self::InsertRelation,
+			"timezone" => Self::Timezone,
+			"locale" => Self::Locale,
+			"duplicate" => Self::Duplicate,
+			"move" => Self::Move,
+			"aggregate" => Self::Aggregate,
+			"count" => Self::Count,
+			"changed" => Self::Changed,
+			"diff" => Self::Diff,
+			"eval" => Self::Eval,
+			"check_permission" => Self::CheckPermission,
+			"prepare" => Self::Prepare,
+			"execute_prepared" => Self::ExecutePrepared,
+			"execute_prepared_batch" => Self::ExecutePreparedBatch,
+			"scan_open" => Self::ScanOpen,
+			"scan_next" => Self::ScanNext,
+			"scan_close" => Self::ScanClose,
+			"key_range" => Self::KeyRange,
+			"query_log" => Self::QueryLog,
+			"query_log_start" => Self::QueryLogStart,
+			"query_log_stop" => Self::QueryLogStop,
+			"webhooks" => Self::Webhooks,
+			"webhook_register" => Self::WebhookRegister,
+			"webhook_remove" => Self::WebhookRemove,
+			"bulk_relate" => Self::BulkRelate,
+			"edges" => Self::Edges,
+			"traverse" => Self::Traverse,
+			"pipeline" => Self::Pipeline,
+			"search" => Self::Search,
+			"field_types" => Self::FieldTypes,
+			"view" => Self::View,
+			"maintain" => Self::Maintain,
+			"temp_table" => Self::TempTable,
+			"migrate" => Self::Migrate,
+			"schema_hash" => Self::SchemaHash,
+			"export_schema" => Self::ExportSchema,
+			"indexes" => Self::Indexes,
+			"permissions" => Self::Permissions,
 			_ => Self::Unknown,
 		}
 	}
@@ -90,6 +188,7 @@ impl Method {
 			Self::Unknown => "unknown",
 			Self::Ping => "ping",
 			Self::Info => "info",
+			Self::Footprint => "footprint",
 			Self::Use => "use",
 			Self::Signup => "signup",
 			Self::Signin => "signin",
@@ -98,22 +197,70 @@ impl Method {
 			Self::Reset => "reset",
 			Self::Kill => "kill",
 			Self::Live => "live",
+			Self::IsLive => "is_live",
+			Self::LiveStats => "live_stats",
+			Self::LiveAggregate => "live_aggregate",
+			Self::KillOrphans => "kill_orphans",
 			Self::Set => "set",
 			Self::Unset => "unset",
+			Self::Vars => "vars",
 			Self::Select => "select",
+			Self::ExistsMany => "exists_many",
 			Self::Insert => "insert",
 			Self::Create => "create",
 			Self::Upsert => "upsert",
+			Self::Swap => "swap",
+			Self::UpsertSplit => "upsert_split",
 			Self::Update => "update",
+			Self::Append => "append",
 			Self::Merge => "merge",
 			Self::Patch => "patch",
 			Self::Delete => "delete",
 			Self::Version => "version",
 			Self::Query => "query",
+			Self::Race => "race",
 			Self::Relate => "relate",
 			Self::Run => "run",
 			Self::GraphQL => "graphql",
+			Self::Batch => "batch",
 			Self::InsertRelation => "insert_relation",
+			Self::Timezone => "timezone",
+			Self::Locale => "locale",
+			Self::Duplicate => "duplicate",
+			Self::Move => "move",
+			Self::Aggregate => "aggregate",
+			Self::Count => "count",
+			Self::Changed => "changed",
+			Self::Diff => "diff",
+			Self::Eval => "eval",
+			Self::CheckPermission => "check_permission",
+			Self::Prepare => "prepare",
+			Self::ExecutePrepared => "execute_prepared",
+			Self::ExecutePreparedBatch => "execute_prepared_batch",
+			Self::ScanOpen => "scan_open",
+			Self::ScanNext => "scan_next",
+			Self::ScanClose => "scan_close",
+			Self::KeyRange => "key_range",
+			Self::QueryLog => "query_log",
+			Self::QueryLogStart => "query_log_start",
+			Self::QueryLogStop => "query_log_stop",
+			Self::Webhooks => "webhooks",
+			Self::WebhookRegister => "webhook_register",
+			Self::WebhookRemove => "webhook_remove",
+			Self::BulkRelate => "bulk_relate",
+			Self::Edges => "edges",
+			Self::Traverse => "traverse",
+			Self::Pipeline => "pipeline",
+			Self::Search => "search",
+			Self::FieldTypes => "field_types",
+			Self::View => "view",
+			Self::Maintain => "maintain",
+			Self::TempTable => "temp_table",
+			Self::Migrate => "migrate",
+			Self::SchemaHash => "schema_hash",
+			Self::ExportSchema => "export_schema",
+			Self::Indexes => "indexes",
+			Self::Permissions => "permissions",
 		}
 	}
 }
@@ -3,18 +3,36 @@ mod context;
 mod error;
 mod method;
 mod protocol;
+mod query_log;
 mod response;
+mod scan;
 
+pub(crate) mod expect;
+pub(crate) mod field_kinds;
 pub mod format;
+pub(crate) mod graph_hops;
+pub(crate) mod live_aggregate;
+pub(crate) mod non_finite;
+pub(crate) mod pipeline;
+pub(crate) mod query_options;
+pub(crate) mod record_ids;
 pub mod request;
+pub(crate) mod retry;
+pub(crate) mod sensitive;
 pub(crate) mod statement_options;
+pub(crate) mod subqueries;
+pub(crate) mod tag;
+pub(crate) mod typed;
+pub(crate) mod webhooks;
 
 pub use context::RpcContext;
 pub use error::RpcError;
 pub use format::Format;
 pub use method::Method;
+pub use query_log::{QueryLog, QueryLogEntry};
 pub use request::Request;
 pub use response::Data;
+pub use scan::ScanCursor;
 
 pub use protocol::v1::RpcProtocolV1;
 pub use protocol::v2::RpcProtocolV2;
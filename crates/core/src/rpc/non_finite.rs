@@ -0,0 +1,56 @@
+use crate::sql::{Number, Value};
+
+use super::RpcError;
+
+/// How non-finite floats are returned to clients which can not represent them
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum NonFinite {
+	/// Fails the request if a result contains a non-finite float
+	Error,
+	/// Returns any non-finite float as `null`
+	Null,
+	/// Returns any non-finite float as `"NaN"`, `"Infinity"`, or `"-Infinity"`
+	String,
+}
+
+impl NonFinite {
+	pub(crate) fn process_options(opts: Value) -> Result<Self, RpcError> {
+		match opts {
+			Value::Strand(v) => match v.as_str() {
+				"error" => Ok(Self::Error),
+				"null" => Ok(Self::Null),
+				"string" => Ok(Self::String),
+				_ => Err(RpcError::InvalidParams),
+			},
+			_ => Err(RpcError::InvalidParams),
+		}
+	}
+
+	/// Replaces any non-finite floats nested within the value according to this policy
+	pub(crate) fn apply(self, v: Value) -> Result<Value, RpcError> {
+		match v {
+			Value::Number(Number::Float(v)) if !v.is_finite() => match self {
+				Self::Error => Err(RpcError::NonFiniteNumber),
+				Self::Null => Ok(Value::Null),
+				Self::String => Ok(Value::from(match v {
+					v if v.is_nan() => "NaN",
+					v if v > 0.0 => "Infinity",
+					_ => "-Infinity",
+				})),
+			},
+			Value::Array(mut v) => {
+				for v in v.iter_mut() {
+					*v = self.apply(std::mem::take(v))?;
+				}
+				Ok(Value::Array(v))
+			}
+			Value::Object(mut v) => {
+				for v in v.values_mut() {
+					*v = self.apply(std::mem::take(v))?;
+				}
+				Ok(Value::Object(v))
+			}
+			v => Ok(v),
+		}
+	}
+}
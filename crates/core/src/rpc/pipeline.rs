@@ -0,0 +1,239 @@
+use crate::{
+	dbs::Capabilities,
+	sql::{
+		statements::SelectStatement, Array, Cond, Expression, Field, Fields, Function, Group,
+		Groups, Idiom, Limit, Number, Object, Operator, Start, Value,
+	},
+	syn::{condition_with_capabilities, idiom_with_capabilities},
+};
+
+use super::{statement_options::ordering, RpcError};
+
+/// The kinds of pipeline stage, in the order in which they must appear
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Stage {
+	Filter,
+	Group,
+	Sort,
+	Limit,
+}
+
+impl Stage {
+	fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"filter" => Some(Self::Filter),
+			"group" => Some(Self::Group),
+			"sort" => Some(Self::Sort),
+			"limit" => Some(Self::Limit),
+			_ => None,
+		}
+	}
+}
+
+/// Compiles the stages of a pipeline into a single select statement over the records
+///
+/// The stages must appear in the order `filter`, `group`, `sort`, and then `limit`.
+/// Any number of `filter` stages are combined together, while each of the other
+/// stages can appear at most once.
+pub(crate) fn statement(
+	what: Value,
+	stages: Array,
+	capabilities: &Capabilities,
+) -> Result<SelectStatement, RpcError> {
+	let mut stmt = SelectStatement {
+		expr: Fields::all(),
+		what: vec![what].into(),
+		..Default::default()
+	};
+	let mut last = None;
+	for (i, stage) in stages.into_iter().enumerate() {
+		let Value::Object(mut stage) = stage else {
+			return Err(RpcError::InvalidStage(i, "it must be an object"));
+		};
+		// Process the kind of stage
+		let kind = match stage.remove("stage") {
+			Some(Value::Strand(v)) => {
+				Stage::from_name(v.as_str()).ok_or(RpcError::InvalidStage(
+					i,
+					"its `stage` must be one of `filter`, `group`, `sort`, or `limit`",
+				))?
+			}
+			_ => return Err(RpcError::InvalidStage(i, "it must have a `stage` name")),
+		};
+		// Check that the stage follows on from the previous stage
+		match last {
+			Some(last) if kind < last => {
+				return Err(RpcError::InvalidStage(
+					i,
+					"the stages must appear in the order `filter`, `group`, `sort`, and then `limit`",
+				));
+			}
+			Some(last) if kind == last && kind != Stage::Filter => {
+				return Err(RpcError::InvalidStage(
+					i,
+					"only a `filter` stage can appear more than once",
+				));
+			}
+			_ => last = Some(kind),
+		}
+		match kind {
+			Stage::Filter => filter(i, &mut stage, &mut stmt, capabilities)?,
+			Stage::Group => group(i, &mut stage, &mut stmt, capabilities)?,
+			Stage::Sort => {
+				stmt.order = match stage.remove("order") {
+					Some(v) => Some(ordering(v, capabilities).map_err(|_| {
+						RpcError::InvalidStage(
+							i,
+							"its `order` must be an array of fields to sort by",
+						)
+					})?),
+					None => return Err(RpcError::InvalidStage(i, "it must have an `order`")),
+				};
+			}
+			Stage::Limit => {
+				stmt.limit = match stage.remove("count") {
+					Some(Value::Number(Number::Int(v))) if v >= 0 => Some(Limit(Value::from(v))),
+					_ => {
+						return Err(RpcError::InvalidStage(
+							i,
+							"its `count` must be a non-negative integer",
+						))
+					}
+				};
+				stmt.start = match stage.remove("start") {
+					Some(Value::Number(Number::Int(v))) if v >= 0 => Some(Start(Value::from(v))),
+					None => None,
+					_ => {
+						return Err(RpcError::InvalidStage(
+							i,
+							"its `start` must be a non-negative integer",
+						))
+					}
+				};
+			}
+		}
+		// Any other fields are a mistake in the stage
+		if !stage.is_empty() {
+			return Err(RpcError::InvalidStage(i, "it has fields which are not understood"));
+		}
+	}
+	Ok(stmt)
+}
+
+/// Adds the condition of a `filter` stage to the conditions of any previous `filter` stages
+fn filter(
+	i: usize,
+	stage: &mut Object,
+	stmt: &mut SelectStatement,
+	capabilities: &Capabilities,
+) -> Result<(), RpcError> {
+	let cond = match stage.remove("cond") {
+		Some(Value::Strand(v)) => condition_with_capabilities(v.as_str(), capabilities)
+			.map_err(|_| RpcError::InvalidStage(i, "its `cond` must be a valid condition"))?,
+		_ => return Err(RpcError::InvalidStage(i, "it must have a `cond` string")),
+	};
+	stmt.cond = Some(match stmt.cond.take() {
+		Some(prev) => Cond(Value::from(Expression::Binary {
+			l: prev.0,
+			o: Operator::And,
+			r: cond.0,
+		})),
+		None => cond,
+	});
+	Ok(())
+}
+
+/// Groups the records by the fields of a `group` stage, selecting the grouped fields and the aggregates
+fn group(
+	i: usize,
+	stage: &mut Object,
+	stmt: &mut SelectStatement,
+	capabilities: &Capabilities,
+) -> Result<(), RpcError> {
+	let mut fields = Vec::new();
+	let mut groups = Vec::new();
+	// Process the fields to group by, grouping all records together by default
+	match stage.remove("by") {
+		Some(Value::Array(v)) => {
+			for v in v {
+				let Value::Strand(v) = v else {
+					return Err(RpcError::InvalidStage(i, "its `by` must be an array of fields"));
+				};
+				let field = idiom_with_capabilities(v.as_str(), capabilities).map_err(|_| {
+					RpcError::InvalidStage(i, "its `by` must be an array of fields")
+				})?;
+				fields.push(Field::Single {
+					expr: Value::Idiom(field.clone()),
+					alias: None,
+				});
+				groups.push(Group(field));
+			}
+		}
+		None => (),
+		_ => return Err(RpcError::InvalidStage(i, "its `by` must be an array of fields")),
+	}
+	// Process the aggregates to compute for each group
+	match stage.remove("aggregate") {
+		Some(Value::Object(v)) => {
+			for (alias, v) in v {
+				fields.push(Field::Single {
+					expr: aggregate(i, v, capabilities)?,
+					alias: Some(Idiom::from(alias)),
+				});
+			}
+		}
+		None => (),
+		_ => {
+			return Err(RpcError::InvalidStage(
+				i,
+				"its `aggregate` must be an object of named aggregates",
+			))
+		}
+	}
+	if fields.is_empty() {
+		return Err(RpcError::InvalidStage(i, "it must have a `by` or an `aggregate`"));
+	}
+	stmt.expr = Fields(fields, false);
+	stmt.group = Some(Groups(groups));
+	Ok(())
+}
+
+/// Converts an aggregate of a `group` stage into the function which computes it
+fn aggregate(i: usize, v: Value, capabilities: &Capabilities) -> Result<Value, RpcError> {
+	let invalid = || {
+		RpcError::InvalidStage(
+			i,
+			"each aggregate must have a `function` of `count`, `sum`, `avg`, `min`, or `max`, and a `field` unless counting",
+		)
+	};
+	let Value::Object(mut v) = v else {
+		return Err(invalid());
+	};
+	let field = match v.remove("field") {
+		Some(Value::Strand(v)) => {
+			Some(idiom_with_capabilities(v.as_str(), capabilities).map_err(|_| invalid())?)
+		}
+		None => None,
+		_ => return Err(invalid()),
+	};
+	let func = match (v.remove("function"), field) {
+		(Some(Value::Strand(v)), None) if v.as_str() == "count" => {
+			Function::Normal("count".to_string(), vec![])
+		}
+		(Some(Value::Strand(v)), Some(field)) => {
+			let name = match v.as_str() {
+				"sum" => "math::sum",
+				"avg" | "mean" => "math::mean",
+				"min" => "math::min",
+				"max" => "math::max",
+				_ => return Err(invalid()),
+			};
+			Function::Normal(name.to_string(), vec![Value::Idiom(field)])
+		}
+		_ => return Err(invalid()),
+	};
+	if !v.is_empty() {
+		return Err(invalid());
+	}
+	Ok(func.into())
+}
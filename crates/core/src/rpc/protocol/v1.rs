@@ -6,6 +6,7 @@ use std::sync::Arc;
 #[cfg(not(target_family = "wasm"))]
 use crate::dbs::capabilities::ExperimentalTarget;
 use crate::err::Error;
+use crate::iam::Error as IamError;
 use crate::rpc::Data;
 use crate::rpc::Method;
 use crate::rpc::RpcContext;
@@ -264,7 +265,28 @@ pub trait RpcProtocolV1: RpcContext {
 			key.0.clone() => Value::None,
 		});
 		// Compute the specified parameter
-		match self.kvs().compute(val, &self.session(), var).await? {
+		let val = match val.is_literal() {
+			// Store literal values directly
+			true => {
+				// Check if the session has expired
+				if self.session().expired() {
+					return Err(Error::ExpiredSession.into());
+				}
+				// Check if anonymous actors can compute values when auth is enabled
+				self.kvs().check_anon(&self.session()).map_err(|_| {
+					Error::from(IamError::NotAllowed {
+						actor: "anonymous".to_string(),
+						action: "compute".to_string(),
+						resource: "value".to_string(),
+					})
+				})?;
+				val
+			}
+			// Compute any other values
+			false => self.kvs().compute(val, &self.session(), var).await?,
+		};
+		// Store the specified parameter
+		match val {
 			// Remove the variable if undefined
 			Value::None => {
 				// Get the context lock
@@ -1,29 +1,81 @@
 #[cfg(not(target_family = "wasm"))]
 use async_graphql::BatchRequest;
-use std::collections::BTreeMap;
+use futures::future::{join_all, select_ok};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
 use std::sync::Arc;
+use trice::Instant;
 
+use crate::cnf::{
+	AS_OF_RETENTION, COUNT_SCAN_LIMIT, MAX_LIVE_REPLAY_CHANGES, MAX_QUERY_LOG_SIZE,
+	MIN_VERSION_TIMEOUT, NORMAL_FETCH_SIZE,
+};
+use crate::ctx::Canceller;
 #[cfg(not(target_family = "wasm"))]
 use crate::dbs::capabilities::ExperimentalTarget;
 use crate::err::Error;
-use crate::rpc::statement_options::StatementOptions;
+use crate::iam::Error as IamError;
+use crate::iam::{Action, ResourceKind};
+use crate::kvs::{export, KeyDecode, LockType::Optimistic, TransactionType::Read, Webhook};
+use crate::rpc::field_kinds;
+use crate::rpc::graph_hops;
+use crate::rpc::live_aggregate::LiveAggregate;
+use crate::rpc::pipeline;
+use crate::rpc::query_options::{self, QueryOptions};
+use crate::rpc::retry::Retry;
+use crate::rpc::sensitive;
+use crate::rpc::statement_options::{
+	checksums, with_adjacency, with_aliases, with_by_id, with_changeset, with_checksums,
+	with_columns, with_defaults, with_partitions, with_sql, with_versionstamp, without_absent,
+	without_futures, StatementOptions,
+};
+use crate::rpc::subqueries;
+use crate::rpc::tag;
+use crate::rpc::typed;
+use crate::rpc::webhooks;
 use crate::rpc::Data;
 use crate::rpc::Method;
+use crate::rpc::QueryLog;
+use crate::rpc::QueryLogEntry;
 use crate::rpc::RpcContext;
 use crate::rpc::RpcError;
+use crate::rpc::ScanCursor;
+use crate::sql::graph::{GraphSubject, GraphSubjects};
+
+use crate::sql::order::{OrderList, Ordering};
+use crate::sql::paths::ID;
+use crate::sql::statements::insert::gen_id;
+use crate::sql::statements::rebuild::{RebuildIndexStatement, RebuildStatement};
+use crate::sql::statements::show::ShowSince;
 use crate::sql::Uuid;
+use crate::syn::{
+	idiom_with_capabilities, parse_with_capabilities, parse_with_compatibility,
+	value_with_capabilities,
+};
+use crate::vs::VersionStamp;
 use crate::{
-	dbs::{capabilities::MethodTarget, QueryType, Response},
+	dbs::{capabilities::MethodTarget, QueryType, Response, Session},
 	rpc::args::Take,
 	sql::{
 		statements::{
-			CreateStatement, DeleteStatement, InsertStatement, KillStatement, LiveStatement,
-			RelateStatement, SelectStatement, UpdateStatement, UpsertStatement,
+			CreateStatement, DefineStatement, DefineTableStatement, DeleteStatement,
+			IfelseStatement, InfoStatement, InsertStatement, KillStatement, LiveStatement,
+			RelateStatement, SelectStatement, SetStatement, ShowStatement, UpdateStatement,
+			UpsertStatement,
 		},
-		Array, Fields, Function, Model, Output, Query, Strand, Value,
+		Array, Block, Cond, Datetime, Dir, Duration, Edges, Entry, Explain, Expression, Field,
+		Fields, Function, Graph, Group, Groups, Id, IdRange, Idiom, Index, Kind, Limit, Model,
+		Number, Object, Operator, Order, Output, Param, Part, Permission, Permissions, Query,
+		Start, Statement, Statements, Strand, Subquery, Table, Thing, Value,
 	},
 };
 
+/// The match reference which links the search conditions to the search functions
+const SEARCH_REF: u8 = 1;
+
+/// An error string returned when an evaluated expression accesses the database
+const EVAL_DATABASE_ACCESS: &str = "Evaluated expressions can not read or write any records";
+
 #[expect(async_fn_in_trait)]
 pub trait RpcProtocolV2: RpcContext {
 	// ------------------------------
@@ -39,10 +91,24 @@ pub trait RpcProtocolV2: RpcContext {
 			warn!("Capabilities denied RPC method call attempt, target: '{method}'");
 			return Err(RpcError::MethodNotAllowed);
 		}
+		// Attach the request tag to the tracing span of this request
+		if let Some(tag) = tag::request_tag(method, &params)? {
+			tag::record_tag(&tag);
+		}
 		// Execute the desired method
 		match method {
 			Method::Ping => Ok(Value::None.into()),
 			Method::Info => self.info().await,
+			Method::Footprint => self.footprint().await,
+			Method::FieldTypes => self.field_types(params).await,
+			Method::View => self.view(params).await,
+			Method::Maintain => self.maintain(params).await,
+			Method::TempTable => self.temp_table(params).await,
+			Method::Migrate => self.migrate(params).await,
+			Method::SchemaHash => self.schema_hash().await,
+			Method::ExportSchema => self.export_schema().await,
+			Method::Indexes => self.indexes(params).await,
+			Method::Permissions => self.permissions().await,
 			Method::Use => self.yuse(params).await,
 			Method::Signup => self.signup(params).await,
 			Method::Signin => self.signin(params).await,
@@ -51,19 +117,58 @@ pub trait RpcProtocolV2: RpcContext {
 			Method::Reset => self.reset().await,
 			Method::Kill => self.kill(params).await,
 			Method::Live => self.live(params).await,
+			Method::IsLive => self.is_live(params).await,
+			Method::LiveStats => self.live_stats().await,
+			Method::LiveAggregate => self.live_aggregate(params).await,
+			Method::KillOrphans => self.kill_orphans().await,
 			Method::Set => self.set(params).await,
 			Method::Unset => self.unset(params).await,
+			Method::Vars => self.vars(params).await,
+			Method::Timezone => self.timezone(params).await,
+			Method::Locale => self.locale(params).await,
 			Method::Select => self.select(params).await,
+			Method::ExistsMany => self.exists_many(params).await,
+			Method::Aggregate => self.aggregate(params).await,
+			Method::Count => self.count(params).await,
+			Method::Search => self.search(params).await,
 			Method::Insert => self.insert(params).await,
 			Method::Create => self.create(params).await,
+			Method::Duplicate => self.duplicate(params).await,
+			Method::Move => self.move_record(params).await,
 			Method::Upsert => self.upsert(params).await,
+			Method::Swap => self.swap(params).await,
+			Method::UpsertSplit => self.upsert_split(params).await,
 			Method::Update => self.update(params).await,
+			Method::Append => self.append(params).await,
 			Method::Delete => self.delete(params).await,
+			Method::Changed => self.changed(params).await,
+			Method::Diff => self.diff(params).await,
 			Method::Version => self.version(params).await,
 			Method::Query => self.query(params).await,
+			Method::Race => self.race(params).await,
 			Method::Relate => self.relate(params).await,
+			Method::BulkRelate => self.bulk_relate(params).await,
+			Method::Edges => self.edges(params).await,
+			Method::Traverse => self.traverse(params).await,
+			Method::Pipeline => self.pipeline(params).await,
 			Method::Run => self.run(params).await,
+			Method::Eval => self.eval(params).await,
+			Method::CheckPermission => self.check_permission(params).await,
+			Method::Prepare => self.prepare(params).await,
+			Method::ExecutePrepared => self.execute_prepared(params).await,
+			Method::ExecutePreparedBatch => self.execute_prepared_batch(params).await,
+			Method::ScanOpen => self.scan_open(params).await,
+			Method::ScanNext => self.scan_next(params).await,
+			Method::ScanClose => self.scan_close(params).await,
+			Method::KeyRange => self.key_range(params).await,
+			Method::QueryLog => self.query_log_entries(params).await,
+			Method::QueryLogStart => self.query_log_start(params).await,
+			Method::QueryLogStop => self.query_log_stop().await,
+			Method::Webhooks => self.webhooks().await,
+			Method::WebhookRegister => self.webhook_register(params).await,
+			Method::WebhookRemove => self.webhook_remove(params).await,
 			Method::GraphQL => self.graphql(params).await,
+			Method::Batch => self.batch(params).await,
 			_ => Err(RpcError::MethodNotFound),
 		}
 	}
@@ -217,6 +322,8 @@ pub trait RpcProtocolV2: RpcContext {
 		std::mem::drop(guard);
 		// Cleanup live queries
 		self.cleanup_lqs().await;
+		// Cleanup temporary tables
+		self.cleanup_temp_tables().await;
 		// Return nothing on success
 		Ok(Value::None.into())
 	}
@@ -239,6 +346,599 @@ pub trait RpcProtocolV2: RpcContext {
 		Ok(res.remove(0).result?.first().into())
 	}
 
+	async fn footprint(&self) -> Result<Data, RpcError> {
+		// Estimate the size of the session variables from their names and text
+		let vars = &self.session().parameters;
+		let bytes: usize = vars.iter().map(|(k, v)| k.len() + v.to_string().len()).sum();
+		// Report the estimated footprint of this connection
+		Ok(Value::from(map! {
+			"vars".to_string() => Value::from(map! {
+				"count".to_string() => Value::from(vars.len()),
+				"bytes".to_string() => Value::from(bytes),
+			}),
+			"prepared".to_string() => Value::from(self.prepared_count().await),
+			"live".to_string() => Value::from(self.live_query_count().await),
+			"buffered".to_string() => Value::from(self.buffered_messages()),
+		})
+		.into())
+	}
+
+	// ------------------------------
+	// Methods for describing schemas
+	// ------------------------------
+
+	async fn field_types(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((tb, opts)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		let tb = match tb {
+			Value::Strand(v) => v.0,
+			Value::Table(v) => v.0,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Process the "detailed" option
+		let detailed = match opts {
+			Value::Object(mut obj) => match obj.remove("detailed") {
+				Some(Value::Bool(v)) => v,
+				None => false,
+				_ => return Err(RpcError::InvalidParams),
+			},
+			Value::None | Value::Null => false,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the current session
+		let sess = self.session();
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession.into());
+		}
+		// Check if anonymous actors can view the schema when auth is enabled
+		self.kvs().check_anon(&sess).map_err(|_| {
+			Error::from(IamError::NotAllowed {
+				actor: "anonymous".to_string(),
+				action: "view".to_string(),
+				resource: "field".to_string(),
+			})
+		})?;
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Users who can not view the field definitions only see the
+		// fields which they are always permitted to select
+		let all = (!self.kvs().is_auth_enabled() && sess.au.is_anon())
+			|| sess.au.is_allowed(Action::View, &ResourceKind::Field.on_db(ns, db)).is_ok();
+		// Fetch the field definitions
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let fds = txn.all_tb_fields(ns, db, &tb, None).await;
+		txn.cancel().await?;
+		// Describe the type of each field
+		let mut out = BTreeMap::new();
+		for fd in fds?.iter() {
+			if all || fd.permissions.select == Permission::Full {
+				let kind = Value::from(fd.kind.clone().unwrap_or(Kind::Any).to_string());
+				// Describe whether the field is computed with a FUTURE value
+				let kind = match detailed {
+					true => Value::from(map! {
+						"kind".to_string() => kind,
+						"computed".to_string() => Value::from(matches!(fd.value, Some(Value::Future(_)))),
+					}),
+					false => kind,
+				};
+				out.insert(fd.name.to_string(), kind);
+			}
+		}
+		Ok(Value::from(out).into())
+	}
+
+	async fn view(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((tb, opts)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		let tb = match tb {
+			Value::Strand(v) => v.0,
+			Value::Table(v) => v.0,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Process the "materialize" option
+		let materialize = match opts {
+			Value::Object(mut obj) => match obj.remove("materialize") {
+				Some(Value::Bool(v)) => v,
+				None => false,
+				_ => return Err(RpcError::InvalidParams),
+			},
+			Value::None | Value::Null => false,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the current session
+		let sess = self.session();
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession.into());
+		}
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Check if the user can view the table definitions
+		if self.kvs().is_auth_enabled() || !sess.au.is_anon() {
+			sess.au
+				.is_allowed(Action::View, &ResourceKind::Table.on_db(ns, db))
+				.map_err(Error::IamError)?;
+		}
+		// Fetch the table definition
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let def = txn.get_tb(ns, db, &tb).await;
+		txn.cancel().await?;
+		let Some(view) = def?.view.clone() else {
+			return Err(RpcError::NotAView(tb));
+		};
+		// Describe the query which computes the view
+		let mut out = map! {
+			"name".to_string() => Value::from(tb.clone()),
+			"definition".to_string() => Value::from(SelectStatement {
+				expr: view.expr,
+				what: view.what.0.iter().cloned().map(Value::Table).collect::<Vec<_>>().into(),
+				cond: view.cond,
+				group: view.group,
+				..Default::default()
+			}.to_string()),
+			"tables".to_string() => Value::from(view.what.0.iter().map(|v| Value::from(v.0.clone())).collect::<Vec<_>>()),
+		};
+		// Fetch the current contents of the view
+		if materialize {
+			let sql = SelectStatement {
+				expr: Fields::all(),
+				what: vec![Value::Table(Table(tb))].into(),
+				..Default::default()
+			};
+			let var = Some(sess.parameters.clone());
+			let mut res = self.kvs().process(sql.into(), &sess, var).await?;
+			out.insert("result".to_string(), res.remove(0).result?);
+		}
+		Ok(Value::from(out).into())
+	}
+
+	async fn maintain(&self, params: Array) -> Result<Data, RpcError> {
+		// Process the method arguments
+		let Ok((tb, opts)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		let tb = match tb {
+			Value::Strand(v) => v.0,
+			Value::Table(v) => v.0,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Process the "indexes" and "changefeed" options
+		let (indexes, changefeed) = match opts {
+			Value::Object(mut obj) => {
+				let mut flag = |k: &str| match obj.remove(k) {
+					Some(Value::Bool(v)) => Ok(v),
+					None => Ok(true),
+					_ => Err(RpcError::InvalidParams),
+				};
+				(flag("indexes")?, flag("changefeed")?)
+			}
+			Value::None | Value::Null => (true, true),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the current session
+		let sess = self.session();
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession.into());
+		}
+		// Only root owners can run maintenance on the datastore
+		if !sess.au.is_root() || !sess.au.has_owner_role() {
+			return Err(Error::from(IamError::NotAllowed {
+				actor: sess.au.id().to_string(),
+				action: "maintain".to_string(),
+				resource: format!("table {tb}"),
+			})
+			.into());
+		}
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Fetch the table and index definitions
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let def = txn.get_tb(ns, db, &tb).await;
+		let ixs = txn.all_tb_indexes(ns, db, &tb).await;
+		txn.cancel().await?;
+		let (def, ixs) = (def?, ixs?);
+		let now = Instant::now();
+		// Rebuild each of the indexes on the table
+		let mut rebuilt = Vec::new();
+		if indexes && !ixs.is_empty() {
+			let sql = Query(Statements(
+				ixs.iter()
+					.map(|ix| {
+						Statement::Rebuild(RebuildStatement::Index(RebuildIndexStatement {
+							name: ix.name.clone(),
+							what: ix.what.clone(),
+							if_exists: true,
+						}))
+					})
+					.collect(),
+			));
+			for (ix, res) in ixs.iter().zip(self.kvs().process(sql, &sess, None).await?) {
+				res.result?;
+				rebuilt.push(Value::from(ix.name.to_raw()));
+			}
+		}
+		// Compact the changefeed entries of the table which have expired
+		let compacted = changefeed && def.changefeed.is_some();
+		if compacted {
+			self.kvs().changefeed_process_table(ns, db, &tb).await?;
+		}
+		// Return a summary of the work done
+		Ok(Value::from(map! {
+			"table".to_string() => Value::from(tb),
+			"indexes".to_string() => Value::from(rebuilt),
+			"changefeed".to_string() => Value::from(compacted),
+			"time".to_string() => Value::from(Duration::from(now.elapsed())),
+		})
+		.into())
+	}
+
+	/// Defines a table which is removed when this connection is reset or closed. The table is
+	/// only temporary in its lifetime: it is a normal table in the current database, so until
+	/// it is removed it is visible to, and can be written by, any other session with access to
+	/// the database. Record users are denied access, as the table has no permissions.
+	async fn temp_table(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Check if temporary tables are supported
+		if !Self::TEMP_TABLE_SUPPORT {
+			return Err(RpcError::BadTempTableConfig);
+		}
+		// Process the method arguments
+		let Ok(tb) = params.needs_one() else {
+			return Err(RpcError::InvalidParams);
+		};
+		let tb = match tb {
+			Value::Strand(v) => v.0,
+			Value::Table(v) => v.0,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the current session
+		let sess = self.session();
+		// Check the NS and DB
+		sess.ns.as_ref().ok_or(Error::NsEmpty)?;
+		sess.db.as_ref().ok_or(Error::DbEmpty)?;
+		// Mark the table uniquely, so that it is only removed if it is still this table
+		let marker = self.kvs().temp_table_marker();
+		// Define the table, which fails if a table with this name already exists
+		let sql = Query(Statements(vec![Statement::Define(DefineStatement::Table(
+			DefineTableStatement {
+				name: tb.clone().into(),
+				permissions: Permissions::none(),
+				comment: Some(marker.clone().into()),
+				..Default::default()
+			},
+		))]));
+		let mut res = self.kvs().process(sql, &sess, None).await?;
+		res.remove(0).result?;
+		// Remove the table when this connection is reset or closed
+		self.add_temp_table(sess, tb.clone(), marker).await;
+		// Return the name of the table
+		Ok(Value::Table(tb.into()).into())
+	}
+
+	async fn migrate(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((Value::Array(steps), opts)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Process the "dry_run" option
+		let dry_run = match opts {
+			Value::Object(mut obj) => match obj.remove("dry_run") {
+				Some(Value::Bool(v)) => v,
+				None => false,
+				_ => return Err(RpcError::InvalidParams),
+			},
+			Value::None | Value::Null => false,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the current session
+		let sess = self.session();
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession.into());
+		}
+		// Only owners can run migrations, as they change the schema
+		if !sess.au.has_owner_role() {
+			return Err(Error::from(IamError::NotAllowed {
+				actor: sess.au.id().to_string(),
+				action: "migrate".to_string(),
+				resource: "database".to_string(),
+			})
+			.into());
+		}
+		// Parse each step, keeping the number of statements in the step which
+		// produce a response, as OPTION statements do not return a result
+		let mut sizes = Vec::with_capacity(steps.len());
+		let mut stmts = Vec::new();
+		for step in steps {
+			let Value::Strand(sql) = step else {
+				return Err(RpcError::InvalidParams);
+			};
+			let sql = parse_with_capabilities(&sql, self.kvs().get_capabilities())?;
+			sizes.push(sql.iter().filter(|s| !matches!(s, Statement::Option(_))).count());
+			stmts.extend(sql.0 .0);
+		}
+		if stmts.is_empty() {
+			return Err(RpcError::InvalidParams);
+		}
+		// Run every step in a single transaction
+		let var = Some(sess.parameters.clone());
+		let res =
+			self.kvs().process_migration(Query(Statements(stmts)), &sess, var, dry_run).await?;
+		// Group the statement results by the step they belong to
+		let mut res = res.into_iter();
+		let mut applied = !dry_run;
+		let mut out = Vec::with_capacity(sizes.len());
+		for size in sizes {
+			let mut results = Vec::with_capacity(size);
+			let mut error = None;
+			let mut time = std::time::Duration::ZERO;
+			for res in res.by_ref().take(size) {
+				time += res.time;
+				match res.result {
+					Ok(v) => results.push(v),
+					Err(e) => {
+						error.get_or_insert(e);
+					}
+				}
+			}
+			let (status, result) = match error {
+				None => ("ok", Value::from(results)),
+				Some(Error::QueryNotExecuted) => ("skipped", Value::None),
+				Some(e) => ("error", Value::from(e.to_string())),
+			};
+			applied &= status == "ok";
+			out.push(Value::from(map! {
+				"status".to_string() => Value::from(status),
+				"result".to_string() => result,
+				"time".to_string() => Value::from(Duration::from(time)),
+			}));
+		}
+		// Return the outcome of each step
+		Ok(Value::from(map! {
+			"applied".to_string() => Value::from(applied),
+			"steps".to_string() => Value::from(out),
+		})
+		.into())
+	}
+
+	async fn schema_hash(&self) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Get the current session
+		let sess = self.session();
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession.into());
+		}
+		// Check if anonymous actors can view the schema when auth is enabled
+		self.kvs().check_anon(&sess).map_err(|_| {
+			Error::from(IamError::NotAllowed {
+				actor: "anonymous".to_string(),
+				action: "view".to_string(),
+				resource: "database".to_string(),
+			})
+		})?;
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Viewing the schema requires the same access as INFO FOR DB
+		if self.kvs().is_auth_enabled() || !sess.au.is_anon() {
+			sess.au
+				.is_allowed(Action::View, &ResourceKind::Any.on_db(ns, db))
+				.map_err(Error::from)?;
+		}
+		// Hash the definitions in their stored order, which is sorted by name
+		let mut hasher = blake3::Hasher::new();
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let res = async {
+			for tb in txn.all_tb(ns, db, None).await?.iter() {
+				hasher.update(tb.to_string().as_bytes());
+				for fd in txn.all_tb_fields(ns, db, &tb.name, None).await?.iter() {
+					hasher.update(fd.to_string().as_bytes());
+				}
+				for ix in txn.all_tb_indexes(ns, db, &tb.name).await?.iter() {
+					hasher.update(ix.to_string().as_bytes());
+				}
+			}
+			for fc in txn.all_db_functions(ns, db).await?.iter() {
+				hasher.update(fc.to_string().as_bytes());
+			}
+			Ok::<_, Error>(())
+		}
+		.await;
+		txn.cancel().await?;
+		res?;
+		// Return the hash as a hexadecimal string
+		Ok(Value::from(hasher.finalize().to_string()).into())
+	}
+
+	async fn export_schema(&self) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Get the current session
+		let sess = self.session();
+		// Get the NS and DB
+		let (ns, db) = crate::iam::check::check_ns_db(&sess)?;
+		// Exporting the schema requires the same access as a full export
+		self.kvs().check(&sess, Action::View, ResourceKind::Any.on_db(&ns, &db))?;
+		// Export the definitions, without users, accesses, or records
+		let cfg = export::Config {
+			users: false,
+			accesses: false,
+			records: false,
+			..Default::default()
+		};
+		let (snd, rcv) = async_channel::unbounded();
+		self.kvs().export_with_config(&sess, snd, cfg).await?.await?;
+		// Collect the exported SurrealQL script
+		let mut out = Vec::new();
+		while let Ok(v) = rcv.try_recv() {
+			out.extend(v);
+		}
+		Ok(Value::from(String::from_utf8_lossy(&out).into_owned()).into())
+	}
+
+	async fn indexes(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok(tb) = params.needs_one() else {
+			return Err(RpcError::InvalidParams);
+		};
+		let tb = match tb {
+			Value::Strand(v) => v.0,
+			Value::Table(v) => v.0,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the current session
+		let sess = self.session();
+		// Get the NS and DB
+		let (ns, db) = crate::iam::check::check_ns_db(&sess)?;
+		// Listing the indexes requires the same access as INFO FOR TABLE
+		self.kvs().check(&sess, Action::View, ResourceKind::Any.on_db(&ns, &db))?;
+		// Fetch the index definitions of the table
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let res = async {
+			txn.get_tb(&ns, &db, &tb).await?;
+			txn.all_tb_indexes(&ns, &db, &tb).await
+		}
+		.await;
+		txn.cancel().await?;
+		let ixs = res?;
+		if ixs.is_empty() {
+			return Ok(Value::from(Vec::<Value>::new()).into());
+		}
+		// Fetch the build status of each index
+		let sql = Query(Statements(
+			ixs.iter()
+				.map(|ix| {
+					Statement::Info(InfoStatement::Index(ix.name.clone(), tb.clone().into(), true))
+				})
+				.collect(),
+		));
+		let mut out = Vec::with_capacity(ixs.len());
+		for (ix, res) in ixs.iter().zip(self.kvs().process(sql, &sess, None).await?) {
+			let building = res.result?.pick(&[Part::from("building")]);
+			// Indexes which are not being built in the background are ready
+			let status = match building.pick(&[Part::from("status")]).as_raw_string().as_str() {
+				"started" | "cleaning" | "indexing" => "building",
+				"aborted" => "aborted",
+				"error" => "error",
+				_ => "ready",
+			};
+			out.push(Value::from(map! {
+				"name".to_string() => Value::from(ix.name.to_raw()),
+				"fields".to_string() => Value::from(
+					ix.cols.iter().map(|c| Value::from(c.to_string())).collect::<Vec<_>>(),
+				),
+				"unique".to_string() => Value::from(matches!(ix.index, Index::Uniq)),
+				"status".to_string() => Value::from(status),
+				"error".to_string(), if let Value::Strand(e) = building.pick(&[Part::from("error")]) => Value::Strand(e),
+			}));
+		}
+		Ok(Value::from(out).into())
+	}
+
+	async fn permissions(&self) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Get the current session
+		let sess = self.session();
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession.into());
+		}
+		// Check if anonymous actors can view the schema when auth is enabled
+		self.kvs().check_anon(&sess).map_err(|_| {
+			Error::from(IamError::NotAllowed {
+				actor: "anonymous".to_string(),
+				action: "view".to_string(),
+				resource: "table".to_string(),
+			})
+		})?;
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Determine whether the table permissions apply, in the same
+		// way as when a statement is executed with this session
+		let opt = self.kvs().setup_options(&sess);
+		let view = opt.check_perms(Action::View)?;
+		let edit = opt.check_perms(Action::Edit)?;
+		// Summarise a table permission, which is conditional if it
+		// depends on the record or the authentication data
+		let summary = |check: bool, perm: &Permission| match (check, perm) {
+			(false, _) | (true, Permission::Full) => Value::from(true),
+			(true, Permission::None) => Value::from(false),
+			(true, Permission::Specific(_)) => Value::from("conditional"),
+		};
+		// Fetch the table definitions
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let tbs = txn.all_tb(ns, db, None).await;
+		txn.cancel().await?;
+		// Summarise the permissions of each table
+		let mut tables = BTreeMap::new();
+		for tb in tbs?.iter() {
+			let perms = &tb.permissions;
+			tables.insert(
+				tb.name.to_raw(),
+				Value::from(map! {
+					"select".to_string() => summary(view, &perms.select),
+					"create".to_string() => summary(edit, &perms.create),
+					"update".to_string() => summary(edit, &perms.update),
+					"delete".to_string() => summary(edit, &perms.delete),
+				}),
+			);
+		}
+		// Report the highest role of the authenticated actor
+		let role = if sess.au.has_owner_role() {
+			Value::from("Owner")
+		} else if sess.au.has_editor_role() {
+			Value::from("Editor")
+		} else if sess.au.has_viewer_role() {
+			Value::from("Viewer")
+		} else {
+			Value::None
+		};
+		Ok(Value::from(map! {
+			"level".to_string() => Value::from(sess.au.level().level_name()),
+			"role".to_string() => role,
+			"tables".to_string() => Value::from(tables),
+		})
+		.into())
+	}
+
 	// ------------------------------
 	// Methods for setting variables
 	// ------------------------------
@@ -257,7 +957,28 @@ pub trait RpcProtocolV2: RpcContext {
 			key.0.clone() => Value::None,
 		});
 		// Compute the specified parameter
-		match self.kvs().compute(val, &self.session(), var).await? {
+		let val = match val.is_literal() {
+			// Store literal values directly
+			true => {
+				// Check if the session has expired
+				if self.session().expired() {
+					return Err(Error::ExpiredSession.into());
+				}
+				// Check if anonymous actors can compute values when auth is enabled
+				self.kvs().check_anon(&self.session()).map_err(|_| {
+					Error::from(IamError::NotAllowed {
+						actor: "anonymous".to_string(),
+						action: "compute".to_string(),
+						resource: "value".to_string(),
+					})
+				})?;
+				val
+			}
+			// Compute any other values
+			false => self.kvs().compute(val, &self.session(), var).await?,
+		};
+		// Store the specified parameter
+		match val {
 			// Remove the variable if undefined
 			Value::None => {
 				// Get the context lock
@@ -318,6 +1039,89 @@ pub trait RpcProtocolV2: RpcContext {
 		Ok(Value::Null.into())
 	}
 
+	async fn vars(&self, params: Array) -> Result<Data, RpcError> {
+		// Process the method arguments
+		let values = match params.is_empty() {
+			true => true,
+			false => match params.needs_one()? {
+				Value::None | Value::Null => true,
+				Value::Object(mut obj) => match obj.remove("values") {
+					Some(Value::Bool(v)) => v,
+					None => true,
+					_ => return Err(RpcError::InvalidParams),
+				},
+				_ => return Err(RpcError::InvalidParams),
+			},
+		};
+		// Get the current session parameters
+		let vars = self.session().parameters.clone();
+		// Return the parameters, or only their names
+		match values {
+			true => Ok(Value::from(vars).into()),
+			false => Ok(Value::from(vars.into_keys().map(Value::from).collect::<Vec<_>>()).into()),
+		}
+	}
+
+	async fn timezone(&self, params: Array) -> Result<Data, RpcError> {
+		// Return the current timezone if no arguments were passed
+		if params.is_empty() {
+			return Ok(self.session().tz.clone().map(Value::from).unwrap_or_default().into());
+		}
+		// Process the method arguments
+		let tz = match params.needs_one()? {
+			Value::Strand(tz) => match crate::dbs::parse_timezone(tz.as_str()) {
+				Ok(_) => Some(tz.0),
+				Err(_) => return Err(RpcError::InvalidParams),
+			},
+			Value::None | Value::Null => None,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the context lock
+		let mutex = self.lock().clone();
+		// Lock the context for update
+		let guard = mutex.acquire().await;
+		// Clone the current session
+		let mut session = self.session().as_ref().clone();
+		// Update the session timezone
+		session.tz = tz;
+		// Store the updated session
+		self.set_session(Arc::new(session));
+		// Drop the mutex guard
+		std::mem::drop(guard);
+		// Return nothing
+		Ok(Value::Null.into())
+	}
+
+	async fn locale(&self, params: Array) -> Result<Data, RpcError> {
+		// Return the current locale if no arguments were passed
+		if params.is_empty() {
+			return Ok(self.session().lc.clone().map(Value::from).unwrap_or_default().into());
+		}
+		// Process the method arguments
+		let lc = match params.needs_one()? {
+			Value::Strand(lc) => match crate::dbs::parse_locale(lc.as_str()) {
+				Ok(lc) => Some(lc),
+				Err(_) => return Err(RpcError::InvalidParams),
+			},
+			Value::None | Value::Null => None,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the context lock
+		let mutex = self.lock().clone();
+		// Lock the context for update
+		let guard = mutex.acquire().await;
+		// Clone the current session
+		let mut session = self.session().as_ref().clone();
+		// Update the session locale
+		session.lc = lc;
+		// Store the updated session
+		self.set_session(Arc::new(session));
+		// Drop the mutex guard
+		std::mem::drop(guard);
+		// Return nothing
+		Ok(Value::Null.into())
+	}
+
 	// ------------------------------
 	// Methods for live queries
 	// ------------------------------
@@ -329,6 +1133,12 @@ pub trait RpcProtocolV2: RpcContext {
 		}
 		// Process the method arguments
 		let id = params.needs_one()?;
+		// Keep the live query id to unregister it
+		let lqid = match &id {
+			Value::Uuid(v) => Some(v.0),
+			Value::Strand(v) => Uuid::try_from(v.as_str()).ok().map(|v| v.0),
+			_ => None,
+		};
 		// Specify the SQL query string
 		let sql = KillStatement {
 			id,
@@ -339,7 +1149,12 @@ pub trait RpcProtocolV2: RpcContext {
 		// Execute the query on the database
 		let mut res = self.query_inner(Value::Query(sql), var).await?;
 		// Extract the first query result
-		Ok(res.remove(0).result?.into())
+		let res = res.remove(0).result?;
+		// Unregister the live query from this connection
+		if let (true, Some(lqid)) = (Self::LQ_SUPPORT, lqid) {
+			self.handle_kill(&lqid).await;
+		}
+		Ok(res.into())
 	}
 
 	async fn live(&self, params: Array) -> Result<Data, RpcError> {
@@ -349,19 +1164,39 @@ pub trait RpcProtocolV2: RpcContext {
 		}
 		// Process the method arguments
 		let (what, opts_value) = params.needs_one_or_two()?;
+		// Check the live query quota of this connection
+		if let Some(max) = self.max_live_queries() {
+			if self.live_query_count().await >= max {
+				return Err(RpcError::TooManyLiveQueries(max));
+			}
+		}
 		// Prepare options
 		let mut opts = StatementOptions::default();
 		// Apply user options
 		if !opts_value.is_none_or_null() {
 			opts.process_options(opts_value, self.kvs().get_capabilities())?;
 		}
+		// Notifications can either be debounced or throttled
+		let window = match (opts.debounce, opts.throttle) {
+			(Some(_), Some(_)) => return Err(RpcError::InvalidParams),
+			(Some(window), None) => Some((window, false)),
+			(None, Some(window)) => Some((window, true)),
+			(None, None) => None,
+		};
+		// Changes can only be replayed for a whole table
+		let what = what.could_be_table();
+		let replay = match (opts.since.take(), &what) {
+			(Some(since), Value::Table(tb)) => Some((since, tb.clone())),
+			(Some(_), _) => return Err(RpcError::InvalidParams),
+			(None, _) => None,
+		};
 		// Specify the query parameters
 		let var = Some(opts.merge_vars(&self.session().parameters));
 		// Specify the SQL query string
 		let sql = LiveStatement {
 			id: Uuid::new_v4(),
 			node: Uuid::new_v4(),
-			what: what.could_be_table(),
+			what,
 			expr: if opts.diff {
 				Fields::default()
 			} else {
@@ -375,7 +1210,210 @@ pub trait RpcProtocolV2: RpcContext {
 		// Execute the query on the database
 		let mut res = self.query_inner(Value::Query(sql), var).await?;
 		// Extract the first query result
-		Ok(res.remove(0).result?.into())
+		let res = res.remove(0).result?;
+		// Attach the token to the live query notifications
+		if let (Some(token), Value::Uuid(lqid)) = (opts.token, &res) {
+			self.handle_live_token(&lqid.0, token).await;
+		}
+		// Coalesce the live query notifications within the window
+		if let (Some((window, throttle)), Value::Uuid(lqid)) = (window, &res) {
+			self.handle_live_window(&lqid.0, window, throttle, opts.diff).await;
+		}
+		// Replay the changes which were made since the requested point,
+		// after the live query has started so that no change is missed
+		let Some((mut since, table)) = replay else {
+			return Ok(res.into());
+		};
+		let max = *MAX_LIVE_REPLAY_CHANGES;
+		let limit = max.clamp(1, u32::MAX as usize) as u32;
+		let mut changes = Vec::new();
+		while changes.len() < max {
+			let sql = ShowStatement {
+				table: Some(table.clone()),
+				since,
+				limit: Some(limit),
+			}
+			.into();
+			let mut res = self.kvs().process(sql, &self.session(), None).await?;
+			let Value::Array(changesets) = res.remove(0).result? else {
+				break;
+			};
+			// Changesets can also contain table definitions, so keep
+			// fetching until enough changes have been replayed
+			let count = changesets.len();
+			let last = replay_changes(changesets, max, &mut changes);
+			match last {
+				Some(v) if count == limit as usize => since = ShowSince::Versionstamp(v + 1),
+				_ => break,
+			}
+		}
+		// Return the live query id alongside the replayed changes
+		Ok(Value::from(map! {
+			"id".to_string() => res,
+			"changes".to_string() => Value::from(changes),
+		})
+		.into())
+	}
+
+	/// Starts a live query which pushes the recomputed aggregate value over a table on each
+	/// change, rather than the changed records. Every change to the table recomputes the
+	/// aggregate over all of the matching records, so the `debounce` option should be used
+	/// to coalesce the changes on tables which are written to frequently.
+	async fn live_aggregate(&self, params: Array) -> Result<Data, RpcError> {
+		// Live aggregates are maintained by the live query handler
+		if !Self::LQ_SUPPORT {
+			return Err(RpcError::BadLQConfig);
+		}
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let (what, opts_value) = params.needs_two()?;
+		// Check the live query quota of this connection
+		if let Some(max) = self.max_live_queries() {
+			if self.live_query_count().await >= max {
+				return Err(RpcError::TooManyLiveQueries(max));
+			}
+		}
+		// Aggregates can only be computed over a whole table
+		let what = what.could_be_table();
+		if !what.is_table() {
+			return Err(RpcError::InvalidParams);
+		}
+		// Prepare options
+		let aggregate = LiveAggregate::process_options(opts_value, self.kvs().get_capabilities())?;
+		// Specify the query parameters
+		let var = Some(self.session().parameters.clone());
+		// Listen to every change on the table, as records which
+		// stop matching the condition also change the aggregate
+		let live = LiveStatement {
+			id: Uuid::new_v4(),
+			node: Uuid::new_v4(),
+			what: what.clone(),
+			expr: Fields(
+				vec![Field::Single {
+					expr: Value::Idiom(Idiom(ID.to_vec())),
+					alias: None,
+				}],
+				false,
+			),
+			..Default::default()
+		};
+		let lqid = live.id;
+		// Register the aggregate before subscribing, so that no change is missed
+		let query = aggregate.query(what.clone());
+		self.handle_live_aggregate(&lqid.0, query, aggregate.debounce).await;
+		// Subscribe and compute the current value within the same transaction
+		let sql = Query::from(vec![
+			Statement::Begin(Default::default()),
+			Statement::Live(live),
+			Statement::Value(aggregate.value(what)),
+			Statement::Commit(Default::default()),
+		]);
+		// Execute the query on the database
+		let res = match self.query_inner(Value::Query(sql), var).await {
+			Ok(mut res) if res.len() == 2 => {
+				let value = res.remove(1).result;
+				res.remove(0).result.and(value).map_err(RpcError::from)
+			}
+			Ok(_) => Err(fail!("Expected a live query id and a value").into()),
+			Err(e) => Err(e),
+		};
+		// Unregister the aggregate if the subscription failed
+		let value = match res {
+			Ok(value) => value,
+			Err(e) => {
+				self.handle_kill(&lqid.0).await;
+				return Err(e);
+			}
+		};
+		// Return the live query id alongside the current value
+		Ok(Value::from(map! {
+			"id".to_string() => Value::from(lqid),
+			"value".to_string() => value,
+		})
+		.into())
+	}
+
+	async fn is_live(&self, params: Array) -> Result<Data, RpcError> {
+		// Process the method arguments
+		let id = match params.needs_one()? {
+			Value::Uuid(v) => v,
+			Value::Strand(v) => Uuid::try_from(v).map_err(|_| RpcError::InvalidParams)?,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Check if the live query is active on this connection
+		Ok(Value::from(self.has_live_query(&id.0).await).into())
+	}
+
+	async fn live_stats(&self) -> Result<Data, RpcError> {
+		// Report the live queries and buffered messages on this connection
+		Ok(Value::from(map! {
+			"count".to_string() => Value::from(self.live_query_count().await),
+			"buffered".to_string() => Value::from(self.buffered_messages()),
+		})
+		.into())
+	}
+
+	async fn kill_orphans(&self) -> Result<Data, RpcError> {
+		// Live queries can only be owned by sessions with live query support
+		if !Self::LQ_SUPPORT {
+			return Err(RpcError::BadLQConfig);
+		}
+		// Get the current session
+		let sess = self.session();
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession.into());
+		}
+		// Only root owners can kill the live queries of other sessions
+		if !sess.au.is_root() || !sess.au.has_owner_role() {
+			return Err(Error::from(IamError::NotAllowed {
+				actor: sess.au.id().to_string(),
+				action: "kill".to_string(),
+				resource: "live queries".to_string(),
+			})
+			.into());
+		}
+		// Find the live queries in the cluster which have no connected session
+		let mut ids: BTreeMap<uuid::Uuid, Vec<uuid::Uuid>> = BTreeMap::new();
+		let mut out = Vec::new();
+		for (nd, lqid, lq, stm) in self.kvs().cluster_queries().await? {
+			// Find the session which started the live query
+			let session = stm.and_then(|v| v.session).and_then(|v| match v.pick(ID.as_ref()) {
+				Value::Strand(v) => uuid::Uuid::parse_str(v.as_str()).ok(),
+				_ => None,
+			});
+			let orphaned = match nd.is_active() {
+				// The connections on this node own its live queries
+				true if nd.id == self.kvs().id() => !self.has_live_session(&lqid, session).await,
+				// The connections on other nodes are not known here
+				true => false,
+				// An archived node has no remaining connections
+				false => true,
+			};
+			if !orphaned {
+				continue;
+			}
+			ids.entry(nd.id).or_default().push(lqid);
+			out.push(Value::from(map! {
+				"id".to_string() => Value::Uuid(lqid.into()),
+				"nd".to_string() => Value::Uuid(nd.id.into()),
+				"ns".to_string() => Value::from(lq.ns),
+				"db".to_string() => Value::from(lq.db),
+				"tb".to_string() => Value::from(lq.tb),
+			}));
+		}
+		// Kill the orphaned live queries
+		for (nd, ids) in ids {
+			self.kvs().delete_node_queries(nd, ids.clone()).await?;
+			for lqid in ids.iter() {
+				self.handle_kill(lqid).await;
+			}
+		}
+		// Return the killed live queries
+		Ok(Value::from(out).into())
 	}
 
 	// ------------------------------
@@ -397,125 +1435,573 @@ pub trait RpcProtocolV2: RpcContext {
 		if !opts_value.is_none_or_null() {
 			opts.process_options(opts_value, self.kvs().get_capabilities())?;
 		}
+		// Check if the user is allowed to reveal sensitive fields
+		if !opts.reveal.is_empty() {
+			sensitive::check_reveal(self.kvs(), &self.session())?;
+		}
+		// Rename the selected fields, which are not selected for distinct values or columns
+		if let Some(aliases) = opts.alias.take() {
+			if opts.distinct.is_some() || opts.columns.is_some() {
+				return Err(RpcError::InvalidParams);
+			}
+			let fields = opts.fields.take().unwrap_or_else(Fields::all);
+			let (fields, omit) = with_aliases(fields, opts.omit.take(), &aliases)?;
+			opts.fields = Some(fields);
+			opts.omit = omit;
+		}
+		// Determine the requested page, which requires the total count
+		let page = match opts.paginate {
+			true => {
+				let start = match &opts.start {
+					Some(Start(Value::Number(Number::Int(v)))) if *v >= 0 => *v,
+					None => 0,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				let size = match &opts.limit {
+					Some(Limit(Value::Number(Number::Int(v)))) if *v > 0 => *v,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				opts.with_total = true;
+				Some((start, size))
+			}
+			false => None,
+		};
+		// The total can not be counted for distinct values
+		if opts.with_total && opts.distinct.is_some() {
+			return Err(RpcError::InvalidParams);
+		}
+		// Records can only be keyed by their id if they are returned whole with an id
+		if opts.by_id && (opts.compact || opts.distinct.is_some() || opts.columns.is_some()) {
+			return Err(RpcError::InvalidParams);
+		}
+		// Records can only be checksummed if they are returned whole with an id
+		if opts.with_checksum && (opts.compact || opts.distinct.is_some() || opts.columns.is_some())
+		{
+			return Err(RpcError::InvalidParams);
+		}
+		// Records can only be partitioned if they are returned as records
+		if opts.partition_by.is_some()
+			&& (opts.by_id || opts.distinct.is_some() || opts.columns.is_some())
+		{
+			return Err(RpcError::InvalidParams);
+		}
+		// Wait until the read reflects the requested versionstamp
+		if let Some(min) = opts.min_version {
+			self.await_versionstamp(min).await?;
+		}
+		// Specify the query parameters
+		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Fetch no more than one result over the maximum
+		if let Some(max) = opts.max_results {
+			opts.limit = match opts.limit {
+				Some(Limit(Value::Number(Number::Int(v)))) if v <= max as i64 => opts.limit,
+				_ => Some(Limit(Value::from(max as i64 + 1))),
+			};
+		}
+		// Specify the SQL query string
+		let what = what.could_be_table();
+		let distinct = opts.distinct.clone();
+		let omit = opts.fetch.as_ref().and(opts.omit.clone());
+		let fields = match &opts.columns {
+			// Select only the requested columns
+			Some(columns) => Fields(
+				columns
+					.iter()
+					.map(|c| Field::Single {
+						expr: Value::Idiom(c.clone()),
+						alias: None,
+					})
+					.collect(),
+				false,
+			),
+			None => opts.fields.clone().unwrap_or_else(Fields::all),
+		};
+		// Count all of the matching records, ignoring the page
+		let count = opts.with_total.then(|| SelectStatement {
+			expr: Fields(
+				vec![Field::Single {
+					expr: Function::Normal("count".to_string(), vec![]).into(),
+					alias: Some(Idiom(vec![Part::from("count")])),
+				}],
+				false,
+			),
+			what: vec![what.clone()].into(),
+			cond: opts.cond.clone(),
+			group: Some(Groups(vec![])),
+			timeout: opts.timeout.clone(),
+			version: opts.version.clone(),
+			..Default::default()
+		});
+		let mut sql: Query = match opts.distinct {
+			// Select the distinct values of a single field
+			Some(field) => SelectStatement {
+				expr: Fields(
+					vec![Field::Single {
+						expr: Value::Idiom(field.clone()),
+						alias: None,
+					}],
+					false,
+				),
+				what: vec![what.clone()].into(),
+				group: Some(Groups(vec![Group(field)])),
+				order: opts.order,
+				start: opts.start,
+				limit: opts.limit,
+				cond: opts.cond,
+				timeout: opts.timeout,
+				version: opts.version,
+				..Default::default()
+			},
+			// Select the records themselves
+			None => SelectStatement {
+				only: opts.only,
+				expr: fields,
+				omit: opts.omit,
+				what: vec![what.clone()].into(),
+				order: opts.order,
+				start: opts.start,
+				limit: opts.limit,
+				cond: opts.cond,
+				timeout: opts.timeout,
+				version: opts.version,
+				fetch: opts.fetch,
+				..Default::default()
+			},
+		}
+		.into();
+		// Append the count of all matching records
+		if let Some(count) = count {
+			sql.0 .0.push(Statement::Select(count));
+		}
+		// Leave any FUTURE values uncomputed if they are not needed
+		let sess = match opts.skip_computed {
+			true => Arc::new(Session {
+				skip_futures: true,
+				..self.session().as_ref().clone()
+			}),
+			false => self.session(),
+		};
+		// Reveal the sensitive fields which were requested, as all others are masked
+		let sess = sensitive::revealed(&sess, &opts.reveal);
+		// Execute the query on the database
+		let mut res = match opts.with_total {
+			// Select the page and the count within a single transaction
+			true => self.kvs().process_snapshot(sql.clone(), &sess, var).await?,
+			false => self.kvs().process(sql.clone(), &sess, var).await?,
+		};
+		// Extract the total number of matching records
+		let total = match opts.with_total {
+			true => match res.remove(1).result?.first().pick(&[Part::from("count")]) {
+				Value::None => Some(Value::from(0)),
+				v => Some(v),
+			},
+			false => None,
+		};
+		// Extract the first query result
+		let res = res.remove(0).result.or_else(|e| match e {
+			Error::SingleOnlyOutput => Ok(Value::None),
+			e => Err(e),
+		})?;
+		// Check that the maximum number of results was not exceeded
+		if let (Some(max), Value::Array(v)) = (opts.max_results, &res) {
+			if v.len() > max {
+				return Err(RpcError::TooManyResults(max));
+			}
+		}
+		// Check that the expected number of results was returned
+		if let Some(expect) = opts.expect {
+			expect.check(&res)?;
+		}
+		// Leave out the fields which were not computed
+		let res = match opts.skip_computed {
+			true => without_futures(res),
+			false => res,
+		};
+		// Omit the fields from any fetched records
+		let mut res = res;
+		if let Some(omit) = omit {
+			match &mut res {
+				Value::Array(v) => v.iter_mut().for_each(|v| omit.iter().for_each(|i| v.cut(i))),
+				v => omit.iter().for_each(|i| v.cut(i)),
+			}
+		}
+		// Strip the ids from the returned records
+		if opts.compact && distinct.is_none() {
+			match &mut res {
+				Value::Array(v) => v.iter_mut().for_each(|v| v.cut(ID.as_ref())),
+				v => v.cut(ID.as_ref()),
+			}
+		}
+		// Flatten the distinct values
+		let res = match distinct {
+			Some(field) => res.pick(&field),
+			None => res,
+		};
+		// Compute the checksum of each record as it is returned
+		let checksums = match opts.with_checksum {
+			true => Some(checksums(&res)?),
+			false => None,
+		};
+		// Describe the type of each field, preferring the declared types
+		let types = match opts.with_types {
+			true => Some(field_kinds::describe(self.kvs(), &self.session(), &what, &res).await?),
+			false => None,
+		};
+		// Arrange the records into ordered rows
+		let res = match &opts.columns {
+			Some(columns) => with_columns(res, columns),
+			None => res,
+		};
+		// Flatten the nested arrays by one level
+		let res = match opts.flatten {
+			true => res.flatten(),
+			false => res,
+		};
+		// Key the records by their id alongside the ordered array
+		let res = match opts.by_id {
+			true => with_by_id(res)?,
+			false => res,
+		};
+		// Partition the records by the value of a field
+		let res = match &opts.partition_by {
+			Some(field) => with_partitions(res, field),
+			None => res,
+		};
+		// Return the total alongside the records
+		let res = match (total, page) {
+			(Some(total), Some((start, size))) => {
+				let count = match &total {
+					Value::Number(v) => v.to_int(),
+					_ => 0,
+				};
+				Value::from(map! {
+					"results".to_string() => res,
+					"page".to_string() => Value::from(start / size + 1),
+					"page_size".to_string() => Value::from(size),
+					"total".to_string() => total,
+					"total_pages".to_string() => Value::from((count + size - 1) / size),
+					"has_next".to_string() => Value::from(start + size < count),
+					"has_prev".to_string() => Value::from(start > 0),
+				})
+			}
+			(Some(total), None) => Value::from(map! {
+				"total".to_string() => total,
+				"results".to_string() => res,
+			}),
+			(None, _) => res,
+		};
+		// Omit any absent fields from the result
+		let res = match opts.explicit_nulls {
+			true => without_absent(res),
+			false => res,
+		};
+		// Replace any non-finite floats in the result
+		let res = match opts.non_finite {
+			Some(policy) => policy.apply(res)?,
+			None => res,
+		};
+		// Replace any record ids in the result
+		let res = match opts.record_ids {
+			Some(format) => format.apply(res),
+			None => res,
+		};
+		// Return the type of each field alongside the result
+		let wrapped = types.is_some();
+		let res = match types {
+			Some(types) => field_kinds::with_types(res, types),
+			None => res,
+		};
+		// Return the checksum of each record alongside the result
+		let res = match checksums {
+			Some(checksums) => with_checksums(res, wrapped, checksums),
+			None => res,
+		};
+		// Attach the generated SurrealQL
+		match opts.echo_sql {
+			true => Ok(with_sql(res, &sql).into()),
+			false => Ok(res.into()),
+		}
+	}
+
+	async fn exists_many(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok(Value::Array(ids)) = params.needs_one() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Check that every argument is a record id
+		let ids = ids
+			.into_iter()
+			.map(|v| match v {
+				Value::Thing(v) => Ok(v),
+				_ => Err(RpcError::InvalidParams),
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		if ids.is_empty() {
+			return Ok(Value::Array(Array::new()).into());
+		}
+		// Specify the SQL query string, selecting only the ids of the existing records
+		let sql = SelectStatement {
+			expr: Fields(
+				vec![Field::Single {
+					expr: Value::Idiom(ID.as_ref().to_vec().into()),
+					alias: None,
+				}],
+				true,
+			),
+			what: ids.iter().cloned().map(Value::Thing).collect::<Vec<_>>().into(),
+			..Default::default()
+		};
+		// Execute the query on the database
+		let mut res = self.kvs().process(sql.into(), &self.session(), None).await?;
+		let existing: BTreeSet<Thing> = match res.remove(0).result? {
+			Value::Array(v) => v
+				.into_iter()
+				.filter_map(|v| match v {
+					Value::Thing(v) => Some(v),
+					_ => None,
+				})
+				.collect(),
+			_ => BTreeSet::new(),
+		};
+		// Report the existence of each record, in the order requested
+		let res: Vec<Value> = ids.iter().map(|id| Value::from(existing.contains(id))).collect();
+		Ok(Value::from(res).into())
+	}
+
+	// ------------------------------
+	// Methods for aggregating
+	// ------------------------------
+
+	async fn aggregate(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((what, func, field, opts_value)) = params.needs_three_or_four() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the aggregate function name
+		let func = match func {
+			Value::Strand(v) => match v.as_str() {
+				"sum" => "math::sum",
+				"avg" | "mean" => "math::mean",
+				"min" => "math::min",
+				"max" => "math::max",
+				_ => return Err(RpcError::InvalidParams),
+			},
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Parse the aggregated field
+		let field = match field {
+			Value::Strand(v) => idiom_with_capabilities(v.as_str(), self.kvs().get_capabilities())?,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Prepare options
+		let mut opts = StatementOptions::default();
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		}
 		// Specify the query parameters
 		let var = Some(opts.merge_vars(&self.session().parameters));
 		// Specify the SQL query string
 		let sql = SelectStatement {
-			only: opts.only,
-			expr: opts.fields.unwrap_or_else(Fields::all),
+			expr: Fields(
+				vec![Field::Single {
+					expr: Function::Normal(func.to_string(), vec![Value::Idiom(field)]).into(),
+					alias: Some(Idiom(vec![Part::from("value")])),
+				}],
+				false,
+			),
 			what: vec![what.could_be_table()].into(),
-			start: opts.start,
-			limit: opts.limit,
 			cond: opts.cond,
+			group: Some(Groups(vec![])),
 			timeout: opts.timeout,
 			version: opts.version,
-			fetch: opts.fetch,
 			..Default::default()
 		}
 		.into();
 		// Execute the query on the database
 		let mut res = self.kvs().process(sql, &self.session(), var).await?;
-		// Extract the first query result
-		Ok(res
-			.remove(0)
-			.result
-			.or_else(|e| match e {
-				Error::SingleOnlyOutput => Ok(Value::None),
-				e => Err(e),
-			})?
-			.into())
+		// Extract the aggregated value
+		Ok(res.remove(0).result?.first().pick(&[Part::from("value")]).into())
 	}
 
-	// ------------------------------
-	// Methods for inserting
-	// ------------------------------
-
-	async fn insert(&self, params: Array) -> Result<Data, RpcError> {
+	async fn count(&self, params: Array) -> Result<Data, RpcError> {
 		// Check if the user is allowed to query
 		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
 			return Err(RpcError::MethodNotAllowed);
 		}
 		// Process the method arguments
-		let Ok((what, data, opts_value)) = params.needs_two_or_three() else {
+		let Ok((what, opts_value)) = params.needs_one_or_two() else {
 			return Err(RpcError::InvalidParams);
 		};
 		// Prepare options
 		let mut opts = StatementOptions::default();
-		// Insert data
-		opts.with_data_content(data);
 		// Apply user options
 		if !opts_value.is_none_or_null() {
 			opts.process_options(opts_value, self.kvs().get_capabilities())?;
 		}
-		// Extract the data from the Option
-		let Some(data) = opts.data_expr() else {
-			return Err(
-				fail!("Data content was previously set, so it cannot be Option::None").into()
-			);
-		};
 		// Specify the query parameters
 		let var = Some(opts.merge_vars(&self.session().parameters));
 		// Specify the SQL query string
-		let sql = InsertStatement {
-			into: match what.is_none_or_null() {
-				false => Some(what.could_be_table()),
-				true => None,
-			},
-			data,
-			output: opts.output,
-			relation: opts.relation,
+		let what = what.could_be_table();
+		let mut sql = SelectStatement {
+			expr: Fields(
+				vec![Field::Single {
+					expr: Function::Normal("count".to_string(), vec![]).into(),
+					alias: Some(Idiom(vec![Part::from("count")])),
+				}],
+				false,
+			),
+			what: vec![what.clone()].into(),
+			cond: opts.cond,
+			group: Some(Groups(vec![])),
 			timeout: opts.timeout,
 			version: opts.version,
 			..Default::default()
+		};
+		// Check that a condition which can not use an index only scans a small table
+		if let (Value::Table(tb), Some(_), false) = (&what, &sql.cond, opts.allow_scan) {
+			sql.explain = Some(Explain(false));
+			let mut res =
+				self.kvs().process(sql.clone().into(), &self.session(), var.clone()).await?;
+			sql.explain = None;
+			if scans_table(&res.remove(0).result?) {
+				let sample = SelectStatement {
+					expr: Fields(
+						vec![Field::Single {
+							expr: Value::Idiom(Idiom(ID.to_vec())),
+							alias: None,
+						}],
+						true,
+					),
+					what: vec![what.clone()].into(),
+					limit: Some(Limit(Value::from(*COUNT_SCAN_LIMIT as i64 + 1))),
+					..Default::default()
+				};
+				let mut res = self.kvs().process(sample.into(), &self.session(), None).await?;
+				if let Value::Array(v) = res.remove(0).result? {
+					if v.len() > *COUNT_SCAN_LIMIT {
+						return Err(RpcError::UnindexedCount(tb.0.clone(), *COUNT_SCAN_LIMIT));
+					}
+				}
+			}
 		}
-		.into();
+		// Execute the query on the database
+		let mut res = self.kvs().process(sql.into(), &self.session(), var).await?;
+		// Extract the count, which is missing when no records match
+		match res.remove(0).result?.first().pick(&[Part::from("count")]) {
+			Value::None => Ok(Value::from(0).into()),
+			v => Ok(v.into()),
+		}
+	}
+
+	async fn pipeline(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((what, Value::Array(stages))) = params.needs_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Compile the stages into a single select statement
+		let sql: Query =
+			pipeline::statement(what.could_be_table(), stages, self.kvs().get_capabilities())?
+				.into();
+		// Specify the query parameters
+		let var = Some(self.session().parameters.clone());
 		// Execute the query on the database
 		let mut res = self.kvs().process(sql, &self.session(), var).await?;
-		// Extract the first query result
-		Ok(res
-			.remove(0)
-			.result
-			.or_else(|e| match e {
-				Error::SingleOnlyOutput => Ok(Value::None),
-				e => Err(e),
-			})?
-			.into())
+		Ok(res.remove(0).result?.into())
 	}
 
 	// ------------------------------
-	// Methods for creating
+	// Methods for searching
 	// ------------------------------
 
-	async fn create(&self, params: Array) -> Result<Data, RpcError> {
+	async fn search(&self, params: Array) -> Result<Data, RpcError> {
 		// Check if the user is allowed to query
 		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
 			return Err(RpcError::MethodNotAllowed);
 		}
 		// Process the method arguments
-		let Ok((what, data, opts_value)) = params.needs_one_two_or_three() else {
+		let Ok((what, field, query, opts_value)) = params.needs_three_or_four() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the searched field
+		let field = match field {
+			Value::Strand(v) => idiom_with_capabilities(v.as_str(), self.kvs().get_capabilities())?,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Check the search query type
+		let Value::Strand(query) = query else {
 			return Err(RpcError::InvalidParams);
 		};
 		// Prepare options
 		let mut opts = StatementOptions::default();
-		// Set the default output
-		opts.with_output(Output::After);
-		// Insert data
-		if !data.is_none_or_null() {
-			opts.with_data_content(data);
-		}
+		// Highlight the matches and score the records by default
+		opts.with_highlight().with_score();
 		// Apply user options
 		if !opts_value.is_none_or_null() {
 			opts.process_options(opts_value, self.kvs().get_capabilities())?;
 		}
-		let what = what.could_be_table();
 		// Specify the query parameters
 		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Match the searched field against the search query
+		let cond = Value::from(Expression::Binary {
+			l: Value::Idiom(field),
+			o: Operator::Matches(Some(SEARCH_REF)),
+			r: Value::Strand(query),
+		});
+		let cond = match opts.cond {
+			Some(Cond(v)) => Value::from(Expression::Binary {
+				l: cond,
+				o: Operator::And,
+				r: v,
+			}),
+			None => cond,
+		};
+		// Select the score and highlights alongside the fields
+		let mut fields = opts.fields.unwrap_or_else(Fields::all);
+		if fields.1 {
+			return Err(RpcError::InvalidParams);
+		}
+		if opts.score {
+			fields.0.push(Field::Single {
+				expr: Function::Normal("search::score".to_string(), vec![SEARCH_REF.into()]).into(),
+				alias: Some(Idiom(vec![Part::from("score")])),
+			});
+		}
+		if let Some((prefix, suffix)) = opts.highlight {
+			fields.0.push(Field::Single {
+				expr: Function::Normal(
+					"search::highlight".to_string(),
+					vec![prefix.into(), suffix.into(), SEARCH_REF.into()],
+				)
+				.into(),
+				alias: Some(Idiom(vec![Part::from("highlight")])),
+			});
+		}
 		// Specify the SQL query string
-		let sql = CreateStatement {
-			only: opts.only,
+		let sql = SelectStatement {
+			expr: fields,
 			what: vec![what.could_be_table()].into(),
-			data: opts.data_expr(),
-			output: opts.output,
+			cond: Some(Cond(cond)),
+			order: opts.score.then(|| {
+				Ordering::Order(OrderList(vec![Order {
+					value: Idiom(vec![Part::from("score")]),
+					..Default::default()
+				}]))
+			}),
+			start: opts.start,
+			limit: opts.limit,
 			timeout: opts.timeout,
 			version: opts.version,
 			..Default::default()
@@ -524,72 +2010,208 @@ pub trait RpcProtocolV2: RpcContext {
 		// Execute the query on the database
 		let mut res = self.kvs().process(sql, &self.session(), var).await?;
 		// Extract the first query result
-		Ok(res
-			.remove(0)
-			.result
-			.or_else(|e| match e {
-				Error::SingleOnlyOutput => Ok(Value::None),
-				e => Err(e),
-			})?
-			.into())
+		Ok(res.remove(0).result?.into())
 	}
 
 	// ------------------------------
-	// Methods for upserting
+	// Methods for inserting
 	// ------------------------------
 
-	async fn upsert(&self, params: Array) -> Result<Data, RpcError> {
+	async fn insert(&self, params: Array) -> Result<Data, RpcError> {
 		// Check if the user is allowed to query
 		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
 			return Err(RpcError::MethodNotAllowed);
 		}
 		// Process the method arguments
-		let Ok((what, data, opts_value)) = params.needs_one_two_or_three() else {
+		let Ok((what, data, opts_value)) = params.needs_two_or_three() else {
 			return Err(RpcError::InvalidParams);
 		};
 		// Prepare options
 		let mut opts = StatementOptions::default();
-		// Set the default output
-		opts.with_output(Output::After);
 		// Insert data
-		if !data.is_none_or_null() {
-			opts.with_data_content(data);
-		}
+		opts.with_data_single(data.clone());
 		// Apply user options
 		if !opts_value.is_none_or_null() {
 			opts.process_options(opts_value, self.kvs().get_capabilities())?;
 		}
+		// Extract the data from the Option
+		let Some(insert_data) = opts.data_expr() else {
+			return Err(
+				fail!("Data content was previously set, so it cannot be Option::None").into()
+			);
+		};
 		// Specify the query parameters
 		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Specify the target table
+		let into = match what.is_none_or_null() {
+			false => Some(what.could_be_table()),
+			true => None,
+		};
+		// Insert each row in its own statement and transaction,
+		// so that the outcome of each row can be reported
+		if opts.row_status {
+			// Existing records and retries would hide the outcome of a row
+			if opts.get_existing || opts.retry.is_some() {
+				return Err(RpcError::InvalidParams);
+			}
+			let rows = match data {
+				Value::Array(v) => v.0,
+				v => vec![v],
+			};
+			let sql = Query::from(
+				rows.into_iter()
+					.map(|row| {
+						Statement::Insert(InsertStatement {
+							into: into.clone(),
+							data: crate::sql::Data::SingleExpression(row),
+							output: opts.output.clone(),
+							relation: opts.relation,
+							timeout: opts.timeout.clone(),
+							version: opts.version.clone(),
+							..Default::default()
+						})
+					})
+					.collect::<Vec<_>>(),
+			);
+			let res = self.kvs().process(sql.clone(), &self.session(), var).await?;
+			// Report the outcome of each row
+			let res: Vec<Value> = res
+				.into_iter()
+				.map(|response| {
+					let (status, result) = match response.result {
+						Ok(Value::Array(v)) => {
+							("inserted", v.0.into_iter().next().unwrap_or_default())
+						}
+						Ok(v) => ("inserted", v),
+						Err(
+							e @ (Error::RecordExists {
+								..
+							}
+							| Error::IndexExists {
+								..
+							}),
+						) => ("conflict", e.to_string().into()),
+						Err(e) => ("error", e.to_string().into()),
+					};
+					Value::from(map! {
+						"status".to_string() => Value::from(status),
+						"result".to_string() => result,
+					})
+				})
+				.collect();
+			let res = match opts.echo_sql {
+				true => with_sql(Value::from(res), &sql),
+				false => Value::from(res),
+			};
+			// Attach the versionstamp which follows the writes
+			return match opts.versionstamp {
+				true => {
+					Ok(with_versionstamp(res, opts.echo_sql, self.write_versionstamp().await?)
+						.into())
+				}
+				false => Ok(res.into()),
+			};
+		}
 		// Specify the SQL query string
-		let sql = UpsertStatement {
-			only: opts.only,
-			what: vec![what.could_be_table()].into(),
-			data: opts.data_expr(),
+		let sql: Query = InsertStatement {
+			into: into.clone(),
+			data: insert_data,
+			ignore: opts.get_existing,
 			output: opts.output,
-			cond: opts.cond,
+			relation: opts.relation,
 			timeout: opts.timeout,
+			version: opts.version,
 			..Default::default()
 		}
 		.into();
 		// Execute the query on the database
-		let mut res = self.kvs().process(sql, &self.session(), var).await?;
+		let sess = self.session();
+		let mut res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
 		// Extract the first query result
-		Ok(res
-			.remove(0)
-			.result
-			.or_else(|e| match e {
-				Error::SingleOnlyOutput => Ok(Value::None),
-				e => Err(e),
-			})?
-			.into())
+		let res = res.remove(0).result.or_else(|e| match e {
+			Error::SingleOnlyOutput => Ok(Value::None),
+			e => Err(e),
+		})?;
+		// Return the existing records which were ignored
+		let res = match (opts.get_existing, res) {
+			(true, Value::Array(created)) => {
+				// Specify the ids of the requested records
+				let into = match into {
+					Some(Value::Table(tb)) => Some(tb),
+					_ => None,
+				};
+				let ids: Vec<Option<Thing>> = match data {
+					Value::Array(v) => v.iter().map(|v| requested_id(v, &into)).collect(),
+					v => vec![requested_id(&v, &into)],
+				};
+				// Collect the records which were created
+				let mut records: BTreeMap<Thing, Value> = BTreeMap::new();
+				let mut generated = Vec::new();
+				for v in created {
+					match v.rid() {
+						Value::Thing(id) if ids.contains(&Some(id.clone())) => {
+							records.insert(id, v);
+						}
+						_ => generated.push(v),
+					}
+				}
+				// Select the records which already existed
+				let existing: Vec<Value> = ids
+					.iter()
+					.flatten()
+					.filter(|id| !records.contains_key(id))
+					.map(|id| Value::Thing(id.clone()))
+					.collect();
+				if !existing.is_empty() {
+					let sql: Query = SelectStatement {
+						expr: Fields::all(),
+						what: existing.into(),
+						..Default::default()
+					}
+					.into();
+					let mut res = self.kvs().process(sql, &sess, var).await?;
+					if let Value::Array(v) = res.remove(0).result? {
+						for v in v {
+							if let Value::Thing(id) = v.rid() {
+								records.insert(id, v);
+							}
+						}
+					}
+				}
+				// Return the records in the requested order
+				let mut generated = generated.into_iter();
+				let res: Vec<Value> = ids
+					.iter()
+					.filter_map(|id| match id {
+						Some(id) => records.get(id).cloned(),
+						None => generated.next(),
+					})
+					.collect();
+				Value::from(res)
+			}
+			(_, res) => res,
+		};
+		// Attach the generated SurrealQL
+		let res = match opts.echo_sql {
+			true => with_sql(res, &sql),
+			false => res,
+		};
+		// Attach the versionstamp which follows the write
+		match opts.versionstamp {
+			true => {
+				Ok(with_versionstamp(res, opts.echo_sql, self.write_versionstamp().await?).into())
+			}
+			false => Ok(res.into()),
+		}
 	}
 
 	// ------------------------------
-	// Methods for updating
+	// Methods for creating
 	// ------------------------------
 
-	async fn update(&self, params: Array) -> Result<Data, RpcError> {
+	async fn create(&self, params: Array) -> Result<Data, RpcError> {
 		// Check if the user is allowed to query
 		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
 			return Err(RpcError::MethodNotAllowed);
@@ -610,167 +2232,1797 @@ pub trait RpcProtocolV2: RpcContext {
 		if !opts_value.is_none_or_null() {
 			opts.process_options(opts_value, self.kvs().get_capabilities())?;
 		}
+		// The defaults are found by comparing the input data against the created record
+		let input = match (opts.defaults, opts.data.as_ref().map(|v| v.value())) {
+			(false, _) => None,
+			(true, None) => Some(Value::Object(Default::default())),
+			(true, Some(v @ Value::Object(_))) => Some(v.clone()),
+			(true, Some(_)) => return Err(RpcError::InvalidParams),
+		};
+		if input.is_some() && (opts.get_existing || !matches!(opts.output, Some(Output::After))) {
+			return Err(RpcError::InvalidParams);
+		}
+		let what = what.could_be_table();
 		// Specify the query parameters
 		let var = Some(opts.merge_vars(&self.session().parameters));
 		// Specify the SQL query string
-		let sql = UpdateStatement {
+		let sql: Query = CreateStatement {
 			only: opts.only,
 			what: vec![what.could_be_table()].into(),
 			data: opts.data_expr(),
 			output: opts.output,
-			cond: opts.cond,
 			timeout: opts.timeout,
+			version: opts.version,
 			..Default::default()
 		}
-		.into();
-		// Execute the query on the database
-		let mut res = self.kvs().process(sql, &self.session(), var).await?;
-		// Extract the first query result
-		Ok(res
-			.remove(0)
-			.result
-			.or_else(|e| match e {
-				Error::SingleOnlyOutput => Ok(Value::None),
-				e => Err(e),
-			})?
-			.into())
+		.into();
+		// Execute the query on the database
+		let sess = self.session();
+		let mut res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
+		// Extract the first query result
+		let res = match res.remove(0).result {
+			// Return the existing record untouched
+			Err(Error::RecordExists {
+				thing,
+			}) if opts.get_existing => {
+				let sql: Query = SelectStatement {
+					only: opts.only,
+					expr: Fields::all(),
+					what: vec![Value::Thing(thing)].into(),
+					..Default::default()
+				}
+				.into();
+				let mut res = self.kvs().process(sql, &sess, var).await?;
+				res.remove(0).result?
+			}
+			res => res.or_else(|e| match e {
+				Error::SingleOnlyOutput => Ok(Value::None),
+				e => Err(e),
+			})?,
+		};
+		// Attach the generated SurrealQL
+		let res = match opts.echo_sql {
+			true => with_sql(res, &sql),
+			false => res,
+		};
+		// Attach the fields which were defaulted
+		let res = match &input {
+			Some(input) => with_defaults(res, opts.echo_sql, input),
+			None => res,
+		};
+		// Attach the versionstamp which follows the write
+		match opts.versionstamp {
+			true => {
+				let wrapped = opts.echo_sql || input.is_some();
+				Ok(with_versionstamp(res, wrapped, self.write_versionstamp().await?).into())
+			}
+			false => Ok(res.into()),
+		}
+	}
+
+	// ------------------------------
+	// Methods for duplicating
+	// ------------------------------
+
+	async fn duplicate(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((from, into, data)) = params.needs_one_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// The source must be a single record
+		let from = match from {
+			Value::Thing(v) if !v.is_range() => v,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Default to a new record in the source table
+		let into = match into {
+			Value::None | Value::Null => Value::Table(from.tb.clone().into()),
+			Value::Thing(v) if !v.is_range() => Value::Thing(v),
+			Value::Strand(v) => Value::Table(v.0.into()),
+			v @ Value::Table(_) => v,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Any fields to override on the copy
+		let data = match data {
+			Value::None | Value::Null => Value::Object(Default::default()),
+			v @ Value::Object(_) => v,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Specify the query parameters
+		let var = Some(map! {
+			String::from("from") => Value::Thing(from),
+			String::from("into") => into,
+			String::from("data") => data,
+			=> &self.session().parameters
+		});
+		// Read and copy the record within the same transaction
+		let sql = value_with_capabilities(
+			r#"{
+				LET $record = SELECT * OMIT id FROM ONLY $from;
+				IF !$record {
+					THROW "The record '" + <string> $from + "' does not exist";
+				};
+				RETURN CREATE ONLY $into CONTENT object::extend($record, $data);
+			}"#,
+			self.kvs().get_capabilities(),
+		)?;
+		// Execute the query on the database
+		Ok(self.kvs().compute(sql, &self.session(), var).await?.into())
+	}
+
+	async fn move_record(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((from, into, opts)) = params.needs_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// The source must be a single record
+		let from = match from {
+			Value::Thing(v) if !v.is_range() => v,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Keep the record id when only a table is specified
+		let into = match into {
+			Value::Thing(v) if !v.is_range() => v,
+			Value::Strand(v) => Thing::from((v.0, from.id.clone())),
+			Value::Table(v) => Thing::from((v.0, from.id.clone())),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Whether to point the record edges at the moved record
+		let edges = match opts {
+			Value::None | Value::Null => false,
+			Value::Object(mut obj) => match obj.remove("edges") {
+				Some(Value::Bool(v)) => v,
+				None => false,
+				_ => return Err(RpcError::InvalidParams),
+			},
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Specify the query parameters
+		let var = Some(map! {
+			String::from("from") => Value::Thing(from),
+			String::from("into") => Value::Thing(into),
+			String::from("edges") => Value::Bool(edges),
+			=> &self.session().parameters
+		});
+		// Read, recreate, and delete the record within the same transaction,
+		// as deleting the original record also deletes any of its edges
+		let sql = value_with_capabilities(
+			r#"{
+				LET $record = SELECT * OMIT id FROM ONLY $from;
+				IF !$record {
+					THROW "The record '" + <string> $from + "' does not exist";
+				};
+				LET $related = SELECT * FROM array::union($from->?, $from<-?) WHERE $edges;
+				DELETE $from;
+				LET $moved = CREATE ONLY $into CONTENT $record;
+				FOR $edge IN $related {
+					LET $kind = $edge.id;
+					LET $left = IF $edge.in = $from { $into } ELSE { $edge.in };
+					LET $right = IF $edge.out = $from { $into } ELSE { $edge.out };
+					RELATE $left->$kind->$right CONTENT $edge;
+				};
+				RETURN $moved;
+			}"#,
+			self.kvs().get_capabilities(),
+		)?;
+		// Execute the query on the database
+		Ok(self.kvs().compute(sql, &self.session(), var).await?.into())
+	}
+
+	// ------------------------------
+	// Methods for upserting
+	// ------------------------------
+
+	async fn upsert(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((what, data, opts_value)) = params.needs_one_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Prepare options
+		let mut opts = StatementOptions::default();
+		// Set the default output
+		opts.with_output(Output::After);
+		// Insert data
+		if !data.is_none_or_null() {
+			opts.with_data_content(data);
+		}
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		}
+		// Specify the query parameters
+		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Specify the SQL query string
+		let sql: Query = UpsertStatement {
+			only: opts.only,
+			what: vec![what.could_be_table()].into(),
+			data: opts.data_expr(),
+			output: opts.output,
+			cond: opts.cond,
+			timeout: opts.timeout,
+			..Default::default()
+		}
+		.into();
+		// Execute the query on the database
+		let sess = self.session();
+		let mut res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
+		// Extract the first query result
+		let res = res.remove(0).result.or_else(|e| match e {
+			Error::SingleOnlyOutput => Ok(Value::None),
+			e => Err(e),
+		})?;
+		// Attach the generated SurrealQL
+		let res = match opts.echo_sql {
+			true => with_sql(res, &sql),
+			false => res,
+		};
+		// Attach the versionstamp which follows the write
+		match opts.versionstamp {
+			true => {
+				Ok(with_versionstamp(res, opts.echo_sql, self.write_versionstamp().await?).into())
+			}
+			false => Ok(res.into()),
+		}
+	}
+
+	async fn swap(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((Value::Thing(what), data @ Value::Object(_), opts_value)) =
+			params.needs_two_or_three()
+		else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Prepare options
+		let mut opts = StatementOptions::default();
+		// Replace the whole record
+		opts.with_data_content(data);
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		}
+		// Specify the query parameters
+		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Specify the SQL query string, which creates the record if it does
+		// not exist, and always returns the record as it was before the swap
+		let sql: Query = UpsertStatement {
+			only: true,
+			what: vec![Value::Thing(what)].into(),
+			data: opts.data_expr(),
+			output: Some(Output::Before),
+			timeout: opts.timeout,
+			..Default::default()
+		}
+		.into();
+		// Execute the query on the database
+		let sess = self.session();
+		let mut res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
+		// Extract the first query result
+		let res = res.remove(0).result?;
+		// Attach the generated SurrealQL
+		let res = match opts.echo_sql {
+			true => with_sql(res, &sql),
+			false => res,
+		};
+		// Attach the versionstamp which follows the write
+		match opts.versionstamp {
+			true => {
+				Ok(with_versionstamp(res, opts.echo_sql, self.write_versionstamp().await?).into())
+			}
+			false => Ok(res.into()),
+		}
+	}
+
+	async fn upsert_split(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((what, on_create, on_update)) = params.needs_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Each record must be a single record id
+		let (single, what) = match what {
+			Value::Thing(v) if !v.is_range() => (true, vec![Value::Thing(v)]),
+			Value::Array(v)
+				if !v.is_empty()
+					&& v.iter().all(|v| matches!(v, Value::Thing(v) if !v.is_range())) =>
+			{
+				(false, v.0)
+			}
+			_ => return Err(RpcError::InvalidParams),
+		};
+		let len = what.len();
+		// The record content when it is created
+		let on_create = match on_create {
+			v @ Value::Object(_) => v,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// The fields to merge when the record is updated
+		let on_update = match on_update {
+			Value::None | Value::Null => Value::Object(Default::default()),
+			v @ Value::Object(_) => v,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Specify the query parameters
+		let var = Some(map! {
+			String::from("what") => Value::from(what),
+			String::from("on_create") => on_create,
+			String::from("on_update") => on_update,
+			=> &self.session().parameters
+		});
+		// Create or update each record in turn, within the same transaction
+		let records = (0..len)
+			.map(|i| {
+				format!(
+					r#"{{
+						LET $id = $what[{i}];
+						LET $exists = record::exists($id);
+						LET $result = IF $exists {{
+							UPDATE ONLY $id MERGE $on_update
+						}} ELSE {{
+							CREATE ONLY $id CONTENT $on_create
+						}};
+						RETURN {{
+							action: IF $exists {{ "update" }} ELSE {{ "create" }},
+							result: $result,
+						}};
+					}}"#
+				)
+			})
+			.collect::<Vec<_>>();
+		let sql = value_with_capabilities(
+			&format!("[{}]", records.join(", ")),
+			self.kvs().get_capabilities(),
+		)?;
+		// Execute the query on the database
+		match self.kvs().compute(sql, &self.session(), var).await? {
+			Value::Array(mut v) if single && v.len() == 1 => Ok(v.0.remove(0).into()),
+			res => Ok(res.into()),
+		}
+	}
+
+	// ------------------------------
+	// Methods for updating
+	// ------------------------------
+
+	async fn update(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((what, data, opts_value)) = params.needs_one_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Prepare options
+		let mut opts = StatementOptions::default();
+		// Set the default output
+		opts.with_output(Output::After);
+		// Insert data
+		if !data.is_none_or_null() {
+			opts.with_data_content(data);
+		}
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		}
+		// Return each record before and after the update for the changeset
+		if opts.changeset {
+			opts.with_output(Output::Fields(Fields(
+				vec![
+					Field::Single {
+						expr: Value::Idiom(Idiom(ID.to_vec())),
+						alias: None,
+					},
+					Field::Single {
+						expr: Value::Param(Param::from("before")),
+						alias: Some(Idiom(vec![Part::from("before")])),
+					},
+					Field::Single {
+						expr: Value::Param(Param::from("after")),
+						alias: Some(Idiom(vec![Part::from("after")])),
+					},
+				],
+				false,
+			)));
+		}
+		// Specify the query parameters
+		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Specify the SQL query string
+		let sql: Query = UpdateStatement {
+			only: opts.only,
+			what: vec![what.could_be_table()].into(),
+			data: opts.data_expr(),
+			output: opts.output,
+			cond: opts.cond,
+			timeout: opts.timeout,
+			..Default::default()
+		}
+		.into();
+		// Execute the query on the database
+		let sess = self.session();
+		let mut res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
+		// Extract the first query result
+		let res = res.remove(0).result.or_else(|e| match e {
+			Error::SingleOnlyOutput => Ok(Value::None),
+			e => Err(e),
+		})?;
+		// Flatten the changed fields of every record
+		let res = match opts.changeset {
+			true => with_changeset(res),
+			false => res,
+		};
+		// Attach the generated SurrealQL
+		let res = match opts.echo_sql {
+			true => with_sql(res, &sql),
+			false => res,
+		};
+		// Attach the versionstamp which follows the write
+		match opts.versionstamp {
+			true => {
+				Ok(with_versionstamp(res, opts.echo_sql, self.write_versionstamp().await?).into())
+			}
+			false => Ok(res.into()),
+		}
+	}
+
+	async fn append(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((Value::Thing(what), Value::Strand(field), value, opts_value)) =
+			params.needs_three_or_four()
+		else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the array field
+		let field = idiom_with_capabilities(field.as_str(), self.kvs().get_capabilities())?;
+		// Prepare options
+		let mut opts = StatementOptions::default();
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		}
+		// Specify the query parameters
+		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Append the value to the array field
+		let mut exprs = vec![(field.clone(), Operator::Inc, Value::from(vec![value]))];
+		// Trim the oldest elements which no longer fit
+		if let Some(max) = opts.max_length {
+			exprs.push((
+				field.clone(),
+				Operator::Equal,
+				Function::Normal(
+					"array::slice".to_string(),
+					vec![Value::Idiom(field.clone()), Value::from(-(max as i64))],
+				)
+				.into(),
+			));
+		}
+		// Specify the SQL query string, which returns the new length of the array
+		let sql: Query = UpdateStatement {
+			only: true,
+			what: vec![Value::Thing(what)].into(),
+			data: Some(crate::sql::Data::SetExpression(exprs)),
+			output: Some(Output::Fields(Fields(
+				vec![Field::Single {
+					expr: Function::Normal("array::len".to_string(), vec![Value::Idiom(field)])
+						.into(),
+					alias: None,
+				}],
+				true,
+			))),
+			timeout: opts.timeout,
+			..Default::default()
+		}
+		.into();
+		// Execute the query on the database
+		let sess = self.session();
+		let mut res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
+		// Extract the first query result
+		let res = res.remove(0).result?;
+		// Attach the generated SurrealQL
+		let res = match opts.echo_sql {
+			true => with_sql(res, &sql),
+			false => res,
+		};
+		// Attach the versionstamp which follows the write
+		match opts.versionstamp {
+			true => {
+				Ok(with_versionstamp(res, opts.echo_sql, self.write_versionstamp().await?).into())
+			}
+			false => Ok(res.into()),
+		}
+	}
+
+	// ------------------------------
+	// Methods for relating
+	// ------------------------------
+
+	async fn relate(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((from, kind, with, data, opts_value)) = params.needs_three_four_or_five() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Prepare options
+		let mut opts = StatementOptions::default();
+		// Set the default output
+		opts.with_output(Output::After);
+		// Insert data
+		let merge = data.clone();
+		if !data.is_none_or_null() {
+			opts.with_data_content(data);
+		}
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		}
+		// Specify the query parameters
+		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Specify the SQL query string
+		let sql: Query = match opts.upsert {
+			// Update an existing edge between the records, or create a new one
+			true => upsert_edge(from, kind, with, merge, &opts)?,
+			false => {
+				let relate = RelateStatement {
+					only: opts.only,
+					from,
+					kind: kind.could_be_table(),
+					with,
+					data: opts.data_expr(),
+					output: opts.output,
+					timeout: opts.timeout,
+					uniq: opts.unique,
+					..Default::default()
+				};
+				match opts.fetch {
+					// Fetch the related records on the created edges
+					Some(fetch) => SelectStatement {
+						only: opts.only,
+						expr: Fields::all(),
+						what: vec![Value::Subquery(Box::new(Subquery::Relate(relate)))].into(),
+						fetch: Some(fetch),
+						..Default::default()
+					}
+					.into(),
+					// Return the created edges
+					None => relate.into(),
+				}
+			}
+		};
+		// Execute the query on the database
+		let sess = self.session();
+		let mut res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
+		// Extract the first query result
+		let res = res.remove(0).result.or_else(|e| match e {
+			Error::SingleOnlyOutput => Ok(Value::None),
+			e => Err(e),
+		})?;
+		// Group the created edges by their source record
+		let res = match opts.group_by_from {
+			true => with_adjacency(res),
+			false => res,
+		};
+		// Attach the generated SurrealQL
+		let res = match opts.echo_sql {
+			true => with_sql(res, &sql),
+			false => res,
+		};
+		// Attach the versionstamp which follows the write
+		match opts.versionstamp {
+			true => {
+				Ok(with_versionstamp(res, opts.echo_sql, self.write_versionstamp().await?).into())
+			}
+			false => Ok(res.into()),
+		}
+	}
+
+	async fn bulk_relate(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((Value::Array(edges), opts_value)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Prepare options
+		let mut opts = StatementOptions::default();
+		// Set the default output
+		opts.with_output(Output::After);
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		}
+		// Specify the query parameters
+		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Create all of the edges within a single transaction
+		let mut stmts = vec![Statement::Begin(Default::default())];
+		for (i, edge) in edges.into_iter().enumerate() {
+			// Check that the edge specifies its endpoints
+			let Value::Object(mut edge) = edge else {
+				return Err(RpcError::InvalidEdge(i));
+			};
+			let (Some(from), Some(kind), Some(with)) =
+				(edge.remove("from"), edge.remove("kind"), edge.remove("to"))
+			else {
+				return Err(RpcError::InvalidEdge(i));
+			};
+			if from.is_none_or_null() || kind.is_none_or_null() || with.is_none_or_null() {
+				return Err(RpcError::InvalidEdge(i));
+			}
+			// Check that the edge specifies nothing else
+			let data = edge.remove("data").filter(|v| !v.is_none_or_null());
+			if !edge.is_empty() {
+				return Err(RpcError::InvalidEdge(i));
+			}
+			stmts.push(Statement::Relate(RelateStatement {
+				from,
+				kind: kind.could_be_table(),
+				with,
+				data: data.map(crate::sql::Data::ContentExpression),
+				output: opts.output.clone(),
+				timeout: opts.timeout.clone(),
+				uniq: opts.unique,
+				..Default::default()
+			}));
+		}
+		stmts.push(Statement::Commit(Default::default()));
+		let sql = Query(Statements(stmts));
+		// Execute the query on the database
+		let sess = self.session();
+		let res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
+		// Collect the created edges
+		let mut out = Vec::with_capacity(res.len());
+		let mut errors = Vec::new();
+		for response in res {
+			match response.result {
+				Ok(Value::Array(v)) => out.extend(v),
+				Ok(Value::None) => (),
+				Ok(v) => out.push(v),
+				Err(e) => errors.push(e),
+			}
+		}
+		// Return the error which caused the transaction to fail
+		if !errors.is_empty() {
+			let i = errors.iter().position(|e| !matches!(e, Error::QueryNotExecuted)).unwrap_or(0);
+			return Err(errors.swap_remove(i).into());
+		}
+		// Return the created edges, grouped by their source record if requested
+		match opts.group_by_from {
+			true => Ok(with_adjacency(Value::from(out)).into()),
+			false => Ok(Value::from(out).into()),
+		}
+	}
+
+	async fn edges(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((Value::Thing(from), opts_value)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Process the direction and edge table options
+		let mut dirs = vec![Dir::In, Dir::Out];
+		let mut what = GraphSubjects::default();
+		match opts_value {
+			Value::Object(mut obj) => {
+				// Process "direction" option
+				if let Some(v) = obj.remove("direction") {
+					dirs = match v {
+						Value::Strand(v) => match v.as_str() {
+							"in" => vec![Dir::In],
+							"out" => vec![Dir::Out],
+							"both" => vec![Dir::In, Dir::Out],
+							_ => return Err(RpcError::InvalidParams),
+						},
+						_ => return Err(RpcError::InvalidParams),
+					};
+				}
+				// Process "tables" option
+				if let Some(v) = obj.remove("tables") {
+					let tables = match v {
+						Value::Strand(v) => vec![Value::Strand(v)],
+						Value::Array(v) => v.0,
+						_ => return Err(RpcError::InvalidParams),
+					};
+					for table in tables {
+						let Value::Strand(table) = table else {
+							return Err(RpcError::InvalidParams);
+						};
+						what.0.push(GraphSubject::Table(Table(table.0)));
+					}
+				}
+			}
+			Value::None | Value::Null => (),
+			_ => return Err(RpcError::InvalidParams),
+		}
+		// Fetch the edges of the record in each direction
+		let sql = Query(Statements(
+			dirs.iter()
+				.map(|dir| {
+					let edges = Edges {
+						dir: dir.clone(),
+						from: from.clone(),
+						what: what.clone(),
+					};
+					Statement::Value(Value::Idiom(Idiom(vec![
+						Part::Start(Value::Edges(Box::new(edges))),
+						Part::All,
+					])))
+				})
+				.collect(),
+		));
+		// Specify the query parameters
+		let var = Some(self.session().parameters.clone());
+		// Execute the query on the database
+		let res = self.kvs().process(sql, &self.session(), var).await?;
+		// Group the edges in each direction by their edge table
+		let mut out = BTreeMap::new();
+		for (dir, response) in dirs.iter().zip(res) {
+			let mut groups: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+			if let Value::Array(edges) = response.result? {
+				for edge in edges {
+					// Skip the edges which could not be fetched
+					if let Value::Thing(id) = edge.rid() {
+						groups.entry(id.tb).or_default().push(edge);
+					}
+				}
+			}
+			let key = match dir {
+				Dir::In => "in",
+				_ => "out",
+			};
+			let groups: BTreeMap<String, Value> =
+				groups.into_iter().map(|(k, v)| (k, Value::from(v))).collect();
+			out.insert(key.to_string(), Value::from(groups));
+		}
+		// Return the grouped edges
+		Ok(Value::from(out).into())
+	}
+
+	async fn traverse(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((Value::Thing(from), Value::Array(hops))) = params.needs_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		if from.is_range() {
+			return Err(RpcError::InvalidParams);
+		}
+		// Follow each of the hops in order from the start record
+		let mut path = vec![Part::Start(Value::Thing(from))];
+		path.extend(graph_hops::parts(hops, self.kvs().get_capabilities())?);
+		// Return each of the reached records once
+		let sql = Query(Statements(vec![Statement::Value(
+			Function::Normal("array::distinct".to_string(), vec![Value::Idiom(Idiom(path))]).into(),
+		)]));
+		// Specify the query parameters
+		let var = Some(self.session().parameters.clone());
+		// Execute the query on the database
+		let mut res = self.kvs().process(sql, &self.session(), var).await?;
+		Ok(res.remove(0).result?.into())
+	}
+
+	// ------------------------------
+	// Methods for deleting
+	// ------------------------------
+
+	async fn delete(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((what, opts_value)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Prepare options
+		let mut opts = StatementOptions::default();
+		// Set the default output
+		opts.with_output(Output::Before);
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		}
+		// Specify the query parameters
+		let var = Some(opts.merge_vars(&self.session().parameters));
+		// Specify the SQL query string
+		let stmt = DeleteStatement {
+			only: opts.only,
+			what: vec![what.clone().could_be_table()].into(),
+			output: opts.output,
+			timeout: opts.timeout,
+			cond: opts.cond,
+			..Default::default()
+		};
+		let if_match = opts.if_match.is_some();
+		let sql: Query = match opts.if_match {
+			// Only delete the record if it has not changed
+			Some(fields) => match &what {
+				Value::Thing(v) if !v.is_range() => delete_if_match(stmt, v.clone(), fields),
+				_ => return Err(RpcError::InvalidParams),
+			},
+			None => stmt.into(),
+		};
+		// Execute the query on the database
+		let sess = self.session();
+		let mut res =
+			Retry::run(opts.retry.as_ref(), || self.kvs().process(sql.clone(), &sess, var.clone()))
+				.await?;
+		// Extract the first query result
+		let res = res.remove(0).result.or_else(|e| match e {
+			Error::SingleOnlyOutput => Ok(Value::None),
+			e => Err(e),
+		})?;
+		// Check that the record matched before it was deleted
+		let res = match (if_match, res) {
+			(true, Value::Object(mut v)) => match v.remove("matched") {
+				Some(Value::Bool(true)) => v.remove("result").unwrap_or_default(),
+				_ => return Err(RpcError::RecordChanged(what.to_string())),
+			},
+			(_, res) => res,
+		};
+		// Attach the generated SurrealQL
+		let res = match opts.echo_sql {
+			true => with_sql(res, &sql),
+			false => res,
+		};
+		// Attach the versionstamp which follows the write
+		match opts.versionstamp {
+			true => {
+				Ok(with_versionstamp(res, opts.echo_sql, self.write_versionstamp().await?).into())
+			}
+			false => Ok(res.into()),
+		}
+	}
+
+	// ------------------------------
+	// Methods for changefeeds
+	// ------------------------------
+
+	async fn diff(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((from, Value::Object(into))) = params.needs_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Compare from the current state of a record, or from an object
+		let from = match from {
+			v @ Value::Object(_) => v,
+			Value::Thing(v) if !v.is_range() => {
+				// Read the record without writing to it
+				let sql = SelectStatement {
+					expr: Fields::all(),
+					what: vec![Value::Thing(v.clone())].into(),
+					only: true,
+					..Default::default()
+				}
+				.into();
+				let mut res = self.kvs().process(sql, &self.session(), None).await?;
+				let mut record = match res.remove(0).result? {
+					v @ Value::Object(_) => v,
+					_ => {
+						return Err(Error::IdNotFound {
+							rid: v.to_string(),
+						}
+						.into())
+					}
+				};
+				// Leave out the id, unless the object is compared with an id
+				if !into.contains_key("id") {
+					record.cut(ID.as_ref());
+				}
+				record
+			}
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Return the JSON Patch which turns the first state into the second
+		Ok(Value::from(from.diff(&Value::Object(into), Idiom::default())).into())
+	}
+
+	async fn changed(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((what, since, limit)) = params.needs_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the changefeed table
+		let table = match what {
+			Value::Table(v) => v,
+			Value::Strand(v) => Table(v.0),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Parse the changefeed starting point
+		let since = match since {
+			Value::Datetime(v) => ShowSince::Timestamp(v),
+			Value::Number(Number::Int(v)) if v >= 0 => ShowSince::Versionstamp(v as u64),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Parse the changeset limit
+		let limit = match limit {
+			Value::None | Value::Null => None,
+			Value::Number(Number::Int(v)) if v > 0 => Some(v as u32),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Specify the SQL query string
+		let sql = ShowStatement {
+			table: Some(table),
+			since,
+			limit,
+		}
+		.into();
+		// Execute the query on the database
+		let mut res = self.kvs().process(sql, &self.session(), None).await?;
+		// Find the latest change for each record
+		let mut changed = BTreeMap::<Thing, bool>::new();
+		let Value::Array(changes) = res.remove(0).result?.pick(&[Part::from("changes")]).flatten()
+		else {
+			return Ok(Value::Array(Array::new()).into());
+		};
+		for changeset in changes {
+			let update = changeset.pick(&[Part::from("update")]);
+			let id = match update {
+				// A record which was updated with diffs enabled
+				Value::Array(_) => {
+					(changeset.pick(&[Part::from("current"), Part::from("id")]), false)
+				}
+				// A record which was updated
+				Value::Object(_) => (update.pick(&[Part::from("id")]), false),
+				// A record which was deleted
+				_ => (changeset.pick(&[Part::from("delete"), Part::from("id")]), true),
+			};
+			if let (Value::Thing(id), deleted) = id {
+				changed.insert(id, deleted);
+			}
+		}
+		// Fetch the current state of the updated records
+		let what: Vec<Value> = changed
+			.iter()
+			.filter(|(_, deleted)| !**deleted)
+			.map(|(id, _)| Value::Thing(id.clone()))
+			.collect();
+		let mut current = BTreeMap::<Thing, Value>::new();
+		if !what.is_empty() {
+			let sql = SelectStatement {
+				expr: Fields::all(),
+				what: what.into(),
+				..Default::default()
+			}
+			.into();
+			let mut res = self.kvs().process(sql, &self.session(), None).await?;
+			if let Value::Array(records) = res.remove(0).result? {
+				for record in records {
+					if let Value::Thing(id) = record.rid() {
+						current.insert(id, record);
+					}
+				}
+			}
+		}
+		// Output the current records and tombstones
+		let out: Vec<Value> = changed
+			.into_keys()
+			.map(|id| match current.remove(&id) {
+				Some(record) => record,
+				None => Value::from(map! {
+					"id".to_string() => Value::Thing(id),
+					"deleted".to_string() => Value::Bool(true),
+				}),
+			})
+			.collect();
+		Ok(Value::from(out).into())
+	}
+
+	// ------------------------------
+	// Methods for getting info
+	// ------------------------------
+
+	async fn version(&self, params: Array) -> Result<Data, RpcError> {
+		match params.len() {
+			0 => Ok(self.version_data()),
+			_ => Err(RpcError::InvalidParams),
+		}
+	}
+
+	// ------------------------------
+	// Methods for querying
+	// ------------------------------
+
+	async fn query(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((query, vars, opts_value)) = params.needs_one_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Check the query input type
+		if !(query.is_query() || query.is_strand()) {
+			return Err(RpcError::InvalidParams);
+		}
+		// Specify the query variables
+		let vars = match vars {
+			Value::Object(v) => {
+				let mut v = typed::process_vars(v.0)?;
+				Some(mrg! {v, self.session().parameters})
+			}
+			Value::None | Value::Null => Some(self.session().parameters.clone()),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Prepare options
+		let mut opts = QueryOptions::default();
+		// Apply user options
+		if !opts_value.is_none_or_null() {
+			opts.process_options(opts_value)?;
+		}
+		// Reading at a point in time runs within a single read-only snapshot
+		let as_of = match &opts.as_of {
+			Some(v) => {
+				opts.snapshot = true;
+				Some(retained_timestamp(v)?)
+			}
+			None => None,
+		};
+		// A snapshot can not stop at a failed statement
+		if opts.snapshot && opts.fail_fast {
+			return Err(RpcError::InvalidParams);
+		}
+		// A streamed query can not be retried, explained, or rearranged once its responses are sent
+		if opts.stream.is_some() {
+			if !Self::STREAM_SUPPORT {
+				return Err(RpcError::BadStreamConfig);
+			}
+			if opts.snapshot
+				|| opts.retry.is_some()
+				|| opts.explain_if_slower_than.is_some()
+				|| opts.with.is_some()
+				|| opts.debug_vars
+			{
+				return Err(RpcError::InvalidParams);
+			}
+		}
+		// Wait until the query reflects the requested versionstamp
+		if let Some(min) = opts.min_version {
+			self.await_versionstamp(min).await?;
+		}
+		// Parse the query text upfront for compatibility flags, snapshots, fail-fast, slow query plans, named subqueries, or streaming
+		let query = match query {
+			Value::Strand(sql)
+				if opts.compatibility()
+					|| opts.snapshot
+					|| opts.fail_fast
+					|| opts.explain_if_slower_than.is_some()
+					|| opts.with.is_some()
+					|| opts.stream.is_some() =>
+			{
+				Value::Query(parse_with_compatibility(
+					&sql,
+					self.kvs().get_capabilities(),
+					opts.legacy_strands,
+					!opts.strict_record_ids,
+				)?)
+			}
+			query => query,
+		};
+		// Bind the named subqueries before the statements of the query
+		let (bound, query) = match (&opts.with, query) {
+			(Some(with), Value::Query(mut sql)) => {
+				let lets = subqueries::statements(with, self.kvs().get_capabilities())?;
+				let bound = lets.len();
+				sql.0 .0.splice(0..0, lets);
+				// Check that every referenced param is defined
+				let params = self.defined_params().await?;
+				let defined = vars
+					.iter()
+					.flat_map(|v| v.keys())
+					.chain(params.iter())
+					.map(String::as_str)
+					.collect();
+				if let Some(name) = subqueries::undefined_param(&sql, &defined)? {
+					return Err(RpcError::UndefinedParam(name));
+				}
+				(bound, Value::Query(sql))
+			}
+			(_, query) => (0, query),
+		};
+		// Narrow the capabilities, seed the random functions, limit the recursion, and read at a point in time for this query only
+		let sess = match (&opts.capabilities, opts.seed, opts.max_depth, as_of) {
+			(None, None, None, None) => self.session(),
+			(caps, seed, max_depth, as_of) => {
+				let mut sess = self.session().as_ref().clone();
+				if let Some(caps) = caps {
+					sess.caps = Some(caps.clone());
+				}
+				sess.seed = seed;
+				sess.max_depth = max_depth;
+				sess.as_of = as_of;
+				Arc::new(sess)
+			}
+		};
+		// Send the response of each statement as soon as it completes
+		if let Some(token) = &opts.stream {
+			let output = |res| opts.output_one(res);
+			return self.query_streamed(query, vars, &sess, opts.fail_fast, output, token).await;
+		}
+		// Keep the statements which may need to be explained if they run slowly
+		let explain = match (&query, opts.explain_if_slower_than) {
+			(Value::Query(sql), Some(threshold)) => Some((sql.clone(), vars.clone(), threshold)),
+			_ => None,
+		};
+		// Keep the variables which the query runs with, if they should be returned
+		let debug_vars = opts.debug_vars.then(|| vars.clone());
+		// Execute the specified query
+		let res = match query {
+			// Execute all statements within a single read-only snapshot
+			Value::Query(sql) if opts.snapshot => {
+				self.kvs().process_snapshot(sql, &sess, vars).await?
+			}
+			query => {
+				Retry::run(opts.retry.as_ref(), || {
+					self.query_inner_with_session(
+						query.clone(),
+						vars.clone(),
+						&sess,
+						opts.fail_fast,
+					)
+				})
+				.await?
+			}
+		};
+		// Capture the execution plans of any slow statements
+		let plans = match explain {
+			Some((sql, vars, threshold)) => {
+				self.explain_slow_statements(sql, vars, &sess, &res, threshold).await
+			}
+			None => Vec::new(),
+		};
+		// Leave out the responses of the named subqueries, unless any of them failed
+		let (mut res, mut plans) = (res, plans);
+		for response in res.drain(..bound.min(res.len())) {
+			response.result?;
+		}
+		plans.drain(..bound.min(plans.len()));
+		// Output the query responses
+		let out = opts.output(res, plans)?;
+		// Attach the variables which the query ran with
+		if let Some(vars) = debug_vars {
+			return Ok(query_options::with_vars(Value::try_from(out)?, vars.as_ref()).into());
+		}
+		Ok(out)
+	}
+
+	async fn race(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((Value::Array(queries), vars)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		if queries.is_empty() {
+			return Err(RpcError::InvalidParams);
+		}
+		// Specify the query variables
+		let vars = match vars {
+			Value::Object(v) => {
+				let mut v = typed::process_vars(v.0)?;
+				Some(mrg! {v, self.session().parameters})
+			}
+			Value::None | Value::Null => Some(self.session().parameters.clone()),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Parse the queries, which may only read data
+		let mut sql = Vec::with_capacity(queries.len());
+		for (i, query) in queries.into_iter().enumerate() {
+			let Value::Strand(query) = query else {
+				return Err(RpcError::InvalidRaceQuery(i));
+			};
+			let query = crate::syn::parse_with_capabilities(&query, self.kvs().get_capabilities())?;
+			if query.iter().any(Statement::writeable) {
+				return Err(RpcError::InvalidRaceQuery(i));
+			}
+			sql.push(query);
+		}
+		// Execute all of the queries concurrently
+		let sess = self.session();
+		let mut cancellers = Vec::with_capacity(sql.len());
+		let mut futures = Vec::with_capacity(sql.len());
+		for (i, query) in sql.into_iter().enumerate() {
+			let (canceller, process) =
+				self.kvs().process_cancellable(query, &sess, vars.clone())?;
+			cancellers.push(canceller);
+			futures.push(Box::pin(async move {
+				let res = process.await?;
+				// A query only completes when all of its statements succeed
+				let res = res.into_iter().map(|r| r.result).collect::<Result<Vec<_>, _>>()?;
+				Ok::<_, Error>((i, res))
+			}));
+		}
+		// Wait for the first query to complete
+		let ((index, res), rest) = select_ok(futures).await?;
+		// Cancel the other queries, and wait for their transactions to be cancelled
+		cancellers.iter().for_each(Canceller::cancel);
+		join_all(rest).await;
+		Ok(Value::from(map! {
+			"index".to_string() => Value::from(index),
+			"result".to_string() => Value::from(res),
+		})
+		.into())
+	}
+
+	// ------------------------------
+	// Methods for prepared statements
+	// ------------------------------
+
+	async fn prepare(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Check if prepared statements are supported
+		if !Self::PREPARED_SUPPORT {
+			return Err(RpcError::BadPreparedConfig);
+		}
+		// Process the method arguments
+		let Ok(Value::Strand(sql)) = params.needs_one() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the query text once
+		let query = crate::syn::parse_with_capabilities(&sql, self.kvs().get_capabilities())?;
+		// Store the parsed query on this connection
+		let id = self.store_prepared(query).await;
+		// Return the handle of the prepared statement
+		Ok(Value::Uuid(id.into()).into())
+	}
+
+	async fn execute_prepared(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Check if prepared statements are supported
+		if !Self::PREPARED_SUPPORT {
+			return Err(RpcError::BadPreparedConfig);
+		}
+		// Process the method arguments
+		let Ok((id, vars)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the prepared statement handle
+		let id = parse_handle(id)?;
+		// Specify the query variables
+		let vars = match vars {
+			Value::Object(v) => {
+				let mut v = typed::process_vars(v.0)?;
+				Some(mrg! {v, self.session().parameters})
+			}
+			Value::None | Value::Null => Some(self.session().parameters.clone()),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Fetch the prepared query
+		let Some(query) = self.get_prepared(&id).await else {
+			return Err(RpcError::PreparedNotFound(id));
+		};
+		// Execute the prepared query
+		let res = self.query_inner(Value::Query(query), vars).await?;
+		// Return the query responses
+		Ok(res.into())
+	}
+
+	async fn execute_prepared_batch(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Check if prepared statements are supported
+		if !Self::PREPARED_SUPPORT {
+			return Err(RpcError::BadPreparedConfig);
+		}
+		// Process the method arguments
+		let Ok((id, Value::Array(sets))) = params.needs_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the prepared statement handle
+		let id = parse_handle(id)?;
+		// Process the variables of each execution
+		let sets = sets
+			.into_iter()
+			.map(|v| match v {
+				Value::Object(v) => typed::process_vars(v.0),
+				_ => Err(RpcError::InvalidParams),
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		if sets.is_empty() {
+			return Err(RpcError::InvalidParams);
+		}
+		// Fetch the prepared query
+		let Some(query) = self.get_prepared(&id).await else {
+			return Err(RpcError::PreparedNotFound(id));
+		};
+		// Execute the prepared query for each set of variables in one transaction
+		let sess = self.session();
+		let var = Some(sess.parameters.clone());
+		let res = self.kvs().process_batch(query, &sess, var, sets).await?;
+		// Return the results of each execution, or the error which rolled back the batch
+		let mut out = Vec::with_capacity(res.len());
+		for (i, res) in res.into_iter().enumerate() {
+			let mut results = Vec::with_capacity(res.len());
+			for res in res {
+				match res.result {
+					Ok(v) => results.push(v),
+					Err(e) => return Err(RpcError::BatchFailed(i, e.to_string())),
+				}
+			}
+			out.push(Value::from(results));
+		}
+		Ok(Value::from(out).into())
+	}
+
+	// ------------------------------
+	// Methods for table scans
+	// ------------------------------
+
+	async fn scan_open(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Check if table scans are supported
+		if !Self::SCAN_SUPPORT {
+			return Err(RpcError::BadScanConfig);
+		}
+		// Process the method arguments
+		let Ok((table, Value::Number(batch_size), range)) = params.needs_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the table name argument
+		let table = match table {
+			Value::Strand(v) => Table(v.0),
+			Value::Table(v) => v,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Check the batch size is a positive integer
+		let batch_size = match batch_size {
+			Number::Int(v) if v > 0 => u32::try_from(v).map_err(|_| RpcError::InvalidParams)?,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Parse the storage key range returned by the `key_range` method
+		let (start, end) = match range {
+			Value::Object(mut obj) => {
+				let start = match obj.remove("start") {
+					Some(Value::Strand(v)) => {
+						self.key_bound(&table, v.as_str(), Bound::Included)?
+					}
+					None => Bound::Unbounded,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				let end = match obj.remove("end") {
+					Some(Value::Strand(v)) => {
+						self.key_bound(&table, v.as_str(), Bound::Excluded)?
+					}
+					None => Bound::Unbounded,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				(start, end)
+			}
+			Value::None | Value::Null => (Bound::Unbounded, Bound::Unbounded),
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Store the cursor at the start of the table, or of the key range
+		let id = self
+			.open_scan(ScanCursor {
+				table,
+				batch_size,
+				start,
+				end,
+				position: None,
+			})
+			.await;
+		// Return the handle of the table scan
+		Ok(Value::Uuid(id.into()).into())
+	}
+
+	async fn scan_next(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Check if table scans are supported
+		if !Self::SCAN_SUPPORT {
+			return Err(RpcError::BadScanConfig);
+		}
+		// Process the method arguments
+		let Ok(id) = params.needs_one() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the table scan handle
+		let id = parse_handle(id)?;
+		// Fetch the table scan cursor
+		let Some(mut cursor) = self.get_scan(&id).await else {
+			return Err(RpcError::ScanNotFound(id));
+		};
+		// Continue the scan after the last record which was returned
+		let beg = match &cursor.position {
+			Some(last) => Bound::Excluded(last.id.clone()),
+			None => cursor.start.clone(),
+		};
+		let what = match (beg, &cursor.end) {
+			(Bound::Unbounded, Bound::Unbounded) => Value::Table(cursor.table.clone()),
+			(beg, end) => Value::Thing(Thing {
+				tb: cursor.table.0.clone(),
+				id: Id::Range(Box::new(IdRange {
+					beg,
+					end: end.clone(),
+				})),
+			}),
+		};
+		// Fetch one more record than the batch size, to check if the scan is complete
+		let sql = SelectStatement {
+			expr: Fields::all(),
+			what: vec![what].into(),
+			limit: Some(Limit(Value::from(cursor.batch_size as i64 + 1))),
+			..Default::default()
+		};
+		// Specify the query parameters
+		let var = Some(self.session().parameters.clone());
+		// Execute the query on the database
+		let mut res = self.kvs().process(sql.into(), &self.session(), var).await?;
+		let mut res = match res.remove(0).result? {
+			Value::Array(v) => v.0,
+			_ => Vec::new(),
+		};
+		// Check if the scan has reached the end of the table
+		let done = res.len() <= cursor.batch_size as usize;
+		res.truncate(cursor.batch_size as usize);
+		// Move the cursor, or close the scan if it is complete
+		match (done, res.last().map(Value::rid)) {
+			(false, Some(Value::Thing(last))) => {
+				cursor.position = Some(last);
+				self.update_scan(&id, cursor).await;
+			}
+			_ => {
+				self.close_scan(&id).await;
+			}
+		}
+		// Return the batch of records
+		Ok(Value::from(map! {
+			"result".to_string() => Value::from(res),
+			"done".to_string() => Value::from(done),
+		})
+		.into())
+	}
+
+	async fn scan_close(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if table scans are supported
+		if !Self::SCAN_SUPPORT {
+			return Err(RpcError::BadScanConfig);
+		}
+		// Process the method arguments
+		let Ok(id) = params.needs_one() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the table scan handle
+		let id = parse_handle(id)?;
+		// Close the table scan
+		if !self.close_scan(&id).await {
+			return Err(RpcError::ScanNotFound(id));
+		}
+		Ok(Value::None.into())
+	}
+
+	async fn key_range(&self, params: Array) -> Result<Data, RpcError> {
+		// Process the method arguments
+		let Ok((tb, opts)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		let tb = match tb {
+			Value::Strand(v) => v.0,
+			Value::Table(v) => v.0,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Process the "partitions" option
+		let partitions = match opts {
+			Value::Object(mut obj) => match obj.remove("partitions") {
+				Some(Value::Number(Number::Int(v))) if v > 0 => v as usize,
+				None => 1,
+				_ => return Err(RpcError::InvalidParams),
+			},
+			Value::None | Value::Null => 1,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Get the current session
+		let sess = self.session();
+		// Check if the session has expired
+		if sess.expired() {
+			return Err(Error::ExpiredSession.into());
+		}
+		// Only root owners can see how records are laid out in storage
+		if !sess.au.is_root() || !sess.au.has_owner_role() {
+			return Err(Error::from(IamError::NotAllowed {
+				actor: sess.au.id().to_string(),
+				action: "view".to_string(),
+				resource: format!("storage keys of table {tb}"),
+			})
+			.into());
+		}
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Get the storage key range of the table
+		let beg = crate::key::thing::prefix(ns, db, &tb)?;
+		let end = crate::key::thing::suffix(ns, db, &tb)?;
+		// Split the table into partitions with a similar number of records
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let splits = async {
+			// Ensure that the table exists
+			txn.get_tb(ns, db, &tb).await?;
+			let mut splits = Vec::new();
+			if partitions > 1 {
+				let count = txn.count(beg.clone()..end.clone()).await?;
+				let step = count.div_ceil(partitions).max(1);
+				let mut seen = 0;
+				let mut next = Some(beg.clone()..end.clone());
+				while let Some(rng) = next {
+					let batch = txn.batch_keys(rng, *NORMAL_FETCH_SIZE, None).await?;
+					next = batch.next;
+					for key in batch.result {
+						if seen > 0 && seen % step == 0 {
+							splits.push(key);
+						}
+						seen += 1;
+					}
+				}
+			}
+			Ok::<_, Error>(splits)
+		}
+		.await;
+		txn.cancel().await?;
+		// Return the key range of each partition, as hex-encoded keys
+		let bounds: Vec<_> = [vec![beg], splits?, vec![end]].concat();
+		let ranges: Vec<Value> = bounds
+			.windows(2)
+			.map(|v| {
+				Value::from(map! {
+					"start".to_string() => Value::from(hex::encode(&v[0])),
+					"end".to_string() => Value::from(hex::encode(&v[1])),
+				})
+			})
+			.collect();
+		Ok(Value::from(map! {
+			"table".to_string() => Value::from(tb),
+			"ranges".to_string() => Value::from(ranges),
+		})
+		.into())
 	}
 
 	// ------------------------------
-	// Methods for relating
+	// Methods for the query log
 	// ------------------------------
 
-	async fn relate(&self, params: Array) -> Result<Data, RpcError> {
-		// Check if the user is allowed to query
-		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
-			return Err(RpcError::MethodNotAllowed);
+	async fn query_log_start(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the query log is supported
+		if !Self::QUERY_LOG_SUPPORT {
+			return Err(RpcError::BadQueryLogConfig);
 		}
 		// Process the method arguments
-		let Ok((from, kind, with, data, opts_value)) = params.needs_three_four_or_five() else {
-			return Err(RpcError::InvalidParams);
+		let opts = match params.is_empty() {
+			true => Value::None,
+			false => params.needs_one()?,
 		};
-		// Prepare options
-		let mut opts = StatementOptions::default();
-		// Set the default output
-		opts.with_output(Output::After);
-		// Insert data
-		if !data.is_none_or_null() {
-			opts.with_data_content(data);
+		// Process the query log options
+		let mut size = 100.min(*MAX_QUERY_LOG_SIZE);
+		let mut redact = false;
+		match opts {
+			Value::Object(mut obj) => {
+				// Process "size" option
+				match obj.remove("size") {
+					Some(Value::Number(Number::Int(v))) if v > 0 => {
+						size = usize::try_from(v).map_err(|_| RpcError::InvalidParams)?;
+						if size > *MAX_QUERY_LOG_SIZE {
+							return Err(RpcError::InvalidParams);
+						}
+					}
+					None => {}
+					_ => return Err(RpcError::InvalidParams),
+				}
+				// Process "redact" option
+				match obj.remove("redact") {
+					Some(Value::Bool(v)) => redact = v,
+					None => {}
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+			Value::None | Value::Null => {}
+			_ => return Err(RpcError::InvalidParams),
 		}
-		// Apply user options
-		if !opts_value.is_none_or_null() {
-			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		// Start recording the queries on this connection
+		self.start_query_log(QueryLog::new(size, redact)).await;
+		Ok(Value::None.into())
+	}
+
+	async fn query_log_entries(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the query log is supported
+		if !Self::QUERY_LOG_SUPPORT {
+			return Err(RpcError::BadQueryLogConfig);
 		}
-		// Specify the query parameters
-		let var = Some(opts.merge_vars(&self.session().parameters));
-		// Specify the SQL query string
-		let sql = RelateStatement {
-			only: opts.only,
-			from,
-			kind: kind.could_be_table(),
-			with,
-			data: opts.data_expr(),
-			output: opts.output,
-			timeout: opts.timeout,
-			uniq: opts.unique,
-			..Default::default()
+		// Process the method arguments
+		let clear = match params.is_empty() {
+			true => false,
+			false => match params.needs_one()? {
+				Value::None | Value::Null => false,
+				Value::Object(mut obj) => match obj.remove("clear") {
+					Some(Value::Bool(v)) => v,
+					None => false,
+					_ => return Err(RpcError::InvalidParams),
+				},
+				_ => return Err(RpcError::InvalidParams),
+			},
+		};
+		// Fetch the queries which were recorded
+		let Some(entries) = self.query_log(clear).await else {
+			return Err(RpcError::QueryLogNotStarted);
+		};
+		// Return the queries from oldest to newest
+		Ok(Value::from(entries.into_iter().map(Value::from).collect::<Vec<_>>()).into())
+	}
+
+	async fn query_log_stop(&self) -> Result<Data, RpcError> {
+		// Check if the query log is supported
+		if !Self::QUERY_LOG_SUPPORT {
+			return Err(RpcError::BadQueryLogConfig);
 		}
-		.into();
-		// Execute the query on the database
-		let mut res = self.kvs().process(sql, &self.session(), var).await?;
-		// Extract the first query result
-		Ok(res
-			.remove(0)
-			.result
-			.or_else(|e| match e {
-				Error::SingleOnlyOutput => Ok(Value::None),
-				e => Err(e),
-			})?
-			.into())
+		// Stop recording the queries, discarding the log
+		if !self.stop_query_log().await {
+			return Err(RpcError::QueryLogNotStarted);
+		}
+		Ok(Value::None.into())
 	}
 
 	// ------------------------------
-	// Methods for deleting
+	// Methods for webhooks
 	// ------------------------------
 
-	async fn delete(&self, params: Array) -> Result<Data, RpcError> {
+	async fn webhook_register(&self, params: Array) -> Result<Data, RpcError> {
 		// Check if the user is allowed to query
 		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
 			return Err(RpcError::MethodNotAllowed);
 		}
 		// Process the method arguments
-		let Ok((what, opts_value)) = params.needs_one_or_two() else {
+		let Ok((Value::Strand(url), tb, opts)) = params.needs_two_or_three() else {
 			return Err(RpcError::InvalidParams);
 		};
-		// Prepare options
-		let mut opts = StatementOptions::default();
-		// Set the default output
-		opts.with_output(Output::Before);
-		// Apply user options
-		if !opts_value.is_none_or_null() {
-			opts.process_options(opts_value, self.kvs().get_capabilities())?;
+		let tb = match tb {
+			Value::Strand(v) => v.0,
+			Value::Table(v) => v.0,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Process the "cond" option
+		let cond = match opts {
+			Value::Object(mut obj) => match obj.remove("cond") {
+				Some(Value::Strand(v)) => {
+					Some(value_with_capabilities(v.as_str(), self.kvs().get_capabilities())?)
+				}
+				None => None,
+				_ => return Err(RpcError::InvalidParams),
+			},
+			Value::None | Value::Null => None,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Check if the user is allowed to manage webhooks
+		let sess = self.session();
+		webhooks::check_access(&sess, "register")?;
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Check the URL can be reached
+		webhooks::check_url(self.kvs(), url.as_str())?;
+		// Check that the changes to the table are kept in a changefeed
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let res = async {
+			let changefeed = txn.get_tb(ns, db, &tb).await?.changefeed.is_some()
+				|| txn.get_db(ns, db).await?.changefeed.is_some();
+			Ok::<_, Error>(changefeed)
 		}
-		// Specify the query parameters
-		let var = Some(opts.merge_vars(&self.session().parameters));
-		// Specify the SQL query string
-		let sql = DeleteStatement {
-			only: opts.only,
-			what: vec![what.could_be_table()].into(),
-			output: opts.output,
-			timeout: opts.timeout,
-			cond: opts.cond,
-			..Default::default()
+		.await;
+		txn.cancel().await?;
+		if !res? {
+			return Err(RpcError::NoChangefeed(tb));
 		}
-		.into();
-		// Execute the query on the database
-		let mut res = self.kvs().process(sql, &self.session(), var).await?;
-		// Extract the first query result
-		Ok(res
-			.remove(0)
-			.result
-			.or_else(|e| match e {
-				Error::SingleOnlyOutput => Ok(Value::None),
-				e => Err(e),
-			})?
-			.into())
+		// Deliver the changes which are committed from now on
+		let vs = self.kvs().next_versionstamp(ns, db).await?.into_u64_lossy();
+		let wh = Webhook::new(url.0, tb, cond, vs);
+		self.kvs().define_webhook(ns, db, &wh).await?;
+		// Return the registered webhook
+		Ok(webhooks::describe(&wh).into())
 	}
 
-	// ------------------------------
-	// Methods for getting info
-	// ------------------------------
-
-	async fn version(&self, params: Array) -> Result<Data, RpcError> {
-		match params.len() {
-			0 => Ok(self.version_data()),
-			_ => Err(RpcError::InvalidParams),
+	async fn webhooks(&self) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
 		}
+		// Check if the user is allowed to manage webhooks
+		let sess = self.session();
+		webhooks::check_access(&sess, "list")?;
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Describe the webhooks of the database
+		let res: Vec<Value> =
+			self.kvs().all_webhooks(ns, db).await?.iter().map(webhooks::describe).collect();
+		Ok(Value::from(res).into())
 	}
 
-	// ------------------------------
-	// Methods for querying
-	// ------------------------------
-
-	async fn query(&self, params: Array) -> Result<Data, RpcError> {
+	async fn webhook_remove(&self, params: Array) -> Result<Data, RpcError> {
 		// Check if the user is allowed to query
 		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
 			return Err(RpcError::MethodNotAllowed);
 		}
 		// Process the method arguments
-		let Ok((query, vars)) = params.needs_one_or_two() else {
-			return Err(RpcError::InvalidParams);
-		};
-		// Check the query input type
-		if !(query.is_query() || query.is_strand()) {
+		let Ok(id) = params.needs_one() else {
 			return Err(RpcError::InvalidParams);
-		}
-		// Specify the query variables
-		let vars = match vars {
-			Value::Object(mut v) => Some(mrg! {v.0, self.session().parameters}),
-			Value::None | Value::Null => Some(self.session().parameters.clone()),
-			_ => return Err(RpcError::InvalidParams),
 		};
-		// Execute the specified query
-		self.query_inner(query, vars).await.map(Into::into)
+		let id = parse_handle(id)?;
+		// Check if the user is allowed to manage webhooks
+		let sess = self.session();
+		webhooks::check_access(&sess, "remove")?;
+		// Get the NS and DB
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Remove the webhook, stopping any further deliveries
+		let res = self.kvs().remove_webhook(ns, db, id).await?;
+		// Return whether the webhook existed
+		Ok(Value::from(res).into())
 	}
 
 	// ------------------------------
@@ -822,6 +4074,107 @@ pub trait RpcProtocolV2: RpcContext {
 		Ok(res.remove(0).result?.into())
 	}
 
+	// ------------------------------
+	// Methods for evaluating expressions
+	// ------------------------------
+
+	async fn eval(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Process the method arguments
+		let Ok((expr, vars)) = params.needs_one_or_two() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the expression to evaluate
+		let expr = match expr {
+			Value::Strand(v) => value_with_capabilities(v.as_str(), self.kvs().get_capabilities())?,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Expressions can never write to the database
+		if expr.writeable() {
+			return Err(RpcError::Thrown(EVAL_DATABASE_ACCESS.to_string()));
+		}
+		// Specify the expression variables
+		let vars = match vars {
+			Value::Object(v) => Some(v.0),
+			Value::None | Value::Null => None,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Evaluate the expression with the permissions and capabilities of
+		// the caller, but without a namespace or database, so no records
+		// are reachable
+		let mut sess = self.session().as_ref().clone();
+		sess.ns = None;
+		sess.db = None;
+		// Compute the expression
+		match self.kvs().compute(expr, &sess, vars).await {
+			Ok(v) => Ok(v.into()),
+			Err(Error::NsEmpty | Error::DbEmpty) => {
+				Err(RpcError::Thrown(EVAL_DATABASE_ACCESS.to_string()))
+			}
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn check_permission(&self, params: Array) -> Result<Data, RpcError> {
+		// Check if the user is allowed to query
+		if !self.kvs().allows_query_by_subject(self.session().au.as_ref()) {
+			return Err(RpcError::MethodNotAllowed);
+		}
+		// Only users which can edit definitions can test permissions
+		let sess = self.session();
+		if (self.kvs().is_auth_enabled() || !sess.au.is_anon()) && !sess.au.has_editor_role() {
+			return Err(Error::from(IamError::NotAllowed {
+				actor: sess.au.id().to_string(),
+				action: "check".to_string(),
+				resource: "permission".to_string(),
+			})
+			.into());
+		}
+		// Process the method arguments
+		let Ok((expr, record, auth)) = params.needs_two_or_three() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Parse the permission expression
+		let expr = match expr {
+			Value::Strand(v) => value_with_capabilities(v.as_str(), self.kvs().get_capabilities())?,
+			_ => return Err(RpcError::InvalidParams),
+		};
+		// Permission expressions can never write to the database
+		if expr.writeable() {
+			return Err(RpcError::Thrown(EVAL_DATABASE_ACCESS.to_string()));
+		}
+		// The sample record and auth must be objects
+		if !record.is_object() || !(auth.is_none_or_null() || auth.is_object()) {
+			return Err(RpcError::InvalidParams);
+		}
+		// Filter the sample record by the expression, so that
+		// the record is the current document being evaluated
+		let sql =
+			Value::Idiom(Idiom(vec![Part::Start(Value::from(vec![record])), Part::Where(expr)]));
+		// Use an ephemeral session with the sample auth and without
+		// a namespace or database, so no records are reachable
+		let sess = Session {
+			au: sess.au.clone(),
+			tz: sess.tz.clone(),
+			rd: (!auth.is_none_or_null()).then_some(auth),
+			..Default::default()
+		};
+		// Report the outcome, along with any error
+		let (allowed, error) = match self.kvs().compute(sql, &sess, None).await {
+			Ok(v) => (v.is_truthy(), Value::None),
+			Err(Error::NsEmpty | Error::DbEmpty) => (false, EVAL_DATABASE_ACCESS.into()),
+			Err(e) => (false, e.to_string().into()),
+		};
+		Ok(Value::from(map! {
+			"allowed".to_string() => Value::from(allowed),
+			"error".to_string() => error,
+		})
+		.into())
+	}
+
 	// ------------------------------
 	// Methods for querying with GraphQL
 	// ------------------------------
@@ -944,6 +4297,58 @@ pub trait RpcProtocolV2: RpcContext {
 		Ok(Value::Strand(out.into()).into())
 	}
 
+	// ------------------------------
+	// Methods for batching
+	// ------------------------------
+
+	async fn batch(&self, params: Array) -> Result<Data, RpcError> {
+		// Process the method arguments
+		let Ok(Value::Array(calls)) = params.needs_one() else {
+			return Err(RpcError::InvalidParams);
+		};
+		// Execute each of the calls in turn, so that calls which
+		// change the session apply to all of the calls after them
+		let mut out = Vec::with_capacity(calls.len());
+		for call in calls {
+			let res = match call {
+				Value::Object(mut call) => {
+					let method = match call.remove("method") {
+						Some(Value::Strand(v)) => Method::parse_case_sensitive(v.as_str()),
+						_ => Method::Unknown,
+					};
+					let params = match call.remove("params") {
+						Some(Value::Array(v)) => Ok(v),
+						None | Some(Value::None | Value::Null) => Ok(Array::new()),
+						_ => Err(RpcError::InvalidParams),
+					};
+					match (method, params) {
+						(Method::Unknown, _) => Err(RpcError::MethodNotFound),
+						// A batch can not be nested within another batch
+						(Method::Batch, _) => Err(RpcError::InvalidRequest),
+						(_, Err(e)) => Err(e),
+						(method, Ok(params)) => {
+							Box::pin(RpcProtocolV2::execute(self, method, params)).await
+						}
+					}
+				}
+				_ => Err(RpcError::InvalidRequest),
+			};
+			// Encode a failed call as an error, without stopping the batch
+			out.push(match res.and_then(|v| Value::try_from(v).map_err(RpcError::from)) {
+				Ok(v) => Value::from(map! {
+					"result".to_string() => v,
+				}),
+				Err(e) => Value::from(map! {
+					"error".to_string() => Value::from(map! {
+						"code".to_string() => Value::from(e.code()),
+						"message".to_string() => Value::from(e.to_string()),
+					}),
+				}),
+			});
+		}
+		Ok(Value::from(out).into())
+	}
+
 	// ------------------------------
 	// Private methods
 	// ------------------------------
@@ -952,17 +4357,44 @@ pub trait RpcProtocolV2: RpcContext {
 		&self,
 		query: Value,
 		vars: Option<BTreeMap<String, Value>>,
+	) -> Result<Vec<Response>, RpcError> {
+		self.query_inner_with_session(query, vars, &self.session(), false).await
+	}
+
+	async fn query_inner_with_session(
+		&self,
+		query: Value,
+		vars: Option<BTreeMap<String, Value>>,
+		sess: &Session,
+		fail_fast: bool,
 	) -> Result<Vec<Response>, RpcError> {
 		// If no live query handler force realtime off
-		if !Self::LQ_SUPPORT && self.session().rt {
+		if !Self::LQ_SUPPORT && sess.rt {
 			return Err(RpcError::BadLQConfig);
 		}
+		// Keep the query text if the query log is being recorded
+		let logged = match Self::QUERY_LOG_SUPPORT && self.is_logging_queries().await {
+			true => match &query {
+				Value::Query(sql) => Some(sql.to_string()),
+				Value::Strand(sql) => Some(sql.0.clone()),
+				_ => None,
+			},
+			false => None,
+		};
+		let time = Datetime::default();
+		let now = Instant::now();
 		// Execute the query on the database
 		let res = match query {
-			Value::Query(sql) => self.kvs().process(sql, &self.session(), vars).await?,
-			Value::Strand(sql) => self.kvs().execute(&sql, &self.session(), vars).await?,
+			Value::Query(sql) if fail_fast => self.kvs().process_fail_fast(sql, sess, vars).await,
+			Value::Query(sql) => self.kvs().process(sql, sess, vars).await,
+			Value::Strand(sql) => self.kvs().execute(&sql, sess, vars).await,
 			_ => return Err(fail!("Unexpected query type: {query:?}").into()),
 		};
+		// Record the query in the query log
+		if let Some(query) = logged {
+			self.log_query(QueryLogEntry::new(query, time, now.elapsed(), &res)).await;
+		}
+		let res = res?;
 
 		// Post-process hooks for web layer
 		for response in &res {
@@ -973,6 +4405,161 @@ pub trait RpcProtocolV2: RpcContext {
 		Ok(res)
 	}
 
+	async fn query_streamed(
+		&self,
+		query: Value,
+		vars: Option<BTreeMap<String, Value>>,
+		sess: &Session,
+		fail_fast: bool,
+		output: impl Fn(Response) -> Result<Value, RpcError>,
+		token: &str,
+	) -> Result<Data, RpcError> {
+		// If no live query handler force realtime off
+		if !Self::LQ_SUPPORT && sess.rt {
+			return Err(RpcError::BadLQConfig);
+		}
+		let Value::Query(sql) = query else {
+			return Err(RpcError::InvalidParams);
+		};
+		let (chn, rcv) = crate::channel::bounded(1);
+		// Execute the query, while sending each response to the client as it arrives
+		let (res, count) = futures::join!(
+			self.kvs().process_streamed(sql, sess, vars, fail_fast, chn),
+			// The receiver is dropped on an error, so that the query is not blocked
+			async move {
+				let mut count = 0;
+				while let Ok(response) = rcv.recv().await {
+					// Post-process hooks for web layer
+					self.handle_live_query_results(&response).await;
+					self.send_stream(token, count, output(response)?).await;
+					count += 1;
+				}
+				Ok::<_, RpcError>(count)
+			}
+		);
+		res?;
+		// Return the number of statement responses which were sent
+		Ok(Value::from(count?).into())
+	}
+
+	/// Converts a hex-encoded storage key returned by the `key_range` method into a bound on the record ids of a table
+	fn key_bound(
+		&self,
+		table: &Table,
+		key: &str,
+		bound: fn(Id) -> Bound<Id>,
+	) -> Result<Bound<Id>, RpcError> {
+		let key = hex::decode(key).map_err(|_| RpcError::InvalidParams)?;
+		let key = key.as_slice();
+		let sess = self.session();
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// The start and end of the table are not bounded
+		if key == crate::key::thing::prefix(ns, db, table)?
+			|| key == crate::key::thing::suffix(ns, db, table)?
+		{
+			return Ok(Bound::Unbounded);
+		}
+		// Any other key must be the key of a record in this table
+		match crate::key::thing::Thing::decode(key) {
+			Ok(v) if v.ns == ns && v.db == db && v.tb == table.as_str() => Ok(bound(v.id)),
+			_ => Err(RpcError::InvalidParams),
+		}
+	}
+
+	/// Returns the names of the params which are defined in the current database
+	async fn defined_params(&self) -> Result<Vec<String>, RpcError> {
+		let sess = self.session();
+		let (Some(ns), Some(db)) = (sess.ns.as_deref(), sess.db.as_deref()) else {
+			return Ok(Vec::new());
+		};
+		let txn = self.kvs().transaction(Read, Optimistic).await?;
+		let params = txn.all_db_params(ns, db).await;
+		txn.cancel().await?;
+		Ok(params?.iter().map(|v| v.name.to_raw()).collect())
+	}
+
+	/// Allocates a versionstamp in the current database, ordered after any writes already committed
+	///
+	/// This is an upper bound on the versionstamp of a write which has already completed,
+	/// rather than the exact versionstamp at which that write was committed.
+	async fn write_versionstamp(&self) -> Result<u64, RpcError> {
+		let sess = self.session();
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		Ok(self.kvs().next_versionstamp(ns, db).await?.into_u64_lossy())
+	}
+
+	/// Waits until the current database reflects the versionstamp, failing if it does not do so in time
+	async fn await_versionstamp(&self, min: u64) -> Result<(), RpcError> {
+		let sess = self.session();
+		let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+		let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+		// Poll the versionstamp of the database until the timeout elapses
+		let wait = std::time::Duration::from_millis(10);
+		let polls = (*MIN_VERSION_TIMEOUT / 10).max(1);
+		for poll in 0..=polls {
+			let current = self.kvs().current_versionstamp(ns, db).await?;
+			if current.map(|v| v.into_u64_lossy()).unwrap_or(0) >= min {
+				return Ok(());
+			}
+			if poll < polls {
+				#[cfg(target_family = "wasm")]
+				wasmtimer::tokio::sleep(wait).await;
+				#[cfg(not(target_family = "wasm"))]
+				tokio::time::sleep(wait).await;
+			}
+		}
+		Err(RpcError::VersionNotReached(min))
+	}
+
+	/// Re-runs each statement which took longer than the threshold with EXPLAIN, returning the
+	/// execution plans in the same order as the statement responses
+	async fn explain_slow_statements(
+		&self,
+		query: Query,
+		vars: Option<BTreeMap<String, Value>>,
+		sess: &Session,
+		res: &[Response],
+		threshold: std::time::Duration,
+	) -> Vec<Option<Value>> {
+		// Transaction and option statements do not produce a response
+		let stmts: Vec<Statement> = query
+			.0
+			 .0
+			.into_iter()
+			.filter(|stmt| {
+				!matches!(
+					stmt,
+					Statement::Begin(_)
+						| Statement::Cancel(_)
+						| Statement::Commit(_)
+						| Statement::Option(_)
+				)
+			})
+			.collect();
+		// The plans can only be matched up if every statement produced a response
+		if stmts.len() != res.len() {
+			return Vec::new();
+		}
+		let mut plans = Vec::with_capacity(res.len());
+		for (stmt, response) in stmts.into_iter().zip(res) {
+			// Only explain the statements which were slow
+			let plan = match explained(stmt) {
+				Some(stmt) if response.time > threshold => {
+					let query = Query(Statements(vec![stmt]));
+					match self.kvs().process(query, sess, vars.clone()).await {
+						Ok(mut out) => out.pop().and_then(|r| r.result.ok()),
+						Err(_) => None,
+					}
+				}
+				_ => None,
+			};
+			plans.push(plan);
+		}
+		plans
+	}
+
 	async fn handle_live_query_results(&self, res: &Response) {
 		match &res.query_type {
 			QueryType::Live => {
@@ -989,3 +4576,243 @@ pub trait RpcProtocolV2: RpcContext {
 		}
 	}
 }
+
+/// Converts the changesets of a table changefeed into notifications which can be
+/// replayed, returning the versionstamp of the last changeset which was processed
+fn replay_changes(changesets: Array, max: usize, out: &mut Vec<Value>) -> Option<u64> {
+	let mut last = None;
+	for changeset in changesets {
+		// Return the versionstamp in the same form as the `since` option
+		let Value::Number(v) = changeset.pick(&[Part::from("versionstamp")]) else {
+			continue;
+		};
+		let Ok(versionstamp) = VersionStamp::try_from_u128(v.to_int() as u128) else {
+			continue;
+		};
+		let versionstamp = versionstamp.into_u64_lossy();
+		last = Some(versionstamp);
+		let Value::Array(changes) = changeset.pick(&[Part::from("changes")]) else {
+			continue;
+		};
+		for change in changes {
+			let (action, result) = match change.pick(&[Part::from("update")]) {
+				// A record which was updated with diffs enabled
+				Value::Array(_) => ("UPDATE", change.pick(&[Part::from("current")])),
+				// A record which was updated
+				v @ Value::Object(_) => ("UPDATE", v),
+				// A record which was deleted, or a table which was defined
+				_ => match change.pick(&[Part::from("delete")]) {
+					v @ Value::Object(_) => ("DELETE", v),
+					_ => continue,
+				},
+			};
+			out.push(Value::from(map! {
+				"versionstamp".to_string() => Value::from(versionstamp),
+				"action".to_string() => Value::from(action),
+				"record".to_string() => result.rid(),
+				"result".to_string() => result,
+			}));
+			// Bound the number of replayed changes
+			if out.len() >= max {
+				return None;
+			}
+		}
+	}
+	last
+}
+
+/// Builds a query which merges the data into the first existing edge between two records,
+/// or otherwise relates the records with a new edge, within a single transaction
+fn upsert_edge(
+	from: Value,
+	kind: Value,
+	with: Value,
+	merge: Value,
+	opts: &StatementOptions,
+) -> Result<Query, RpcError> {
+	// An upsert relates a single pair of records with a single edge
+	let (Value::Thing(from), Value::Table(kind), Value::Thing(with)) =
+		(from, kind.could_be_table(), with)
+	else {
+		return Err(RpcError::InvalidParams);
+	};
+	if opts.unique || opts.group_by_from || opts.fetch.is_some() {
+		return Err(RpcError::InvalidParams);
+	}
+	// Find the first existing edge between the records
+	let existing = Value::Idiom(Idiom(vec![
+		Part::Start(Value::Thing(from.clone())),
+		Part::Graph(Graph {
+			dir: Dir::Out,
+			what: GraphSubjects(vec![GraphSubject::Table(kind.clone())]),
+			cond: Some(Cond(Value::Expression(Box::new(Expression::Binary {
+				l: Value::Idiom(Idiom(vec![Part::from("out")])),
+				o: Operator::Equal,
+				r: Value::Thing(with.clone()),
+			})))),
+			..Default::default()
+		}),
+		Part::First,
+	]));
+	let edge = Value::Param(Param::from("edge"));
+	// Merge the data into the existing edge
+	let update = UpdateStatement {
+		only: true,
+		what: vec![edge.clone()].into(),
+		data: (!merge.is_none_or_null()).then_some(crate::sql::Data::MergeExpression(merge)),
+		output: opts.output.clone(),
+		timeout: opts.timeout.clone(),
+		..Default::default()
+	};
+	// Otherwise create a new edge with the data
+	let relate = RelateStatement {
+		only: true,
+		from: Value::Thing(from),
+		kind: Value::Table(kind),
+		with: Value::Thing(with),
+		data: opts.data_expr(),
+		output: opts.output.clone(),
+		timeout: opts.timeout.clone(),
+		..Default::default()
+	};
+	let outcome = |status: &str, stmt: Subquery| {
+		Value::from(map! {
+			"status".to_string() => Value::from(status),
+			"result".to_string() => Value::Subquery(Box::new(stmt)),
+		})
+	};
+	Ok(Query::from(vec![Statement::Value(Value::Block(Box::new(Block(vec![
+		Entry::Set(SetStatement {
+			name: "edge".to_string(),
+			what: existing,
+			kind: None,
+		}),
+		Entry::Value(Value::Subquery(Box::new(Subquery::Ifelse(IfelseStatement {
+			exprs: vec![(edge, outcome("updated", Subquery::Update(update)))],
+			close: Some(outcome("created", Subquery::Relate(relate))),
+		})))),
+	]))))]))
+}
+
+/// Checks if an execution plan iterates over every record in a table, rather than an index
+fn scans_table(plan: &Value) -> bool {
+	match plan {
+		Value::Array(v) => {
+			v.iter().any(|v| v.pick(&[Part::from("operation")]) == Value::from("Iterate Table"))
+		}
+		_ => false,
+	}
+}
+
+/// Checks that a datetime falls within the retained history, returning its timestamp
+fn retained_timestamp(v: &Datetime) -> Result<u64, RpcError> {
+	let now = chrono::Utc::now();
+	let retained = match *AS_OF_RETENTION {
+		0 => true,
+		secs => now.signed_duration_since(v.0).num_seconds() <= secs as i64,
+	};
+	match v.to_u64() {
+		Some(ts) if v.0 <= now && retained => Ok(ts),
+		_ => Err(RpcError::HistoryNotRetained(v.to_string())),
+	}
+}
+
+/// Deletes a record only if its fields still match the expected values,
+/// returning whether the record matched alongside the result
+fn delete_if_match(mut stmt: DeleteStatement, what: Thing, fields: Object) -> Query {
+	// Check each of the expected field values, alongside any other condition
+	stmt.cond = fields
+		.0
+		.into_iter()
+		.map(|(k, v)| {
+			Value::Expression(Box::new(Expression::Binary {
+				l: Value::Idiom(Idiom(vec![Part::from(k)])),
+				o: Operator::Equal,
+				r: v,
+			}))
+		})
+		.chain(stmt.cond.take().map(|c| c.0))
+		.reduce(|l, r| {
+			Value::Expression(Box::new(Expression::Binary {
+				l,
+				o: Operator::And,
+				r,
+			}))
+		})
+		.map(Cond);
+	// Find the record if it still matches
+	let matched = SelectStatement {
+		only: true,
+		expr: Fields(
+			vec![Field::Single {
+				expr: Value::Idiom(Idiom(ID.to_vec())),
+				alias: None,
+			}],
+			true,
+		),
+		what: vec![Value::Thing(what)].into(),
+		cond: stmt.cond.clone(),
+		..Default::default()
+	};
+	let param = Value::Param(Param::from("matched"));
+	Query::from(vec![Statement::Value(Value::Block(Box::new(Block(vec![
+		Entry::Set(SetStatement {
+			name: "matched".to_string(),
+			what: Value::Subquery(Box::new(Subquery::Select(matched))),
+			kind: None,
+		}),
+		Entry::Value(Value::Subquery(Box::new(Subquery::Ifelse(IfelseStatement {
+			exprs: vec![(
+				param,
+				Value::from(map! {
+					"matched".to_string() => Value::Bool(true),
+					"result".to_string() => Value::Subquery(Box::new(Subquery::Delete(stmt))),
+				}),
+			)],
+			close: Some(Value::from(map! {
+				"matched".to_string() => Value::Bool(false),
+			})),
+		})))),
+	]))))])
+}
+
+/// Parses the handle of a prepared statement or table scan
+fn parse_handle(v: Value) -> Result<uuid::Uuid, RpcError> {
+	match v {
+		Value::Uuid(v) => Ok(v.0),
+		Value::Strand(v) => Uuid::try_from(v).map(|v| v.0).map_err(|_| RpcError::InvalidParams),
+		_ => Err(RpcError::InvalidParams),
+	}
+}
+
+/// Converts a statement which supports EXPLAIN into one which only returns its execution
+/// plan, without iterating over or modifying any records
+fn explained(stmt: Statement) -> Option<Statement> {
+	match stmt {
+		Statement::Select(mut s) if s.explain.is_none() => {
+			s.explain = Some(Explain(false));
+			Some(Statement::Select(s))
+		}
+		Statement::Update(mut s) if s.explain.is_none() => {
+			s.explain = Some(Explain(false));
+			Some(Statement::Update(s))
+		}
+		Statement::Upsert(mut s) if s.explain.is_none() => {
+			s.explain = Some(Explain(false));
+			Some(Statement::Upsert(s))
+		}
+		Statement::Delete(mut s) if s.explain.is_none() => {
+			s.explain = Some(Explain(false));
+			Some(Statement::Delete(s))
+		}
+		_ => None,
+	}
+}
+
+/// Returns the id of a record which is requested to be inserted
+fn requested_id(v: &Value, into: &Option<Table>) -> Option<Thing> {
+	match v.rid() {
+		Value::None => None,
+		_ => gen_id(v, into).ok(),
+	}
+}
@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::dbs::Response;
+use crate::err::Error;
+use crate::cnf::REDACTED;
+use crate::sql::{Datetime, Value};
+
+/// A bounded log of the queries which were recently executed on a connection
+#[derive(Clone, Debug)]
+pub struct QueryLog {
+	/// The maximum number of queries which are kept, after which the oldest are discarded
+	size: usize,
+	/// Whether string literals are removed from the query text before it is kept
+	redact: bool,
+	/// The queries which were recorded, from oldest to newest
+	entries: VecDeque<QueryLogEntry>,
+}
+
+/// The metadata of a query which was executed on a connection
+#[derive(Clone, Debug)]
+pub struct QueryLogEntry {
+	/// The text of the query
+	pub query: String,
+	/// The time at which the query started
+	pub time: Datetime,
+	/// How long the query took to execute
+	pub duration: Duration,
+	/// The number of rows returned by all of the statements in the query
+	pub rows: usize,
+	/// The first error returned by the query, if any
+	pub error: Option<String>,
+}
+
+impl QueryLog {
+	/// Creates an empty query log which keeps up to the specified number of queries
+	pub fn new(size: usize, redact: bool) -> Self {
+		Self {
+			size,
+			redact,
+			entries: VecDeque::with_capacity(size),
+		}
+	}
+
+	/// Records a query, discarding the oldest queries if the log is full
+	pub fn push(&mut self, mut entry: QueryLogEntry) {
+		if self.redact {
+			entry.query = redact(&entry.query);
+		}
+		while self.entries.len() >= self.size.max(1) {
+			self.entries.pop_front();
+		}
+		self.entries.push_back(entry);
+	}
+
+	/// Returns the queries which were recorded, from oldest to newest
+	pub fn entries(&self) -> Vec<QueryLogEntry> {
+		self.entries.iter().cloned().collect()
+	}
+
+	/// Removes all of the queries which were recorded
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+}
+
+impl QueryLogEntry {
+	/// Summarises the outcome of a query which started at the specified time
+	pub(crate) fn new(
+		query: String,
+		time: Datetime,
+		duration: Duration,
+		res: &Result<Vec<Response>, Error>,
+	) -> Self {
+		let (rows, error) = match res {
+			Ok(res) => (
+				res.iter()
+					.map(|v| match &v.result {
+						Ok(Value::Array(v)) => v.len(),
+						Ok(Value::None) | Err(_) => 0,
+						Ok(_) => 1,
+					})
+					.sum(),
+				res.iter().find_map(|v| v.result.as_ref().err().map(ToString::to_string)),
+			),
+			Err(e) => (0, Some(e.to_string())),
+		};
+		Self {
+			query,
+			time,
+			duration,
+			rows,
+			error,
+		}
+	}
+}
+
+impl From<QueryLogEntry> for Value {
+	fn from(v: QueryLogEntry) -> Self {
+		Value::from(map! {
+			"query".to_string() => Value::from(v.query),
+			"time".to_string() => Value::from(v.time),
+			"duration".to_string() => Value::from(v.duration),
+			"rows".to_string() => Value::from(v.rows),
+			"error".to_string() => v.error.map(Value::from).unwrap_or_default(),
+		})
+	}
+}
+
+/// Replaces the contents of the string literals in the query text
+fn redact(sql: &str) -> String {
+	let mut out = String::with_capacity(sql.len());
+	let mut chars = sql.chars();
+	while let Some(c) = chars.next() {
+		out.push(c);
+		if c == '\'' || c == '"' {
+			// Skip to the end of the string, ignoring escaped quotes
+			let mut escaped = false;
+			for n in chars.by_ref() {
+				match n {
+					_ if escaped => escaped = false,
+					'\\' => escaped = true,
+					n if n == c => break,
+					_ => {}
+				}
+			}
+			out.push_str(REDACTED);
+			out.push(c);
+		}
+	}
+	out
+}
@@ -0,0 +1,500 @@
+use std::{collections::BTreeMap, hash::Hash, str::FromStr, sync::Arc, time};
+
+use crate::{
+	cnf::{PROTECTED_PARAM_NAMES, REDACTED},
+	dbs::{
+		capabilities::{ExperimentalTarget, FuncTarget, NetTarget, Targets},
+		Capabilities, Response,
+	},
+	sql::{self, Duration, Number, Value},
+};
+
+use super::{
+	field_kinds, non_finite::NonFinite, record_ids::RecordIds, retry::Retry,
+	statement_options::without_absent, subqueries, Data, RpcError,
+};
+
+/// The words which mark a variable as holding a secret, when redacting the variables of a query
+const SECRET_PARAM_WORDS: &[&str] = &["password", "secret", "token", "apikey", "api_key"];
+
+/// Query Options for the `query` method.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct QueryOptions {
+	/// - A number, stating how many rows of each statement result should be returned
+	/// - The total number of rows is still reported for each statement
+	pub preview: Option<usize>,
+	/// - An object, containing capabilities which narrow the datastore capabilities
+	/// - The capabilities can only ever be restricted further, and never expanded
+	pub capabilities: Option<Arc<Capabilities>>,
+	/// - An object, containing the number of `attempts` and the `backoff` duration
+	/// - Retries the query when it fails due to a transient transaction conflict
+	pub retry: Option<Retry>,
+	/// - A number, used to seed the random number generator for this query
+	/// - The random functions return the same values for the same seed and query
+	pub seed: Option<u64>,
+	/// - A number, stating the maximum depth to which recursive graph traversals can recurse
+	/// - Statements which would recurse any deeper fail with an error, independently of the global limit
+	pub max_depth: Option<u32>,
+	/// - A number, containing a versionstamp returned by the `versionstamp` write option
+	/// - The query waits briefly until the database reflects this versionstamp, failing with an error otherwise
+	pub min_version: Option<u64>,
+	/// - A datetime, or a string containing a datetime, at which all statements read the database
+	/// - All statements run within a single read-only snapshot, and the query is rejected if any of its statements could write
+	/// - The datetime must fall within the history which is retained by the datastore
+	pub as_of: Option<sql::Datetime>,
+	/// - An object, containing named subqueries which can be referenced as params throughout the query
+	/// - Each subquery is bound once before the query runs, and may reference the other named subqueries
+	/// - The query is rejected if it references a param which is not defined
+	pub with: Option<BTreeMap<String, String>>,
+	/// - A duration, stating how long a statement can run before its execution plan is captured
+	/// - The plan is returned in the `plan` field of each statement response which exceeded the duration
+	pub explain_if_slower_than: Option<time::Duration>,
+	/// - A number, stating the maximum number of rows which each statement can return
+	/// - The query is aborted with an error if any statement result exceeds this number
+	pub max_results: Option<usize>,
+	/// - One of: `"error"`, `"null"` or `"string"`, stating how non-finite floats are returned
+	/// - With `"string"`, they are returned as `"NaN"`, `"Infinity"` or `"-Infinity"`
+	/// - When not set, non-finite floats are returned as the response format represents them
+	pub non_finite: Option<NonFinite>,
+	/// - One of: `"string"` or `"object"`, stating how record ids are returned
+	/// - With `"object"`, they are returned as an object with `tb` and `id` fields
+	/// - When not set, record ids are returned as the response format represents them
+	pub record_ids: Option<RecordIds>,
+	/// - A boolean, stating whether fields set to null should be told apart from absent fields
+	/// - Absent fields are omitted from the objects in each statement result, while fields set to null are returned as `null`
+	pub explicit_nulls: bool,
+	/// - A boolean, stating whether the type of each top-level field should be returned for each statement
+	/// - The types are inferred from the values, and returned in the `types` field of each statement response
+	pub with_types: bool,
+	/// - A boolean, stating whether the execution time of each statement should be returned in nanoseconds
+	/// - The time is returned in the `time_ns` field of each statement response
+	pub timing: bool,
+	/// - A boolean, stating whether the kind of statement which produced each response should be returned
+	/// - The kind is returned as a lowercase keyword, such as `select` or `create`, in the `statement` field of each statement response
+	pub statement_types: bool,
+	/// - A boolean, stating whether nested arrays in each statement result should be flattened
+	/// - Only one level is flattened, so arrays nested more deeply are returned unchanged
+	pub flatten: bool,
+	/// - A boolean, stating whether all statements should run within a single read-only snapshot
+	/// - The query is rejected if any of its statements could write
+	pub snapshot: bool,
+	/// - A boolean, stating whether a failed statement stops the execution of any further statements
+	/// - When enabled, the statements after a failed statement are returned with an error without being executed
+	/// - When disabled, which is the default, every statement is executed independently of the others
+	pub fail_fast: bool,
+	/// - A boolean, stating whether the variables which the query ran with should be returned
+	/// - The variables are returned after the session variables are merged in, with protected or secret variables redacted
+	/// - The statement responses are returned in the `result` field, and the variables in the `vars` field
+	pub debug_vars: bool,
+	/// - A string, containing a token which is attached to the response of each statement
+	/// - The response of each statement is sent as a separate message as soon as the statement completes, and the query returns the number of statements
+	/// - The responses within a transaction are sent once the transaction is committed or cancelled
+	pub stream: Option<String>,
+	/// - Set with the `legacy_strands` flag in the `parser` array option
+	/// - Strings which look like record ids, uuids, or datetimes are parsed as such
+	pub legacy_strands: bool,
+	/// - Set with the `strict_record_ids` flag in the `parser` array option
+	/// - Record ids must adhere to the regular identifier rules
+	pub strict_record_ids: bool,
+}
+
+impl QueryOptions {
+	pub(crate) fn process_options(&mut self, opts: Value) -> Result<&mut Self, RpcError> {
+		if let Value::Object(mut obj) = opts {
+			// Process "preview" option
+			if let Some(v) = obj.remove("preview") {
+				match v {
+					Value::Number(Number::Int(v)) if v >= 0 => self.preview = Some(v as usize),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "max_results" option
+			if let Some(v) = obj.remove("max_results") {
+				match v {
+					Value::Number(Number::Int(v)) if v >= 0 => self.max_results = Some(v as usize),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "non_finite" option
+			if let Some(v) = obj.remove("non_finite") {
+				self.non_finite = Some(NonFinite::process_options(v)?);
+			}
+
+			// Process "record_ids" option
+			if let Some(v) = obj.remove("record_ids") {
+				self.record_ids = Some(RecordIds::process_options(v)?);
+			}
+
+			// Process "explicit_nulls" option
+			if let Some(v) = obj.remove("explicit_nulls") {
+				if let Value::Bool(v) = v {
+					self.explicit_nulls = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "with_types" option
+			if let Some(v) = obj.remove("with_types") {
+				if let Value::Bool(v) = v {
+					self.with_types = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "timing" option
+			if let Some(v) = obj.remove("timing") {
+				if let Value::Bool(v) = v {
+					self.timing = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "statement_types" option
+			if let Some(v) = obj.remove("statement_types") {
+				if let Value::Bool(v) = v {
+					self.statement_types = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "flatten" option
+			if let Some(v) = obj.remove("flatten") {
+				if let Value::Bool(v) = v {
+					self.flatten = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "snapshot" option
+			if let Some(v) = obj.remove("snapshot") {
+				if let Value::Bool(v) = v {
+					self.snapshot = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "fail_fast" option
+			if let Some(v) = obj.remove("fail_fast") {
+				if let Value::Bool(v) = v {
+					self.fail_fast = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "debug_vars" option
+			if let Some(v) = obj.remove("debug_vars") {
+				if let Value::Bool(v) = v {
+					self.debug_vars = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "stream" option
+			if let Some(v) = obj.remove("stream") {
+				match v {
+					Value::Strand(v) if !v.is_empty() => self.stream = Some(v.0),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "parser" option
+			if let Some(v) = obj.remove("parser") {
+				let Value::Array(flags) = v else {
+					return Err(RpcError::InvalidParams);
+				};
+				for flag in flags {
+					let Value::Strand(flag) = flag else {
+						return Err(RpcError::InvalidParams);
+					};
+					match flag.as_str() {
+						"legacy_strands" => self.legacy_strands = true,
+						"strict_record_ids" => self.strict_record_ids = true,
+						_ => return Err(RpcError::InvalidParams),
+					}
+				}
+			}
+
+			// Process "retry" option
+			if let Some(v) = obj.remove("retry") {
+				self.retry = Some(Retry::process_options(v)?);
+			}
+
+			// Process "capabilities" option
+			if let Some(v) = obj.remove("capabilities") {
+				self.capabilities = Some(Arc::new(process_capabilities(v)?));
+			}
+
+			// Process "seed" option
+			if let Some(v) = obj.remove("seed") {
+				match v {
+					Value::Number(Number::Int(v)) if v >= 0 => self.seed = Some(v as u64),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "with" option
+			if let Some(v) = obj.remove("with") {
+				let Value::Object(v) = v else {
+					return Err(RpcError::InvalidParams);
+				};
+				let mut with = BTreeMap::new();
+				for (name, v) in v.0 {
+					match v {
+						Value::Strand(v) if subqueries::valid_name(&name) => {
+							with.insert(name, v.0);
+						}
+						_ => return Err(RpcError::InvalidParams),
+					}
+				}
+				self.with = Some(with);
+			}
+
+			// Process "explain_if_slower_than" option
+			if let Some(v) = obj.remove("explain_if_slower_than") {
+				self.explain_if_slower_than = match v {
+					Value::Duration(v) => Some(v.0),
+					Value::Strand(v) => {
+						Some(Duration::try_from(v.as_str()).map_err(|_| RpcError::InvalidParams)?.0)
+					}
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "max_depth" option
+			if let Some(v) = obj.remove("max_depth") {
+				match v {
+					Value::Number(Number::Int(v)) if v > 0 && v <= u32::MAX as i64 => {
+						self.max_depth = Some(v as u32)
+					}
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "min_version" option
+			if let Some(v) = obj.remove("min_version") {
+				match v {
+					Value::Number(Number::Int(v)) if v >= 0 => self.min_version = Some(v as u64),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "as_of" option
+			if let Some(v) = obj.remove("as_of") {
+				match v {
+					Value::Datetime(v) => self.as_of = Some(v),
+					Value::Strand(v) => {
+						self.as_of = Some(
+							sql::Datetime::try_from(v.as_str())
+								.map_err(|_| RpcError::InvalidParams)?,
+						)
+					}
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			Ok(self)
+		} else {
+			Err(RpcError::InvalidParams)
+		}
+	}
+
+	/// Checks if the query text needs to be parsed with compatibility flags
+	pub(crate) fn compatibility(&self) -> bool {
+		self.legacy_strands || self.strict_record_ids
+	}
+
+	/// Checks if the query responses need to be reshaped before being returned
+	fn reshapes(&self) -> bool {
+		self.preview.is_some()
+			|| self.timing
+			|| self.with_types
+			|| self.statement_types
+			|| self.explain_if_slower_than.is_some()
+	}
+
+	/// Converts the query responses, and any captured execution plans, into the data returned to the client
+	pub(crate) fn output(
+		&self,
+		mut res: Vec<Response>,
+		plans: Vec<Option<Value>>,
+	) -> Result<Data, RpcError> {
+		// Flatten each statement result by one level
+		if self.flatten {
+			for response in res.iter_mut() {
+				if let Ok(v) = &mut response.result {
+					*v = std::mem::take(v).flatten();
+				}
+			}
+		}
+		// Replace any non-finite floats in each statement result
+		if let Some(policy) = self.non_finite {
+			for response in res.iter_mut() {
+				if let Ok(v) = &mut response.result {
+					*v = policy.apply(std::mem::take(v))?;
+				}
+			}
+		}
+		// Replace any record ids in each statement result
+		if let Some(format) = self.record_ids {
+			for response in res.iter_mut() {
+				if let Ok(v) = &mut response.result {
+					*v = format.apply(std::mem::take(v));
+				}
+			}
+		}
+		// Omit any absent fields in each statement result
+		if self.explicit_nulls {
+			for response in res.iter_mut() {
+				if let Ok(v) = &mut response.result {
+					*v = without_absent(std::mem::take(v));
+				}
+			}
+		}
+		// Check that no statement returned too many rows
+		if let Some(max) = self.max_results {
+			for response in &res {
+				if let Ok(Value::Array(v)) = &response.result {
+					if v.len() > max {
+						return Err(RpcError::TooManyResults(max));
+					}
+				}
+			}
+		}
+		// Return the responses untouched if no option applies
+		if !self.reshapes() {
+			return Ok(res.into());
+		}
+		// Reshape each of the statement responses
+		let mut out = Vec::with_capacity(res.len());
+		let plans = plans.into_iter().chain(std::iter::repeat(None));
+		for (mut response, plan) in res.into_iter().zip(plans) {
+			// Truncate the statement result
+			let mut total = None;
+			if let (Some(n), Ok(Value::Array(v))) = (self.preview, &mut response.result) {
+				total = Some(v.len());
+				v.truncate(n);
+			}
+			// Infer the type of each field
+			let types = match (self.with_types, &response.result) {
+				(true, Ok(v)) => Some(field_kinds::infer(v)),
+				_ => None,
+			};
+			// Measure the statement execution time
+			let nanos = response.time.as_nanos() as u64;
+			// Keep the kind of statement
+			let statement = response.statement;
+			// Convert the statement response
+			let mut response = sql::to_value(response)?;
+			if let Value::Object(obj) = &mut response {
+				// Note the total number of rows
+				if let Some(total) = total {
+					obj.insert("total".to_string(), total.into());
+				}
+				// Note the type of each field
+				if let Some(types) = types {
+					obj.insert("types".to_string(), field_kinds::into_value(types));
+				}
+				// Note the statement execution time
+				if self.timing {
+					obj.insert("time_ns".to_string(), nanos.into());
+				}
+				// Note the kind of statement
+				if let (true, Some(statement)) = (self.statement_types, statement) {
+					obj.insert("statement".to_string(), statement.into());
+				}
+				// Note the execution plan of a slow statement
+				if let Some(plan) = plan {
+					obj.insert("plan".to_string(), plan);
+				}
+			}
+			out.push(response);
+		}
+		Ok(Value::from(out).into())
+	}
+
+	/// Converts the response of a single statement of a streamed query into the value sent to the client
+	pub(crate) fn output_one(&self, res: Response) -> Result<Value, RpcError> {
+		match Value::try_from(self.output(vec![res], Vec::new())?)? {
+			Value::Array(mut v) if v.len() == 1 => Ok(v.0.remove(0)),
+			_ => Err(fail!("Expected a single statement response").into()),
+		}
+	}
+}
+
+/// Attaches the variables which a query ran with to the query responses, redacting any protected or secret variables
+pub(crate) fn with_vars(res: Value, vars: Option<&BTreeMap<String, Value>>) -> Value {
+	let vars = vars
+		.into_iter()
+		.flatten()
+		.map(|(k, v)| match secret(k) {
+			true => (k.clone(), Value::from(REDACTED)),
+			false => (k.clone(), v.clone()),
+		})
+		.collect::<BTreeMap<_, _>>();
+	Value::from(map! {
+		"result".to_string() => res,
+		"vars".to_string() => Value::from(vars),
+	})
+}
+
+/// Checks if a variable is protected, or is named as if it holds a secret
+fn secret(name: &str) -> bool {
+	let name = name.to_lowercase();
+	PROTECTED_PARAM_NAMES.contains(&name.as_str())
+		|| SECRET_PARAM_WORDS.iter().any(|word| name.contains(word))
+}
+
+/// Converts the capabilities option into the capabilities to narrow by
+fn process_capabilities(opts: Value) -> Result<Capabilities, RpcError> {
+	let Value::Object(obj) = opts else {
+		return Err(RpcError::InvalidParams);
+	};
+	// Start from capabilities which do not narrow anything
+	let mut caps = Capabilities::all().with_experimental(Targets::All);
+	for (k, v) in obj.0 {
+		caps = match (k.as_str(), v) {
+			("scripting", Value::Bool(v)) => caps.with_scripting(v),
+			("allow_functions", v) => caps.with_functions(process_targets::<FuncTarget>(v)?),
+			("deny_functions", v) => caps.without_functions(process_targets::<FuncTarget>(v)?),
+			("allow_net", v) => caps.with_network_targets(process_targets::<NetTarget>(v)?),
+			("deny_net", v) => caps.without_network_targets(process_targets::<NetTarget>(v)?),
+			("allow_experimental", v) => {
+				caps.with_experimental(process_targets::<ExperimentalTarget>(v)?)
+			}
+			("deny_experimental", v) => {
+				caps.without_experimental(process_targets::<ExperimentalTarget>(v)?)
+			}
+			// Reject anything which is not understood, so
+			// that a restriction is never silently ignored
+			_ => return Err(RpcError::InvalidParams),
+		};
+	}
+	Ok(caps)
+}
+
+/// Converts a boolean or an array of strings into capability targets
+fn process_targets<T: FromStr + Hash + Eq>(v: Value) -> Result<Targets<T>, RpcError> {
+	match v {
+		Value::Bool(true) => Ok(Targets::All),
+		Value::Bool(false) => Ok(Targets::None),
+		Value::Array(v) => v
+			.into_iter()
+			.map(|v| match v {
+				Value::Strand(v) => T::from_str(v.as_str()).map_err(|_| RpcError::InvalidParams),
+				_ => Err(RpcError::InvalidParams),
+			})
+			.collect::<Result<_, _>>()
+			.map(Targets::Some),
+		_ => Err(RpcError::InvalidParams),
+	}
+}
@@ -0,0 +1,54 @@
+use crate::sql::{Thing, Value};
+
+use super::RpcError;
+
+/// How record ids are serialized in the results returned to clients
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum RecordIds {
+	/// Returns any record id as a string, such as `"user:123"`
+	String,
+	/// Returns any record id as an object, such as `{ tb: "user", id: 123 }`
+	Object,
+}
+
+impl RecordIds {
+	pub(crate) fn process_options(opts: Value) -> Result<Self, RpcError> {
+		match opts {
+			Value::Strand(v) => match v.as_str() {
+				"string" => Ok(Self::String),
+				"object" => Ok(Self::Object),
+				_ => Err(RpcError::InvalidParams),
+			},
+			_ => Err(RpcError::InvalidParams),
+		}
+	}
+
+	/// Replaces any record ids nested within the value according to this format
+	pub(crate) fn apply(self, v: Value) -> Value {
+		match v {
+			Value::Thing(Thing {
+				tb,
+				id,
+			}) => match self {
+				Self::String => Value::from(Thing::from((tb, id)).to_string()),
+				Self::Object => Value::from(map! {
+					"tb".to_string() => Value::from(tb),
+					"id".to_string() => self.apply(Value::from(id)),
+				}),
+			},
+			Value::Array(mut v) => {
+				for v in v.iter_mut() {
+					*v = self.apply(std::mem::take(v));
+				}
+				Value::Array(v)
+			}
+			Value::Object(mut v) => {
+				for v in v.values_mut() {
+					*v = self.apply(std::mem::take(v));
+				}
+				Value::Object(v)
+			}
+			v => v,
+		}
+	}
+}
@@ -0,0 +1,243 @@
+use std::future::Future;
+use std::time;
+
+use crate::{
+	dbs::Response,
+	err::Error,
+	sql::{Duration, Number, Value},
+};
+
+use super::RpcError;
+
+/// The maximum number of times a method can be retried
+const MAX_RETRY_ATTEMPTS: u32 = 10;
+
+/// The maximum time to wait before any single retry
+const MAX_RETRY_BACKOFF: time::Duration = time::Duration::from_secs(1);
+
+/// The maximum time to wait across all of the retries of a method
+const MAX_RETRY_WAIT: time::Duration = time::Duration::from_secs(5);
+
+/// Retry options for methods which hit transient transaction conflicts
+#[derive(Clone, Debug)]
+pub(crate) struct Retry {
+	/// - A number, stating how many times the method should be retried
+	pub attempts: u32,
+	/// - A duration, stating how long to wait before the first retry
+	/// - The wait doubles with each subsequent retry, up to one second
+	pub backoff: time::Duration,
+}
+
+impl Default for Retry {
+	fn default() -> Self {
+		Self {
+			attempts: 3,
+			backoff: time::Duration::from_millis(10),
+		}
+	}
+}
+
+impl Retry {
+	pub(crate) fn process_options(opts: Value) -> Result<Self, RpcError> {
+		if let Value::Object(mut obj) = opts {
+			let mut retry = Self::default();
+
+			// Process "attempts" option
+			if let Some(v) = obj.remove("attempts") {
+				match v {
+					Value::Number(Number::Int(v))
+						if (0..=MAX_RETRY_ATTEMPTS as i64).contains(&v) =>
+					{
+						retry.attempts = v as u32
+					}
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "backoff" option
+			if let Some(v) = obj.remove("backoff") {
+				retry.backoff = match v {
+					Value::Duration(v) => v.0,
+					Value::Strand(v) => {
+						Duration::try_from(v.as_str()).map_err(|_| RpcError::InvalidParams)?.0
+					}
+					_ => return Err(RpcError::InvalidParams),
+				};
+				if retry.backoff > MAX_RETRY_BACKOFF {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			Ok(retry)
+		} else {
+			Err(RpcError::InvalidParams)
+		}
+	}
+
+	/// Executes the method, retrying it on transient transaction conflicts
+	pub(crate) async fn run<F, R, E>(retry: Option<&Retry>, mut f: F) -> Result<Vec<Response>, E>
+	where
+		F: FnMut() -> R,
+		R: Future<Output = Result<Vec<Response>, E>>,
+		E: Conflict,
+	{
+		let mut attempt = 0;
+		let mut waited = time::Duration::ZERO;
+		loop {
+			let res = f().await;
+			match retry {
+				Some(retry) if attempt < retry.attempts && Self::conflicted(&res) => {
+					// Wait with a capped exponential backoff
+					let wait = retry.wait(attempt);
+					// Give up once the total wait would be exceeded
+					waited += wait;
+					if waited > MAX_RETRY_WAIT {
+						return res;
+					}
+					#[cfg(target_family = "wasm")]
+					wasmtimer::tokio::sleep(wait).await;
+					#[cfg(not(target_family = "wasm"))]
+					tokio::time::sleep(wait).await;
+					attempt += 1;
+				}
+				_ => return res,
+			}
+		}
+	}
+
+	/// The time to wait before the given retry attempt
+	fn wait(&self, attempt: u32) -> time::Duration {
+		self.backoff.saturating_mul(1 << attempt).min(MAX_RETRY_BACKOFF)
+	}
+
+	/// Checks if the responses failed only because of a transient
+	/// transaction conflict. If any statement succeeded, then the
+	/// method is not retried, so that changes are never applied twice.
+	fn conflicted<E: Conflict>(res: &Result<Vec<Response>, E>) -> bool {
+		match res {
+			Err(e) => e.is_conflict(),
+			Ok(res) => {
+				res.iter().all(|r| r.result.is_err())
+					&& res.iter().any(|r| matches!(&r.result, Err(e) if e.is_conflict()))
+			}
+		}
+	}
+}
+
+/// An error which may be caused by a transient transaction conflict
+pub(crate) trait Conflict {
+	fn is_conflict(&self) -> bool;
+}
+
+impl Conflict for Error {
+	fn is_conflict(&self) -> bool {
+		matches!(self, Error::TxRetryable)
+	}
+}
+
+impl Conflict for RpcError {
+	fn is_conflict(&self) -> bool {
+		matches!(self, RpcError::InternalError(e) if e.is_conflict())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::dbs::QueryType;
+	use std::sync::atomic::{AtomicU32, Ordering};
+
+	fn response(result: Result<Value, Error>) -> Vec<Response> {
+		vec![Response {
+			time: time::Duration::ZERO,
+			result,
+			query_type: QueryType::Other,
+			statement: None,
+		}]
+	}
+
+	fn retry(attempts: u32) -> Retry {
+		Retry {
+			attempts,
+			backoff: time::Duration::from_millis(1),
+		}
+	}
+
+	#[tokio::test]
+	async fn retries_a_conflict_until_it_succeeds() {
+		let calls = AtomicU32::new(0);
+		let res = Retry::run(Some(&retry(3)), || async {
+			match calls.fetch_add(1, Ordering::SeqCst) {
+				0 | 1 => Ok::<_, Error>(response(Err(Error::TxRetryable))),
+				_ => Ok(response(Ok(Value::from(true)))),
+			}
+		})
+		.await
+		.unwrap();
+		assert_eq!(calls.load(Ordering::SeqCst), 3);
+		assert_eq!(res[0].result.as_ref().unwrap(), &Value::from(true));
+	}
+
+	#[tokio::test]
+	async fn returns_the_last_conflict_when_attempts_run_out() {
+		let calls = AtomicU32::new(0);
+		let res = Retry::run(Some(&retry(2)), || async {
+			calls.fetch_add(1, Ordering::SeqCst);
+			Err::<Vec<Response>, _>(Error::TxRetryable)
+		})
+		.await;
+		assert_eq!(calls.load(Ordering::SeqCst), 3);
+		assert!(matches!(res, Err(Error::TxRetryable)));
+	}
+
+	#[tokio::test]
+	async fn does_not_retry_an_application_error() {
+		let calls = AtomicU32::new(0);
+		let res = Retry::run(Some(&retry(3)), || async {
+			calls.fetch_add(1, Ordering::SeqCst);
+			Ok::<_, Error>(response(Err(Error::Thrown("failed".to_string()))))
+		})
+		.await
+		.unwrap();
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+		assert!(matches!(res[0].result, Err(Error::Thrown(_))));
+	}
+
+	#[tokio::test]
+	async fn does_not_retry_without_the_option() {
+		let calls = AtomicU32::new(0);
+		let res = Retry::run(None, || async {
+			calls.fetch_add(1, Ordering::SeqCst);
+			Err::<Vec<Response>, _>(Error::TxRetryable)
+		})
+		.await;
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+		assert!(res.is_err());
+	}
+
+	#[test]
+	fn caps_the_backoff() {
+		let retry = Retry {
+			attempts: MAX_RETRY_ATTEMPTS,
+			backoff: time::Duration::from_millis(100),
+		};
+		assert_eq!(retry.wait(0), time::Duration::from_millis(100));
+		assert_eq!(retry.wait(2), time::Duration::from_millis(400));
+		assert_eq!(retry.wait(9), MAX_RETRY_BACKOFF);
+		// The total wait is capped before every attempt can be used
+		let total: time::Duration = (0..MAX_RETRY_ATTEMPTS).map(|i| retry.wait(i)).sum();
+		assert!(total > MAX_RETRY_WAIT);
+	}
+
+	#[test]
+	fn rejects_a_backoff_above_the_cap() {
+		let opts = Value::from(map! {
+			"backoff".to_string() => Value::from("2s"),
+		});
+		assert!(matches!(Retry::process_options(opts), Err(RpcError::InvalidParams)));
+		let opts = Value::from(map! {
+			"backoff".to_string() => Value::from("50ms"),
+		});
+		assert_eq!(Retry::process_options(opts).unwrap().backoff, time::Duration::from_millis(50));
+	}
+}
@@ -0,0 +1,18 @@
+use std::ops::Bound;
+
+use crate::sql::{Id, Table, Thing};
+
+/// The position of a table scan which is held open on a connection
+#[derive(Clone, Debug)]
+pub struct ScanCursor {
+	/// The table which is being scanned
+	pub table: Table,
+	/// The number of records which are returned in each batch
+	pub batch_size: u32,
+	/// The record id at which the scan starts, if the scan covers part of the table
+	pub start: Bound<Id>,
+	/// The record id at which the scan ends, if the scan covers part of the table
+	pub end: Bound<Id>,
+	/// The last record which was returned, after which the scan continues
+	pub position: Option<Thing>,
+}
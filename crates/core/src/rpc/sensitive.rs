@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use crate::{
+	dbs::Session,
+	err::Error,
+	iam::{Action, ResourceKind},
+	kvs::Datastore,
+	sql::{Idiom, Value},
+};
+
+/// Checks if the session is allowed to reveal sensitive fields
+pub(crate) fn check_reveal(kvs: &Datastore, sess: &Session) -> Result<(), Error> {
+	// Get the NS and DB
+	let ns = sess.ns.as_deref().ok_or(Error::NsEmpty)?;
+	let db = sess.db.as_deref().ok_or(Error::DbEmpty)?;
+	// If auth is disabled, allow all actions for anonymous users
+	if !kvs.is_auth_enabled() && sess.au.is_anon() {
+		return Ok(());
+	}
+	// Revealing a field requires access to the field definitions
+	sess.au.is_allowed(Action::View, &ResourceKind::Field.on_db(ns, db)).map_err(Error::IamError)
+}
+
+/// Returns a session which reveals the specified sensitive fields, which
+/// must only be used once the session is checked with [`check_reveal`]
+pub(crate) fn revealed(sess: &Arc<Session>, reveal: &[Idiom]) -> Arc<Session> {
+	match reveal.is_empty() {
+		true => sess.clone(),
+		false => Arc::new(Session {
+			reveal: reveal.to_vec(),
+			..sess.as_ref().clone()
+		}),
+	}
+}
+
+/// Returns the tables which are targeted by a selection
+pub(crate) fn tables(what: &Value) -> Vec<String> {
+	match what {
+		Value::Table(v) => vec![v.0.clone()],
+		Value::Thing(v) => vec![v.tb.clone()],
+		Value::Array(v) => v.iter().flat_map(tables).collect(),
+		_ => vec![],
+	}
+}
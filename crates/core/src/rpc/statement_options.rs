@@ -1,15 +1,387 @@
 use std::collections::BTreeMap;
+use std::time;
 
 use crate::{
 	dbs::Capabilities,
-	sql::{Cond, Data, Fetchs, Fields, Limit, Number, Output, Start, Timeout, Value, Version},
+	sql::operation::Operation,
+	sql::order::{Order, OrderList, Ordering},
+	sql::paths::IN,
+	sql::statements::show::ShowSince,
+	sql::{
+		Cond, Data, Duration, Expression, Fetchs, Field, Fields, Idiom, Idioms, Limit, Number,
+		Object, Operator, Output, Part, Query, Start, Timeout, Value, Version,
+	},
 	syn::{
 		condition_with_capabilities, fetchs_with_capabilities, fields_with_capabilities,
-		output_with_capabilities, value_with_capabilities,
+		idiom_with_capabilities, output_with_capabilities, value_with_capabilities,
 	},
 };
 
-use super::RpcError;
+use super::{
+	expect::Expect, live_aggregate::MAX_LIVE_DEBOUNCE, non_finite::NonFinite,
+	record_ids::RecordIds, retry::Retry, typed, RpcError,
+};
+
+/// The default text which is inserted before a highlighted match
+const HIGHLIGHT_PREFIX: &str = "<b>";
+/// The default text which is inserted after a highlighted match
+const HIGHLIGHT_SUFFIX: &str = "</b>";
+
+/// Attaches the generated SurrealQL statement to a method result
+pub(crate) fn with_sql(res: Value, sql: &Query) -> Value {
+	Value::from(map! {
+		"result".to_string() => res,
+		"sql".to_string() => Value::from(sql.to_string()),
+	})
+}
+
+/// Attaches the versionstamp which follows a write, alongside any generated SurrealQL
+pub(crate) fn with_versionstamp(res: Value, echoed: bool, versionstamp: u64) -> Value {
+	match (echoed, res) {
+		(true, Value::Object(mut v)) => {
+			v.insert("versionstamp".to_string(), Value::from(versionstamp));
+			Value::Object(v)
+		}
+		(_, res) => Value::from(map! {
+			"result".to_string() => res,
+			"versionstamp".to_string() => Value::from(versionstamp),
+		}),
+	}
+}
+
+/// Attaches the fields of each created record which were not in the input, and so were defaulted
+pub(crate) fn with_defaults(res: Value, wrapped: bool, input: &Value) -> Value {
+	let fields = |record: &Value| -> Value {
+		input
+			.diff(record, Idiom::default())
+			.into_iter()
+			.filter_map(|op| match op {
+				// The id is assigned by the server, but is not a field default
+				Operation::Add {
+					path,
+					..
+				} if path.to_string() != "id" => Some(Value::from(path.to_string())),
+				_ => None,
+			})
+			.collect::<Vec<_>>()
+			.into()
+	};
+	let records = match wrapped {
+		true => res.pick(&[Part::from("result")]),
+		false => res.clone(),
+	};
+	let defaults = match records {
+		Value::Array(v) => v.iter().map(fields).collect::<Vec<_>>().into(),
+		Value::None => Value::None,
+		v => fields(&v),
+	};
+	match (wrapped, res) {
+		(true, Value::Object(mut v)) => {
+			v.insert("defaults".to_string(), defaults);
+			Value::Object(v)
+		}
+		(_, res) => Value::from(map! {
+			"result".to_string() => res,
+			"defaults".to_string() => defaults,
+		}),
+	}
+}
+
+/// Renames the selected fields, adding any aliased fields which are not already selected
+///
+/// When every field is selected, the source fields of the aliases are omitted, so that
+/// they are only returned under their new names.
+pub(crate) fn with_aliases(
+	mut fields: Fields,
+	mut omit: Option<Idioms>,
+	aliases: &[(Idiom, Idiom)],
+) -> Result<(Fields, Option<Idioms>), RpcError> {
+	// A single value can not be renamed
+	if fields.1 {
+		return Err(RpcError::InvalidParams);
+	}
+	let all = fields.is_all();
+	for (source, alias) in aliases {
+		let selected = fields.0.iter_mut().find(
+			|f| matches!(f, Field::Single { expr: Value::Idiom(v), alias: None } if v == source),
+		);
+		match selected {
+			Some(Field::Single {
+				alias: v,
+				..
+			}) => *v = Some(alias.clone()),
+			_ => {
+				fields.0.push(Field::Single {
+					expr: Value::Idiom(source.clone()),
+					alias: Some(alias.clone()),
+				});
+				if all {
+					omit.get_or_insert_with(|| Idioms(vec![])).0.push(source.clone());
+				}
+			}
+		}
+	}
+	Ok((fields, omit))
+}
+
+/// Arranges the selected records into rows of values in the requested column order
+pub(crate) fn with_columns(res: Value, columns: &[Idiom]) -> Value {
+	let row = |v: &Value| -> Value {
+		columns
+			.iter()
+			.map(|c| match v.pick(c) {
+				Value::None => Value::Null,
+				v => v,
+			})
+			.collect::<Vec<_>>()
+			.into()
+	};
+	let rows: Vec<Value> = match &res {
+		Value::Array(v) => v.iter().map(row).collect(),
+		Value::None => vec![],
+		v => vec![row(v)],
+	};
+	let columns: Vec<Value> = columns.iter().map(|c| Value::from(c.to_string())).collect();
+	Value::from(map! {
+		"columns".to_string() => columns.into(),
+		"rows".to_string() => rows.into(),
+	})
+}
+
+/// Groups the created edges into adjacency lists keyed by their source record
+pub(crate) fn with_adjacency(res: Value) -> Value {
+	let edges = match res {
+		Value::Array(v) => v.0,
+		Value::None => vec![],
+		v => vec![v],
+	};
+	let mut out: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+	for edge in edges {
+		// Fetched source records are keyed by their id
+		let key = match edge.pick(&*IN) {
+			v @ Value::Object(_) => v.rid().as_raw_string(),
+			v => v.as_raw_string(),
+		};
+		out.entry(key).or_default().push(edge);
+	}
+	Value::from(out.into_iter().map(|(k, v)| (k, Value::from(v))).collect::<BTreeMap<_, _>>())
+}
+
+/// Returns the records both as an ordered array and as an object keyed by their id
+pub(crate) fn with_by_id(res: Value) -> Result<Value, RpcError> {
+	let records = match res {
+		Value::Array(v) => v.0,
+		Value::None => vec![],
+		v => vec![v],
+	};
+	let mut by_id = BTreeMap::new();
+	for (i, record) in records.iter().enumerate() {
+		match record.rid() {
+			Value::Thing(id) => by_id.insert(id.to_raw(), record.clone()),
+			_ => return Err(RpcError::MissingRecordId(i)),
+		};
+	}
+	Ok(Value::from(map! {
+		"array".to_string() => Value::from(records),
+		"by_id".to_string() => Value::from(by_id),
+	}))
+}
+
+/// Computes a checksum of the content of each record, keyed by the record id
+///
+/// The record id is left out of the content, which is hashed in its canonical
+/// form, so that the same content always produces the same checksum.
+pub(crate) fn checksums(res: &Value) -> Result<Value, RpcError> {
+	let records = match res {
+		Value::Array(v) => v.iter().collect(),
+		Value::None => vec![],
+		v => vec![v],
+	};
+	let mut out = BTreeMap::new();
+	for (i, record) in records.into_iter().enumerate() {
+		let Value::Thing(id) = record.rid() else {
+			return Err(RpcError::MissingRecordId(i));
+		};
+		let mut content = record.clone();
+		content.cut(&[Part::from("id")]);
+		let hash = blake3::hash(content.to_string().as_bytes());
+		out.insert(id.to_raw(), Value::from(hash.to_string()));
+	}
+	Ok(Value::from(out))
+}
+
+/// Attaches the checksum of each record, alongside any types of the fields
+pub(crate) fn with_checksums(res: Value, wrapped: bool, checksums: Value) -> Value {
+	match (wrapped, res) {
+		(true, Value::Object(mut v)) => {
+			v.insert("checksums".to_string(), checksums);
+			Value::Object(v)
+		}
+		(_, res) => Value::from(map! {
+			"result".to_string() => res,
+			"checksums".to_string() => checksums,
+		}),
+	}
+}
+
+/// Partitions the records into lists keyed by the value of a field
+pub(crate) fn with_partitions(res: Value, field: &Idiom) -> Value {
+	let records = match res {
+		Value::Array(v) => v.0,
+		Value::None => vec![],
+		v => vec![v],
+	};
+	let mut out: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+	for record in records {
+		// Records without a value for the field share a bucket
+		let key = match record.pick(field) {
+			Value::None | Value::Null => "null".to_string(),
+			v => v.as_raw_string(),
+		};
+		out.entry(key).or_default().push(record);
+	}
+	Value::from(out.into_iter().map(|(k, v)| (k, Value::from(v))).collect::<BTreeMap<_, _>>())
+}
+
+/// Flattens the records before and after a write into an entry for each changed field
+pub(crate) fn with_changeset(res: Value) -> Value {
+	let records = match res {
+		Value::Array(v) => v.0,
+		Value::None => vec![],
+		v => vec![v],
+	};
+	let value = |v: Value| match v {
+		Value::None => Value::Null,
+		v => v,
+	};
+	let mut changes = Vec::new();
+	for record in records {
+		let id = record.pick(&[Part::from("id")]);
+		let before = record.pick(&[Part::from("before")]);
+		let after = record.pick(&[Part::from("after")]);
+		for op in before.diff(&after, Idiom::default()) {
+			let path = match op {
+				Operation::Add {
+					path,
+					..
+				}
+				| Operation::Remove {
+					path,
+				}
+				| Operation::Replace {
+					path,
+					..
+				}
+				| Operation::Change {
+					path,
+					..
+				} => path,
+				_ => continue,
+			};
+			changes.push(Value::from(map! {
+				"id".to_string() => id.clone(),
+				"field".to_string() => Value::from(path.to_string()),
+				"old".to_string() => value(before.pick(&path)),
+				"new".to_string() => value(after.pick(&path)),
+			}));
+		}
+	}
+	Value::from(changes)
+}
+
+/// Removes the uncomputed FUTURE values from the returned records
+pub(crate) fn without_futures(res: Value) -> Value {
+	match res {
+		Value::Array(v) => Value::from(v.into_iter().map(without_futures).collect::<Vec<_>>()),
+		Value::Object(mut v) => {
+			v.retain(|_, v| !v.is_future());
+			v.values_mut().for_each(|v| *v = without_futures(std::mem::take(v)));
+			Value::Object(v)
+		}
+		v => v,
+	}
+}
+
+/// Removes the fields which are absent from the returned records, keeping any fields set to null
+pub(crate) fn without_absent(res: Value) -> Value {
+	match res {
+		Value::Array(v) => Value::from(v.into_iter().map(without_absent).collect::<Vec<_>>()),
+		Value::Object(mut v) => {
+			v.retain(|_, v| !v.is_none());
+			v.values_mut().for_each(|v| *v = without_absent(std::mem::take(v)));
+			Value::Object(v)
+		}
+		v => v,
+	}
+}
+
+/// Converts the fields of the order option into an ordering
+pub(crate) fn ordering(v: Value, capabilities: &Capabilities) -> Result<Ordering, RpcError> {
+	let Value::Array(v) = v else {
+		return Err(RpcError::InvalidParams);
+	};
+	let mut orders = Vec::with_capacity(v.len());
+	for v in v {
+		let mut order = Order {
+			direction: true,
+			..Default::default()
+		};
+		match v {
+			Value::Strand(v) => order.value = idiom_with_capabilities(v.as_str(), capabilities)?,
+			Value::Object(mut v) => {
+				order.value = match v.remove("field") {
+					Some(Value::Strand(v)) => idiom_with_capabilities(v.as_str(), capabilities)?,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				order.direction = match v.remove("direction") {
+					Some(Value::Strand(v)) if v.as_str() == "asc" => true,
+					Some(Value::Strand(v)) if v.as_str() == "desc" => false,
+					None => true,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				order.collate = match v.remove("collate") {
+					Some(Value::Bool(v)) => v,
+					None => false,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				order.numeric = match v.remove("numeric") {
+					Some(Value::Bool(v)) => v,
+					None => false,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				order.nulls_first = match v.remove("nulls") {
+					Some(Value::Strand(v)) if v.as_str() == "first" => Some(true),
+					Some(Value::Strand(v)) if v.as_str() == "last" => Some(false),
+					None => None,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				// Reject anything which is not understood
+				if !v.is_empty() {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+			_ => return Err(RpcError::InvalidParams),
+		}
+		orders.push(order);
+	}
+	if orders.is_empty() {
+		return Err(RpcError::InvalidParams);
+	}
+	Ok(Ordering::Order(OrderList(orders)))
+}
+
+/// Converts a debounce or throttle option into the period over which notifications are coalesced
+fn live_window(v: Value) -> Result<time::Duration, RpcError> {
+	let window = match v {
+		Value::Duration(v) => v.0,
+		Value::Strand(v) => Duration::try_from(v.as_str()).map_err(|_| RpcError::InvalidParams)?.0,
+		_ => return Err(RpcError::InvalidParams),
+	};
+	match window.is_zero() || window > MAX_LIVE_DEBOUNCE {
+		true => Err(RpcError::InvalidParams),
+		false => Ok(window),
+	}
+}
 
 #[derive(Clone, Debug)]
 pub(crate) enum RpcData {
@@ -61,10 +433,102 @@ pub(crate) struct StatementOptions {
 	/// - One of: `"content"`, `"replace"`, `"merge"`, `"patch"` or `"single"`.
 	/// - For the `insert`, `create`, `upsert`, `update` and `relate` methods
 	pub data: Option<RpcData>,
+	/// - A boolean, stating whether merged fields are only written where they are currently null or absent
+	/// - Requires the `"merge"` data mode, with an object of fields to merge
+	/// - For the `upsert` and `update` methods
+	pub only_null: bool,
+	/// - A string, which when set to `"from"` groups the created edges by their source record
+	/// - Returns an object of adjacency lists instead of an array of edges
+	/// - For the `relate` and `bulk_relate` methods
+	pub group_by_from: bool,
 	/// - A string, containing fields to select. Also works with the `VALUE` keyword.
 	/// - For the `select` method
 	pub fields: Option<Fields>,
+	/// - An array of strings, containing fields which are returned as rows of values in this order
+	/// - Fields which are not present in a record are returned as `null`
+	/// - For the `select` method
+	pub columns: Option<Vec<Idiom>>,
+	/// - An object, mapping the fields to select to the names they are returned as
+	/// - Fields which are not otherwise selected are added to the selection under their new names
+	/// - Each field must be returned under a different name
+	/// - For the `select` method
+	pub alias: Option<Vec<(Idiom, Idiom)>>,
+	/// - A string or an array of strings, containing fields to omit from the selection
+	/// - Fields are omitted after the projection of the `fields` option
+	/// - For the `select` method
+	pub omit: Option<Idioms>,
+	/// - A boolean, stating whether the `id` field should be stripped from each returned record
+	/// - This is lossy, as the returned records can no longer be told apart or referenced
+	/// - For the `select` method
+	pub compact: bool,
+	/// - A boolean, stating whether nested arrays in the result should be flattened
+	/// - Only one level is flattened, so arrays nested more deeply are returned unchanged
+	/// - For the `select` method
+	pub flatten: bool,
+	/// - A boolean, stating whether the total number of matching records should be returned
+	/// - The count ignores the `start` and `limit` options, and is taken within the same transaction
+	/// - The result is returned as an object containing the `total` and the `results`
+	/// - For the `select` method
+	pub with_total: bool,
+	/// - A boolean, stating whether the results should be returned within a pagination envelope
+	/// - The page is determined by the `start` and `limit` options, so a `limit` is required
+	/// - The result is returned as an object containing the `results`, `page`, `page_size`,
+	///   `total`, `total_pages`, `has_next`, and `has_prev`, all taken within the same transaction
+	/// - For the `select` method
+	pub paginate: bool,
+	/// - A boolean, stating whether the records should also be returned keyed by their id
+	/// - The result is returned as an object containing the ordered `array` and the `by_id` object
+	/// - Every returned record must have an id, so this can not be combined with `compact`
+	/// - For the `select` method
+	pub by_id: bool,
+	/// - A string, containing a field by whose value the selected records are partitioned
+	/// - The result is returned as an object, keyed by each value of the field, containing the records with that value in their selected order
+	/// - Records where the field is null or missing are returned in the `null` bucket
+	/// - Unlike `GROUP BY`, the records are returned whole rather than aggregated
+	/// - For the `select` method
+	pub partition_by: Option<Idiom>,
+	/// - A boolean, stating whether fields defined with a FUTURE value should be left out
+	/// - The FUTURE values are not computed, avoiding their cost when they are not needed
+	/// - For the `select` method
+	pub skip_computed: bool,
+	/// - One of: `"error"`, `"null"` or `"string"`, stating how non-finite floats are returned
+	/// - With `"string"`, they are returned as `"NaN"`, `"Infinity"` or `"-Infinity"`
+	/// - For the `select` method
+	pub non_finite: Option<NonFinite>,
+	/// - One of: `"string"` or `"object"`, stating how record ids are returned
+	/// - With `"object"`, they are returned as an object with `tb` and `id` fields
+	/// - For the `select` method
+	pub record_ids: Option<RecordIds>,
+	/// - A boolean, stating whether fields set to null should be told apart from absent fields
+	/// - Absent fields are omitted from the returned records, while fields set to null are returned as `null`
+	/// - Fields returned by the `columns` option are still returned as `null` when absent
+	/// - For the `select` method
+	pub explicit_nulls: bool,
+	/// - A boolean, stating whether the type of each top-level field should be returned alongside the records
+	/// - Declared field types are taken from the schema of the selected table, and inferred from the values otherwise
+	/// - The result is returned as an object containing the `result` and the `types`
+	/// - For the `select` method
+	pub with_types: bool,
+	/// - A boolean, stating whether a checksum of the content of each record should be returned alongside the records
+	/// - The checksum is a hash of the record without its id, as returned before any other options are applied
+	/// - The result is returned as an object containing the `result` and the `checksums`, keyed by record id
+	/// - Every returned record must have an id, so this can not be combined with `compact`
+	/// - For the `select` method
+	pub with_checksum: bool,
+	/// - A number, containing a versionstamp returned by the `versionstamp` write option
+	/// - The read waits briefly until the database reflects this versionstamp, failing with an error otherwise
+	/// - For the `select` method
+	pub min_version: Option<u64>,
+	/// - A string, containing a field for which the distinct values should be returned
+	/// - For the `select` method
+	pub distinct: Option<Idiom>,
+	/// - An array of strings or objects, stating the fields by which the selected records are ordered
+	/// - Each object contains a `field`, and optionally a `direction` of `"asc"` or `"desc"`, `collate` and `numeric` booleans, and `nulls` of `"first"` or `"last"`
+	/// - Without `nulls`, null and absent values are ordered as the smallest values, as in SurrealQL, so they are placed first when ascending and last when descending
+	/// - For the `select` method
+	pub order: Option<Ordering>,
 	/// - One of: `"none"`, `"null"`, `"diff"`, `"before"`, `"after"` or a list of fields
+	/// - Or a single field with the `return_value` option, which is returned as a bare value
 	/// - For the `insert`, `create`, `upsert`, `update`, `relate` and `delete` methods
 	pub output: Option<Output>,
 	/// - A number, stating how many records can be selected or affected
@@ -74,7 +538,7 @@ pub(crate) struct StatementOptions {
 	/// - For the `select` method
 	pub start: Option<Start>,
 	/// - A string, containing an expression for a `WHERE` clause
-	/// - For the `select`, `upsert`, `update` and `delete` methods
+	/// - For the `select`, `count`, `upsert`, `update` and `delete` methods
 	pub cond: Option<Cond>,
 	/// - A boolean, stating where we want to select or affect only a single record.
 	/// - For the `select`, `create`, `upsert`, `update`, `relate` and `delete` methods
@@ -85,6 +549,11 @@ pub(crate) struct StatementOptions {
 	/// - A boolean, stating wether the relation we are inserting needs to be unique
 	/// - For the `relate` method
 	pub unique: bool,
+	/// - A boolean, stating whether an existing edge between the same records should be updated
+	/// - The data is merged into the existing edge, or a new edge is created, in one transaction
+	/// - Returns an object with a `status` of `"created"` or `"updated"`, and the resulting edge
+	/// - For the `relate` method
+	pub upsert: bool,
 	/// - Can contain either:
 	///    - A datetime
 	///    - A string, containing an expression which computes into a datetime
@@ -100,8 +569,88 @@ pub(crate) struct StatementOptions {
 	/// - For the `live` method
 	pub diff: bool,
 	/// - A string, containing fields to fetch.
-	/// - For the `select` and `live` methods
+	/// - For the `select`, `live` and `relate` methods
 	pub fetch: Option<Fetchs>,
+	/// - A string, containing a client-chosen token which is echoed in the LQ notifications
+	/// - Tokens are scoped to the connection on which the live query was started
+	/// - For the `live` method
+	pub token: Option<String>,
+	/// - A number (versionstamp) or a datetime, from which the changes of the table are replayed
+	/// - The changes are returned alongside the live query id, up to a server-configured maximum
+	/// - The `versionstamp` of a replayed change can be used as this option to resume a replay
+	/// - Requires the table to be defined with a changefeed
+	/// - For the `live` method
+	pub since: Option<ShowSince>,
+	/// - A duration, stating how long the notifications are held until no further changes are made
+	/// - Only the latest notification for each record is sent, with the patches merged in diff mode
+	/// - For the `live` method
+	pub debounce: Option<time::Duration>,
+	/// - A duration, stating how long the notifications are held after the first change is made
+	/// - Only the latest notification for each record is sent, with the patches merged in diff mode
+	/// - For the `live` method
+	pub throttle: Option<time::Duration>,
+	/// - An object, containing the number of `attempts` and the `backoff` duration
+	/// - Retries the method when it fails due to a transient transaction conflict
+	/// - For the `insert`, `create`, `upsert`, `update`, `relate` and `delete` methods
+	pub retry: Option<Retry>,
+	/// - A boolean, stating whether the generated SurrealQL should be returned alongside the result
+	/// - For the `select`, `insert`, `create`, `upsert`, `update`, `relate` and `delete` methods
+	pub echo_sql: bool,
+	/// - A boolean, stating whether a versionstamp which follows the write should be returned alongside the result
+	/// - The versionstamp is an upper bound, allocated after the write has committed, and not the
+	///   exact versionstamp at which the write committed, so reads at or after it observe the write
+	/// - Allocating the versionstamp requires an additional write to the datastore
+	/// - For the `insert`, `create`, `upsert`, `update`, `relate` and `delete` methods
+	pub versionstamp: bool,
+	/// - One of: `"one"`, `"at_least_one"` or `"many"`, stating how many results are expected
+	/// - The method fails with an error if the number of results does not meet the expectation
+	/// - For the `select` method
+	pub expect: Option<Expect>,
+	/// - A number, stating the maximum number of results which can be returned
+	/// - The method is aborted with an error if the result exceeds this number
+	/// - For the `select` method
+	pub max_results: Option<usize>,
+	/// - An array of strings, containing sensitive fields which should not be masked
+	/// - Requires permission to view the field definitions of the database
+	/// - Sensitive fields are masked before any fields or conditions are computed, and are always masked by every other method
+	/// - For the `select` method
+	pub reveal: Vec<Idiom>,
+	/// - A boolean, stating whether existing records should be returned untouched instead of failing
+	/// - For the `insert` and `create` methods
+	pub get_existing: bool,
+	/// - A boolean, stating whether the fields which were defaulted should be returned alongside the result
+	/// - Fields in the created record which were not in the input data are reported as defaulted
+	/// - For the `create` method
+	pub defaults: bool,
+	/// - A boolean, stating whether each row should be inserted separately, reporting the outcome of each
+	/// - Returns an object for each row, with a `status` of `"inserted"`, `"conflict"` or `"error"`,
+	///   and a `result` containing the inserted record or the error message
+	/// - Rows which fail do not prevent the other rows from being inserted
+	/// - For the `insert` method
+	pub row_status: bool,
+	/// - A boolean, or an object containing a `prefix` and a `suffix` string, stating how matches are highlighted
+	/// - Matches are highlighted with `<b>` and `</b>` by default
+	/// - For the `search` method
+	pub highlight: Option<(String, String)>,
+	/// - A boolean, stating whether the relevance score of each record should be returned
+	/// - Records are ordered by their relevance score when it is returned
+	/// - For the `search` method
+	pub score: bool,
+	/// - An object, containing the field values which the record must still have, such as a version field
+	/// - The method fails with a conflict error if the record has changed since it was read
+	/// - For the `delete` method
+	pub if_match: Option<Object>,
+	/// - A boolean, stating whether a flat list of the changed fields should be returned instead of the records
+	/// - Returns an object with the `id`, `field`, `old` and `new` values for each field which changed in each record
+	/// - For the `update` method
+	pub changeset: bool,
+	/// - A boolean, stating whether a condition which can not use an index may scan a large table
+	/// - For the `count` method
+	pub allow_scan: bool,
+	/// - A number, stating the maximum length of the array after the value is appended
+	/// - The oldest elements are trimmed from the start of the array to fit
+	/// - For the `append` method
+	pub max_length: Option<usize>,
 }
 
 impl StatementOptions {
@@ -110,11 +659,26 @@ impl StatementOptions {
 		self
 	}
 
+	pub(crate) fn with_data_single(&mut self, v: Value) -> &mut Self {
+		self.data = Some(RpcData::Single(v));
+		self
+	}
+
 	pub(crate) fn with_output(&mut self, output: Output) -> &mut Self {
 		self.output = Some(output);
 		self
 	}
 
+	pub(crate) fn with_highlight(&mut self) -> &mut Self {
+		self.highlight = Some((HIGHLIGHT_PREFIX.to_string(), HIGHLIGHT_SUFFIX.to_string()));
+		self
+	}
+
+	pub(crate) fn with_score(&mut self) -> &mut Self {
+		self.score = true;
+		self
+	}
+
 	pub(crate) fn process_options(
 		&mut self,
 		opts: Value,
@@ -125,14 +689,24 @@ impl StatementOptions {
 			if let Some(data) = &self.data {
 				if let Some(v) = obj.remove("data_expr") {
 					if let Value::Strand(v) = v {
-						self.data =
-							Some(RpcData::from_string(v.to_string(), data.value().to_owned())?);
+						self.data = Some(RpcData::from_string(v.0, data.value().to_owned())?);
 					} else {
 						return Err(RpcError::InvalidParams);
 					}
 				}
 			}
 
+			// Process "only_null" option
+			if let Some(v) = obj.remove("only_null") {
+				match (v, &self.data) {
+					(Value::Bool(false), _) => self.only_null = false,
+					(Value::Bool(true), Some(RpcData::Merge(Value::Object(_)))) => {
+						self.only_null = true
+					}
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
 			// Process "fields" option
 			if let Some(v) = obj.remove("fields") {
 				if let Value::Strand(v) = v {
@@ -142,6 +716,229 @@ impl StatementOptions {
 				}
 			}
 
+			// Process "omit" option
+			if let Some(v) = obj.remove("omit") {
+				let v = match v {
+					Value::Strand(v) => vec![Value::Strand(v)],
+					Value::Array(v) => v.0,
+					_ => return Err(RpcError::InvalidParams),
+				};
+				let mut omit = Vec::with_capacity(v.len());
+				for v in v {
+					let Value::Strand(v) = v else {
+						return Err(RpcError::InvalidParams);
+					};
+					omit.push(
+						idiom_with_capabilities(v.as_str(), capabilities)
+							.map_err(|_| RpcError::InvalidParams)?,
+					);
+				}
+				self.omit = Some(Idioms(omit));
+			}
+
+			// Process "alias" option
+			if let Some(v) = obj.remove("alias") {
+				let Value::Object(v) = v else {
+					return Err(RpcError::InvalidParams);
+				};
+				let mut alias = Vec::with_capacity(v.len());
+				for (source, name) in v.0 {
+					let Value::Strand(name) = name else {
+						return Err(RpcError::InvalidParams);
+					};
+					let source = idiom_with_capabilities(&source, capabilities)
+						.map_err(|_| RpcError::InvalidParams)?;
+					let name = idiom_with_capabilities(name.as_str(), capabilities)
+						.map_err(|_| RpcError::InvalidParams)?;
+					// Two fields can not be returned under the same name
+					if alias.iter().any(|(_, v)| *v == name) {
+						return Err(RpcError::InvalidParams);
+					}
+					alias.push((source, name));
+				}
+				self.alias = Some(alias);
+			}
+
+			// Process "compact" option
+			if let Some(v) = obj.remove("compact") {
+				if let Value::Bool(v) = v {
+					self.compact = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "flatten" option
+			if let Some(v) = obj.remove("flatten") {
+				if let Value::Bool(v) = v {
+					self.flatten = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "with_total" option
+			if let Some(v) = obj.remove("with_total") {
+				if let Value::Bool(v) = v {
+					self.with_total = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "paginate" option
+			if let Some(v) = obj.remove("paginate") {
+				if let Value::Bool(v) = v {
+					self.paginate = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "by_id" option
+			if let Some(v) = obj.remove("by_id") {
+				if let Value::Bool(v) = v {
+					self.by_id = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "partition_by" option
+			if let Some(v) = obj.remove("partition_by") {
+				if let Value::Strand(v) = v {
+					self.partition_by = Some(idiom_with_capabilities(v.as_str(), capabilities)?)
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "skip_computed" option
+			if let Some(v) = obj.remove("skip_computed") {
+				if let Value::Bool(v) = v {
+					self.skip_computed = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "expect" option
+			if let Some(v) = obj.remove("expect") {
+				self.expect = Some(Expect::process_options(v)?);
+			}
+
+			// Process "non_finite" option
+			if let Some(v) = obj.remove("non_finite") {
+				self.non_finite = Some(NonFinite::process_options(v)?);
+			}
+
+			// Process "record_ids" option
+			if let Some(v) = obj.remove("record_ids") {
+				self.record_ids = Some(RecordIds::process_options(v)?);
+			}
+
+			// Process "explicit_nulls" option
+			if let Some(v) = obj.remove("explicit_nulls") {
+				if let Value::Bool(v) = v {
+					self.explicit_nulls = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "with_types" option
+			if let Some(v) = obj.remove("with_types") {
+				if let Value::Bool(v) = v {
+					self.with_types = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "with_checksum" option
+			if let Some(v) = obj.remove("with_checksum") {
+				if let Value::Bool(v) = v {
+					self.with_checksum = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "min_version" option
+			if let Some(v) = obj.remove("min_version") {
+				match v {
+					Value::Number(Number::Int(v)) if v >= 0 => self.min_version = Some(v as u64),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "distinct" option
+			if let Some(v) = obj.remove("distinct") {
+				if let Value::Strand(v) = v {
+					self.distinct = Some(idiom_with_capabilities(v.as_str(), capabilities)?)
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "order" option
+			if let Some(v) = obj.remove("order") {
+				self.order = Some(ordering(v, capabilities)?);
+			}
+
+			// Process "reveal" option
+			if let Some(v) = obj.remove("reveal") {
+				if let Value::Array(v) = v {
+					for v in v {
+						if let Value::Strand(v) = v {
+							self.reveal.push(idiom_with_capabilities(v.as_str(), capabilities)?)
+						} else {
+							return Err(RpcError::InvalidParams);
+						}
+					}
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "columns" option
+			if let Some(v) = obj.remove("columns") {
+				if self.fields.is_some() || self.distinct.is_some() {
+					// columns cannot co-exist with fields or distinct, as columns overwrites the fields
+					return Err(RpcError::InvalidParams);
+				}
+
+				let Value::Array(v) = v else {
+					return Err(RpcError::InvalidParams);
+				};
+				let mut columns = Vec::with_capacity(v.len());
+				for v in v {
+					if let Value::Strand(v) = v {
+						columns.push(idiom_with_capabilities(v.as_str(), capabilities)?)
+					} else {
+						return Err(RpcError::InvalidParams);
+					}
+				}
+				self.columns = Some(columns);
+			}
+
+			// Process "return_value" option
+			if let Some(v) = obj.remove("return_value") {
+				// A value can not be returned alongside another output
+				if obj.contains_key("return") {
+					return Err(RpcError::InvalidParams);
+				}
+				if let Value::Strand(v) = v {
+					let field = Field::Single {
+						expr: Value::Idiom(idiom_with_capabilities(v.as_str(), capabilities)?),
+						alias: None,
+					};
+					self.output = Some(Output::Fields(Fields(vec![field], true)));
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
 			// Process "return" option
 			if let Some(v) = obj.remove("return") {
 				if let Value::Strand(v) = v {
@@ -151,6 +948,50 @@ impl StatementOptions {
 				}
 			}
 
+			// Process "group_by" option
+			if let Some(v) = obj.remove("group_by") {
+				match (v, &self.output) {
+					// The edges can only be grouped when they are returned
+					(_, Some(Output::None | Output::Null | Output::Diff)) => {
+						return Err(RpcError::InvalidParams)
+					}
+					// The edges can not be grouped when bare values are returned
+					(_, Some(Output::Fields(Fields(_, true)))) => {
+						return Err(RpcError::InvalidParams)
+					}
+					(Value::Strand(v), _) if v.as_str() == "from" => self.group_by_from = true,
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "highlight" option
+			if let Some(v) = obj.remove("highlight") {
+				match v {
+					Value::Bool(true) => {
+						self.with_highlight();
+					}
+					Value::Bool(false) => self.highlight = None,
+					Value::Object(mut v) => match (v.remove("prefix"), v.remove("suffix")) {
+						(Some(Value::Strand(prefix)), Some(Value::Strand(suffix)))
+							if v.is_empty() =>
+						{
+							self.highlight = Some((prefix.0, suffix.0))
+						}
+						_ => return Err(RpcError::InvalidParams),
+					},
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "score" option
+			if let Some(v) = obj.remove("score") {
+				if let Value::Bool(v) = v {
+					self.score = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
 			// Process "limit" option
 			if let Some(v) = obj.remove("limit") {
 				if let Value::Number(Number::Int(_)) = v {
@@ -160,6 +1001,14 @@ impl StatementOptions {
 				}
 			}
 
+			// Process "max_results" option
+			if let Some(v) = obj.remove("max_results") {
+				match v {
+					Value::Number(Number::Int(v)) if v >= 0 => self.max_results = Some(v as usize),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
 			// Process "start" option
 			if let Some(v) = obj.remove("start") {
 				if let Value::Number(Number::Int(_)) = v {
@@ -178,6 +1027,38 @@ impl StatementOptions {
 				}
 			}
 
+			// Process "row_status" option
+			if let Some(v) = obj.remove("row_status") {
+				if let Value::Bool(v) = v {
+					self.row_status = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "get_existing" option
+			if let Some(v) = obj.remove("get_existing") {
+				if !matches!(self.output, None | Some(Output::After)) {
+					// get_existing and return cannot co-exist, as existing records are returned in full
+					return Err(RpcError::InvalidParams);
+				}
+
+				if let Value::Bool(v) = v {
+					self.get_existing = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "defaults" option
+			if let Some(v) = obj.remove("defaults") {
+				if let Value::Bool(v) = v {
+					self.defaults = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
 			// Process "version" option
 			if let Some(v) = obj.remove("version") {
 				let v = match v {
@@ -227,10 +1108,19 @@ impl StatementOptions {
 				}
 			}
 
+			// Process "upsert" option
+			if let Some(v) = obj.remove("upsert") {
+				if let Value::Bool(v) = v {
+					self.upsert = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
 			// Process "vars" option
 			if let Some(v) = obj.remove("vars") {
 				if let Value::Object(v) = v {
-					self.vars = Some(v.0)
+					self.vars = Some(typed::process_vars(v.0)?)
 				} else {
 					return Err(RpcError::InvalidParams);
 				}
@@ -259,6 +1149,93 @@ impl StatementOptions {
 				}
 			}
 
+			// Process "token" option
+			if let Some(v) = obj.remove("token") {
+				if let Value::Strand(v) = v {
+					self.token = Some(v.0);
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "since" option
+			if let Some(v) = obj.remove("since") {
+				self.since = match v {
+					Value::Datetime(v) => Some(ShowSince::Timestamp(v)),
+					Value::Number(Number::Int(v)) if v >= 0 => {
+						Some(ShowSince::Versionstamp(v as u64))
+					}
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "debounce" option
+			if let Some(v) = obj.remove("debounce") {
+				self.debounce = Some(live_window(v)?);
+			}
+
+			// Process "throttle" option
+			if let Some(v) = obj.remove("throttle") {
+				self.throttle = Some(live_window(v)?);
+			}
+
+			// Process "echo_sql" option
+			if let Some(v) = obj.remove("echo_sql") {
+				if let Value::Bool(v) = v {
+					self.echo_sql = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "versionstamp" option
+			if let Some(v) = obj.remove("versionstamp") {
+				if let Value::Bool(v) = v {
+					self.versionstamp = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "if_match" option
+			if let Some(v) = obj.remove("if_match") {
+				match v {
+					Value::Object(v) if !v.is_empty() => self.if_match = Some(v),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "changeset" option
+			if let Some(v) = obj.remove("changeset") {
+				if let Value::Bool(v) = v {
+					self.changeset = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "allow_scan" option
+			if let Some(v) = obj.remove("allow_scan") {
+				if let Value::Bool(v) = v {
+					self.allow_scan = v;
+				} else {
+					return Err(RpcError::InvalidParams);
+				}
+			}
+
+			// Process "max_length" option
+			if let Some(v) = obj.remove("max_length") {
+				match v {
+					Value::Number(Number::Int(v)) if v > 0 => self.max_length = Some(v as usize),
+					_ => return Err(RpcError::InvalidParams),
+				}
+			}
+
+			// Process "retry" option
+			if let Some(v) = obj.remove("retry") {
+				self.retry = Some(Retry::process_options(v)?);
+			}
+
 			Ok(self)
 		} else {
 			Err(RpcError::InvalidParams)
@@ -266,7 +1243,15 @@ impl StatementOptions {
 	}
 
 	pub(crate) fn data_expr(&self) -> Option<Data> {
-		self.data.clone().map(|v| v.into())
+		match (&self.data, self.only_null) {
+			// Only write the merged fields which are currently null or absent
+			(Some(RpcData::Merge(Value::Object(v))), true) if !v.is_empty() => {
+				let mut exprs = Vec::new();
+				only_null_assignments(&mut exprs, &[], v);
+				Some(Data::SetExpression(exprs))
+			}
+			_ => self.data.clone().map(|v| v.into()),
+		}
 	}
 
 	pub(crate) fn merge_vars(&self, v: &BTreeMap<String, Value>) -> BTreeMap<String, Value> {
@@ -276,3 +1261,24 @@ impl StatementOptions {
 		}
 	}
 }
+
+/// Converts the fields of a merge into assignments which only write null or absent fields
+fn only_null_assignments(out: &mut Vec<(Idiom, Operator, Value)>, path: &[Part], obj: &Object) {
+	for (k, v) in obj.iter() {
+		let path = [path, &[Part::from(k.as_str())]].concat();
+		match v {
+			// Descend into nested objects, as a merge does
+			Value::Object(v) if !v.is_empty() => only_null_assignments(out, &path, v),
+			// Assign the field only if it is currently null or absent
+			v => out.push((
+				Idiom(path.clone()),
+				Operator::Equal,
+				Value::from(Expression::Binary {
+					l: Value::Idiom(Idiom(path)),
+					o: Operator::Nco,
+					r: v.clone(),
+				}),
+			)),
+		}
+	}
+}
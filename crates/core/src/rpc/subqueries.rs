@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+	cnf::PROTECTED_PARAM_NAMES,
+	dbs::Capabilities,
+	sql::{statements::SetStatement, Query, Statement},
+	syn::value_with_capabilities,
+};
+
+use super::RpcError;
+
+/// The params which are bound by the database while a statement is computed
+const RUNTIME_PARAMS: &[&str] = &[
+	"this",
+	"self",
+	"parent",
+	"value",
+	"before",
+	"after",
+	"event",
+	"input",
+	"reference",
+	"action",
+	"request",
+	"access",
+	"auth",
+	"session",
+	"token",
+];
+
+/// Checks that the name of a named subquery can be referenced as a param
+pub(crate) fn valid_name(name: &str) -> bool {
+	!name.is_empty()
+		&& name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+		&& !PROTECTED_PARAM_NAMES.contains(&name)
+}
+
+/// Converts the named subqueries into `LET` statements, ordered so that each
+/// one is bound before any of the other named subqueries which reference it
+pub(crate) fn statements(
+	with: &BTreeMap<String, String>,
+	capabilities: &Capabilities,
+) -> Result<Vec<Statement>, RpcError> {
+	// Parse each of the named subqueries
+	let mut pending = BTreeMap::new();
+	for (name, sql) in with {
+		let what = value_with_capabilities(&format!("({sql})"), capabilities)?;
+		let refs: BTreeSet<String> =
+			unbound_params(&what)?.into_iter().filter(|v| with.contains_key(v)).collect();
+		pending.insert(name.clone(), (what, refs));
+	}
+	// Bind the named subqueries once their references are bound
+	let mut out = Vec::with_capacity(pending.len());
+	let mut bound = BTreeSet::new();
+	while !pending.is_empty() {
+		let ready =
+			pending.iter().find(|(_, (_, refs))| refs.is_subset(&bound)).map(|(k, _)| k.clone());
+		let Some((name, (what, _))) = ready.and_then(|k| pending.remove_entry(&k)) else {
+			// The remaining named subqueries reference each other
+			let name = pending.into_keys().next().unwrap_or_default();
+			return Err(RpcError::CyclicSubquery(name));
+		};
+		out.push(Statement::Set(SetStatement {
+			name: name.clone(),
+			what,
+			kind: None,
+		}));
+		bound.insert(name);
+	}
+	Ok(out)
+}
+
+/// Returns the first param which is referenced by the query without being defined
+pub(crate) fn undefined_param(
+	query: &Query,
+	defined: &BTreeSet<&str>,
+) -> Result<Option<String>, RpcError> {
+	Ok(unbound_params(query)?
+		.into_iter()
+		.find(|v| !RUNTIME_PARAMS.contains(&v.as_str()) && !defined.contains(v.as_str())))
+}
+
+/// Returns the params which are referenced, but not bound by a `LET`
+/// statement, a `FOR` loop, or the arguments of a function or closure
+fn unbound_params(v: &impl serde::Serialize) -> Result<BTreeSet<String>, RpcError> {
+	let v = serde_json::to_value(v).map_err(|e| RpcError::Thrown(e.to_string()))?;
+	let mut refs = BTreeSet::new();
+	let mut binds = BTreeSet::new();
+	collect_params(&v, &mut refs, &mut binds);
+	Ok(refs.difference(&binds).cloned().collect())
+}
+
+/// Walks a serialized statement, collecting the referenced and bound params
+fn collect_params(
+	v: &serde_json::Value,
+	refs: &mut BTreeSet<String>,
+	binds: &mut BTreeSet<String>,
+) {
+	use serde_json::Value as Json;
+	match v {
+		Json::Object(obj) => {
+			for (k, v) in obj {
+				match (k.as_str(), v) {
+					// A param which is referenced
+					("Param", Json::String(name)) => {
+						refs.insert(name.clone());
+					}
+					// A param which is bound by a LET statement
+					("Set", Json::Object(v)) => {
+						if let Some(Json::String(name)) = v.get("name") {
+							binds.insert(name.clone());
+						}
+					}
+					// A param which is bound by a FOR loop
+					("Foreach", Json::Object(v)) => {
+						if let Some(Json::String(name)) = v.get("param") {
+							binds.insert(name.clone());
+						}
+					}
+					// The params which are bound as function or closure arguments
+					("args", Json::Array(args)) => {
+						for arg in args {
+							if let Some(Json::String(name)) = arg.get(0) {
+								binds.insert(name.clone());
+							}
+						}
+					}
+					_ => (),
+				}
+				collect_params(v, refs, binds);
+			}
+		}
+		Json::Array(v) => v.iter().for_each(|v| collect_params(v, refs, binds)),
+		_ => (),
+	}
+}
@@ -0,0 +1,48 @@
+use crate::sql::{Array, Value};
+
+use super::{Method, RpcError};
+
+/// The maximum length of a tag attached to an RPC request
+const MAX_TAG_LENGTH: usize = 64;
+
+/// Parses the tag which is attached to a request for logging and tracing
+///
+/// The tag is specified with the `tag` option of the `query`, `select`, `insert`,
+/// `create`, `upsert`, `update`, `relate` and `delete` methods.
+pub(crate) fn request_tag(method: Method, params: &Array) -> Result<Option<String>, RpcError> {
+	// Find the position of the options argument of the method
+	let position = match method {
+		Method::Select | Method::Delete => 1,
+		Method::Query | Method::Insert | Method::Create | Method::Upsert | Method::Update => 2,
+		Method::Relate => 4,
+		_ => return Ok(None),
+	};
+	// Fetch the tag from the options argument
+	let Some(Value::Object(opts)) = params.get(position) else {
+		return Ok(None);
+	};
+	match opts.get("tag") {
+		Some(Value::Strand(v)) => Ok(sanitize(v.as_str())),
+		Some(_) => Err(RpcError::InvalidParams),
+		None => Ok(None),
+	}
+}
+
+/// Attaches a request tag to the current tracing span
+pub(crate) fn record_tag(tag: &str) {
+	tracing::Span::current().record("rpc.tag", tag);
+	debug!("Processing RPC request with tag '{tag}'");
+}
+
+/// Limits and sanitizes a request tag so that it is safe to log
+fn sanitize(tag: &str) -> Option<String> {
+	let tag: String = tag
+		.chars()
+		.filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '/'))
+		.take(MAX_TAG_LENGTH)
+		.collect();
+	match tag.is_empty() {
+		true => None,
+		false => Some(tag),
+	}
+}
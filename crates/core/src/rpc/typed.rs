@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+
+use crate::sql::Value;
+
+use super::RpcError;
+
+/// The field which declares the intended type of a typed param
+const TYPE_FIELD: &str = "__type";
+/// The field which contains the value of a typed param
+const VALUE_FIELD: &str = "value";
+
+/// Converts any typed params within the variables into their declared types
+pub(crate) fn process_vars(
+	vars: BTreeMap<String, Value>,
+) -> Result<BTreeMap<String, Value>, RpcError> {
+	vars.into_iter().map(|(k, v)| Ok((k, process_value(v)?))).collect()
+}
+
+/// Converts a typed param, such as `{ __type: "datetime", value: "..." }`,
+/// into a value of the declared type, descending into arrays and objects
+fn process_value(v: Value) -> Result<Value, RpcError> {
+	match v {
+		Value::Object(mut obj) if obj.contains_key(TYPE_FIELD) => {
+			// A typed param only contains a type and a value
+			if obj.len() != 2 {
+				return Err(RpcError::InvalidParams);
+			}
+			let (Some(Value::Strand(kind)), Some(value)) =
+				(obj.remove(TYPE_FIELD), obj.remove(VALUE_FIELD))
+			else {
+				return Err(RpcError::InvalidParams);
+			};
+			// Parse the declared type
+			let kind = crate::syn::kind(kind.as_str()).map_err(|_| RpcError::InvalidParams)?;
+			// Convert the value into the declared type
+			value.cast_to_kind(&kind).map_err(|_| RpcError::InvalidParams)
+		}
+		Value::Object(obj) => Ok(Value::Object(
+			obj.0
+				.into_iter()
+				.map(|(k, v)| Ok((k, process_value(v)?)))
+				.collect::<Result<BTreeMap<_, _>, RpcError>>()?
+				.into(),
+		)),
+		Value::Array(arr) => Ok(Value::Array(
+			arr.into_iter().map(process_value).collect::<Result<Vec<_>, _>>()?.into(),
+		)),
+		v => Ok(v),
+	}
+}
@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+use url::Url;
+
+use crate::{
+	dbs::{capabilities::NetTarget, Session},
+	err::Error,
+	iam::Error as IamError,
+	kvs::{Datastore, Webhook},
+	sql::Value,
+};
+
+use super::RpcError;
+
+/// Checks that the session is allowed to manage webhooks, which send data out of the datastore
+pub(crate) fn check_access(sess: &Session, action: &str) -> Result<(), RpcError> {
+	// Check if the session has expired
+	if sess.expired() {
+		return Err(Error::ExpiredSession.into());
+	}
+	// Only owners can manage webhooks
+	if !sess.au.has_owner_role() {
+		return Err(Error::from(IamError::NotAllowed {
+			actor: sess.au.id().to_string(),
+			action: action.to_string(),
+			resource: "webhook".to_string(),
+		})
+		.into());
+	}
+	Ok(())
+}
+
+/// Checks that the URL of a webhook can be reached with the network capabilities
+pub(crate) fn check_url(kvs: &Datastore, url: &str) -> Result<(), RpcError> {
+	let invalid = || Error::InvalidUrl(url.to_string());
+	let parsed = Url::parse(url).map_err(|_| invalid())?;
+	if !matches!(parsed.scheme(), "http" | "https") {
+		return Err(invalid().into());
+	}
+	let addr = match (parsed.host_str(), parsed.port()) {
+		(Some(host), Some(port)) => format!("{host}:{port}"),
+		(Some(host), None) => host.to_string(),
+		(None, _) => return Err(invalid().into()),
+	};
+	let target = NetTarget::from_str(&addr).map_err(|_| invalid())?;
+	if !kvs.get_capabilities().allows_network_target(&target) {
+		return Err(invalid().into());
+	}
+	Ok(())
+}
+
+/// Describes a webhook
+pub(crate) fn describe(wh: &Webhook) -> Value {
+	Value::from(map! {
+		"id".to_string() => Value::Uuid(wh.id.into()),
+		"url".to_string() => Value::from(wh.url.as_str()),
+		"table".to_string() => Value::from(wh.tb.as_str()),
+		"cond".to_string(), if let Some(v) = &wh.cond => Value::from(v.to_string()),
+		"versionstamp".to_string() => Value::from(wh.vs),
+		"failures".to_string() => Value::from(wh.failures),
+		"retry_at".to_string(), if let Some(v) = &wh.retry_at => Value::from(v.clone()),
+		"error".to_string(), if let Some(v) = &wh.error => Value::from(v.as_str()),
+	})
+}
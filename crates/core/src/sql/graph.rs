@@ -72,6 +72,7 @@ impl Graph {
 					collate: x.collate,
 					numeric: x.numeric,
 					direction: x.direction,
+					nulls_first: None,
 				})
 				.collect();
 
@@ -1,3 +1,4 @@
+use crate::fnc::util::string::collation::Collation;
 use crate::sql::fmt::Fmt;
 use crate::sql::idiom::Idiom;
 use crate::sql::Value;
@@ -44,12 +45,33 @@ impl fmt::Display for OrderList {
 }
 
 impl OrderList {
-	pub(crate) fn compare(&self, a: &Value, b: &Value) -> cmp::Ordering {
+	/// Compares two values by this order, collating strings with the given collation if specified
+	pub(crate) fn compare(
+		&self,
+		a: &Value,
+		b: &Value,
+		collation: Option<&Collation>,
+	) -> cmp::Ordering {
 		for order in &self.0 {
+			// Place null and absent values first or last, if requested
+			if let Some(first) = order.nulls_first {
+				let nulls = (
+					a.pick(&order.value.0).is_none_or_null(),
+					b.pick(&order.value.0).is_none_or_null(),
+				);
+				match nulls {
+					(true, true) => continue,
+					(true, false) if first => return cmp::Ordering::Less,
+					(true, false) => return cmp::Ordering::Greater,
+					(false, true) if first => return cmp::Ordering::Greater,
+					(false, true) => return cmp::Ordering::Less,
+					(false, false) => (),
+				}
+			}
 			// Reverse the ordering if DESC
 			let o = match order.direction {
-				true => a.compare(b, &order.value.0, order.collate, order.numeric),
-				false => b.compare(a, &order.value.0, order.collate, order.numeric),
+				true => a.compare(b, &order.value.0, order.collate, order.numeric, collation),
+				false => b.compare(a, &order.value.0, order.collate, order.numeric, collation),
 			};
 			//
 			match o {
@@ -63,17 +85,26 @@ impl OrderList {
 	}
 }
 
-#[revisioned(revision = 1)]
+#[revisioned(revision = 2)]
 #[derive(Clone, Debug, Default, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub struct Order {
 	/// The value to order by
 	pub value: Idiom,
+	/// true if strings are ordered using the collation rules of the session locale,
+	/// or lexically when no locale is set. Comparison operators and index scans
+	/// always compare strings by their code points, so that they stay consistent
+	/// with the order in which the values are stored.
 	pub collate: bool,
 	pub numeric: bool,
 	/// true if the direction is ascending
 	pub direction: bool,
+	/// true if null and absent values are placed first, and false if they are placed last,
+	/// regardless of the direction. When not set, they are ordered as the smallest values,
+	/// so they are placed first when ascending and last when descending.
+	#[revision(start = 2)]
+	pub nulls_first: Option<bool>,
 }
 
 impl fmt::Display for Order {
@@ -88,6 +119,11 @@ impl fmt::Display for Order {
 		if !self.direction {
 			write!(f, " DESC")?;
 		}
+		match self.nulls_first {
+			Some(true) => write!(f, " NULLS FIRST")?,
+			Some(false) => write!(f, " NULLS LAST")?,
+			None => (),
+		}
 		Ok(())
 	}
 }
@@ -21,6 +21,10 @@ pub static OR: LazyLock<[Part; 1]> = LazyLock::new(|| [Part::from("or")]);
 
 pub static TK: LazyLock<[Part; 1]> = LazyLock::new(|| [Part::from(OBJ_PATH_TOKEN)]);
 
+pub static TZ: LazyLock<[Part; 1]> = LazyLock::new(|| [Part::from("tz")]);
+
+pub static LC: LazyLock<[Part; 1]> = LazyLock::new(|| [Part::from("lc")]);
+
 pub static IN: LazyLock<[Part; 1]> = LazyLock::new(|| [Part::from("in")]);
 
 pub static OUT: LazyLock<[Part; 1]> = LazyLock::new(|| [Part::from("out")]);
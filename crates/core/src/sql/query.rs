@@ -8,6 +8,7 @@ use crate::sql::statements::KillStatement;
 use crate::sql::statements::LiveStatement;
 use crate::sql::statements::RelateStatement;
 use crate::sql::statements::SelectStatement;
+use crate::sql::statements::ShowStatement;
 use crate::sql::statements::UpdateStatement;
 use crate::sql::statements::UpsertStatement;
 use crate::sql::statements::{DefineStatement, RemoveStatement};
@@ -89,6 +90,12 @@ impl From<LiveStatement> for Query {
 	}
 }
 
+impl From<ShowStatement> for Query {
+	fn from(s: ShowStatement) -> Self {
+		Query(Statements(vec![Statement::Show(s)]))
+	}
+}
+
 impl From<KillStatement> for Query {
 	fn from(s: KillStatement) -> Self {
 		Query(Statements(vec![Statement::Kill(s)]))
@@ -100,6 +100,42 @@ pub enum Statement {
 }
 
 impl Statement {
+	/// Returns the keyword which identifies the kind of this statement
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Self::Value(_) => "value",
+			Self::Access(_) => "access",
+			Self::Alter(_) => "alter",
+			Self::Analyze(_) => "analyze",
+			Self::Begin(_) => "begin",
+			Self::Break(_) => "break",
+			Self::Cancel(_) => "cancel",
+			Self::Commit(_) => "commit",
+			Self::Continue(_) => "continue",
+			Self::Create(_) => "create",
+			Self::Define(_) => "define",
+			Self::Delete(_) => "delete",
+			Self::Foreach(_) => "for",
+			Self::Ifelse(_) => "if",
+			Self::Info(_) => "info",
+			Self::Insert(_) => "insert",
+			Self::Kill(_) => "kill",
+			Self::Live(_) => "live",
+			Self::Option(_) => "option",
+			Self::Output(_) => "return",
+			Self::Rebuild(_) => "rebuild",
+			Self::Relate(_) => "relate",
+			Self::Remove(_) => "remove",
+			Self::Select(_) => "select",
+			Self::Set(_) => "let",
+			Self::Show(_) => "show",
+			Self::Sleep(_) => "sleep",
+			Self::Throw(_) => "throw",
+			Self::Upsert(_) => "upsert",
+			Self::Update(_) => "update",
+			Self::Use(_) => "use",
+		}
+	}
 	/// Check if we require a writeable transaction
 	pub(crate) fn writeable(&self) -> bool {
 		match self {
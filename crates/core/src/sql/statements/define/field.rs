@@ -17,7 +17,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display, Write};
 use uuid::Uuid;
 
-#[revisioned(revision = 6)]
+#[revisioned(revision = 7)]
 #[derive(Clone, Debug, Default, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
@@ -43,6 +43,9 @@ pub struct DefineFieldStatement {
 	pub reference: Option<Reference>,
 	#[revision(start = 6)]
 	pub default_always: bool,
+	/// Whether the field is masked in query output unless explicitly revealed.
+	#[revision(start = 7)]
+	pub sensitive: bool,
 }
 
 impl DefineFieldStatement {
@@ -421,6 +424,9 @@ impl Display for DefineFieldStatement {
 		if self.readonly {
 			write!(f, " READONLY")?
 		}
+		if self.sensitive {
+			write!(f, " SENSITIVE")?
+		}
 		if let Some(ref v) = self.value {
 			write!(f, " VALUE {v}")?
 		}
@@ -460,6 +466,7 @@ impl InfoStructure for DefineFieldStatement {
 			"default".to_string(), if let Some(v) = self.default => v.structure(),
 			"reference".to_string(), if let Some(v) = self.reference => v.structure(),
 			"readonly".to_string() => self.readonly.into(),
+			"sensitive".to_string(), if self.sensitive => true.into(),
 			"permissions".to_string() => self.permissions.structure(),
 			"comment".to_string(), if let Some(v) = self.comment => v.into(),
 		})
@@ -191,7 +191,7 @@ fn iterable(id: Thing, v: Value, relation: bool) -> Result<Iterable, Error> {
 	}
 }
 
-fn gen_id(v: &Value, into: &Option<Table>) -> Result<Thing, Error> {
+pub(crate) fn gen_id(v: &Value, into: &Option<Table>) -> Result<Thing, Error> {
 	match into {
 		Some(into) => v.rid().generate(into, true),
 		None => match v.rid() {
@@ -70,6 +70,7 @@ impl SelectStatement {
 					collate: x.collate,
 					numeric: x.numeric,
 					direction: x.direction,
+					nulls_first: None,
 				})
 				.collect();
 
@@ -109,10 +110,11 @@ impl SelectStatement {
 		let stm = Statement::from(self);
 		// Create a new iterator
 		let mut i = Iterator::new();
-		// Ensure futures are stored and the version is set if specified
+		// Ensure futures are stored and the version is set if specified,
+		// otherwise keep reading at any version which is already set
 		let version = match &self.version {
 			Some(v) => Some(v.compute(stk, ctx, opt, doc).await?),
-			_ => None,
+			_ => opt.version,
 		};
 		let opt = Arc::new(opt.new_with_futures(false).with_version(version));
 		// Extract the limits
@@ -8,6 +8,10 @@ use crate::sql::{Base, Duration, Value};
 use revision::revisioned;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use trice::Instant;
+
+/// How often a sleeping statement checks whether the query has been cancelled
+const CANCELLATION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
 
 #[revisioned(revision = 1)]
 #[derive(Clone, Debug, Default, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
@@ -31,11 +35,22 @@ impl SleepStatement {
 			(Some(t), d) if t < d => t,
 			(_, d) => d,
 		};
-		// Sleep for the specified time
-		#[cfg(target_family = "wasm")]
-		wasmtimer::tokio::sleep(dur).await;
-		#[cfg(not(target_family = "wasm"))]
-		tokio::time::sleep(dur).await;
+		// Sleep for the specified time, stopping if the query is cancelled
+		let end = Instant::now() + dur;
+		loop {
+			let now = Instant::now();
+			if now >= end {
+				break;
+			}
+			let dur = (end - now).min(CANCELLATION_CHECK_INTERVAL);
+			#[cfg(target_family = "wasm")]
+			wasmtimer::tokio::sleep(dur).await;
+			#[cfg(not(target_family = "wasm"))]
+			tokio::time::sleep(dur).await;
+			if ctx.is_done(false).await? {
+				return Err(Error::QueryCancelled);
+			}
+		}
 		// Ok all good
 		Ok(Value::None)
 	}
@@ -50,6 +65,7 @@ impl fmt::Display for SleepStatement {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::ctx::MutableContext;
 	use crate::dbs::test::mock;
 	use std::time::{self, SystemTime};
 
@@ -64,4 +80,19 @@ mod tests {
 		assert!(time.elapsed().unwrap() >= time::Duration::from_micros(500));
 		assert_eq!(value, Value::None);
 	}
+
+	#[tokio::test]
+	async fn test_sleep_compute_cancelled() {
+		let time = SystemTime::now();
+		let (ctx, opt) = mock().await;
+		let mut ctx = MutableContext::new(&ctx);
+		ctx.add_cancel().cancel();
+		let ctx = ctx.freeze();
+		let stm = SleepStatement {
+			duration: Duration(time::Duration::from_secs(10)),
+		};
+		let res = stm.compute(&ctx, &opt, None).await;
+		assert!(matches!(res, Err(Error::QueryCancelled)));
+		assert!(time.elapsed().unwrap() < time::Duration::from_secs(10));
+	}
 }
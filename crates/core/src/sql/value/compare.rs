@@ -1,3 +1,4 @@
+use crate::fnc::util::string::collation::Collation;
 use crate::sql::part::Next;
 use crate::sql::part::Part;
 use crate::sql::value::Value;
@@ -10,6 +11,7 @@ impl Value {
 		path: &[Part],
 		collate: bool,
 		numeric: bool,
+		collation: Option<&Collation>,
 	) -> Option<Ordering> {
 		match path.first() {
 			// Get the current path part
@@ -17,7 +19,9 @@ impl Value {
 				// Current path part is an object
 				(Value::Object(a), Value::Object(b)) => match p {
 					Part::Field(f) => match (a.get(f.as_str()), b.get(f.as_str())) {
-						(Some(a), Some(b)) => a.compare(b, path.next(), collate, numeric),
+						(Some(a), Some(b)) => {
+							a.compare(b, path.next(), collate, numeric, collation)
+						}
 						(Some(_), None) => Some(Ordering::Greater),
 						(None, Some(_)) => Some(Ordering::Less),
 						(_, _) => Some(Ordering::Equal),
@@ -28,7 +32,7 @@ impl Value {
 				(Value::Array(a), Value::Array(b)) => match p {
 					Part::All => {
 						for (a, b) in a.iter().zip(b.iter()) {
-							match a.compare(b, path.next(), collate, numeric) {
+							match a.compare(b, path.next(), collate, numeric, collation) {
 								Some(Ordering::Equal) => continue,
 								None => continue,
 								o => return o,
@@ -41,26 +45,32 @@ impl Value {
 						}
 					}
 					Part::First => match (a.first(), b.first()) {
-						(Some(a), Some(b)) => a.compare(b, path.next(), collate, numeric),
+						(Some(a), Some(b)) => {
+							a.compare(b, path.next(), collate, numeric, collation)
+						}
 						(Some(_), None) => Some(Ordering::Greater),
 						(None, Some(_)) => Some(Ordering::Less),
 						(_, _) => Some(Ordering::Equal),
 					},
 					Part::Last => match (a.last(), b.last()) {
-						(Some(a), Some(b)) => a.compare(b, path.next(), collate, numeric),
+						(Some(a), Some(b)) => {
+							a.compare(b, path.next(), collate, numeric, collation)
+						}
 						(Some(_), None) => Some(Ordering::Greater),
 						(None, Some(_)) => Some(Ordering::Less),
 						(_, _) => Some(Ordering::Equal),
 					},
 					Part::Index(i) => match (a.get(i.to_usize()), b.get(i.to_usize())) {
-						(Some(a), Some(b)) => a.compare(b, path.next(), collate, numeric),
+						(Some(a), Some(b)) => {
+							a.compare(b, path.next(), collate, numeric, collation)
+						}
 						(Some(_), None) => Some(Ordering::Greater),
 						(None, Some(_)) => Some(Ordering::Less),
 						(_, _) => Some(Ordering::Equal),
 					},
 					_ => {
 						for (a, b) in a.iter().zip(b.iter()) {
-							match a.compare(b, path, collate, numeric) {
+							match a.compare(b, path, collate, numeric, collation) {
 								Some(Ordering::Equal) => continue,
 								None => continue,
 								o => return o,
@@ -74,13 +84,14 @@ impl Value {
 					}
 				},
 				// Ignore everything else
-				(a, b) => a.compare(b, path.next(), collate, numeric),
+				(a, b) => a.compare(b, path.next(), collate, numeric, collation),
 			},
 			// No more parts so get the value
-			None => match (collate, numeric) {
-				(true, true) => self.natural_lexical_cmp(other),
-				(true, false) => self.lexical_cmp(other),
-				(false, true) => self.natural_cmp(other),
+			None => match (collate, numeric, collation) {
+				(true, numeric, Some(c)) => self.collated_cmp(other, c, numeric),
+				(true, true, None) => self.natural_lexical_cmp(other),
+				(true, false, None) => self.lexical_cmp(other),
+				(false, true, _) => self.natural_cmp(other),
 				_ => self.partial_cmp(other),
 			},
 		}
@@ -99,7 +110,7 @@ mod tests {
 		let idi = Idiom::default();
 		let one = Value::parse("{ test: { other: null, something: 456 } }");
 		let two = Value::parse("{ test: { other: null, something: 123 } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Greater));
 	}
 
@@ -108,7 +119,7 @@ mod tests {
 		let idi = Idiom::parse("test.something");
 		let one = Value::parse("{ test: { other: null, something: 456 } }");
 		let two = Value::parse("{ test: { other: null, something: 123 } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Greater));
 	}
 
@@ -117,7 +128,7 @@ mod tests {
 		let idi = Idiom::parse("test.something");
 		let one = Value::parse("{ test: { other: null } }");
 		let two = Value::parse("{ test: { other: null, something: 123 } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Less));
 	}
 
@@ -126,7 +137,7 @@ mod tests {
 		let idi = Idiom::parse("test.something");
 		let one = Value::parse("{ test: { other: null, something: 456 } }");
 		let two = Value::parse("{ test: { other: null } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Greater));
 	}
 
@@ -135,7 +146,7 @@ mod tests {
 		let idi = Idiom::parse("test.something.*");
 		let one = Value::parse("{ test: { other: null, something: [4, 5, 6] } }");
 		let two = Value::parse("{ test: { other: null, something: [1, 2, 3] } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Greater));
 	}
 
@@ -144,7 +155,7 @@ mod tests {
 		let idi = Idiom::parse("test.something.*");
 		let one = Value::parse("{ test: { other: null, something: [1, 2, 3, 4, 5, 6] } }");
 		let two = Value::parse("{ test: { other: null, something: [1, 2, 3] } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Greater));
 	}
 
@@ -153,7 +164,7 @@ mod tests {
 		let idi = Idiom::parse("test.something.*");
 		let one = Value::parse("{ test: { other: null, something: [1, 2, 3] } }");
 		let two = Value::parse("{ test: { other: null, something: [1, 2, 3, 4, 5, 6] } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Less));
 	}
 
@@ -162,7 +173,7 @@ mod tests {
 		let idi = Idiom::parse("test.something.*");
 		let one = Value::parse("{ test: { other: null, something: null } }");
 		let two = Value::parse("{ test: { other: null, something: [1, 2, 3] } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Less));
 	}
 
@@ -171,7 +182,7 @@ mod tests {
 		let idi = Idiom::parse("test.something.*");
 		let one = Value::parse("{ test: { other: null, something: [4, 5, 6] } }");
 		let two = Value::parse("{ test: { other: null, something: null } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Greater));
 	}
 
@@ -180,7 +191,7 @@ mod tests {
 		let idi = Idiom::parse("test.something.*");
 		let one = Value::parse("{ test: { other: null, something: [1, null, 3] } }");
 		let two = Value::parse("{ test: { other: null, something: [1, 2, 3] } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Less));
 	}
 
@@ -189,7 +200,7 @@ mod tests {
 		let idi = Idiom::parse("test.something.*");
 		let one = Value::parse("{ test: { other: null, something: [1, 2, 3] } }");
 		let two = Value::parse("{ test: { other: null, something: [1, null, 3] } }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Greater));
 	}
 
@@ -198,7 +209,21 @@ mod tests {
 		let idi = Idiom::parse("test[$]");
 		let one = Value::parse("{ test: [1,5] }");
 		let two = Value::parse("{ test: [2,4] }");
-		let res = one.compare(&two, &idi, false, false);
+		let res = one.compare(&two, &idi, false, false, None);
 		assert_eq!(res, Some(Ordering::Greater))
 	}
+
+	#[test]
+	fn compare_collated() {
+		let idi = Idiom::parse("test");
+		let one = Value::parse("{ test: 'ä' }");
+		let two = Value::parse("{ test: 'z' }");
+		let res = one.compare(&two, &idi, true, false, None);
+		assert_eq!(res, Some(Ordering::Less));
+		let sv = Collation::new("sv").unwrap();
+		let res = one.compare(&two, &idi, true, false, Some(&sv));
+		assert_eq!(res, Some(Ordering::Greater));
+		let res = one.compare(&two, &idi, false, false, Some(&sv));
+		assert_eq!(res, Some(Ordering::Greater));
+	}
 }
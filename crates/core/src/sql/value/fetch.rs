@@ -1,3 +1,4 @@
+use crate::cnf::MAX_FETCH_DEPTH;
 use crate::ctx::Context;
 use crate::dbs::Options;
 use crate::err::Error;
@@ -6,6 +7,7 @@ use crate::sql::field::{Field, Fields};
 use crate::sql::part::Next;
 use crate::sql::part::Part;
 use crate::sql::statements::select::SelectStatement;
+use crate::sql::thing::Thing;
 use crate::sql::value::{Value, Values};
 use crate::sql::FlowResultExt as _;
 use futures::future::try_join_all;
@@ -18,10 +20,25 @@ impl Value {
 		ctx: &Context,
 		opt: &Options,
 		path: &[Part],
+	) -> Result<(), Error> {
+		self.fetch_within(stk, ctx, opt, path, &[]).await
+	}
+
+	/// Fetches the records along the path, keeping track of the chain of
+	/// records which the path has passed through. Any record which is already
+	/// within the chain, or which is nested too deeply, is left as a record id.
+	async fn fetch_within(
+		&mut self,
+		stk: &mut Stk,
+		ctx: &Context,
+		opt: &Options,
+		path: &[Part],
+		chain: &[Thing],
 	) -> Result<(), Error> {
 		let mut this = self;
 		let mut iter = path.iter();
 		let mut prev = path;
+		let mut chain = chain.to_vec();
 
 		// Loop over the path.
 		// If the we just need to select a sub section of a value we update this to point to the
@@ -30,6 +47,12 @@ impl Value {
 		// If we encounter a idiom application which does not make sense, like `(1).foo` just
 		// return Ok(())
 		while let Some(p) = iter.next() {
+			// Keep track of the records which have been passed through
+			if let Value::Object(o) = this {
+				if let Some(id) = o.rid() {
+					chain.push(id);
+				}
+			}
 			match p {
 				Part::Graph(g) => match this {
 					Value::Object(o) => {
@@ -38,7 +61,9 @@ impl Value {
 						};
 
 						let mut v = Value::Thing(v);
-						return stk.run(|stk| v.fetch(stk, ctx, opt, iter.as_slice())).await;
+						return stk
+							.run(|stk| v.fetch_within(stk, ctx, opt, iter.as_slice(), &chain))
+							.await;
 					}
 					Value::Thing(x) => {
 						let stm = SelectStatement {
@@ -70,8 +95,9 @@ impl Value {
 					Value::Array(x) => {
 						// apply this path to every entry of the array.
 						stk.scope(|scope| {
-							let futs =
-								x.iter_mut().map(|v| scope.run(|stk| v.fetch(stk, ctx, opt, prev)));
+							let futs = x.iter_mut().map(|v| {
+								scope.run(|stk| v.fetch_within(stk, ctx, opt, prev, &chain))
+							});
 							try_join_all(futs)
 						})
 						.await?;
@@ -90,8 +116,9 @@ impl Value {
 					Value::Array(x) => {
 						// apply this path to every entry of the array.
 						stk.scope(|scope| {
-							let futs =
-								x.iter_mut().map(|v| scope.run(|stk| v.fetch(stk, ctx, opt, prev)));
+							let futs = x.iter_mut().map(|v| {
+								scope.run(|stk| v.fetch_within(stk, ctx, opt, prev, &chain))
+							});
 							try_join_all(futs)
 						})
 						.await?;
@@ -130,7 +157,9 @@ impl Value {
 								};
 								let mut range = Value::Array(range.to_vec().into());
 								return stk
-									.run(|stk| range.fetch(stk, ctx, opt, iter.as_slice()))
+									.run(|stk| {
+										range.fetch_within(stk, ctx, opt, iter.as_slice(), &chain)
+									})
 									.await;
 							}
 							let idx = v.coerce_to::<i64>()?;
@@ -147,8 +176,9 @@ impl Value {
 					Value::Array(x) => {
 						// apply this path to every entry of the array.
 						stk.scope(|scope| {
-							let futs =
-								x.iter_mut().map(|v| scope.run(|stk| v.fetch(stk, ctx, opt, prev)));
+							let futs = x.iter_mut().map(|v| {
+								scope.run(|stk| v.fetch_within(stk, ctx, opt, prev, &chain))
+							});
 							try_join_all(futs)
 						})
 						.await?;
@@ -157,7 +187,10 @@ impl Value {
 						for p in p.iter() {
 							let mut destructure_path = p.path();
 							destructure_path.extend_from_slice(path);
-							stk.run(|stk| this.fetch(stk, ctx, opt, &destructure_path)).await?;
+							stk.run(|stk| {
+								this.fetch_within(stk, ctx, opt, &destructure_path, &chain)
+							})
+							.await?;
 						}
 						return Ok(());
 					}
@@ -173,9 +206,9 @@ impl Value {
 						}
 
 						stk.scope(|scope| {
-							let futs = x
-								.iter_mut()
-								.map(|(_, v)| scope.run(|stk| v.fetch(stk, ctx, opt, next_path)));
+							let futs = x.iter_mut().map(|(_, v)| {
+								scope.run(|stk| v.fetch_within(stk, ctx, opt, next_path, &chain))
+							});
 							try_join_all(futs)
 						})
 						.await?;
@@ -190,9 +223,9 @@ impl Value {
 						}
 
 						stk.scope(|scope| {
-							let futs = x
-								.iter_mut()
-								.map(|v| scope.run(|stk| v.fetch(stk, ctx, opt, next_path)));
+							let futs = x.iter_mut().map(|v| {
+								scope.run(|stk| v.fetch_within(stk, ctx, opt, next_path, &chain))
+							});
 							try_join_all(futs)
 						})
 						.await?;
@@ -227,7 +260,10 @@ impl Value {
 								.catch_return()?
 								.is_truthy()
 							{
-								stk.run(|stk| v.fetch(stk, ctx, opt, iter.as_slice())).await?;
+								stk.run(|stk| {
+									v.fetch_within(stk, ctx, opt, iter.as_slice(), &chain)
+								})
+								.await?;
 							}
 						}
 					}
@@ -242,13 +278,19 @@ impl Value {
 		match this {
 			Value::Array(v) => {
 				stk.scope(|scope| {
-					let futs = v.iter_mut().map(|v| scope.run(|stk| v.fetch(stk, ctx, opt, path)));
+					let futs = v
+						.iter_mut()
+						.map(|v| scope.run(|stk| v.fetch_within(stk, ctx, opt, path, &chain)));
 					try_join_all(futs)
 				})
 				.await?;
 				Ok(())
 			}
 			Value::Thing(v) => {
+				// Leave cyclic or deeply nested records as a reference
+				if chain.contains(v) || chain.len() > *MAX_FETCH_DEPTH {
+					return Ok(());
+				}
 				// Clone the thing
 				let val = v.clone();
 				// Fetch the remote embedded record
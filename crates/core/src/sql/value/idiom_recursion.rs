@@ -76,6 +76,8 @@ pub(crate) async fn compute_idiom_recursion(
 ) -> Result<Value, Error> {
 	// Find the recursion limit
 	let limit = *IDIOM_RECURSION_LIMIT as u32;
+	// Find the recursion depth limit of this request
+	let depth = ctx.max_depth();
 	// Do we recursion instead of looping?
 	let marked_recursive = rec.plan.is_some();
 
@@ -110,7 +112,16 @@ pub(crate) async fn compute_idiom_recursion(
 			if i >= max {
 				return Ok(current);
 			}
-		} else if i >= limit {
+		}
+		// Check the recursion depth limit of this request
+		if let Some(depth) = depth {
+			if i >= depth {
+				return Err(Error::RecursionDepthExceeded {
+					limit: depth,
+				});
+			}
+		}
+		if rec.max.is_none() && i >= limit {
 			return Err(Error::IdiomRecursionLimitExceeded {
 				limit,
 			});
@@ -188,7 +199,16 @@ pub(crate) async fn compute_idiom_recursion(
 			if i >= max {
 				return Ok(output!());
 			}
-		} else if i >= limit {
+		}
+		// Check the recursion depth limit of this request
+		if let Some(depth) = depth {
+			if i >= depth {
+				return Err(Error::RecursionDepthExceeded {
+					limit: depth,
+				});
+			}
+		}
+		if rec.max.is_none() && i >= limit {
 			return Err(Error::IdiomRecursionLimitExceeded {
 				limit,
 			});
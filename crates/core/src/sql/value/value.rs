@@ -4,6 +4,7 @@ use crate::ctx::Context;
 use crate::dbs::Options;
 use crate::doc::CursorDoc;
 use crate::err::Error;
+use crate::fnc::util::string::collation::Collation;
 use crate::fnc::util::string::fuzzy::Fuzzy;
 use crate::sql::id::range::IdRange;
 use crate::sql::range::OldRange;
@@ -625,6 +626,26 @@ impl Value {
 		}
 	}
 
+	/// Check if this Value is a literal, which needs no computation
+	pub(crate) fn is_literal(&self) -> bool {
+		match self {
+			Value::None => true,
+			Value::Null => true,
+			Value::Bool(_) => true,
+			Value::Bytes(_) => true,
+			Value::Uuid(_) => true,
+			Value::Number(_) => true,
+			Value::Strand(_) => true,
+			Value::Duration(_) => true,
+			Value::Datetime(_) => true,
+			Value::Geometry(_) => true,
+			Value::Thing(v) => matches!(v.id, Id::Number(_) | Id::String(_) | Id::Uuid(_)),
+			Value::Array(v) => v.iter().all(Value::is_literal),
+			Value::Object(v) => v.values().all(Value::is_literal),
+			_ => false,
+		}
+	}
+
 	// -----------------------------------
 	// Value operations
 	// -----------------------------------
@@ -849,6 +870,19 @@ impl Value {
 		}
 	}
 
+	/// Compare this Value to another Value using the collation rules of a locale
+	pub(crate) fn collated_cmp(
+		&self,
+		other: &Value,
+		collation: &Collation,
+		numeric: bool,
+	) -> Option<Ordering> {
+		match (self, other) {
+			(Value::Strand(a), Value::Strand(b)) => Some(collation.compare(a, b, numeric)),
+			_ => self.partial_cmp(other),
+		}
+	}
+
 	/// Compare this Value to another Value using natural numerical comparison
 	pub fn natural_cmp(&self, other: &Value) -> Option<Ordering> {
 		match (self, other) {
@@ -1441,6 +1475,45 @@ mod tests {
 	use super::*;
 	use crate::syn::Parse;
 
+	#[test]
+	fn check_literal() {
+		assert!(Value::parse("NONE").is_literal());
+		assert!(Value::parse("123").is_literal());
+		assert!(Value::parse("'test'").is_literal());
+		assert!(Value::parse("person:test").is_literal());
+		assert!(Value::parse("[1, 'test', { a: d'2020-01-01T00:00:00Z' }]").is_literal());
+		assert!(!Value::parse("$param").is_literal());
+		assert!(!Value::parse("1 + 1").is_literal());
+		assert!(!Value::parse("time::now()").is_literal());
+		assert!(!Value::parse("person:rand()").is_literal());
+		assert!(!Value::parse("[1, { a: $param }]").is_literal());
+	}
+
+	#[tokio::test]
+	async fn check_literal_compute() {
+		let ds = crate::kvs::Datastore::new("memory").await.unwrap();
+		let ses = crate::dbs::Session::owner();
+		for val in [
+			"NONE",
+			"NULL",
+			"true",
+			"-1.5dec",
+			"'test'",
+			"u'e72bee20-f49b-11ec-b939-0242ac120002'",
+			"1w2d",
+			"d'2020-01-01T00:00:00Z'",
+			"person:test",
+			"(1, 2)",
+			"[1, 'test', { a: person:1, b: [NULL] }]",
+		] {
+			let val = Value::parse(val);
+			assert!(val.is_literal());
+			// A literal computes into itself
+			let res = ds.compute(val.clone(), &ses, None).await.unwrap();
+			assert_eq!(res, val);
+		}
+	}
+
 	#[test]
 	fn check_none() {
 		assert!(Value::None.is_none());
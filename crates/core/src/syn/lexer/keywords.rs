@@ -137,6 +137,7 @@ pub(crate) static KEYWORDS: phf::Map<UniCase<&'static str>, TokenKind> = phf_map
 	UniCase::ascii("FLEXIBLE") => TokenKind::Keyword(Keyword::Flexible),
 	UniCase::ascii("FLEXI") => TokenKind::Keyword(Keyword::Flexible),
 	UniCase::ascii("FLEX") => TokenKind::Keyword(Keyword::Flexible),
+	UniCase::ascii("FIRST") => TokenKind::Keyword(Keyword::First),
 	UniCase::ascii("FOR") => TokenKind::Keyword(Keyword::For),
 	UniCase::ascii("FROM") => TokenKind::Keyword(Keyword::From),
 	UniCase::ascii("FULL") => TokenKind::Keyword(Keyword::Full),
@@ -162,6 +163,7 @@ pub(crate) static KEYWORDS: phf::Map<UniCase<&'static str>, TokenKind> = phf_map
 	UniCase::ascii("KEY") => TokenKind::Keyword(Keyword::Key),
 	UniCase::ascii("KEEP_PRUNED_CONNECTIONS") => TokenKind::Keyword(Keyword::KeepPrunedConnections),
 	UniCase::ascii("KILL") => TokenKind::Keyword(Keyword::Kill),
+	UniCase::ascii("LAST") => TokenKind::Keyword(Keyword::Last),
 	UniCase::ascii("LET") => TokenKind::Keyword(Keyword::Let),
 	UniCase::ascii("LIMIT") => TokenKind::Keyword(Keyword::Limit),
 	UniCase::ascii("LIVE") => TokenKind::Keyword(Keyword::Live),
@@ -183,6 +185,7 @@ pub(crate) static KEYWORDS: phf::Map<UniCase<&'static str>, TokenKind> = phf_map
 	UniCase::ascii("NOINDEX") => TokenKind::Keyword(Keyword::NoIndex),
 	UniCase::ascii("NONE") => TokenKind::Keyword(Keyword::None),
 	UniCase::ascii("NULL") => TokenKind::Keyword(Keyword::Null),
+	UniCase::ascii("NULLS") => TokenKind::Keyword(Keyword::Nulls),
 	UniCase::ascii("NUMERIC") => TokenKind::Keyword(Keyword::Numeric),
 	UniCase::ascii("OMIT") => TokenKind::Keyword(Keyword::Omit),
 	UniCase::ascii("ON") => TokenKind::Keyword(Keyword::On),
@@ -224,6 +227,7 @@ pub(crate) static KEYWORDS: phf::Map<UniCase<&'static str>, TokenKind> = phf_map
 	UniCase::ascii("SC") => TokenKind::Keyword(Keyword::Scope),
 	UniCase::ascii("SEARCH") => TokenKind::Keyword(Keyword::Search),
 	UniCase::ascii("SELECT") => TokenKind::Keyword(Keyword::Select),
+	UniCase::ascii("SENSITIVE") => TokenKind::Keyword(Keyword::Sensitive),
 	UniCase::ascii("SEQUENCE") => TokenKind::Keyword(Keyword::Sequence),
 	UniCase::ascii("SESSION") => TokenKind::Keyword(Keyword::Session),
 	UniCase::ascii("SET") => TokenKind::Keyword(Keyword::Set),
@@ -64,6 +64,23 @@ pub fn parse(input: &str) -> Result<Query, Error> {
 /// please [open an issue](https://github.com/surrealdb/surrealdb/issues)!
 #[instrument(level = "trace", target = "surrealdb::core::syn", fields(length = input.len()))]
 pub fn parse_with_capabilities(input: &str, capabilities: &Capabilities) -> Result<Query, Error> {
+	let settings = ParserSettings::default();
+	parse_with_compatibility(
+		input,
+		capabilities,
+		settings.legacy_strands,
+		settings.flexible_record_id,
+	)
+}
+
+/// Parses a SurrealQL [`Query`], allowing older syntax behaviours to be selected
+#[instrument(level = "trace", target = "surrealdb::core::syn", fields(length = input.len()))]
+pub(crate) fn parse_with_compatibility(
+	input: &str,
+	capabilities: &Capabilities,
+	legacy_strands: bool,
+	flexible_record_id: bool,
+) -> Result<Query, Error> {
 	trace!(target: TARGET, "Parsing SurrealQL query");
 
 	if input.len() > u32::MAX as usize {
@@ -81,7 +98,8 @@ pub fn parse_with_capabilities(input: &str, capabilities: &Capabilities) -> Resu
 				.allows_experimental(&ExperimentalTarget::BearerAccess),
 			define_api_enabled: capabilities.allows_experimental(&ExperimentalTarget::DefineApi),
 			files_enabled: capabilities.allows_experimental(&ExperimentalTarget::Files),
-			..Default::default()
+			legacy_strands,
+			flexible_record_id,
 		},
 	);
 	let mut stack = Stack::new();
@@ -385,6 +403,41 @@ pub(crate) fn fields_with_capabilities(
 		.map_err(Error::InvalidQuery)
 }
 
+/// Parses a SurrealQL [`Idiom`]
+#[instrument(level = "trace", target = "surrealdb::core::syn", fields(length = input.len()))]
+pub(crate) fn idiom_with_capabilities(
+	input: &str,
+	capabilities: &Capabilities,
+) -> Result<Idiom, Error> {
+	trace!(target: TARGET, "Parsing SurrealQL idiom");
+
+	if input.len() > u32::MAX as usize {
+		return Err(Error::QueryTooLarge);
+	}
+
+	let mut parser = Parser::new_with_settings(
+		input.as_bytes(),
+		ParserSettings {
+			object_recursion_limit: *MAX_OBJECT_PARSING_DEPTH as usize,
+			query_recursion_limit: *MAX_QUERY_PARSING_DEPTH as usize,
+			references_enabled: capabilities
+				.allows_experimental(&ExperimentalTarget::RecordReferences),
+			bearer_access_enabled: capabilities
+				.allows_experimental(&ExperimentalTarget::BearerAccess),
+			files_enabled: capabilities.allows_experimental(&ExperimentalTarget::Files),
+			..Default::default()
+		},
+	);
+	parser.table_as_field = true;
+	let mut stack = Stack::new();
+	stack
+		.enter(|stk| parser.parse_plain_idiom(stk))
+		.finish()
+		.and_then(|e| parser.assert_finished().map(|_| e))
+		.map_err(|e| e.render_on(input))
+		.map_err(Error::InvalidQuery)
+}
+
 /// Parses fields for a SELECT statement
 #[instrument(level = "trace", target = "surrealdb::core::syn", fields(length = input.len()))]
 pub(crate) fn fetchs_with_capabilities(
@@ -981,6 +981,10 @@ impl Parser<'_> {
 					self.pop_peek();
 					res.readonly = true;
 				}
+				t!("SENSITIVE") => {
+					self.pop_peek();
+					res.sensitive = true;
+				}
 				t!("VALUE") => {
 					self.pop_peek();
 					res.value = Some(ctx.run(|ctx| self.parse_value_field(ctx)).await?);
@@ -7,7 +7,10 @@ use crate::{
 		Field, Fields, Idioms, Limit, Order, Split, Splits, Start, Values, Version,
 	},
 	syn::{
-		parser::{mac::expected, ParseResult, Parser},
+		parser::{
+			mac::{expected, unexpected},
+			ParseResult, Parser,
+		},
 		token::{t, Span},
 	},
 };
@@ -178,11 +181,22 @@ impl Parser<'_> {
 			}
 			_ => true,
 		};
+		let nulls_first = if self.eat(t!("NULLS")) {
+			let next = self.next();
+			match next.kind {
+				t!("FIRST") => Some(true),
+				t!("LAST") => Some(false),
+				_ => unexpected!(self, next, "`FIRST` or `LAST`"),
+			}
+		} else {
+			None
+		};
 		Ok(Order {
 			value: start,
 			collate,
 			numeric,
 			direction,
+			nulls_first,
 		})
 	}
 
@@ -1926,6 +1926,7 @@ fn parse_define_field() {
 				overwrite: false,
 				reference: None,
 				default_always: false,
+				sensitive: false,
 			}))
 		)
 	}
@@ -1960,6 +1961,25 @@ fn parse_define_field() {
 				overwrite: false,
 				reference: None,
 				default_always: false,
+				sensitive: false,
+			}))
+		)
+	}
+
+	// Sensitive field
+	{
+		let res =
+			test_parse!(parse_stmt, r#"DEFINE FIELD ssn ON TABLE person TYPE string SENSITIVE"#)
+				.unwrap();
+
+		assert_eq!(
+			res,
+			Statement::Define(DefineStatement::Field(DefineFieldStatement {
+				name: Idiom(vec![Part::Field(Ident("ssn".to_owned())),]),
+				what: Ident("person".to_owned()),
+				kind: Some(Kind::String),
+				sensitive: true,
+				..Default::default()
 			}))
 		)
 	}
@@ -2345,6 +2365,7 @@ SELECT bar as foo,[1,2],bar OMIT bar FROM ONLY a,1
 				collate: true,
 				numeric: true,
 				direction: true,
+				nulls_first: None,
 			}]))),
 			limit: Some(Limit(Value::Thing(Thing {
 				tb: "a".to_owned(),
@@ -2365,6 +2386,41 @@ SELECT bar as foo,[1,2],bar OMIT bar FROM ONLY a,1
 	);
 }
 
+#[test]
+fn parse_select_order_nulls() {
+	let res =
+		test_parse!(parse_stmt, r#"SELECT * FROM a ORDER BY foo DESC NULLS FIRST, bar NULLS LAST"#)
+			.unwrap();
+	let Statement::Select(SelectStatement {
+		order: Some(order),
+		..
+	}) = res
+	else {
+		panic!("expected a select statement with an ordering")
+	};
+	assert_eq!(
+		order,
+		Ordering::Order(OrderList(vec![
+			Order {
+				value: Idiom(vec![Part::Field(Ident("foo".to_owned()))]),
+				collate: false,
+				numeric: false,
+				direction: false,
+				nulls_first: Some(true),
+			},
+			Order {
+				value: Idiom(vec![Part::Field(Ident("bar".to_owned()))]),
+				collate: false,
+				numeric: false,
+				direction: true,
+				nulls_first: Some(false),
+			},
+		]))
+	);
+	// The nulls policy is rendered back into SurrealQL
+	assert_eq!(order.to_string(), "ORDER BY foo DESC NULLS FIRST, bar NULLS LAST\n");
+}
+
 #[test]
 fn parse_let() {
 	let res = test_parse!(parse_stmt, r#"LET $param = 1"#).unwrap();
@@ -335,6 +335,7 @@ fn statements() -> Vec<Statement> {
 			overwrite: false,
 			reference: None,
 			default_always: false,
+			sensitive: false,
 		})),
 		Statement::Define(DefineStatement::Index(DefineIndexStatement {
 			name: Ident("index".to_owned()),
@@ -525,6 +526,7 @@ fn statements() -> Vec<Statement> {
 				collate: true,
 				numeric: true,
 				direction: true,
+				nulls_first: None,
 			}]))),
 			limit: Some(Limit(Value::Thing(Thing {
 				tb: "a".to_owned(),
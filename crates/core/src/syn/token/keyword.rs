@@ -96,6 +96,7 @@ keyword! {
 	Field => "FIELD",
 	Fields => "FIELDS",
 	Filters => "FILTERS",
+	First => "FIRST",
 	Flexible => "FLEXIBLE",
 	For => "FOR",
 	From => "FROM",
@@ -122,6 +123,7 @@ keyword! {
 	Key => "KEY",
 	KeepPrunedConnections => "KEEP_PRUNED_CONNECTIONS",
 	Kill => "KILL",
+	Last => "LAST",
 	Let => "LET",
 	Limit => "LIMIT",
 	Live => "LIVE",
@@ -141,6 +143,7 @@ keyword! {
 	NoIndex => "NOINDEX",
 	None => "NONE",
 	Null => "NULL",
+	Nulls => "NULLS",
 	Numeric => "NUMERIC",
 	Omit => "OMIT",
 	On => "ON",
@@ -179,6 +182,7 @@ keyword! {
 	Scope => "SCOPE",
 	Search => "SEARCH",
 	Select => "SELECT",
+	Sensitive => "SENSITIVE",
 	Sequence => "SEQUENCE",
 	Session => "SESSION",
 	Set => "SET",
@@ -88,6 +88,18 @@ pub static WEBSOCKET_RESPONSE_BUFFER_SIZE: LazyLock<usize> =
 pub static WEBSOCKET_RESPONSE_FLUSH_PERIOD: LazyLock<u64> =
 	lazy_env_parse!("SURREAL_WEBSOCKET_RESPONSE_FLUSH_PERIOD", u64, 3);
 
+/// How many prepared statements can be stored on each WebSocket connection (default: 100)
+pub static WEBSOCKET_MAX_PREPARED_STATEMENTS: LazyLock<usize> =
+	lazy_env_parse!("SURREAL_WEBSOCKET_MAX_PREPARED_STATEMENTS", usize, 100);
+
+/// How many seconds an unused table scan is kept open on each WebSocket connection (default: 60 seconds)
+pub static WEBSOCKET_SCAN_TIMEOUT: LazyLock<u64> =
+	lazy_env_parse!("SURREAL_WEBSOCKET_SCAN_TIMEOUT", u64, 60);
+
+/// How many live queries can be started with the `live` method on each WebSocket connection (default: 1000)
+pub static WEBSOCKET_MAX_LIVE_QUERIES: LazyLock<usize> =
+	lazy_env_parse!("SURREAL_WEBSOCKET_MAX_LIVE_QUERIES", usize, 1000);
+
 /// The number of runtime worker threads to start (default: the number of CPU cores, minimum 4)
 pub static RUNTIME_WORKER_THREADS: LazyLock<usize> =
 	lazy_env_parse!("SURREAL_RUNTIME_WORKER_THREADS", usize, || {
@@ -12,7 +12,10 @@ use opentelemetry::Context as TelemetryContext;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
+use surrealdb::dbs::Notification;
 use surrealdb::kvs::Datastore;
+use surrealdb::rpc::Data;
+use surrealdb::sql::Value;
 use tokio::sync::RwLock;
 use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
@@ -77,8 +80,31 @@ pub(crate) async fn notifications(
 						};
 						// Ensure the specified WebSocket exists
 						if let Some(rpc) = websocket {
+							// Recompute the aggregate instead of sending the change
+							let aggregate = {
+								rpc.live_aggregates.read().await.get(notification.id.as_ref()).cloned()
+							};
+							if let Some(aggregate) = aggregate {
+								Websocket::refresh_live_aggregate(rpc, notification.id.0, aggregate);
+								continue;
+							}
+							// Buffer the change if it is coalesced within a window
+							let window = {
+								rpc.live_windows.read().await.get(notification.id.as_ref()).cloned()
+							};
+							if let Some(window) = window {
+								Websocket::buffer_live_notification(rpc, window, notification);
+								continue;
+							}
+							// Get the token attached to this live query
+							let token = {
+								rpc.live_tokens.read().await.get(notification.id.as_ref()).cloned()
+							};
 							// Serialize the message to send
-							let message = success(None, notification);
+							let message = match token {
+								Some(token) => success(None, with_token(notification, token)),
+								None => success(None, notification),
+							};
 							// Add telemetry metrics
 							let cx = TelemetryContext::new();
 							let not_ctx = NotificationContext::default()
@@ -100,6 +126,21 @@ pub(crate) async fn notifications(
 	}
 }
 
+/// Echoes a client-chosen token in a live query notification
+fn with_token(notification: Notification, token: String) -> Data {
+	match Value::try_from(Data::from(notification)) {
+		Ok(Value::Object(mut v)) => {
+			v.insert("token".to_string(), token.into());
+			Value::Object(v).into()
+		}
+		Ok(v) => v.into(),
+		Err(err) => {
+			error!("Error serializing live query notification: {err}");
+			Value::None.into()
+		}
+	}
+}
+
 /// Closes all WebSocket connections, waiting for graceful shutdown
 pub(crate) async fn graceful_shutdown(state: Arc<RpcState>) {
 	// Close WebSocket connections, ensuring queued messages are processed
@@ -1,13 +1,16 @@
 use super::RpcState;
+use crate::cnf::WEBSOCKET_MAX_LIVE_QUERIES;
+use crate::cnf::WEBSOCKET_MAX_PREPARED_STATEMENTS;
 use crate::cnf::WEBSOCKET_PING_FREQUENCY;
 use crate::cnf::WEBSOCKET_RESPONSE_BUFFER_SIZE;
 use crate::cnf::WEBSOCKET_RESPONSE_CHANNEL_SIZE;
 use crate::cnf::WEBSOCKET_RESPONSE_FLUSH_PERIOD;
+use crate::cnf::WEBSOCKET_SCAN_TIMEOUT;
 use crate::cnf::{PKG_NAME, PKG_VERSION};
 use crate::rpc::failure::Failure;
 use crate::rpc::format::WsFormat;
-use crate::rpc::response::{failure, IntoRpcResponse};
-use crate::rpc::CONN_CLOSED_ERR;
+use crate::rpc::response::{failure, success, IntoRpcResponse};
+use crate::rpc::{with_token, CONN_CLOSED_ERR};
 use crate::telemetry;
 use crate::telemetry::metrics::ws::RequestContext;
 use crate::telemetry::traces::rpc::span_for_request;
@@ -18,22 +21,28 @@ use futures::stream::FuturesUnordered;
 use futures::{Sink, SinkExt, StreamExt};
 use opentelemetry::trace::FutureExt;
 use opentelemetry::Context as TelemetryContext;
-use std::sync::Arc;
-use std::time::Duration;
-use surrealdb::dbs::Session;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use surrealdb::dbs::{Action, Notification, Session};
 use surrealdb::gql::{Pessimistic, SchemaCache};
 use surrealdb::kvs::Datastore;
 use surrealdb::mem::ALLOC;
 use surrealdb::rpc::format::Format;
 use surrealdb::rpc::Data;
 use surrealdb::rpc::Method;
+use surrealdb::rpc::QueryLog;
+use surrealdb::rpc::QueryLogEntry;
 use surrealdb::rpc::RpcContext;
+use surrealdb::rpc::ScanCursor;
 use surrealdb::sql::Array;
+use surrealdb::sql::Query;
 use surrealdb::sql::Value;
 use surrealdb_core::rpc::RpcProtocolV1;
 use surrealdb_core::rpc::RpcProtocolV2;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
-use tokio::sync::Semaphore;
+use tokio::sync::{RwLock, Semaphore};
 use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use tracing::Instrument;
@@ -43,9 +52,6 @@ use uuid::Uuid;
 /// An error string sent when the server is out of memory
 const SERVER_OVERLOADED: &str = "The server is unable to handle the request";
 
-/// An error string sent when the server is gracefully shutting down
-const SERVER_SHUTTING_DOWN: &str = "The server is gracefully shutting down";
-
 pub struct Websocket {
 	/// The unique id of this WebSocket connection
 	pub(crate) id: Uuid,
@@ -67,6 +73,83 @@ pub struct Websocket {
 	pub(crate) channel: Sender<Message>,
 	/// The GraphQL schema cache stored in advance
 	pub(crate) gql_schema: SchemaCache<Pessimistic>,
+	/// The prepared statements stored on this WebSocket connection
+	pub(crate) prepared: RwLock<HashMap<Uuid, (Instant, Query)>>,
+	/// The table scans held open on this WebSocket connection, with the time they were last used
+	pub(crate) scans: RwLock<HashMap<Uuid, (Instant, ScanCursor)>>,
+	/// The recently executed queries on this WebSocket connection, if they are being recorded
+	pub(crate) query_log: RwLock<Option<QueryLog>>,
+	/// The client-chosen tokens attached to the live queries on this WebSocket connection
+	pub(crate) live_tokens: RwLock<HashMap<Uuid, String>>,
+	/// The aggregates which are recomputed on the changes of live queries on this WebSocket connection
+	pub(crate) live_aggregates: RwLock<HashMap<Uuid, Arc<LiveAggregate>>>,
+	/// The windows over which the notifications of live queries on this WebSocket connection are coalesced
+	pub(crate) live_windows: RwLock<HashMap<Uuid, Arc<LiveWindow>>>,
+	/// The session which defined, the name, and the marker of the temporary tables defined on this WebSocket connection
+	pub(crate) temp_tables: RwLock<Vec<(Arc<Session>, String, String)>>,
+}
+
+/// A debounce or throttle window over which the notifications of a live query are coalesced
+pub(crate) struct LiveWindow {
+	/// How long the notifications are held
+	window: Duration,
+	/// Whether the window starts at the first change, rather than at the latest change
+	throttle: bool,
+	/// Whether the notifications contain patches which need to be merged
+	diff: bool,
+	/// The latest notification for each changed record, in the order of the first changes
+	buffer: Mutex<Vec<Notification>>,
+	/// The number of changes so far, used to detect changes during a debounce
+	changes: AtomicU64,
+	/// Whether a flush of the buffered notifications is already waiting to run
+	pending: AtomicBool,
+}
+
+impl LiveWindow {
+	/// Merges a notification into the latest notification for the same record
+	fn coalesce(&self, notification: Notification) {
+		let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+		let Some(pos) = buffer.iter().position(|v| v.record == notification.record) else {
+			buffer.push(notification);
+			return;
+		};
+		let previous = &mut buffer[pos];
+		match notification.action {
+			// A record which was created and deleted within the window was never seen
+			Action::Delete if previous.action == Action::Create => {
+				buffer.remove(pos);
+			}
+			// A record which was deleted only needs its deletion
+			Action::Delete => *previous = notification,
+			// A record which was deleted and created again has been replaced
+			Action::Create => {
+				previous.action = Action::Update;
+				previous.result = notification.result;
+			}
+			// A record which was created or updated keeps its first action
+			_ => {
+				let result = std::mem::take(&mut previous.result);
+				previous.result = match (self.diff, result, notification.result) {
+					// Apply the patches one after another
+					(true, Value::Array(mut a), Value::Array(b)) => {
+						a.extend(b);
+						Value::Array(a)
+					}
+					(_, _, result) => result,
+				};
+			}
+		}
+	}
+}
+
+/// An aggregate value which is recomputed when its live query is notified of a change
+pub(crate) struct LiveAggregate {
+	/// The query which computes the current aggregate value
+	query: Query,
+	/// How long to wait for further changes before recomputing
+	debounce: Duration,
+	/// Whether a recomputation is already waiting to run
+	pending: AtomicBool,
 }
 
 impl Websocket {
@@ -94,6 +177,13 @@ impl Websocket {
 			session: ArcSwap::from(Arc::new(session)),
 			channel: sender.clone(),
 			gql_schema: SchemaCache::new(datastore.clone()),
+			prepared: RwLock::new(HashMap::new()),
+			scans: RwLock::new(HashMap::new()),
+			query_log: RwLock::new(None),
+			live_tokens: RwLock::new(HashMap::new()),
+			live_aggregates: RwLock::new(HashMap::new()),
+			live_windows: RwLock::new(HashMap::new()),
+			temp_tables: RwLock::new(Vec::new()),
 			datastore,
 		});
 		// Add this WebSocket to the list
@@ -137,6 +227,8 @@ impl Websocket {
 		trace!("WebSocket {id} disconnected");
 		// Cleanup the live queries for this WebSocket
 		rpc.cleanup_lqs().await;
+		// Remove the temporary tables for this WebSocket
+		rpc.cleanup_temp_tables().await;
 		// Remove this WebSocket from the list
 		state.web_sockets.write().await.remove(&id);
 		// Stop telemetry metrics for this connection
@@ -198,7 +290,18 @@ impl Websocket {
 				// Process brances in order
 				biased;
 				// Check if we should teardown
-				_ = canceller.cancelled() => break,
+				_ = canceller.cancelled() => {
+					// Send the responses to any requests drained during a graceful shutdown
+					if rpc.shutdown.is_cancelled() {
+						while let Ok(res) = internal_receiver.try_recv() {
+							if socket.send(res).await.is_err() {
+								break;
+							}
+						}
+					}
+					// Exit out of the loop
+					break;
+				},
 				// Retrieve a response from the channel
 				Some(res) = internal_receiver.recv() => {
 					// Check if the socket is buffered
@@ -328,8 +431,6 @@ impl Websocket {
 
 	/// Handle an individual WebSocket message
 	async fn handle_message(rpc: &Arc<Websocket>, msg: Message, chn: Sender<Message>) {
-		// Clone the WebSocket cancellation token
-		let shutdown = rpc.shutdown.clone();
 		// Clone the WebSocket cancellation token
 		let canceller = rpc.canceller.clone();
 		// Calculate the message length and format
@@ -366,16 +467,8 @@ impl Websocket {
 						_ = canceller.cancelled() => (),
 						// Wait for the message to be processed
 						_ = async move {
-							// Don't start processing if we are gracefully shutting down
-							if shutdown.is_cancelled() {
-								// Process the response
-								failure(req.id, Failure::custom(SERVER_SHUTTING_DOWN))
-									.send(otel_cx.clone(), rpc.format, chn)
-									.with_context(otel_cx.as_ref().clone())
-									.await;
-							}
 							// Check to see whether we have available memory
-							else if ALLOC.is_beyond_threshold() {
+							if ALLOC.is_beyond_threshold() {
 								// Process the response
 								failure(req.id, Failure::custom(SERVER_OVERLOADED))
 									.send(otel_cx.clone(), rpc.format, chn)
@@ -439,6 +532,102 @@ impl Websocket {
 		// Cancel the WebSocket tasks
 		rpc.canceller.cancel();
 	}
+
+	/// Buffers a live query notification, sending the latest notification
+	/// for each changed record once the window has passed
+	pub(crate) fn buffer_live_notification(
+		rpc: Arc<Websocket>,
+		window: Arc<LiveWindow>,
+		notification: Notification,
+	) {
+		let lqid = notification.id.0;
+		// Merge the change into the buffered notifications
+		window.coalesce(notification);
+		window.changes.fetch_add(1, Ordering::AcqRel);
+		// A pending flush will include this change
+		if window.pending.swap(true, Ordering::AcqRel) {
+			return;
+		}
+		tokio::spawn(async move {
+			loop {
+				let changes = window.changes.load(Ordering::Acquire);
+				// Wait for the window to pass
+				tokio::select! {
+					_ = rpc.canceller.cancelled() => return,
+					_ = tokio::time::sleep(window.window) => {},
+				}
+				// A debounce waits until no further changes are made
+				if window.throttle || window.changes.load(Ordering::Acquire) == changes {
+					break;
+				}
+			}
+			// Any changes from now on need another flush
+			window.pending.store(false, Ordering::Release);
+			let notifications = {
+				let mut buffer = window.buffer.lock().unwrap_or_else(|e| e.into_inner());
+				std::mem::take(&mut *buffer)
+			};
+			// Check that the live query has not been killed
+			if !rpc.live_windows.read().await.contains_key(&lqid) {
+				return;
+			}
+			// Get the token attached to this live query
+			let token = rpc.live_tokens.read().await.get(&lqid).cloned();
+			// Send the coalesced notifications to the client
+			for notification in notifications {
+				let message = match &token {
+					Some(token) => success(None, with_token(notification, token.clone())),
+					None => success(None, notification),
+				};
+				let cx = Arc::new(TelemetryContext::new());
+				message.send(cx, rpc.format, rpc.channel.clone()).await;
+			}
+		});
+	}
+
+	/// Recomputes a live aggregate after a change, coalescing any further
+	/// changes which are made within the debounce period
+	pub(crate) fn refresh_live_aggregate(
+		rpc: Arc<Websocket>,
+		lqid: Uuid,
+		aggregate: Arc<LiveAggregate>,
+	) {
+		// A pending recomputation will include this change
+		if aggregate.pending.swap(true, Ordering::AcqRel) {
+			return;
+		}
+		tokio::spawn(async move {
+			// Wait for any further changes
+			tokio::select! {
+				_ = rpc.canceller.cancelled() => return,
+				_ = tokio::time::sleep(aggregate.debounce) => {},
+			}
+			// Any changes from now on need another recomputation
+			aggregate.pending.store(false, Ordering::Release);
+			// Recompute the aggregate with the current session
+			let session = rpc.session();
+			let vars = Some(session.parameters.clone());
+			let value = match rpc.datastore.process(aggregate.query.clone(), &session, vars).await {
+				Ok(mut res) => res.remove(0).result,
+				Err(err) => Err(err),
+			};
+			let value = match value {
+				Ok(value) => value,
+				Err(err) => {
+					warn!("Error recomputing live aggregate {lqid}: {err}");
+					return;
+				}
+			};
+			// Check that the live aggregate has not been killed
+			if !rpc.live_aggregates.read().await.contains_key(&lqid) {
+				return;
+			}
+			// Send the recomputed value to the client
+			let notification = Notification::new(lqid.into(), Action::Update, Value::None, value);
+			let cx = Arc::new(TelemetryContext::new());
+			success(None, notification).send(cx, rpc.format, rpc.channel.clone()).await;
+		});
+	}
 }
 
 impl RpcProtocolV1 for Websocket {}
@@ -466,6 +655,15 @@ impl RpcContext for Websocket {
 		format!("{PKG_NAME}-{}", *PKG_VERSION).into()
 	}
 
+	// ------------------------------
+	// Shutdown
+	// ------------------------------
+
+	/// New method calls are rejected once a graceful shutdown has started
+	fn is_shutting_down(&self) -> bool {
+		self.shutdown.is_cancelled()
+	}
+
 	// ------------------------------
 	// Realtime
 	// ------------------------------
@@ -479,13 +677,81 @@ impl RpcContext for Websocket {
 		trace!("Registered live query {lqid} on websocket {}", self.id);
 	}
 
+	/// Attaches a client-chosen token to the notifications of a live query
+	async fn handle_live_token(&self, lqid: &Uuid, token: String) {
+		self.live_tokens.write().await.insert(*lqid, token);
+	}
+
+	/// Coalesces the notifications of a live query which are made within a debounce or throttle window
+	async fn handle_live_window(&self, lqid: &Uuid, window: Duration, throttle: bool, diff: bool) {
+		let window = LiveWindow {
+			window,
+			throttle,
+			diff,
+			buffer: Mutex::new(Vec::new()),
+			changes: AtomicU64::new(0),
+			pending: AtomicBool::new(false),
+		};
+		self.live_windows.write().await.insert(*lqid, Arc::new(window));
+	}
+
+	/// Registers a live query whose notifications are replaced by a recomputed aggregate value
+	async fn handle_live_aggregate(&self, lqid: &Uuid, query: Query, debounce: Duration) {
+		let aggregate = LiveAggregate {
+			query,
+			debounce,
+			pending: AtomicBool::new(false),
+		};
+		self.live_aggregates.write().await.insert(*lqid, Arc::new(aggregate));
+	}
+
 	/// Handles the execution of a KILL statement
 	async fn handle_kill(&self, lqid: &Uuid) {
+		self.live_tokens.write().await.remove(lqid);
+		self.live_aggregates.write().await.remove(lqid);
+		self.live_windows.write().await.remove(lqid);
 		if let Some(id) = self.state.live_queries.write().await.remove(lqid) {
 			trace!("Unregistered live query {lqid} on websocket {id}");
 		}
 	}
 
+	/// Checks if a live query is still active on this websocket
+	async fn has_live_query(&self, lqid: &Uuid) -> bool {
+		// Check that the live query belongs to this connection
+		if self.state.live_queries.read().await.get(lqid) != Some(&self.id) {
+			return false;
+		}
+		// Check that the live query has not been killed
+		self.kvs().has_query(*lqid).await.unwrap_or(false)
+	}
+
+	/// Checks if a live query on this node belongs to a connected websocket
+	async fn has_live_session(&self, lqid: &Uuid, session: Option<Uuid>) -> bool {
+		// Find the websocket which is tracking the live query, or which
+		// started the live query but has not yet started tracking it
+		let tracked = self.state.live_queries.read().await.get(lqid).copied();
+		let Some(id) = tracked.or(session) else {
+			return false;
+		};
+		// Check that the websocket is still connected
+		self.state.web_sockets.read().await.contains_key(&id)
+	}
+
+	/// Counts the live queries which are active on this websocket
+	async fn live_query_count(&self) -> usize {
+		self.state.live_queries.read().await.values().filter(|id| *id == &self.id).count()
+	}
+
+	/// The maximum number of live queries which can be started on this websocket
+	fn max_live_queries(&self) -> Option<usize> {
+		Some(*WEBSOCKET_MAX_LIVE_QUERIES)
+	}
+
+	/// Estimates the number of messages queued for sending on this websocket
+	fn buffered_messages(&self) -> usize {
+		self.channel.max_capacity() - self.channel.capacity()
+	}
+
 	/// Handles the cleanup of live queries
 	async fn cleanup_lqs(&self) {
 		let mut gc = Vec::new();
@@ -498,12 +764,177 @@ impl RpcContext for Websocket {
 			}
 			true
 		});
+		// Remove the tokens, aggregates, and windows attached to the live queries
+		self.live_tokens.write().await.clear();
+		self.live_aggregates.write().await.clear();
+		self.live_windows.write().await.clear();
 		// Garbage collect the live queries on this connection
 		if let Err(err) = self.kvs().delete_queries(gc).await {
 			error!("Error handling RPC connection: {err}");
 		}
 	}
 
+	// ------------------------------
+	// Prepared statements
+	// ------------------------------
+
+	/// Prepared statements are enabled on WebSockets
+	const PREPARED_SUPPORT: bool = true;
+
+	/// Counts the prepared queries which are stored on this websocket
+	async fn prepared_count(&self) -> usize {
+		self.prepared.read().await.len()
+	}
+
+	/// Stores a prepared query, expiring the oldest one if the limit is reached
+	async fn store_prepared(&self, query: Query) -> Uuid {
+		let id = Uuid::new_v4();
+		let mut prepared = self.prepared.write().await;
+		if prepared.len() >= *WEBSOCKET_MAX_PREPARED_STATEMENTS {
+			let oldest = prepared.iter().min_by_key(|(_, (at, _))| *at).map(|(id, _)| *id);
+			if let Some(oldest) = oldest {
+				trace!("Expiring prepared statement {oldest} on websocket {}", self.id);
+				prepared.remove(&oldest);
+			}
+		}
+		prepared.insert(id, (Instant::now(), query));
+		id
+	}
+
+	/// Retrieves a prepared query stored on this websocket
+	async fn get_prepared(&self, id: &Uuid) -> Option<Query> {
+		self.prepared.read().await.get(id).map(|(_, query)| query.clone())
+	}
+
+	// ------------------------------
+	// Table scans
+	// ------------------------------
+
+	/// Table scans are enabled on WebSockets
+	const SCAN_SUPPORT: bool = true;
+
+	/// Stores the cursor of a table scan, expiring any abandoned scans
+	async fn open_scan(&self, cursor: ScanCursor) -> Uuid {
+		let id = Uuid::new_v4();
+		let mut scans = self.scans.write().await;
+		let timeout = Duration::from_secs(*WEBSOCKET_SCAN_TIMEOUT);
+		scans.retain(|_, (at, _)| at.elapsed() < timeout);
+		scans.insert(id, (Instant::now(), cursor));
+		id
+	}
+
+	/// Retrieves the cursor of a table scan, expiring it if it was abandoned
+	async fn get_scan(&self, id: &Uuid) -> Option<ScanCursor> {
+		let mut scans = self.scans.write().await;
+		let timeout = Duration::from_secs(*WEBSOCKET_SCAN_TIMEOUT);
+		match scans.get_mut(id) {
+			Some((at, _)) if at.elapsed() >= timeout => {
+				trace!("Expiring table scan {id} on websocket {}", self.id);
+				scans.remove(id);
+				None
+			}
+			Some((at, cursor)) => {
+				*at = Instant::now();
+				Some(cursor.clone())
+			}
+			None => None,
+		}
+	}
+
+	/// Moves the cursor of a table scan stored on this websocket
+	async fn update_scan(&self, id: &Uuid, cursor: ScanCursor) {
+		if let Some(scan) = self.scans.write().await.get_mut(id) {
+			*scan = (Instant::now(), cursor);
+		}
+	}
+
+	/// Closes a table scan stored on this websocket
+	async fn close_scan(&self, id: &Uuid) -> bool {
+		self.scans.write().await.remove(id).is_some()
+	}
+
+	// ------------------------------
+	// Query log
+	// ------------------------------
+
+	/// The query log is enabled on WebSockets
+	const QUERY_LOG_SUPPORT: bool = true;
+
+	/// Starts recording the queries executed on this websocket
+	async fn start_query_log(&self, log: QueryLog) {
+		*self.query_log.write().await = Some(log);
+	}
+
+	/// Stops recording the queries executed on this websocket
+	async fn stop_query_log(&self) -> bool {
+		self.query_log.write().await.take().is_some()
+	}
+
+	/// Checks if the queries executed on this websocket are being recorded
+	async fn is_logging_queries(&self) -> bool {
+		self.query_log.read().await.is_some()
+	}
+
+	/// Records a query in the query log of this websocket
+	async fn log_query(&self, entry: QueryLogEntry) {
+		if let Some(log) = self.query_log.write().await.as_mut() {
+			log.push(entry);
+		}
+	}
+
+	/// Retrieves the queries recorded on this websocket
+	async fn query_log(&self, clear: bool) -> Option<Vec<QueryLogEntry>> {
+		let mut log = self.query_log.write().await;
+		let log = log.as_mut()?;
+		let entries = log.entries();
+		if clear {
+			log.clear();
+		}
+		Some(entries)
+	}
+
+	// ------------------------------
+	// Temporary tables
+	// ------------------------------
+
+	/// Temporary tables are enabled on WebSockets
+	const TEMP_TABLE_SUPPORT: bool = true;
+
+	/// Tracks a temporary table defined on this websocket
+	async fn add_temp_table(&self, sess: Arc<Session>, tb: String, marker: String) {
+		self.temp_tables.write().await.push((sess, tb, marker));
+	}
+
+	/// Removes the temporary tables defined on this websocket
+	async fn cleanup_temp_tables(&self) {
+		let tables = std::mem::take(&mut *self.temp_tables.write().await);
+		for (sess, tb, marker) in tables {
+			trace!("Removing temporary table {tb} on websocket {}", self.id);
+			// The table is removed with the session which defined it
+			if let Err(err) = self.kvs().remove_temp_table(&sess, &tb, &marker).await {
+				error!("Error removing temporary table: {err}");
+			}
+		}
+	}
+
+	// ------------------------------
+	// Streamed queries
+	// ------------------------------
+
+	/// Streamed queries are enabled on WebSockets
+	const STREAM_SUPPORT: bool = true;
+
+	/// Sends the response of a single statement of a streamed query to the client
+	async fn send_stream(&self, token: &str, index: usize, response: Value) {
+		let message = map! {
+			"stream".to_string() => Value::from(token),
+			"index".to_string() => Value::from(index),
+			"response".to_string() => response,
+		};
+		let cx = Arc::new(TelemetryContext::new());
+		success(None, Value::from(message)).send(cx, self.format, self.channel.clone()).await;
+	}
+
 	// ------------------------------
 	// GraphQL
 	// ------------------------------
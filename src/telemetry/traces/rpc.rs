@@ -17,6 +17,7 @@ pub fn span_for_request(ws_id: &Uuid) -> Span {
 		rpc.method = field::Empty,
 		rpc.service = "surrealdb",
 		rpc.request_id = field::Empty,
+		rpc.tag = field::Empty,
 		rpc.error_code = field::Empty,
 		rpc.error_message = field::Empty,
 	);
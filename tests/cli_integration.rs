@@ -1170,6 +1170,58 @@ mod cli_integration {
 		}
 	}
 
+	#[cfg(unix)]
+	#[test(tokio::test)]
+	async fn test_server_graceful_shutdown_drains_requests() {
+		let (addr, mut server) = common::start_server_without_auth().await.unwrap();
+
+		// Start a request which is still running when the shutdown starts
+		let socket =
+			Socket::connect(&addr, None, Format::Json).await.expect("Failed to connect to server");
+
+		let send_future = socket.send_request("query", json!(["SLEEP 2s; RETURN 'done';"]));
+
+		let signal_send_fut = async {
+			// Make sure the SLEEP query is being executed
+			tokio::time::timeout(time::Duration::from_secs(10), async {
+				loop {
+					let err = server.stdout_and_stderr();
+					if err.contains("SLEEP 2s") {
+						break;
+					}
+					tokio::time::sleep(time::Duration::from_millis(100)).await;
+				}
+			})
+			.await
+			.expect("Server didn't start executing the SLEEP query");
+
+			info!("* Send SIGINT signal");
+			server
+				.send_signal(nix::sys::signal::Signal::SIGINT)
+				.expect("Failed to send SIGINT to server");
+		};
+
+		// The running request is completed before the server shuts down
+		let (res, _) = futures::future::join(send_future, signal_send_fut).await;
+		let res = res.expect("The running request was not completed");
+		assert_eq!(res["result"][1]["result"], "done", "result: {res:?}");
+
+		info!("* Waiting for server to exit gracefully ...");
+		tokio::time::timeout(time::Duration::from_secs(5), async {
+			loop {
+				if let Ok(Some(exit)) = server.status() {
+					assert!(exit.success(), "Server didn't shutdown successfully");
+					break;
+				}
+				tokio::time::sleep(time::Duration::from_millis(100)).await;
+			}
+		})
+		.await
+		.expect("Server didn't exit after draining the running request");
+
+		server.finish().unwrap();
+	}
+
 	#[cfg(unix)]
 	#[test(tokio::test)]
 	async fn test_server_second_signal_handling() {
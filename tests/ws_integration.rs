@@ -26,6 +26,7 @@ use assert_fs::TempDir;
 use common::{Format, Socket, StartServerArguments, DB, NS, PASS, USER};
 use http::header::{HeaderMap, HeaderValue};
 use serde_json::json;
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::time::Duration;
@@ -96,6 +97,38 @@ pub async fn info(cfg_server: Option<Format>, cfg_format: Format) {
 	server.finish().unwrap();
 }
 
+pub async fn footprint(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send FOOTPRINT command on an empty session
+	let res = socket.send_versioned_request(Some(2), "footprint", json!([])).await.unwrap();
+	assert_eq!(res["result"]["vars"], json!({ "count": 0, "bytes": 0 }), "result: {res:?}");
+	assert_eq!(res["result"]["prepared"], 0, "result: {res:?}");
+	assert_eq!(res["result"]["live"], 0, "result: {res:?}");
+	// Grow the session
+	socket.send_request("let", json!(["name", "value"])).await.unwrap();
+	socket
+		.send_versioned_request(Some(2), "prepare", json!(["SELECT * FROM tester"]))
+		.await
+		.unwrap();
+	socket.send_request("live", json!(["tester"])).await.unwrap();
+	// Send FOOTPRINT command on the grown session
+	let res = socket.send_versioned_request(Some(2), "footprint", json!([])).await.unwrap();
+	assert_eq!(res["result"]["vars"]["count"], 1, "result: {res:?}");
+	assert!(res["result"]["vars"]["bytes"].as_u64().unwrap() > 0, "result: {res:?}");
+	assert_eq!(res["result"]["prepared"], 1, "result: {res:?}");
+	assert_eq!(res["result"]["live"], 1, "result: {res:?}");
+	assert!(res["result"]["buffered"].is_number(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
 pub async fn signup(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
@@ -305,6 +338,45 @@ pub async fn unset(cfg_server: Option<Format>, cfg_format: Format) {
 	server.finish().unwrap();
 }
 
+pub async fn vars(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send VARS command without any variables
+	let res = socket.send_versioned_request(Some(2), "vars", json!([])).await.unwrap();
+	assert_eq!(res["result"], json!({}), "result: {res:?}");
+	// Send LET commands
+	socket.send_request("let", json!(["name", "value"])).await.unwrap();
+	socket.send_request("let", json!(["count", 1])).await.unwrap();
+	// Send VARS command
+	let res = socket.send_versioned_request(Some(2), "vars", json!([])).await.unwrap();
+	assert_eq!(res["result"], json!({ "count": 1, "name": "value" }), "result: {res:?}");
+	// Send VARS command for only the variable names
+	let res =
+		socket.send_versioned_request(Some(2), "vars", json!([{ "values": false }])).await.unwrap();
+	assert_eq!(res["result"], json!(["count", "name"]), "result: {res:?}");
+	// Send UNSET command
+	socket.send_request("unset", json!(["name"])).await.unwrap();
+	let res = socket.send_versioned_request(Some(2), "vars", json!([null])).await.unwrap();
+	assert_eq!(res["result"], json!({ "count": 1 }), "result: {res:?}");
+	// The variables of another connection are not returned
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "vars", json!([])).await.unwrap();
+	assert_eq!(res["result"], json!({}), "result: {res:?}");
+	// Send VARS command with invalid options
+	let res =
+		socket.send_versioned_request(Some(2), "vars", json!([{ "values": "no" }])).await.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
 pub async fn select(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
@@ -328,7 +400,7 @@ pub async fn select(cfg_server: Option<Format>, cfg_format: Format) {
 	server.finish().unwrap();
 }
 
-pub async fn insert(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_compact(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -337,70 +409,31 @@ pub async fn insert(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Send INSERT command
+	// Create a test record
+	socket.send_message_query("CREATE tester SET name = 'foo', value = 'bar'").await.unwrap();
+	// Send SELECT command with compact results
 	let res = socket
-		.send_request(
-			"insert",
+		.send_versioned_request(
+			Some(2),
+			"select",
 			json!([
 				"tester",
 				{
-					"name": "foo",
-					"value": "bar",
+					"compact": true,
 				}
 			]),
 		)
 		.await
 		.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
 	assert!(res["result"].is_array(), "result: {res:?}");
 	let res = res["result"].as_array().unwrap();
 	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
-	// Send INSERT command trying to create multiple records
-	let res = socket
-		.send_request(
-			"insert",
-			json!([
-				"tester",
-				[
-					{
-						"name": "foo",
-						"value": "bar",
-					},
-					{
-						"name": "foo",
-						"value": "bar",
-					}
-				]
-			]),
-		)
-		.await
-		.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 2, "result: {res:?}");
-	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
-	assert_eq!(res[1]["name"], "foo", "result: {res:?}");
-	assert_eq!(res[1]["value"], "bar", "result: {res:?}");
-	// Verify the data was inserted and can be queried
-	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
-	assert!(res[0]["result"].is_array(), "result: {res:?}");
-	let res = res[0]["result"].as_array().unwrap();
-	assert_eq!(res.len(), 3, "result: {res:?}");
-	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
-	assert_eq!(res[1]["name"], "foo", "result: {res:?}");
-	assert_eq!(res[1]["value"], "bar", "result: {res:?}");
-	assert_eq!(res[2]["name"], "foo", "result: {res:?}");
-	assert_eq!(res[2]["value"], "bar", "result: {res:?}");
+	assert_eq!(res[0], json!({ "name": "foo", "value": "bar" }), "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn create(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_flatten(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -409,34 +442,64 @@ pub async fn create(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Send CREATE command
+	// Create the test records
+	socket
+		.send_message_query(
+			"CREATE tester:1 SET tags = ['a', 'b']; CREATE tester:2 SET tags = ['c', ['d']];",
+		)
+		.await
+		.unwrap();
+	// Send SELECT command with a flattened VALUE projection
 	let res = socket
-		.send_request(
-			"create",
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "fields": "VALUE tags", "flatten": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(["a", "b", "c", ["d"]]), "result: {res:?}");
+	// Send SELECT command with a flattened record selection
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "fields": "tags", "flatten": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([{ "tags": ["a", "b"] }, { "tags": ["c", ["d"]] }]),
+		"result: {res:?}"
+	);
+	// Send QUERY command with flattened results
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
 			json!([
-				"tester",
-				{
-					"value": "bar",
-				}
+				"SELECT VALUE tags FROM tester; SELECT VALUE tags FROM tester; RETURN 1",
+				null,
+				{ "flatten": true }
 			]),
 		)
 		.await
 		.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["value"], "bar", "result: {res:?}");
-	// Verify the data was created
-	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
-	assert!(res[0]["result"].is_array(), "result: {res:?}");
-	let res = res[0]["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	assert_eq!(res["result"][0]["result"], json!(["a", "b", "c", ["d"]]), "result: {res:?}");
+	assert_eq!(res["result"][1]["result"], json!(["a", "b", "c", ["d"]]), "result: {res:?}");
+	assert_eq!(res["result"][2]["result"], json!(1), "result: {res:?}");
+	// Send QUERY command without flattened results
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["SELECT VALUE tags FROM tester"]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"], json!([["a", "b"], ["c", ["d"]]]), "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn update(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_with_total(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -445,38 +508,34 @@ pub async fn update(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Create a test record
-	socket.send_message_query("CREATE tester SET name = 'foo'").await.unwrap();
-	// Send UPDATE command
+	// Create test records
+	socket.send_message_query("CREATE |tester:1..5| SET value = id.id()").await.unwrap();
+	// Send SELECT command for a page with the total
 	let res = socket
-		.send_request(
-			"update",
+		.send_versioned_request(
+			Some(2),
+			"select",
 			json!([
 				"tester",
 				{
-					"value": "bar",
+					"cond": "value > 1",
+					"limit": 2,
+					"with_total": true,
 				}
 			]),
 		)
 		.await
 		.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
-	// Verify the data was updated
-	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
-	assert!(res[0]["result"].is_array(), "result: {res:?}");
-	let res = res[0]["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert_eq!(res[0]["name"], json!(null), "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	assert_eq!(res["result"]["total"], 4, "result: {res:?}");
+	let res = res["result"]["results"].as_array().unwrap();
+	assert_eq!(res.len(), 2, "result: {res:?}");
+	assert_eq!(res[0]["value"], 2, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn merge(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_max_results(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -485,39 +544,86 @@ pub async fn merge(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Create a test record
-	socket.send_message_query("CREATE tester SET name = 'foo'").await.unwrap();
-	// Send UPDATE command
+	// Create some records
+	let res = socket.send_message_query("CREATE |tester:1..5|").await.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Send SELECT command with a maximum which is not exceeded
 	let res = socket
-		.send_request(
-			"merge",
-			json!([
-				"tester",
-				{
-					"value": "bar",
-				}
-			]),
+		.send_versioned_request(Some(2), "select", json!(["tester", { "max_results": 5 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"].as_array().unwrap().len(), 5, "result: {res:?}");
+	// Send SELECT command with a maximum which is exceeded
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "max_results": 4 }]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"], "The result exceeds the maximum of 4 results",
+		"result: {res:?}"
+	);
+	// Send SELECT command with a limit within the maximum
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "max_results": 4, "limit": 3 }]),
 		)
 		.await
 		.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
-	// Verify the data was merged
-	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
-	assert!(res[0]["result"].is_array(), "result: {res:?}");
-	let res = res[0]["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	assert_eq!(res["result"].as_array().unwrap().len(), 3, "result: {res:?}");
+	// Send SELECT command with a limit above the maximum
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "max_results": 4, "limit": 10 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"], "The result exceeds the maximum of 4 results",
+		"result: {res:?}"
+	);
+	// Send QUERY command with a maximum which is not exceeded
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT * FROM tester; RETURN 1", null, { "max_results": 5 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"].as_array().unwrap().len(), 5, "result: {res:?}");
+	// Send QUERY command with a maximum which is exceeded by any statement
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["RETURN 1; SELECT * FROM tester", null, { "max_results": 2 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"], "The result exceeds the maximum of 2 results",
+		"result: {res:?}"
+	);
+	// Send SELECT and QUERY commands with an invalid maximum
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "max_results": -1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["RETURN 1", null, { "max_results": "1" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn patch(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_paginate(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -526,44 +632,50 @@ pub async fn patch(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Create a test record
-	socket.send_message_query("CREATE tester:id SET name = 'foo'").await.unwrap();
-	// Send PATCH command
+	// Create the test records
+	socket.send_message_query("CREATE |tester:1..5|").await.unwrap();
+	// Send SELECT command for the first page
 	let res = socket
-		.send_request(
-			"patch",
-			json!([
-				"tester:id",
-				[
-					{
-						"op": "add",
-						"path": "value",
-						"value": "bar"
-					},
-					{
-						"op": "remove",
-						"path": "name",
-					}
-				]
-			]),
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "paginate": true, "limit": 2 }]),
 		)
 		.await
 		.unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res.get("value"), Some(json!("bar")).as_ref(), "result: {res:?}");
-	// Verify the data was patched
-	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
-	assert!(res[0]["result"].is_array(), "result: {res:?}");
-	let res = res[0]["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert_eq!(res[0]["name"], json!(null), "result: {res:?}");
-	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	let res = &res["result"];
+	assert_eq!(res["results"].as_array().unwrap().len(), 2, "result: {res:?}");
+	assert_eq!(res["page"], 1, "result: {res:?}");
+	assert_eq!(res["page_size"], 2, "result: {res:?}");
+	assert_eq!(res["total"], 5, "result: {res:?}");
+	assert_eq!(res["total_pages"], 3, "result: {res:?}");
+	assert_eq!(res["has_next"], true, "result: {res:?}");
+	assert_eq!(res["has_prev"], false, "result: {res:?}");
+	// Send SELECT command for the last page
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "paginate": true, "start": 4, "limit": 2 }]),
+		)
+		.await
+		.unwrap();
+	let res = &res["result"];
+	assert_eq!(res["results"].as_array().unwrap().len(), 1, "result: {res:?}");
+	assert_eq!(res["page"], 3, "result: {res:?}");
+	assert_eq!(res["has_next"], false, "result: {res:?}");
+	assert_eq!(res["has_prev"], true, "result: {res:?}");
+	// Send SELECT command without a page size
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "paginate": true }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn delete(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_distinct(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -572,33 +684,60 @@ pub async fn delete(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Create a test record
-	socket.send_message_query("CREATE tester:id").await.unwrap();
-	// Send DELETE command
-	let res = socket.send_request("delete", json!(["tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert_eq!(res[0]["id"], "tester:id", "result: {res:?}");
-	// Create a test record
-	socket.send_message_query("CREATE tester:id").await.unwrap();
-	// Send DELETE command
-	let res = socket.send_request("delete", json!(["tester:id"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:id", "result: {res:?}");
-	// Verify the data was merged
-	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
-	assert!(res[0]["result"].is_array(), "result: {res:?}");
-	let res = res[0]["result"].as_array().unwrap();
-	assert_eq!(res.len(), 0, "result: {res:?}");
+	// Create records with repeated values
+	let res = socket
+		.send_message_query(
+			"CREATE tester:1 SET status = 'open', info.kind = 'a';
+			CREATE tester:2 SET status = 'done', info.kind = 'b';
+			CREATE tester:3 SET status = 'open', info.kind = 'a';
+			CREATE tester:4 SET status = 'closed', info.kind = 'a';",
+		)
+		.await
+		.unwrap();
+	assert!(res.iter().all(|v| v["status"] == "OK"), "result: {res:?}");
+	// Send SELECT command for the distinct values of a field
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "distinct": "status" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(["closed", "done", "open"]), "result: {res:?}");
+	// Send SELECT command for the distinct values of a nested field
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "distinct": "info.kind" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(["a", "b"]), "result: {res:?}");
+	// Send SELECT command for the distinct values of matching records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "distinct": "status", "cond": "status != 'done'", "limit": 1 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(["closed"]), "result: {res:?}");
+	// Send SELECT command for distinct values with a total
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "distinct": "status", "with_total": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send SELECT command with an invalid field
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "distinct": 1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn query(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_columns(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -607,40 +746,140 @@ pub async fn query(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Send QUERY command
+	// Create records with different fields
 	let res = socket
-		.send_request("query", json!(["CREATE tester; SELECT * FROM tester;",]))
+		.send_message_query(
+			"CREATE tester:1 SET name = 'a', age = 1, info.city = 'x'; CREATE tester:2 SET name = 'b';",
+		)
 		.await
 		.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 2, "result: {res:?}");
-	// Verify the data was created
-	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
-	assert!(res[0]["result"].is_array(), "result: {res:?}");
-	let res = res[0]["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert!(res.iter().all(|v| v["status"] == "OK"), "result: {res:?}");
+	// Send SELECT command returning ordered rows
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "columns": ["id", "name", "age", "info.city"] }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!({
+			"columns": ["id", "name", "age", "info.city"],
+			"rows": [["tester:1", "a", 1, "x"], ["tester:2", "b", null, null]],
+		}),
+		"result: {res:?}"
+	);
+	// Send SELECT command for a single record
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester:2", { "columns": ["name"] }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!({ "columns": ["name"], "rows": [["b"]] }), "result: {res:?}");
+	// Send SELECT command for a record which does not exist
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester:3", { "columns": ["name"] }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!({ "columns": ["name"], "rows": [] }), "result: {res:?}");
+	// Send SELECT command with both columns and fields
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "fields": "name", "columns": ["name"] }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send SELECT command with invalid columns
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "columns": "name" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn version(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_omit(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
-	let socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
-	// Send version command
-	let res = socket.send_request("version", json!([])).await.unwrap();
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let res = res["result"].as_str().unwrap();
-	assert!(res.starts_with("surrealdb-"), "result: {res}");
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create records with fields to omit
+	let res = socket
+		.send_message_query(
+			"CREATE owner:1 SET name = 'o', secret = 's';
+			CREATE tester:1 SET name = 'a', secret = 's', info = { city: 'x', code: 1 }, owner = owner:1;",
+		)
+		.await
+		.unwrap();
+	assert!(res.iter().all(|v| v["status"] == "OK"), "result: {res:?}");
+	// Send SELECT command omitting a field
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "omit": "secret" }]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([{ "id": "tester:1", "name": "a", "info": { "city": "x", "code": 1 }, "owner": "owner:1" }]),
+		"result: {res:?}"
+	);
+	// Send SELECT command omitting several fields, including nested fields
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester:1", { "omit": ["secret", "info.code", "owner"] }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([{ "id": "tester:1", "name": "a", "info": { "city": "x" } }]),
+		"result: {res:?}"
+	);
+	// Send SELECT command omitting fields after the projection
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "fields": "name, secret", "omit": "secret" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!([{ "name": "a" }]), "result: {res:?}");
+	// Send SELECT command omitting fields from the fetched records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "fetch": "owner", "omit": ["secret", "info", "owner.secret"] }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([{ "id": "tester:1", "name": "a", "owner": { "id": "owner:1", "name": "o" } }]),
+		"result: {res:?}"
+	);
+	// Send SELECT command with an invalid field to omit
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "omit": [1] }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-// Validate that the WebSocket is able to process multiple queries concurrently
-pub async fn concurrency(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_order(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -649,34 +888,91 @@ pub async fn concurrency(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Send 5 long-running queries and verify they run concurrently
-
-	let mut futures = Vec::<Pin<Box<dyn Future<Output = _>>>>::new();
-	for i in 0..5 {
-		let future = socket.send_request("query", json!([format!("SLEEP 3s; RETURN {i};")]));
-		futures.push(Box::pin(future))
-	}
-
-	let res =
-		tokio::time::timeout(Duration::from_secs(5), futures::future::join_all(futures)).await;
-	let Ok(res) = res else {
-		panic!("future timed-out");
-	};
-
-	let res = res
-		.into_iter()
-		.try_fold(Vec::new(), |mut acc, x| -> Result<_, Box<dyn std::error::Error>> {
-			acc.push(x?);
-			Ok(acc)
-		})
+	// Create the test records
+	socket
+		.send_message_query(
+			"CREATE tester:1 SET n = 2; CREATE tester:2 SET n = NULL; CREATE tester:3 SET n = 1;",
+		)
+		.await
 		.unwrap();
-
-	assert!(res.iter().all(|v| v["error"].is_null()), "Unexpected error received: {res:#?}");
+	// Check the default ordering places nulls first when ascending
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "order": ["n"], "fields": "id, n" }]),
+		)
+		.await
+		.unwrap();
+	let ids: Vec<_> = res["result"].as_array().unwrap().iter().map(|v| v["id"].clone()).collect();
+	assert_eq!(
+		ids,
+		vec![json!("tester:2"), json!("tester:3"), json!("tester:1")],
+		"result: {res:?}"
+	);
+	// Check the default ordering places nulls last when descending
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "order": [{ "field": "n", "direction": "desc" }], "fields": "id, n" }]),
+		)
+		.await
+		.unwrap();
+	let ids: Vec<_> = res["result"].as_array().unwrap().iter().map(|v| v["id"].clone()).collect();
+	assert_eq!(
+		ids,
+		vec![json!("tester:1"), json!("tester:3"), json!("tester:2")],
+		"result: {res:?}"
+	);
+	// Check nulls can be placed last when ascending
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "order": [{ "field": "n", "nulls": "last" }], "fields": "id, n" }]),
+		)
+		.await
+		.unwrap();
+	let ids: Vec<_> = res["result"].as_array().unwrap().iter().map(|v| v["id"].clone()).collect();
+	assert_eq!(
+		ids,
+		vec![json!("tester:3"), json!("tester:1"), json!("tester:2")],
+		"result: {res:?}"
+	);
+	// Check nulls can be placed first when descending
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!([
+				"tester",
+				{ "order": [{ "field": "n", "direction": "desc", "nulls": "first" }], "fields": "id, n" }
+			]),
+		)
+		.await
+		.unwrap();
+	let ids: Vec<_> = res["result"].as_array().unwrap().iter().map(|v| v["id"].clone()).collect();
+	assert_eq!(
+		ids,
+		vec![json!("tester:2"), json!("tester:1"), json!("tester:3")],
+		"result: {res:?}"
+	);
+	// Check an invalid null ordering is rejected
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "order": [{ "field": "n", "nulls": "middle" }] }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn live_query(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_by_id(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -685,74 +981,43 @@ pub async fn live_query(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Send LIVE command
-	let res = socket.send_request("query", json!(["LIVE SELECT * FROM tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert!(res[0]["result"].is_string(), "result: {res:?}");
-	assert!(res[0]["type"].is_string(), "type: {res:?}");
-	let live1 = res[0]["result"].as_str().unwrap();
-	// Send LIVE command
-	let res = socket.send_request("query", json!(["LIVE SELECT * FROM tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert!(res[0]["result"].is_string(), "result: {res:?}");
-	assert!(res[0]["type"].is_string(), "type: {res:?}");
-	let live2 = res[0]["result"].as_str().unwrap();
-	// Create a new test record
-	let res =
-		socket.send_request("query", json!(["CREATE tester:id SET name = 'foo'"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	// Wait some time for all messages to arrive, and then search for the notification message
-	let msgs: Result<_, Box<dyn std::error::Error>> =
-		tokio::time::timeout(Duration::from_secs(1), async {
-			Ok(vec![
-				socket.receive_other_message().await.unwrap(),
-				socket.receive_other_message().await.unwrap(),
-			])
-		})
+	// Create the test records
+	socket.send_message_query("CREATE tester:one, tester:two SET name = 'test'").await.unwrap();
+	// Send SELECT command returning both shapes
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "by_id": true }]))
 		.await
 		.unwrap();
-	let msgs = msgs.unwrap();
-	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
-	// Check for first live query notifcation
-	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live1));
-	assert!(res.is_some(), "Expected to find a notification for LQ id {live1}: {msgs:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert!(res["action"].is_string(), "result: {res:?}");
-	assert_eq!(res["action"], "CREATE", "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:id", "result: {res:?}");
-	// Check for second live query notifcation
-	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live2));
-	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["action"], "CREATE", "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+	let res = &res["result"];
+	assert_eq!(res["array"].as_array().unwrap().len(), 2, "result: {res:?}");
+	assert_eq!(res["by_id"].as_object().unwrap().len(), 2, "result: {res:?}");
+	assert_eq!(res["by_id"]["tester:one"], res["array"][0], "result: {res:?}");
+	assert_eq!(res["by_id"]["tester:two"], res["array"][1], "result: {res:?}");
+	// Send SELECT command returning records without ids
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "by_id": true, "fields": "name" }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Send SELECT command with compacted records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "by_id": true, "compact": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-/// Same as live but uses the RPC for both methods.
-pub async fn live_rpc(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn exists_many(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -761,67 +1026,30 @@ pub async fn live_rpc(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Send LIVE command
-	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let live1 = res["result"].as_str().unwrap();
-
-	// Send LIVE command
-	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let live2 = res["result"].as_str().unwrap();
-
-	// Create a new test record
+	// Create the test records
+	socket.send_message_query("CREATE tester:one, tester:three, other:one").await.unwrap();
+	// Send EXISTS_MANY command across tables
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"exists_many",
+			json!([["tester:three", "tester:two", "other:one", "tester:one", "other:two"]]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!([true, false, true, true, false]), "result: {res:?}");
+	// Send EXISTS_MANY command without any ids
+	let res = socket.send_versioned_request(Some(2), "exists_many", json!([[]])).await.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// Send EXISTS_MANY command with a table instead of an id
 	let res =
-		socket.send_request("query", json!(["CREATE tester:id SET name = 'foo'"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	// Wait some time for all messages to arrive, and then search for the notification message
-	let msgs: Result<_, Box<dyn std::error::Error>> =
-		tokio::time::timeout(Duration::from_secs(1), async {
-			Ok(vec![
-				socket.receive_other_message().await.unwrap(),
-				socket.receive_other_message().await.unwrap(),
-			])
-		})
-		.await
-		.unwrap();
-	let msgs = msgs.unwrap();
-	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
-	// Check for first live query notifcation
-	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live1));
-	assert!(res.is_some(), "Expected to find a notification for LQ id {live1}: {msgs:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert!(res["action"].is_string(), "result: {res:?}");
-	assert_eq!(res["action"], "CREATE", "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:id", "result: {res:?}");
-	// Check for second live query notifcation
-	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live2));
-	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["action"], "CREATE", "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+		socket.send_versioned_request(Some(2), "exists_many", json!([["tester"]])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn kill(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_partition_by(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -830,218 +1058,199 @@ pub async fn kill(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Send LIVE command
-	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let live1 = res["result"].as_str().unwrap();
-	// Send QUERY command
-	let res = socket.send_request("query", json!(["LIVE SELECT * FROM tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert!(res[0]["result"].is_string(), "result: {res:?}");
-	assert!(res[0]["type"].is_string(), "type: {res:?}");
-	let live2 = res[0]["result"].as_str().unwrap();
-	// Create a new test record
-	let res =
-		socket.send_request("query", json!(["CREATE tester:one SET name = 'one'"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	// Wait some time for all messages to arrive, and then search for the notification message
-	let msgs = socket.receive_all_other_messages(2, Duration::from_secs(1)).await.unwrap();
-	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
-	// Check for first live query notifcation
-	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live1));
-	assert!(res.is_some(), "Expected to find a notification for LQ id {live1}: {msgs:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert!(res["action"].is_string(), "result: {res:?}");
-	assert_eq!(res["action"], "CREATE", "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:one", "result: {res:?}");
-	// Check for second live query notifcation
-	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live2));
-	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["action"], "CREATE", "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:one", "result: {res:?}");
-	// Send KILL command
-	let res = socket.send_request("kill", json!([live1])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_null(), "result: {res:?}");
-	// Create a new test record
-	let res =
-		socket.send_request("query", json!(["CREATE tester:two SET name = 'two'"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	// Wait some time for all messages to arrive, and then search for the notification message
-	let msgs = socket.receive_all_other_messages(1, Duration::from_secs(1)).await.unwrap();
-	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
-	// Check for second live query notifcation
-	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live2));
-	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["action"], "CREATE", "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:two", "result: {res:?}");
-	// Send QUERY command
-	let res = socket.send_request("query", json!([format!("KILL u'{live2}'")])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	assert!(res[0]["result"].is_null(), "result: {res:?}");
-	assert!(res[0]["type"].is_string(), "type: {res:?}");
-	// Create a new test record
-	let res =
-		socket.send_request("query", json!(["CREATE tester:tre SET name = 'two'"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	// Wait some time for all messages to arrive, and then search for the notification message
-	let msgs = socket.receive_all_other_messages(0, Duration::from_secs(1)).await.unwrap();
-	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	// Create the test records
+	socket
+		.send_message_query(
+			"CREATE tester:1 SET kind = 'a'; CREATE tester:2 SET kind = 'b'; \
+			CREATE tester:3 SET kind = 'a'; CREATE tester:4;",
+		)
+		.await
+		.unwrap();
+	// Send SELECT command partitioned by a field
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "partition_by": "kind" }]))
+		.await
+		.unwrap();
+	let res = &res["result"];
+	assert_eq!(
+		res["a"],
+		json!([{ "id": "tester:1", "kind": "a" }, { "id": "tester:3", "kind": "a" }]),
+		"result: {res:?}"
+	);
+	assert_eq!(res["b"], json!([{ "id": "tester:2", "kind": "b" }]), "result: {res:?}");
+	// Check the records without the field are in the null bucket
+	assert_eq!(res["null"], json!([{ "id": "tester:4" }]), "result: {res:?}");
+	// Send SELECT command partitioned alongside the records keyed by id
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "partition_by": "kind", "by_id": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn live_second_connection(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_with_checksum(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
-	let mut socket1 = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
 	// Authenticate the connection
-	socket1.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
-	socket1.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Send LIVE command
-	let res = socket1.send_request("live", json!(["tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let liveid = res["result"].as_str().unwrap();
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test records with the same content
+	socket
+		.send_message_query("CREATE tester:1 SET name = 'a'; CREATE tester:2 SET name = 'a';")
+		.await
+		.unwrap();
+	// Send SELECT command with checksums
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "with_checksum": true }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["result"].as_array().unwrap().len(), 2, "result: {res:?}");
+	let before = res["result"]["checksums"].clone();
+	assert!(before["tester:1"].is_string(), "result: {res:?}");
+	// Check the checksum depends on the content rather than the id
+	assert_eq!(before["tester:1"], before["tester:2"], "result: {res:?}");
+	// Check the checksum changes with the content of the record
+	socket.send_message_query("UPDATE tester:2 SET name = 'b'").await.unwrap();
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "with_checksum": true }]))
+		.await
+		.unwrap();
+	let after = &res["result"]["checksums"];
+	assert_eq!(after["tester:1"], before["tester:1"], "result: {res:?}");
+	assert_ne!(after["tester:2"], before["tester:2"], "result: {res:?}");
+	// Send SELECT command with checksums for records without ids
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "with_checksum": true, "compact": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn select_expect(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
-	let mut socket2 = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
 	// Authenticate the connection
-	socket2.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
-	socket2.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Create a new test record
-	let res =
-		socket2.send_request("query", json!(["CREATE tester:id SET name = 'foo'"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	// Wait some time for all messages to arrive, and then search for the notification message
-	let msgs = socket1.receive_all_other_messages(1, Duration::from_secs(1)).await.unwrap();
-	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
-	// Check for live query notifcation
-	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, liveid));
-	assert!(res.is_some(), "Expected to find a notification for LQ id {liveid}: {msgs:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert!(res["action"].is_string(), "result: {res:?}");
-	assert_eq!(res["action"], "CREATE", "result: {res:?}");
-	assert!(res["result"].is_object(), "result: {res:?}");
-	let res = res["result"].as_object().unwrap();
-	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test records
+	socket.send_message_query("CREATE tester:1; CREATE tester:2;").await.unwrap();
+	// Send SELECT command expecting exactly one record
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester:1", { "expect": "one" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!([{ "id": "tester:1" }]), "result: {res:?}");
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "expect": "one" }]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"], "Expected exactly one result, but found 2",
+		"result: {res:?}"
+	);
+	// Send SELECT command expecting at least one record
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "expect": "at_least_one" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"].as_array().unwrap().len(), 2, "result: {res:?}");
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester:3", { "expect": "at_least_one", "only": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"], "Expected at least one result, but found 0",
+		"result: {res:?}"
+	);
+	// Send SELECT command expecting any number of records
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["missing", { "expect": "many" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn variable_auth_live_query(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_alias(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
-	let mut socket_permanent = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
 	// Authenticate the connection
-	socket_permanent.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
-	socket_permanent.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Define a user record access method
-	socket_permanent
-		.send_message_query(
-			r#"
-			DEFINE ACCESS user ON DATABASE TYPE RECORD
-				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
-				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
-				DURATION FOR SESSION 1s, FOR TOKEN 24h
-			;"#,
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test record
+	socket
+		.send_message_query("CREATE tester:1 SET name = 'foo', age = 42, secret = 'x'")
+		.await
+		.unwrap();
+	// Send SELECT command renaming a field of the whole record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester:1", { "alias": { "name": "full_name" }, "omit": "secret" }]),
 		)
 		.await
 		.unwrap();
-	// Send SIGNUP command
-	let mut socket_expiring_auth = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
-
-	let res = socket_expiring_auth
-		.send_request(
-			"signup",
-			json!([{
-				"ns": NS,
-				"db": DB,
-				"ac": "user",
-				"email": "email@email.com",
-				"pass": "pass",
-			}]),
+	assert_eq!(
+		res["result"],
+		json!([{ "id": "tester:1", "full_name": "foo", "age": 42 }]),
+		"result: {res:?}"
+	);
+	// Send SELECT command renaming a projected field
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester:1", { "fields": "id, name", "alias": { "name": "full_name", "age": "years" } }]),
 		)
-		.await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	// Authenticate the connection
-	socket_expiring_auth.send_message_signin(USER, PASS, None, None, None).await.unwrap();
-	// Send LIVE command
-	let res = socket_expiring_auth.send_request("live", json!(["tester"])).await.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_string(), "result: {res:?}");
-	// Wait 2 seconds for auth to expire
-	tokio::time::sleep(Duration::from_secs(1)).await;
-	// Create a new test record
-	let res = socket_permanent
-		.send_request("query", json!(["CREATE tester:id SET name = 'foo'"]))
 		.await
 		.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	assert!(res["result"].is_array(), "result: {res:?}");
-	let res = res["result"].as_array().unwrap();
-	assert_eq!(res.len(), 1, "result: {res:?}");
-	// Wait some time for all messages to arrive, and then search for the notification message
-	let msgs =
-		socket_expiring_auth.receive_all_other_messages(0, Duration::from_secs(1)).await.unwrap();
-	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	assert_eq!(
+		res["result"],
+		json!([{ "id": "tester:1", "full_name": "foo", "years": 42 }]),
+		"result: {res:?}"
+	);
+	// Check two fields can not be returned under the same name
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester:1", { "alias": { "name": "label", "age": "label" } }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn session_expiration(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_sensitive(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -1050,748 +1259,5833 @@ pub async fn session_expiration(cfg_server: Option<Format>, cfg_format: Format)
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Define a user record access method
+	// Define a sensitive field and create a test record
 	socket
 		.send_message_query(
-			r#"
-			DEFINE ACCESS user ON DATABASE TYPE RECORD
-				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
-				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
-				DURATION FOR SESSION 1s, FOR TOKEN 1d
-			;"#,
+			"DEFINE FIELD ssn ON person TYPE string SENSITIVE;
+			CREATE person:tobie SET name = 'Tobie', ssn = '123-45-6789';",
 		)
 		.await
 		.unwrap();
-	// Create resource that requires a session with the access method to query
-	socket
-		.send_message_query(
-			r#"
-			DEFINE TABLE test SCHEMALESS
-				PERMISSIONS FOR select, create, update, delete WHERE $access = "user"
-			;"#,
-		)
+	// The sensitive field is masked by default
+	let res =
+		socket.send_versioned_request(Some(2), "select", json!(["person:tobie"])).await.unwrap();
+	assert_eq!(res["result"]["name"], "Tobie", "result: {res:?}");
+	assert_eq!(res["result"]["ssn"], "[REDACTED]", "result: {res:?}");
+	// The sensitive field is returned when revealed
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["person:tobie", { "reveal": ["ssn"] }]))
 		.await
 		.unwrap();
-	socket
-		.send_message_query(
-			r#"
-			CREATE test:1 SET working = "yes"
-			;"#,
+	assert_eq!(res["result"]["ssn"], "123-45-6789", "result: {res:?}");
+	// The sensitive field is masked within computed fields
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["person:tobie", { "fields": ["string::concat(ssn, '') AS copy"] }]),
 		)
 		.await
 		.unwrap();
-	// Send SIGNUP command
+	assert_eq!(res["result"]["copy"], "[REDACTED]", "result: {res:?}");
+	// The sensitive field can not be matched by a condition
 	let res = socket
-		.send_request(
-			"signup",
-			json!(
-				[{
-					"ns": NS,
-					"db": DB,
-					"ac": "user",
-					"email": "email@email.com",
-					"pass": "pass",
-				}]
-			),
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["person", { "cond": "ssn = '123-45-6789'" }]),
 		)
-		.await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	// Verify it returns a token
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let res = res["result"].as_str().unwrap();
-	assert!(res.starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzUxMiJ9"), "result: {res}");
-	// Authenticate using the token, which expires in a day
-	socket.send_request("authenticate", json!([res,])).await.unwrap();
-	// Check if the session is now authenticated
-	let res = socket.send_message_query("SELECT VALUE working FROM test:1").await.unwrap();
-	assert_eq!(res[0]["result"], json!(["yes"]), "result: {res:?}");
-	// Wait two seconds for the session to expire
-	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-	// Check that the session has expired and queries fail
-	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// The sensitive field is masked by the query method
+	let res = socket
+		.send_message_query("SELECT ssn, string::concat(ssn, '') AS copy FROM ONLY person:tobie")
+		.await
+		.unwrap();
 	assert_eq!(
-		res["error"],
-		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
+		res[0]["result"],
+		json!({ "ssn": "[REDACTED]", "copy": "[REDACTED]" }),
+		"result: {res:?}"
 	);
-	// Sign in again using the same session
+	// The sensitive field is masked by the run method
+	socket
+		.send_message_query("DEFINE FUNCTION fn::ssn() { RETURN person:tobie.ssn; }")
+		.await
+		.unwrap();
+	let res = socket.send_request("run", json!(["fn::ssn"])).await.unwrap();
+	assert_eq!(res["result"], "[REDACTED]", "result: {res:?}");
+	// The sensitive field is masked in the output of a write
+	let res =
+		socket.send_request("merge", json!(["person:tobie", { "name": "Tobie M" }])).await.unwrap();
+	assert_eq!(res["result"]["name"], "Tobie M", "result: {res:?}");
+	assert_eq!(res["result"]["ssn"], "[REDACTED]", "result: {res:?}");
+	// The sensitive field is unchanged by the write
 	let res = socket
-		.send_request(
-			"signin",
-			json!(
-				[{
-					"ns": NS,
-					"db": DB,
-					"ac": "user",
-					"email": "email@email.com",
-					"pass": "pass",
-				}]
-			),
-		)
-		.await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	// Check that the session is now valid again and queries succeed
-	let res = socket.send_message_query("SELECT VALUE working FROM test:1").await.unwrap();
-	assert_eq!(res[0]["result"], json!(["yes"]), "result: {res:?}");
+		.send_versioned_request(Some(2), "select", json!(["person:tobie", { "reveal": ["ssn"] }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["ssn"], "123-45-6789", "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn session_expiration_operations(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn select_computed(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
 	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
 	// Authenticate the connection
-	// We store the root token to test reauthentication later
-	let root_token = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Define a user record access method
+	// Define a computed field and create the test records
 	socket
 		.send_message_query(
-			r#"
-			DEFINE ACCESS user ON DATABASE TYPE RECORD
-				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
-				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
-				DURATION FOR SESSION 1s, FOR TOKEN 1d
-			;"#,
+			"DEFINE FIELD total ON tester VALUE <future> { price * quantity };
+			CREATE tester:one SET price = 2, quantity = 3;
+			CREATE owner:one SET item = tester:one;",
 		)
 		.await
 		.unwrap();
-	// Create resource that requires a session with the access method to query
+	// Send SELECT command for the table
+	let res = socket.send_versioned_request(Some(2), "select", json!(["tester"])).await.unwrap();
+	assert_eq!(res["result"][0]["total"], 6, "result: {res:?}");
+	// Send SELECT command for a single record
+	let res =
+		socket.send_versioned_request(Some(2), "select", json!(["tester:one"])).await.unwrap();
+	assert_eq!(res["result"][0]["total"], 6, "result: {res:?}");
+	// Send SELECT command for the computed field only
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "fields": "total" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["total"], 6, "result: {res:?}");
+	// Send SELECT command fetching a linked record
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["owner", { "fetch": "item" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["item"]["total"], 6, "result: {res:?}");
+	// Send SELECT command skipping the computed field
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "skip_computed": true }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["price"], 2, "result: {res:?}");
+	assert!(res["result"][0].get("total").is_none(), "result: {res:?}");
+	// Send FIELD_TYPES command describing the computed fields
+	let res = socket
+		.send_versioned_request(Some(2), "field_types", json!(["tester", { "detailed": true }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["total"]["computed"], true, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn select_fetch_cycle(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create records which reference each other
 	socket
 		.send_message_query(
-			r#"
-			DEFINE TABLE test SCHEMALESS
-				PERMISSIONS FOR select, create, update, delete WHERE $access = "user"
-			;"#,
+			"CREATE person:a SET friend = person:b; CREATE person:b SET friend = person:c; CREATE person:c SET friend = person:a",
+		)
+		.await
+		.unwrap();
+	// Fetch the records around the cycle
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["person:a", { "fetch": "friend, friend.friend, friend.friend.friend" }]),
 		)
 		.await
 		.unwrap();
+	// Check the already fetched record is left as a reference
+	assert_eq!(res["result"][0]["friend"]["id"], "person:b", "result: {res:?}");
+	assert_eq!(res["result"][0]["friend"]["friend"]["id"], "person:c", "result: {res:?}");
+	assert_eq!(res["result"][0]["friend"]["friend"]["friend"], "person:a", "result: {res:?}");
+	// Create a chain of records deeper than the maximum fetch depth
+	socket
+		.send_message_query("FOR $i IN 0..12 { CREATE type::thing('node', $i) SET next = type::thing('node', $i + 1) }")
+		.await
+		.unwrap();
+	// Fetch every record along the chain
+	let fetch = (1..=12).map(|i| vec!["next"; i].join(".")).collect::<Vec<_>>().join(", ");
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["node:0", { "fetch": fetch }]))
+		.await
+		.unwrap();
+	// Check the records beyond the maximum depth are left as references
+	let mut node = &res["result"][0];
+	for i in 1..=10 {
+		node = &node["next"];
+		assert_eq!(node["id"], format!("node:{i}"), "result: {res:?}");
+	}
+	assert_eq!(node["next"], "node:11", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn search(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a full-text index and test records
 	socket
 		.send_message_query(
-			r#"
-			CREATE test:1 SET working = "yes"
-			;"#,
+			"DEFINE ANALYZER simple TOKENIZERS blank,class FILTERS lowercase;
+			DEFINE INDEX body ON tester FIELDS body SEARCH ANALYZER simple BM25 HIGHLIGHTS;
+			CREATE tester:1 SET body = 'hello world', title = 'first';
+			CREATE tester:2 SET body = 'goodbye world', title = 'second';",
 		)
 		.await
 		.unwrap();
-	// Send SIGNUP command
+	// Send SEARCH command
 	let res = socket
-		.send_request(
-			"signup",
-			json!(
-				[{
-					"ns": NS,
-					"db": DB,
-					"ac": "user",
-					"email": "email@email.com",
-					"pass": "pass",
-				}]
-			),
+		.send_versioned_request(Some(2), "search", json!(["tester", "body", "hello"]))
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["title"], "first", "result: {res:?}");
+	assert_eq!(res[0]["highlight"], "<b>hello</b> world", "result: {res:?}");
+	assert!(res[0]["score"].is_number(), "result: {res:?}");
+	// Send SEARCH command with custom highlighting
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"search",
+			json!([
+				"tester",
+				"body",
+				"goodbye",
+				{
+					"fields": "title",
+					"highlight": { "prefix": "[", "suffix": "]" },
+					"score": false,
+				}
+			]),
 		)
-		.await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	// Verify it returns a token
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let res = res["result"].as_str().unwrap();
-	assert!(res.starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzUxMiJ9"), "result: {res}");
-	// Authenticate using the token, which expires in a day
-	socket.send_request("authenticate", json!([res,])).await.unwrap();
-	// Check if the session is now authenticated
-	let res = socket.send_message_query("SELECT VALUE working FROM test:1").await.unwrap();
-	assert_eq!(res[0]["result"], json!(["yes"]), "result: {res:?}");
-	// Wait two seconds for the session to expire
-	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-	// Check if the session is now expired
-	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert_eq!(
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([{ "title": "second", "highlight": "[goodbye] world" }]),
+		"result: {res:?}"
+	);
+	// Send SEARCH command on a field which is not indexed
+	let res = socket
+		.send_versioned_request(Some(2), "search", json!(["tester", "title", "first"]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn maintain(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a table with a changefeed and an index
+	let res = socket
+		.send_message_query(
+			"DEFINE TABLE tester CHANGEFEED 1h;
+			DEFINE INDEX by_name ON tester FIELDS name;
+			CREATE tester:1 SET name = 'a';
+			DEFINE USER viewer ON ROOT PASSWORD 'secret' ROLES VIEWER;",
+		)
+		.await
+		.unwrap();
+	assert!(res.iter().all(|v| v["status"] == "OK"), "result: {res:?}");
+	// Send MAINTAIN command
+	let res = socket.send_versioned_request(Some(2), "maintain", json!(["tester"])).await.unwrap();
+	assert_eq!(res["result"]["table"], "tester", "result: {res:?}");
+	assert_eq!(res["result"]["indexes"], json!(["by_name"]), "result: {res:?}");
+	assert_eq!(res["result"]["changefeed"], true, "result: {res:?}");
+	// Check the rebuilt index can still be used
+	let res = socket
+		.send_message_query("SELECT VALUE id FROM tester WITH INDEX by_name WHERE name = 'a'")
+		.await
+		.unwrap();
+	assert_eq!(res[0]["result"], json!(["tester:1"]), "result: {res:?}");
+	// Send MAINTAIN command skipping the index rebuild
+	let res = socket
+		.send_versioned_request(Some(2), "maintain", json!(["tester", { "indexes": false }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["indexes"], json!([]), "result: {res:?}");
+	// Send MAINTAIN command for a table which does not exist
+	let res = socket.send_versioned_request(Some(2), "maintain", json!(["missing"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Check a root user without the owner role is not allowed
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin("viewer", "secret", None, None, None).await.unwrap();
+	other.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "maintain", json!(["tester"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn temp_table(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	socket.send_message_query("DEFINE TABLE tester").await.unwrap();
+	// Send TEMP_TABLE command
+	let res =
+		socket.send_versioned_request(Some(2), "temp_table", json!(["scratch"])).await.unwrap();
+	assert_eq!(res["result"], "scratch", "result: {res:?}");
+	// Check the table can be used like any other table
+	let res =
+		socket.send_message_query("CREATE scratch:1; SELECT VALUE id FROM scratch").await.unwrap();
+	assert_eq!(res[1]["result"], json!(["scratch:1"]), "result: {res:?}");
+	// Check the names of existing tables are rejected
+	let res =
+		socket.send_versioned_request(Some(2), "temp_table", json!(["scratch"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	let res =
+		socket.send_versioned_request(Some(2), "temp_table", json!(["tester"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Check the table is removed when the connection is reset
+	socket.send_request("reset", json!([])).await.unwrap();
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	let res = socket.send_message_query("INFO FOR DB").await.unwrap();
+	assert!(res[0]["result"]["tables"]["scratch"].is_null(), "result: {res:?}");
+	assert!(res[0]["result"]["tables"]["tester"].is_string(), "result: {res:?}");
+	// Check the table is removed when the connection is closed
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	other.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	let res =
+		other.send_versioned_request(Some(2), "temp_table", json!(["scratch"])).await.unwrap();
+	assert_eq!(res["result"], "scratch", "result: {res:?}");
+	// Check a table which was since defined again is not removed
+	let res =
+		other.send_versioned_request(Some(2), "temp_table", json!(["redefined"])).await.unwrap();
+	assert_eq!(res["result"], "redefined", "result: {res:?}");
+	let res =
+		socket.send_message_query("REMOVE TABLE redefined; DEFINE TABLE redefined").await.unwrap();
+	assert_eq!(res[1]["status"], "OK", "result: {res:?}");
+	other.close().await.unwrap();
+	tokio::time::sleep(Duration::from_millis(500)).await;
+	let res = socket.send_message_query("INFO FOR DB").await.unwrap();
+	assert!(res[0]["result"]["tables"]["scratch"].is_null(), "result: {res:?}");
+	assert!(res[0]["result"]["tables"]["redefined"].is_string(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn migrate(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	socket.send_message_query("CREATE person:1 SET age = '42'").await.unwrap();
+	let steps = json!([
+		"OPTION IMPORT = false; UPDATE person SET age = <int> age",
+		"DEFINE FIELD age ON person TYPE int; DEFINE INDEX age ON person FIELDS age",
+	]);
+	// Send MIGRATE command as a dry run
+	let res = socket
+		.send_versioned_request(Some(2), "migrate", json!([steps, { "dry_run": true }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["applied"], false, "result: {res:?}");
+	let results = res["result"]["steps"].as_array().unwrap();
+	assert_eq!(results.len(), 2, "result: {res:?}");
+	assert!(results.iter().all(|step| step["status"] == "ok"), "result: {res:?}");
+	assert_eq!(results[0]["result"].as_array().unwrap().len(), 1, "result: {res:?}");
+	assert_eq!(results[0]["result"][0][0]["age"], 42, "result: {res:?}");
+	assert_eq!(results[1]["result"].as_array().unwrap().len(), 2, "result: {res:?}");
+	let res = socket.send_message_query("SELECT VALUE age FROM person").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["42"]), "result: {res:?}");
+	// Send MIGRATE command with a failing step, which rolls back every step
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"migrate",
+			json!([[
+				"DEFINE FIELD name ON person TYPE string",
+				"THROW 'failed'",
+				"REMOVE TABLE person"
+			]]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["applied"], false, "result: {res:?}");
+	let results = res["result"]["steps"].as_array().unwrap();
+	assert_eq!(results[0]["status"], "ok", "result: {res:?}");
+	assert_eq!(results[1]["status"], "error", "result: {res:?}");
+	assert_eq!(results[2]["status"], "skipped", "result: {res:?}");
+	let res = socket.send_message_query("INFO FOR TABLE person").await.unwrap();
+	assert_eq!(res[0]["result"]["fields"], json!({}), "result: {res:?}");
+	// Send MIGRATE command
+	let res = socket.send_versioned_request(Some(2), "migrate", json!([steps])).await.unwrap();
+	assert_eq!(res["result"]["applied"], true, "result: {res:?}");
+	let res = socket.send_message_query("SELECT VALUE age FROM person").await.unwrap();
+	assert_eq!(res[0]["result"], json!([42]), "result: {res:?}");
+	// Check migrations which manage transactions are rejected
+	let res = socket
+		.send_versioned_request(Some(2), "migrate", json!([["BEGIN; CREATE person:2; COMMIT"]]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn schema_hash(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Get the hash of the empty schema
+	let res = socket.send_versioned_request(Some(2), "schema_hash", json!([])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let empty = res["result"].clone();
+	// Define a schema
+	let schema = "DEFINE TABLE person SCHEMAFULL; DEFINE FIELD name ON person TYPE string; \
+		DEFINE INDEX name ON person FIELDS name; DEFINE FUNCTION fn::greet() { 'hello' };";
+	socket.send_message_query(schema).await.unwrap();
+	// Check the hash changes when the schema changes
+	let res = socket.send_versioned_request(Some(2), "schema_hash", json!([])).await.unwrap();
+	assert_ne!(res["result"], empty, "result: {res:?}");
+	let hash = res["result"].clone();
+	// Check the hash does not change when data changes
+	socket.send_message_query("CREATE person SET name = 'Tobie'").await.unwrap();
+	let res = socket.send_versioned_request(Some(2), "schema_hash", json!([])).await.unwrap();
+	assert_eq!(res["result"], hash, "result: {res:?}");
+	// Check the hash is identical for an identical schema in another database
+	socket.send_message_use(Some(NS), Some("other")).await.unwrap();
+	socket.send_message_query(schema).await.unwrap();
+	let res = socket.send_versioned_request(Some(2), "schema_hash", json!([])).await.unwrap();
+	assert_eq!(res["result"], hash, "result: {res:?}");
+	// Check the hash changes when a field is added
+	socket.send_message_query("DEFINE FIELD age ON person TYPE int").await.unwrap();
+	let res = socket.send_versioned_request(Some(2), "schema_hash", json!([])).await.unwrap();
+	assert_ne!(res["result"], hash, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn export_schema(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Check a database must be selected
+	let res = socket.send_versioned_request(Some(2), "export_schema", json!([])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a schema and create some records
+	let res = socket
+		.send_message_query(
+			r#"
+			DEFINE TABLE person SCHEMAFULL;
+			DEFINE FIELD name ON person TYPE string;
+			DEFINE INDEX name ON person FIELDS name;
+			DEFINE FUNCTION fn::greet($name: string) { RETURN 'Hello ' + $name };
+			DEFINE PARAM $limit VALUE 10;
+			DEFINE ANALYZER simple TOKENIZERS blank;
+			CREATE person:tobie SET name = 'Tobie';
+			"#,
+		)
+		.await
+		.unwrap();
+	assert!(res.iter().all(|r| r["status"] == "OK"), "result: {res:?}");
+	// Send EXPORT_SCHEMA command
+	let res = socket.send_versioned_request(Some(2), "export_schema", json!([])).await.unwrap();
+	let sql = res["result"].as_str().unwrap();
+	assert!(sql.contains("DEFINE TABLE person"), "result: {sql}");
+	assert!(sql.contains("DEFINE FIELD name ON person"), "result: {sql}");
+	assert!(sql.contains("DEFINE INDEX name ON person"), "result: {sql}");
+	assert!(sql.contains("DEFINE FUNCTION fn::greet"), "result: {sql}");
+	assert!(sql.contains("DEFINE PARAM $limit"), "result: {sql}");
+	assert!(sql.contains("DEFINE ANALYZER simple"), "result: {sql}");
+	assert!(!sql.contains("Tobie"), "result: {sql}");
+	// Check a record user is not allowed
+	let res = socket
+		.send_message_query(
+			r#"
+			DEFINE ACCESS user ON DATABASE TYPE RECORD
+				SIGNIN ( SELECT * FROM person WHERE name = $name )
+				DURATION FOR SESSION 24h;
+			"#,
+		)
+		.await
+		.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other
+		.send_request("signin", json!([{ "ns": NS, "db": DB, "ac": "user", "name": "Tobie" }]))
+		.await
+		.unwrap();
+	other.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "export_schema", json!([])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn indexes(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define the test indexes
+	socket
+		.send_message_query(
+			"DEFINE TABLE tester; \
+			DEFINE INDEX email ON tester FIELDS email UNIQUE; \
+			DEFINE INDEX name ON tester FIELDS first, last;",
+		)
+		.await
+		.unwrap();
+	// Send INDEXES command
+	let res = socket.send_versioned_request(Some(2), "indexes", json!(["tester"])).await.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([
+			{ "name": "email", "fields": ["email"], "unique": true, "status": "ready" },
+			{ "name": "name", "fields": ["first", "last"], "unique": false, "status": "ready" },
+		]),
+		"result: {res:?}"
+	);
+	// Check a table which does not exist is an error
+	let res = socket.send_versioned_request(Some(2), "indexes", json!(["missing"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn permissions(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define tables with permissions, and a record access method
+	let res = socket
+		.send_message_query(
+			r#"
+			DEFINE TABLE person PERMISSIONS
+				FOR select FULL
+				FOR create, update WHERE id = $auth.id
+				FOR delete NONE;
+			DEFINE TABLE secret;
+			DEFINE ACCESS user ON DATABASE TYPE RECORD
+				SIGNIN ( SELECT * FROM person WHERE name = $name )
+				DURATION FOR SESSION 24h;
+			CREATE person:tobie SET name = 'Tobie';
+			"#,
+		)
+		.await
+		.unwrap();
+	assert!(res.iter().all(|r| r["status"] == "OK"), "result: {res:?}");
+	// Send PERMISSIONS command as the root user
+	let res = socket.send_versioned_request(Some(2), "permissions", json!([])).await.unwrap();
+	let res = &res["result"];
+	assert_eq!(res["level"], "Root", "result: {res:?}");
+	assert_eq!(res["role"], "Owner", "result: {res:?}");
+	let all = json!({ "select": true, "create": true, "update": true, "delete": true });
+	assert_eq!(res["tables"]["person"], all, "result: {res:?}");
+	assert_eq!(res["tables"]["secret"], all, "result: {res:?}");
+	// Send PERMISSIONS command as a record user
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other
+		.send_request("signin", json!([{ "ns": NS, "db": DB, "ac": "user", "name": "Tobie" }]))
+		.await
+		.unwrap();
+	other.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "permissions", json!([])).await.unwrap();
+	let res = &res["result"];
+	assert_eq!(res["level"], "Record", "result: {res:?}");
+	assert!(res["role"].is_null(), "result: {res:?}");
+	assert_eq!(
+		res["tables"]["person"],
+		json!({ "select": true, "create": "conditional", "update": "conditional", "delete": false }),
+		"result: {res:?}"
+	);
+	assert_eq!(
+		res["tables"]["secret"],
+		json!({ "select": false, "create": false, "update": false, "delete": false }),
+		"result: {res:?}"
+	);
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn field_types(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a table schema
+	socket
+		.send_message_query(
+			"DEFINE TABLE tester SCHEMAFULL;
+			DEFINE FIELD name ON tester TYPE string;
+			DEFINE FIELD age ON tester TYPE option<int>;
+			DEFINE FIELD extra ON tester;",
+		)
+		.await
+		.unwrap();
+	// Send FIELD_TYPES command
+	let res =
+		socket.send_versioned_request(Some(2), "field_types", json!(["tester"])).await.unwrap();
+	assert_eq!(
+		res["result"],
+		json!({ "name": "string", "age": "option<int>", "extra": "any" }),
+		"result: {res:?}"
+	);
+	// Send FIELD_TYPES command for a schemaless table
+	let res =
+		socket.send_versioned_request(Some(2), "field_types", json!(["other"])).await.unwrap();
+	assert_eq!(res["result"], json!({}), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn view(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a view over a table
+	socket
+		.send_message_query(
+			"DEFINE TABLE totals AS SELECT count() AS total, kind FROM tester WHERE value > 1 GROUP BY kind;
+			CREATE tester:1 SET kind = 'a', value = 1;
+			CREATE tester:2 SET kind = 'a', value = 2;
+			CREATE tester:3 SET kind = 'b', value = 3;",
+		)
+		.await
+		.unwrap();
+	// Send VIEW command
+	let res = socket.send_versioned_request(Some(2), "view", json!(["totals"])).await.unwrap();
+	assert_eq!(
+		res["result"],
+		json!({
+			"name": "totals",
+			"definition": "SELECT count() AS total, kind FROM tester WHERE value > 1 GROUP BY kind",
+			"tables": ["tester"],
+		}),
+		"result: {res:?}"
+	);
+	// Send VIEW command materializing the current contents
+	let res = socket
+		.send_versioned_request(Some(2), "view", json!(["totals", { "materialize": true }]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"]["result"],
+		json!([
+			{ "id": "totals:['a']", "kind": "a", "total": 1 },
+			{ "id": "totals:['b']", "kind": "b", "total": 1 },
+		]),
+		"result: {res:?}"
+	);
+	// Send VIEW command for a table which is not a view
+	let res = socket.send_versioned_request(Some(2), "view", json!(["tester"])).await.unwrap();
+	assert_eq!(res["error"]["message"], "The table tester is not a view", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn count(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server with a limit of three scanned records
+	let (addr, mut server) = common::start_server(StartServerArguments {
+		vars: Some(HashMap::from([("SURREAL_COUNT_SCAN_LIMIT".to_string(), "3".to_string())])),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test records, with an index on one of the fields
+	socket
+		.send_message_query(
+			"DEFINE INDEX status ON tester FIELDS status; CREATE |tester:1..10| SET status = IF record::id(id) > 6 { 'done' } ELSE { 'open' }, value = record::id(id)",
+		)
+		.await
+		.unwrap();
+	// Send COUNT command for all records
+	let res = socket.send_versioned_request(Some(2), "count", json!(["tester"])).await.unwrap();
+	assert_eq!(res["result"], json!(10), "result: {res:?}");
+	// The condition on the indexed field produces an index-backed plan
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT count() AS count FROM tester WHERE status = 'done' GROUP ALL EXPLAIN"]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"][0]["result"][0]["operation"], "Iterate Index Count",
+		"result: {res:?}"
+	);
+	// Send COUNT command with a condition on the indexed field
+	let res = socket
+		.send_versioned_request(Some(2), "count", json!(["tester", { "cond": "status = 'done'" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(4), "result: {res:?}");
+	// Send COUNT command with a condition on an unindexed field
+	let res = socket
+		.send_versioned_request(Some(2), "count", json!(["tester", { "cond": "value > 8" }]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		"The condition can not use an index on the table tester, which has more than 3 records, so counting requires the `allow_scan` option",
+		"result: {res:?}"
+	);
+	// Send COUNT command allowing the table to be scanned
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"count",
+			json!(["tester", { "cond": "value > 8", "allow_scan": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(2), "result: {res:?}");
+	// Send COUNT command with a condition matching no records
+	let res = socket
+		.send_versioned_request(Some(2), "count", json!(["tester", { "cond": "status = 'none'" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(0), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn aggregate(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test records
+	let res = socket
+		.send_message_query("CREATE |tester:1..4| SET value = record::id(id) * 10")
+		.await
+		.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Send AGGREGATE command for each function
+	for (func, expected) in [("sum", 100), ("min", 10), ("max", 40)] {
+		let res = socket
+			.send_versioned_request(Some(2), "aggregate", json!(["tester", func, "value"]))
+			.await
+			.unwrap();
+		assert_eq!(res["result"], json!(expected), "function: {func}, result: {res:?}");
+	}
+	let res = socket
+		.send_versioned_request(Some(2), "aggregate", json!(["tester", "avg", "value"]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"].as_f64(), Some(25.0), "result: {res:?}");
+	// Send AGGREGATE command with a condition
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"aggregate",
+			json!(["tester", "sum", "value", { "cond": "value > $min", "vars": { "min": 20 } }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(70), "result: {res:?}");
+	// Send AGGREGATE command for a table without records
+	let res = socket
+		.send_versioned_request(Some(2), "aggregate", json!(["other", "sum", "value"]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	assert!(res["result"].is_null(), "result: {res:?}");
+	// Send AGGREGATE command with an unknown function
+	let res = socket
+		.send_versioned_request(Some(2), "aggregate", json!(["tester", "median", "value"]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send AGGREGATE command without a field
+	let res = socket
+		.send_versioned_request(Some(2), "aggregate", json!(["tester", "sum"]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn insert(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send INSERT command
+	let res = socket
+		.send_request(
+			"insert",
+			json!([
+				"tester",
+				{
+					"name": "foo",
+					"value": "bar",
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	// Send INSERT command trying to create multiple records
+	let res = socket
+		.send_request(
+			"insert",
+			json!([
+				"tester",
+				[
+					{
+						"name": "foo",
+						"value": "bar",
+					},
+					{
+						"name": "foo",
+						"value": "bar",
+					}
+				]
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 2, "result: {res:?}");
+	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	assert_eq!(res[1]["name"], "foo", "result: {res:?}");
+	assert_eq!(res[1]["value"], "bar", "result: {res:?}");
+	// Verify the data was inserted and can be queried
+	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
+	assert!(res[0]["result"].is_array(), "result: {res:?}");
+	let res = res[0]["result"].as_array().unwrap();
+	assert_eq!(res.len(), 3, "result: {res:?}");
+	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	assert_eq!(res[1]["name"], "foo", "result: {res:?}");
+	assert_eq!(res[1]["value"], "bar", "result: {res:?}");
+	assert_eq!(res[2]["name"], "foo", "result: {res:?}");
+	assert_eq!(res[2]["value"], "bar", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn insert_v2(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send INSERT command with the version 2 protocol
+	let res = socket
+		.send_versioned_request(Some(2), "insert", json!(["tester", { "name": "foo" }]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
+	// Send INSERT command for multiple records with the version 2 protocol
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"insert",
+			json!(["tester", [{ "name": "bar" }, { "name": "baz" }]]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	assert_eq!(res["result"].as_array().unwrap().len(), 2, "result: {res:?}");
+	// Verify the data was inserted
+	let res = socket.send_message_query("SELECT VALUE name FROM tester").await.unwrap();
+	assert_eq!(res[0]["result"].as_array().unwrap().len(), 3, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn create(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send CREATE command
+	let res = socket
+		.send_request(
+			"create",
+			json!([
+				"tester",
+				{
+					"value": "bar",
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["value"], "bar", "result: {res:?}");
+	// Verify the data was created
+	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
+	assert!(res[0]["result"].is_array(), "result: {res:?}");
+	let res = res[0]["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn create_defaults(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a schemaful table with default values
+	socket
+		.send_message_query(
+			"DEFINE TABLE tester SCHEMAFULL;
+			DEFINE FIELD name ON tester TYPE string;
+			DEFINE FIELD status ON tester TYPE string DEFAULT 'active';
+			DEFINE FIELD score ON tester TYPE int DEFAULT 0;",
+		)
+		.await
+		.unwrap();
+	// Send CREATE command returning the defaulted fields
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"create",
+			json!(["tester:1", { "name": "foo", "score": 5 }, { "defaults": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["result"]["status"], "active", "result: {res:?}");
+	assert_eq!(res["result"]["result"]["score"], 5, "result: {res:?}");
+	assert_eq!(res["result"]["defaults"], json!(["status"]), "result: {res:?}");
+	// Send CREATE command for a table returning the defaulted fields of each record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"create",
+			json!(["tester", { "name": "bar" }, { "defaults": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["defaults"], json!([["score", "status"]]), "result: {res:?}");
+	// Check the defaults can not be found when the record is not returned
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"create",
+			json!(["tester:2", { "name": "baz" }, { "defaults": true, "return": "none" }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn create_get_existing(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a test record
+	socket.send_message_query("CREATE tester:1 SET value = 'foo'").await.unwrap();
+	// Send CREATE command for the existing record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"create",
+			json!([
+				"tester",
+				{
+					"id": 1,
+					"value": "bar",
+				},
+				{
+					"get_existing": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["value"], "foo", "result: {res:?}");
+	// Send CREATE command for a new record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"create",
+			json!([
+				"tester",
+				{
+					"id": 2,
+					"value": "bar",
+				},
+				{
+					"get_existing": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	// Verify the existing record was untouched
+	let res =
+		socket.send_message_query("SELECT VALUE value FROM [tester:1, tester:2]").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["foo", "bar"]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn insert_get_existing(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a test record
+	socket.send_message_query("CREATE tester:1 SET value = 'foo'").await.unwrap();
+	// Send INSERT command for an existing and a new record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"insert",
+			json!([
+				"tester",
+				[
+					{
+						"id": 1,
+						"value": "bar",
+					},
+					{
+						"id": 2,
+						"value": "bar",
+					}
+				],
+				{
+					"get_existing": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 2, "result: {res:?}");
+	assert_eq!(res[0]["value"], "foo", "result: {res:?}");
+	assert_eq!(res[1]["value"], "bar", "result: {res:?}");
+	// Verify the existing record was untouched
+	let res =
+		socket.send_message_query("SELECT VALUE value FROM [tester:1, tester:2]").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["foo", "bar"]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn echo_sql(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send CREATE command returning the generated SurrealQL
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"create",
+			json!(["tester:1", { "value": 1 }, { "echo_sql": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["result"]["value"], 1, "result: {res:?}");
+	let sql = res["result"]["sql"].as_str().unwrap();
+	assert!(sql.starts_with("CREATE tester:1"), "result: {res:?}");
+	// Send SELECT command returning the generated SurrealQL
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester", { "cond": "value > 0", "echo_sql": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"]["result"],
+		json!([{ "id": "tester:1", "value": 1 }]),
+		"result: {res:?}"
+	);
+	let sql = res["result"]["sql"].as_str().unwrap();
+	assert_eq!(sql, "SELECT * FROM tester WHERE value > 0", "result: {res:?}");
+	// Check that the generated SurrealQL returns the same result
+	let rerun = socket.send_message_query(sql).await.unwrap();
+	assert_eq!(rerun[0]["result"], res["result"]["result"], "result: {rerun:?}");
+	// Send DELETE command returning the generated SurrealQL
+	let res = socket
+		.send_versioned_request(Some(2), "delete", json!(["tester:1", { "echo_sql": true }]))
+		.await
+		.unwrap();
+	assert!(
+		res["result"]["sql"].as_str().unwrap().starts_with("DELETE tester:1"),
+		"result: {res:?}"
+	);
+	// Send SELECT command without the generated SurrealQL
+	let res = socket.send_versioned_request(Some(2), "select", json!(["tester"])).await.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// Send SELECT command with an invalid option
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "echo_sql": "yes" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn write_versionstamp(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send CREATE command returning the versionstamp
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"create",
+			json!(["tester:one", { "value": "foo" }, { "versionstamp": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["result"][0]["value"], "foo", "result: {res:?}");
+	let first = res["result"]["versionstamp"].as_u64().unwrap();
+	// Send UPDATE command returning the versionstamp and the generated SurrealQL
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!(["tester:one", { "value": "bar" }, { "versionstamp": true, "echo_sql": true }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"]["sql"].is_string(), "result: {res:?}");
+	let second = res["result"]["versionstamp"].as_u64().unwrap();
+	assert!(second > first, "result: {res:?}");
+	// Send DELETE command without the versionstamp
+	let res =
+		socket.send_versioned_request(Some(2), "delete", json!(["tester:one"])).await.unwrap();
+	assert!(res["result"].get("versionstamp").is_none(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn read_min_version(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send CREATE command returning the versionstamp
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"create",
+			json!(["tester:one", { "value": "foo" }, { "versionstamp": true }]),
+		)
+		.await
+		.unwrap();
+	let version = res["result"]["versionstamp"].as_u64().unwrap();
+	// Send SELECT command from another connection reading at least the write
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	other.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	let res = other
+		.send_versioned_request(Some(2), "select", json!(["tester", { "min_version": version }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["value"], "foo", "result: {res:?}");
+	// Send QUERY command reading at least the write
+	let res = other
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT VALUE value FROM tester", null, { "min_version": version }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"], json!(["foo"]), "result: {res:?}");
+	// Send QUERY command with a versionstamp which is never reached
+	let res = other
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT * FROM tester", null, { "min_version": version + 1000 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		format!("The database did not reach the versionstamp {} in time", version + 1000),
+		"result: {res:?}"
+	);
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_as_of(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server which retains the history of records
+	let path = format!("surrealkv+versioned:{}", tempfile::tempdir().unwrap().path().display());
+	let (addr, mut server) = common::start_server(StartServerArguments {
+		path: Some(path),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the initial record, and note the time afterwards
+	socket.send_message_query("CREATE tester:one SET value = 1").await.unwrap();
+	let res = socket.send_message_query("RETURN time::now()").await.unwrap();
+	let as_of = res[0]["result"].as_str().unwrap().to_owned();
+	// Change the records after the noted time
+	tokio::time::sleep(Duration::from_millis(10)).await;
+	socket
+		.send_message_query("UPDATE tester:one SET value = 2; CREATE tester:two SET value = 3")
+		.await
+		.unwrap();
+	// Send QUERY command reading every statement at the noted time
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"SELECT * FROM tester; RETURN (SELECT VALUE value FROM tester:one)",
+				null,
+				{ "as_of": as_of }
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"][0]["result"],
+		json!([{ "id": "tester:one", "value": 1 }]),
+		"result: {res:?}"
+	);
+	assert_eq!(res["result"][1]["result"], json!([1]), "result: {res:?}");
+	// Send QUERY command which writes at the noted time
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["UPDATE tester:one SET value = 4", null, { "as_of": as_of }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Send QUERY command reading at a time in the future
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT * FROM tester", null, { "as_of": "2999-01-01T00:00:00Z" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		"The datetime d'2999-01-01T00:00:00Z' is outside of the history retained by the database",
+		"result: {res:?}"
+	);
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn insert_row_status(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a test record
+	socket.send_message_query("CREATE tester:1 SET value = 'foo'").await.unwrap();
+	// Send INSERT command for an existing, a new, and an invalid record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"insert",
+			json!([
+				"tester",
+				[
+					{
+						"id": 1,
+						"value": "bar",
+					},
+					{
+						"id": 2,
+						"value": "bar",
+					},
+					"invalid"
+				],
+				{
+					"row_status": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 3, "result: {res:?}");
+	assert_eq!(res[0]["status"], "conflict", "result: {res:?}");
+	assert!(res[0]["result"].is_string(), "result: {res:?}");
+	assert_eq!(res[1]["status"], "inserted", "result: {res:?}");
+	assert_eq!(res[1]["result"]["value"], "bar", "result: {res:?}");
+	assert_eq!(res[2]["status"], "error", "result: {res:?}");
+	assert!(res[2]["result"].is_string(), "result: {res:?}");
+	// Verify the new record was inserted and the existing record was untouched
+	let res =
+		socket.send_message_query("SELECT VALUE value FROM [tester:1, tester:2]").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["foo", "bar"]), "result: {res:?}");
+	// Send INSERT command combining incompatible options
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"insert",
+			json!([
+				"tester",
+				[{ "id": 3 }],
+				{
+					"row_status": true,
+					"get_existing": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn append(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a record with an array field
+	socket.send_message_query("CREATE tester:1 SET events = ['a']").await.unwrap();
+	// Send APPEND command
+	let res = socket
+		.send_versioned_request(Some(2), "append", json!(["tester:1", "events", "b"]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], 2, "result: {res:?}");
+	// Send APPEND command to a field which does not exist yet
+	let res = socket
+		.send_versioned_request(Some(2), "append", json!(["tester:1", "other", 1]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], 1, "result: {res:?}");
+	// Send APPEND command capping the length of the array
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"append",
+			json!(["tester:1", "events", "c", { "max_length": 2 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], 2, "result: {res:?}");
+	// Check the oldest elements were trimmed
+	let res = socket.send_message_query("SELECT VALUE events FROM ONLY tester:1").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["b", "c"]), "result: {res:?}");
+	// Send APPEND command with an invalid maximum length
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"append",
+			json!(["tester:1", "events", "d", { "max_length": 0 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn update(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a test record
+	socket.send_message_query("CREATE tester SET name = 'foo'").await.unwrap();
+	// Send UPDATE command
+	let res = socket
+		.send_request(
+			"update",
+			json!([
+				"tester",
+				{
+					"value": "bar",
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	// Verify the data was updated
+	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
+	assert!(res[0]["result"].is_array(), "result: {res:?}");
+	let res = res[0]["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["name"], json!(null), "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn update_changeset(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the records to update
+	socket
+		.send_message_query(
+			"CREATE tester:1 SET status = 'new', score = 1; CREATE tester:2 SET status = 'new', score = 2",
+		)
+		.await
+		.unwrap();
+	// Send UPDATE command returning the changed fields of every record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!(["tester", { "status": "done", "score": 1 }, { "data_expr": "merge", "changeset": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([
+			{ "id": "tester:1", "field": "status", "old": "new", "new": "done" },
+			{ "id": "tester:2", "field": "score", "old": 2, "new": 1 },
+			{ "id": "tester:2", "field": "status", "old": "new", "new": "done" },
+		]),
+		"result: {res:?}"
+	);
+	// Send UPDATE command which changes nothing
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!(["tester", { "status": "done" }, { "data_expr": "merge", "changeset": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn update_return_value(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test records
+	socket.send_message_query("CREATE tester:1, tester:2 SET name = 'foo'").await.unwrap();
+	// Send UPDATE command returning a single value
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!(["tester:1", { "value": 1 }, { "return_value": "value", "only": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(1), "result: {res:?}");
+	// Send UPDATE command returning a value for each record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!(["tester", { "value": 2 }, { "return_value": "value" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!([2, 2]), "result: {res:?}");
+	// Send UPDATE command with an invalid field
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!(["tester:1", { "value": 3 }, { "return_value": "value +" }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Send UPDATE command with both a return value and an output
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!(["tester:1", { "value": 3 }, { "return": "after", "return_value": "value" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn update_only_null(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create test records
+	socket
+		.send_message_query(
+			"CREATE tester:1 SET name = 'foo', value = NULL, nested = { a: 1 }; CREATE tester:2",
+		)
+		.await
+		.unwrap();
+	// Send UPDATE command which only writes null or absent fields
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!([
+				"tester",
+				{
+					"name": "bar",
+					"value": "bar",
+					"nested": { "a": 2, "b": 2 },
+				},
+				{
+					"data_expr": "merge",
+					"only_null": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 2, "result: {res:?}");
+	// Verify the existing values were preserved
+	let res = socket
+		.send_message_query("SELECT name, value, nested FROM [tester:1, tester:2]")
+		.await
+		.unwrap();
+	assert_eq!(
+		res[0]["result"],
+		json!([
+			{ "name": "foo", "value": "bar", "nested": { "a": 1, "b": 2 } },
+			{ "name": "bar", "value": "bar", "nested": { "a": 2, "b": 2 } },
+		]),
+		"result: {res:?}"
+	);
+	// Send UPDATE command which does not merge
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"update",
+			json!([
+				"tester",
+				{
+					"name": "bar",
+				},
+				{
+					"only_null": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn swap(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send SWAP command for a missing record
+	let res = socket
+		.send_versioned_request(Some(2), "swap", json!(["tester:one", { "state": "open" }]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	assert!(res["result"].is_null(), "result: {res:?}");
+	// Send SWAP command for an existing record
+	let res = socket
+		.send_versioned_request(Some(2), "swap", json!(["tester:one", { "closed": true }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!({ "id": "tester:one", "state": "open" }), "result: {res:?}");
+	// Verify the whole record was replaced
+	let res = socket.send_message_query("SELECT * FROM tester:one").await.unwrap();
+	assert_eq!(
+		res[0]["result"],
+		json!([{ "id": "tester:one", "closed": true }]),
+		"result: {res:?}"
+	);
+	// Send SWAP command for a table
+	let res = socket
+		.send_versioned_request(Some(2), "swap", json!(["tester", { "closed": false }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn upsert_split(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a test record
+	socket.send_message_query("CREATE tester:one SET name = 'foo', visits = 1").await.unwrap();
+	// Send UPSERT_SPLIT command for an existing and a missing record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"upsert_split",
+			json!([
+				["tester:one", "tester:two"],
+				{ "name": "new", "visits": 0 },
+				{ "visits": 2 },
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([
+			{ "action": "update", "result": { "id": "tester:one", "name": "foo", "visits": 2 } },
+			{ "action": "create", "result": { "id": "tester:two", "name": "new", "visits": 0 } },
+		]),
+		"result: {res:?}"
+	);
+	// Send UPSERT_SPLIT command for a single record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"upsert_split",
+			json!(["tester:two", { "name": "other" }, { "name": "updated" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!({ "action": "update", "result": { "id": "tester:two", "name": "updated", "visits": 0 } }),
+		"result: {res:?}"
+	);
+	// Send UPSERT_SPLIT command for a table
+	let res = socket
+		.send_versioned_request(Some(2), "upsert_split", json!(["tester", { "name": "new" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Verify no record was written when any record fails
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"upsert_split",
+			json!([["tester:three", "tester:four"], { "id": "tester:five" }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	let res = socket.send_message_query("SELECT VALUE id FROM tester").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["tester:one", "tester:two"]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn merge(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a test record
+	socket.send_message_query("CREATE tester SET name = 'foo'").await.unwrap();
+	// Send UPDATE command
+	let res = socket
+		.send_request(
+			"merge",
+			json!([
+				"tester",
+				{
+					"value": "bar",
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	// Verify the data was merged
+	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
+	assert!(res[0]["result"].is_array(), "result: {res:?}");
+	let res = res[0]["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["name"], "foo", "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn patch(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a test record
+	socket.send_message_query("CREATE tester:id SET name = 'foo'").await.unwrap();
+	// Send PATCH command
+	let res = socket
+		.send_request(
+			"patch",
+			json!([
+				"tester:id",
+				[
+					{
+						"op": "add",
+						"path": "value",
+						"value": "bar"
+					},
+					{
+						"op": "remove",
+						"path": "name",
+					}
+				]
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res.get("value"), Some(json!("bar")).as_ref(), "result: {res:?}");
+	// Verify the data was patched
+	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
+	assert!(res[0]["result"].is_array(), "result: {res:?}");
+	let res = res[0]["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["name"], json!(null), "result: {res:?}");
+	assert_eq!(res[0]["value"], "bar", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn delete(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a test record
+	socket.send_message_query("CREATE tester:id").await.unwrap();
+	// Send DELETE command
+	let res = socket.send_request("delete", json!(["tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert_eq!(res[0]["id"], "tester:id", "result: {res:?}");
+	// Create a test record
+	socket.send_message_query("CREATE tester:id").await.unwrap();
+	// Send DELETE command
+	let res = socket.send_request("delete", json!(["tester:id"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+	// Verify the data was merged
+	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
+	assert!(res[0]["result"].is_array(), "result: {res:?}");
+	let res = res[0]["result"].as_array().unwrap();
+	assert_eq!(res.len(), 0, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn delete_if_match(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a record which has since been changed
+	socket.send_message_query("CREATE tester:one SET version = 2, value = 'foo'").await.unwrap();
+	// Send DELETE command with an outdated version
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"delete",
+			json!(["tester:one", { "if_match": { "version": 1 } }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"], "The record tester:one has changed since it was read",
+		"result: {res:?}"
+	);
+	// Verify the record was not deleted
+	let res = socket.send_message_query("RETURN tester:one.value").await.unwrap();
+	assert_eq!(res[0]["result"], "foo", "result: {res:?}");
+	// Send DELETE command with the current version
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"delete",
+			json!(["tester:one", { "if_match": { "version": 2 } }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["value"], "foo", "result: {res:?}");
+	// Verify the record was deleted
+	let res = socket.send_message_query("RETURN tester:one.*").await.unwrap();
+	assert_eq!(res[0]["result"], json!(null), "result: {res:?}");
+	// Send DELETE command matching a whole table
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"delete",
+			json!(["tester", { "if_match": { "version": 2 } }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send QUERY command
+	let res = socket
+		.send_request("query", json!(["CREATE tester; SELECT * FROM tester;",]))
+		.await
+		.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 2, "result: {res:?}");
+	// Verify the data was created
+	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
+	assert!(res[0]["result"].is_array(), "result: {res:?}");
+	let res = res[0]["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn race(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send RACE command with a slow and a fast query
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"race",
+			json!([["SLEEP 5s; RETURN 'slow'", "RETURN 'fast' + $suffix"], { "suffix": "!" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["index"], 1, "result: {res:?}");
+	assert_eq!(res["result"]["result"], json!(["fast!"]), "result: {res:?}");
+	// Send RACE command where the fastest query fails
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"race",
+			json!([["THROW 'failed'", "SLEEP 100ms; RETURN 'fallback'"]]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["index"], 1, "result: {res:?}");
+	assert_eq!(res["result"]["result"], json!([null, "fallback"]), "result: {res:?}");
+	// Send RACE command with a query which writes data
+	let res = socket
+		.send_versioned_request(Some(2), "race", json!([["RETURN 1", "CREATE tester:one"]]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		"The query at index 1 must be a string containing only read-only statements",
+		"result: {res:?}"
+	);
+	// Verify no data was written
+	let res = socket.send_message_query("SELECT * FROM tester").await.unwrap();
+	assert_eq!(res[0]["result"], json!([]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_preview(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create some records
+	let res = socket.send_message_query("CREATE |tester:1..5|").await.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Send QUERY command with a preview
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT * FROM tester ORDER BY id; RETURN 1", null, { "preview": 2 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["status"], "OK", "result: {res:?}");
+	assert_eq!(
+		res["result"][0]["result"],
+		json!([{ "id": "tester:1" }, { "id": "tester:2" }]),
+		"result: {res:?}"
+	);
+	assert_eq!(res["result"][0]["total"], 5, "result: {res:?}");
+	// Results which are not arrays are returned untouched
+	assert_eq!(res["result"][1]["result"], 1, "result: {res:?}");
+	assert!(res["result"][1]["total"].is_null(), "result: {res:?}");
+	// Send QUERY command with a preview larger than the result
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT * FROM tester", null, { "preview": 10 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"].as_array().unwrap().len(), 5, "result: {res:?}");
+	assert_eq!(res["result"][0]["total"], 5, "result: {res:?}");
+	// Send QUERY command without a preview
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["SELECT * FROM tester"]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"].as_array().unwrap().len(), 5, "result: {res:?}");
+	assert!(res["result"][0]["total"].is_null(), "result: {res:?}");
+	// Send QUERY command with an invalid preview
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["RETURN 1", null, { "preview": -1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_statement_types(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send QUERY command with statement types
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"CREATE tester:one; SELECT * FROM tester; LET $x = 1; UPDATE tester:one SET x = $x; THROW 'failed'",
+				null,
+				{ "statement_types": true }
+			]),
+		)
+		.await
+		.unwrap();
+	let res = res["result"].as_array().unwrap();
+	let kinds: Vec<_> = res.iter().map(|r| r["statement"].clone()).collect();
+	assert_eq!(kinds, vec!["create", "select", "let", "update", "throw"], "result: {res:?}");
+	assert_eq!(res[1]["result"][0]["id"], "tester:one", "result: {res:?}");
+	assert_eq!(res[4]["status"], "ERR", "result: {res:?}");
+	// Send QUERY command without statement types
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["SELECT * FROM tester"]))
+		.await
+		.unwrap();
+	assert!(res["result"][0].get("statement").is_none(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_max_depth(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a chain of five connected records
+	socket
+		.send_message_query(
+			"CREATE |person:1..6|; \
+			RELATE person:1->knows->person:2; RELATE person:2->knows->person:3; \
+			RELATE person:3->knows->person:4; RELATE person:4->knows->person:5; \
+			RELATE person:5->knows->person:6",
+		)
+		.await
+		.unwrap();
+	// The query which recursively traverses the chain
+	let sql = "RETURN person:1.{..+collect}(->knows->person)";
+	// Send QUERY command with a sufficient depth
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "max_depth": 10 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["status"], "OK", "result: {res:?}");
+	assert_eq!(
+		res["result"][0]["result"],
+		json!(["person:2", "person:3", "person:4", "person:5", "person:6"]),
+		"result: {res:?}"
+	);
+	// Send QUERY command with a bounded recursion within the depth
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["RETURN person:1.{1..3+collect}(->knows->person)", null, { "max_depth": 3 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["status"], "OK", "result: {res:?}");
+	assert_eq!(
+		res["result"][0]["result"],
+		json!(["person:2", "person:3", "person:4"]),
+		"result: {res:?}"
+	);
+	// Send QUERY command with an insufficient depth
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "max_depth": 3 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["status"], "ERR", "result: {res:?}");
+	assert_eq!(
+		res["result"][0]["result"], "Exceeded the recursion depth limit of 3 for this request.",
+		"result: {res:?}"
+	);
+	// Send QUERY command with an invalid depth
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "max_depth": 0 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_non_finite(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// The query which returns non-finite floats
+	let sql = "RETURN [math::inf - math::inf, math::inf, math::neg_inf, 1.5f]";
+	// Send QUERY command returning them as strings
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "non_finite": "string" }]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"][0]["result"],
+		json!(["NaN", "Infinity", "-Infinity", 1.5]),
+		"result: {res:?}"
+	);
+	// Send QUERY command returning them as null
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "non_finite": "null" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"], json!([null, null, null, 1.5]), "result: {res:?}");
+	// Send QUERY command rejecting them
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "non_finite": "error" }]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		"The result contains a non-finite number, which can not be represented",
+		"result: {res:?}"
+	);
+	// Send SELECT command returning them as strings
+	socket.send_message_query("CREATE tester:one SET value = math::inf").await.unwrap();
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "non_finite": "string" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["value"], "Infinity", "result: {res:?}");
+	// Send QUERY command with an unknown policy
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "non_finite": "zero" }]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn record_id_format(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create records which link to each other
+	socket
+		.send_message_query("CREATE tester:1 SET next = tester:['a', 2]; CREATE tester:['a', 2]")
+		.await
+		.unwrap();
+	// Send SELECT command returning record ids as strings
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester:1", { "record_ids": "string" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["id"], "tester:1", "result: {res:?}");
+	assert_eq!(res["result"][0]["next"], "tester:['a', 2]", "result: {res:?}");
+	// Send SELECT command returning record ids as objects
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester:1", { "record_ids": "object" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["id"], json!({ "tb": "tester", "id": 1 }), "result: {res:?}");
+	assert_eq!(
+		res["result"][0]["next"],
+		json!({ "tb": "tester", "id": ["a", 2] }),
+		"result: {res:?}"
+	);
+	// Send QUERY command returning record ids as objects
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT VALUE id FROM tester:1", null, { "record_ids": "object" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"], json!([{ "tb": "tester", "id": 1 }]), "result: {res:?}");
+	// Send SELECT command with an unknown record id format
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "record_ids": "array" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn explicit_nulls(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a record with a field set to null and a field which is absent
+	socket.send_message_query("CREATE tester:1 SET name = 'a', email = NULL").await.unwrap();
+	// Send SELECT command without telling null and absent fields apart
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!(["tester:1", { "fields": "name, email, phone", "only": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["email"], json!(null), "result: {res:?}");
+	assert_eq!(res["result"]["phone"], json!(null), "result: {res:?}");
+	// Send SELECT command telling null and absent fields apart
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"select",
+			json!([
+				"tester:1",
+				{ "fields": "name, email, phone", "only": true, "explicit_nulls": true }
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!({ "name": "a", "email": null }), "result: {res:?}");
+	// Send QUERY command telling null and absent fields apart
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT email, phone FROM tester", null, { "explicit_nulls": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"], json!([{ "email": null }]), "result: {res:?}");
+	// Send SELECT command with an invalid option value
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "explicit_nulls": "yes" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn with_types(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a field type and create records with differing fields
+	socket
+		.send_message_query(
+			"DEFINE FIELD age ON tester TYPE option<int>;
+			CREATE tester:1 SET name = 'a', age = 1, score = 1.5;
+			CREATE tester:2 SET name = 'b', score = 2;",
+		)
+		.await
+		.unwrap();
+	// Send SELECT command with the field types
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "with_types": true }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["result"].as_array().unwrap().len(), 2, "result: {res:?}");
+	assert_eq!(
+		res["result"]["types"],
+		json!({
+			"age": "option<int>",
+			"id": "record<tester>",
+			"name": "string",
+			"score": "float | int",
+		}),
+		"result: {res:?}"
+	);
+	// Send QUERY command with the inferred field types
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SELECT name, age FROM tester", null, { "with_types": true }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"][0]["types"],
+		json!({ "age": "option<int>", "name": "string" }),
+		"result: {res:?}"
+	);
+	// Send SELECT command with an invalid option value
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "with_types": 1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_with(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create some records
+	socket
+		.send_message_query(
+			"CREATE tester:1 SET name = 'a', active = true;
+			CREATE tester:2 SET name = 'b', active = false;",
+		)
+		.await
+		.unwrap();
+	// Send QUERY command with named subqueries which reference each other
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"RETURN $names; RETURN array::len($active);",
+				null,
+				{
+					"with": {
+						"names": "SELECT VALUE name FROM $active",
+						"active": "SELECT VALUE id FROM tester WHERE active",
+					}
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"].as_array().unwrap().len(), 2, "result: {res:?}");
+	assert_eq!(res["result"][0]["result"], json!(["a"]), "result: {res:?}");
+	assert_eq!(res["result"][1]["result"], 1, "result: {res:?}");
+	// Send QUERY command referencing a name which is not defined
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"RETURN $missing",
+				null,
+				{ "with": { "names": "SELECT VALUE name FROM tester" } }
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		"The query references $missing, which is not defined by a named subquery or variable",
+		"result: {res:?}"
+	);
+	// Send QUERY command with named subqueries which reference themselves
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["RETURN $a", null, { "with": { "a": "RETURN $b", "b": "RETURN $a" } }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Send QUERY command with an invalid name
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["RETURN 1", null, { "with": { "a-b": "1" } }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn request_tag(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send QUERY command with a tag
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["RETURN 1", null, { "tag": "req-1" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"], 1, "result: {res:?}");
+	// Send SELECT command with a tag which needs sanitizing
+	let res = socket
+		.send_versioned_request(Some(2), "select", json!(["tester", { "tag": "sel 2\n'x" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// Check that the tags are logged with the requests
+	let mut logs = String::new();
+	for _ in 0..50 {
+		logs = server.stdout_and_stderr();
+		if logs.contains("with tag 'sel2x'") {
+			break;
+		}
+		tokio::time::sleep(Duration::from_millis(100)).await;
+	}
+	assert!(logs.contains("Processing RPC request with tag 'req-1'"), "logs: {logs}");
+	assert!(logs.contains("Processing RPC request with tag 'sel2x'"), "logs: {logs}");
+	// Send QUERY command with an invalid tag
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["RETURN 1", null, { "tag": 1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_parser_flags(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Strings are parsed as strings by default
+	let sql = "RETURN type::is::record('foo:one')";
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_eq!(res["result"][0]["result"], false, "result: {res:?}");
+	// Send QUERY command parsing strings which look like record ids as record ids
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([sql, null, { "parser": ["legacy_strands"] }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"], true, "result: {res:?}");
+	// Record ids starting with digits are parsed by default
+	let sql = "RETURN record::id(foo:1abc)";
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_eq!(res["result"][0]["result"], "1abc", "result: {res:?}");
+	// Send QUERY command requiring record ids to be regular identifiers
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([sql, null, { "parser": ["strict_record_ids"] }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Send QUERY command with both flags
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"RETURN type::is::record('foo:one')",
+				null,
+				{ "parser": ["legacy_strands", "strict_record_ids"] }
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["result"], true, "result: {res:?}");
+	// Send QUERY command with an unknown flag
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["RETURN 1", null, { "parser": ["unknown"] }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send QUERY command with flags which are not an array
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["RETURN 1", null, { "parser": "legacy_strands" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_seed(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// The query which generates random values
+	let sql = "RETURN [rand(), rand::int(), rand::string(), rand::uuid::v4()]";
+	// Send QUERY command with a seed
+	let one = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "seed": 42 }]))
+		.await
+		.unwrap();
+	assert_eq!(one["result"][0]["status"], "OK", "result: {one:?}");
+	// Send QUERY command with the same seed
+	let two = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "seed": 42 }]))
+		.await
+		.unwrap();
+	assert_eq!(one["result"][0]["result"], two["result"][0]["result"], "result: {two:?}");
+	// Send QUERY command with a different seed
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "seed": 7 }]))
+		.await
+		.unwrap();
+	assert_ne!(one["result"][0]["result"], res["result"][0]["result"], "result: {res:?}");
+	// Send QUERY command without a seed
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_ne!(one["result"][0]["result"], res["result"][0]["result"], "result: {res:?}");
+	// Send QUERY command with an invalid seed
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!([sql, null, { "seed": -1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_explain_if_slower_than(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// The query which creates and selects records
+	let sql = "CREATE tester:one SET name = 'one'; SELECT * FROM tester WHERE name = 'one'";
+	// Send QUERY command where every statement is slower than the threshold
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([sql, null, { "explain_if_slower_than": "0ns" }]),
+		)
+		.await
+		.unwrap();
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 2, "result: {res:?}");
+	assert!(res[0].get("plan").is_none(), "result: {res:?}");
+	assert_eq!(res[1]["result"].as_array().unwrap().len(), 1, "result: {res:?}");
+	assert_eq!(res[1]["plan"][0]["operation"], "Iterate Table", "result: {res:?}");
+	// Send QUERY command where no statement is slower than the threshold
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([sql, null, { "explain_if_slower_than": "1h" }]),
+		)
+		.await
+		.unwrap();
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 2, "result: {res:?}");
+	assert!(res[1].get("plan").is_none(), "result: {res:?}");
+	// Send QUERY command with an invalid threshold
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([sql, null, { "explain_if_slower_than": "soon" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_fail_fast(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send QUERY command which continues after a failed statement
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"CREATE tester:1; CREATE tester:1; CREATE tester:2;",
+				{},
+				{
+					"fail_fast": false,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 3, "result: {res:?}");
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	assert_eq!(res[1]["status"], "ERR", "result: {res:?}");
+	assert_eq!(res[2]["status"], "OK", "result: {res:?}");
+	// Send QUERY command which stops after a failed statement
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"CREATE tester:3; CREATE tester:3; CREATE tester:4;",
+				{},
+				{
+					"fail_fast": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 3, "result: {res:?}");
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	assert_eq!(res[1]["status"], "ERR", "result: {res:?}");
+	assert_eq!(res[2]["status"], "ERR", "result: {res:?}");
+	assert_eq!(
+		res[2]["result"], "The query was not executed because a previous statement failed",
+		"result: {res:?}"
+	);
+	// Verify only the statements before the failure were executed
+	let res = socket.send_message_query("SELECT VALUE id FROM tester").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["tester:1", "tester:2", "tester:3"]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_debug_vars(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define some session variables
+	socket.send_request("let", json!(["name", "session"])).await.unwrap();
+	socket.send_request("let", json!(["db_password", "hunter2"])).await.unwrap();
+	// Send QUERY command which returns the variables
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"RETURN [$name, $extra];",
+				{
+					"name": "request",
+					"extra": 1,
+				},
+				{
+					"debug_vars": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = &res["result"];
+	assert_eq!(res["result"][0]["status"], "OK", "result: {res:?}");
+	assert_eq!(res["result"][0]["result"], json!(["session", 1]), "result: {res:?}");
+	// The session variables take precedence over the request variables
+	assert_eq!(res["vars"]["name"], "session", "result: {res:?}");
+	assert_eq!(res["vars"]["extra"], 1, "result: {res:?}");
+	// Secret variables are redacted
+	assert_eq!(res["vars"]["db_password"], "[REDACTED]", "result: {res:?}");
+	// Send QUERY command without returning the variables
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["RETURN 1;", {}, { "debug_vars": false }]))
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	// Send QUERY command with an invalid option
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["RETURN 1;", {}, { "debug_vars": "yes" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_timing(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send QUERY command returning the execution times
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!(["SLEEP 50ms; RETURN 1", null, { "timing": true }]),
+		)
+		.await
+		.unwrap();
+	let slept = res["result"][0]["time_ns"].as_u64().unwrap();
+	assert!(slept >= 50_000_000, "result: {res:?}");
+	let returned = res["result"][1]["time_ns"].as_u64().unwrap();
+	assert!(returned < slept, "result: {res:?}");
+	// The responses are otherwise unchanged
+	assert_eq!(res["result"][1]["status"], "OK", "result: {res:?}");
+	assert_eq!(res["result"][1]["result"], 1, "result: {res:?}");
+	assert!(res["result"][1]["time"].is_string(), "result: {res:?}");
+	// Send QUERY command without the execution times
+	let res = socket.send_versioned_request(Some(2), "query", json!(["RETURN 1"])).await.unwrap();
+	assert!(res["result"][0].get("time_ns").is_none(), "result: {res:?}");
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["RETURN 1", null, { "timing": false }]))
+		.await
+		.unwrap();
+	assert!(res["result"][0].get("time_ns").is_none(), "result: {res:?}");
+	// Send QUERY command with an invalid option
+	let res = socket
+		.send_versioned_request(Some(2), "query", json!(["RETURN 1", null, { "timing": 1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_stream(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send QUERY command which streams the response of each statement
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"CREATE tester:1; BEGIN; CREATE tester:2; CREATE tester:3; COMMIT; CREATE tester:1;",
+				{},
+				{
+					"stream": "stream-1",
+					"timing": true,
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!(4), "result: {res:?}");
+	// Verify the responses were sent in order
+	let msgs = socket.receive_all_other_messages(4, Duration::from_secs(1)).await.unwrap();
+	for (i, msg) in msgs.iter().enumerate() {
+		assert_eq!(msg["result"]["stream"], "stream-1", "message: {msg:?}");
+		assert_eq!(msg["result"]["index"], json!(i), "message: {msg:?}");
+		assert!(msg["result"]["response"]["time_ns"].is_number(), "message: {msg:?}");
+	}
+	assert_eq!(msgs[0]["result"]["response"]["status"], "OK", "message: {:?}", msgs[0]);
+	assert_eq!(msgs[1]["result"]["response"]["status"], "OK", "message: {:?}", msgs[1]);
+	assert_eq!(msgs[2]["result"]["response"]["status"], "OK", "message: {:?}", msgs[2]);
+	assert_eq!(msgs[3]["result"]["response"]["status"], "ERR", "message: {:?}", msgs[3]);
+	// Streamed queries can not be retried
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"CREATE tester:4;",
+				{},
+				{
+					"stream": "stream-2",
+					"retry": { "attempts": 3 },
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_typed_params(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send QUERY command with typed params
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"RETURN [type::is::datetime($t), type::is::duration($d), type::is::uuid($u), type::is::datetime($a[0])]",
+				{
+					"t": { "__type": "datetime", "value": "2024-01-01T00:00:00Z" },
+					"d": { "__type": "duration", "value": "1h30m" },
+					"u": { "__type": "uuid", "value": "4a4c2e1b-3b4c-4d4e-8f9a-0b1c2d3e4f5a" },
+					"a": [{ "__type": "datetime", "value": "2024-01-01T00:00:00Z" }],
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res[0]["result"], json!([true, true, true, true]), "result: {res:?}");
+	// Send QUERY command with an invalid value for the declared type
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"RETURN $t",
+				{
+					"t": { "__type": "datetime", "value": "yesterday" },
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send QUERY command with an unknown declared type
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"query",
+			json!([
+				"RETURN $t",
+				{
+					"t": { "__type": "timestamp", "value": "2024-01-01T00:00:00Z" },
+				}
+			]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn prepared_statements(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send PREPARE command
+	let res = socket
+		.send_versioned_request(Some(2), "prepare", json!(["CREATE tester SET value = $value"]))
+		.await
+		.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let handle = res["result"].as_str().unwrap().to_owned();
+	// Send EXECUTE_PREPARED command with varying params
+	for value in ["foo", "bar"] {
+		let res = socket
+			.send_versioned_request(
+				Some(2),
+				"execute_prepared",
+				json!([handle, { "value": value }]),
+			)
+			.await
+			.unwrap();
+		assert!(res["result"].is_array(), "result: {res:?}");
+		let res = res["result"].as_array().unwrap();
+		assert_eq!(res.len(), 1, "result: {res:?}");
+		assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+		assert_eq!(res[0]["result"][0]["value"], value, "result: {res:?}");
+	}
+	// Send EXECUTE_PREPARED command with an unknown handle
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"execute_prepared",
+			json!(["4a4c2e1b-3b4c-4d4e-8f9a-0b1c2d3e4f5a"]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	assert_eq!(
+		res["error"]["message"],
+		"The prepared statement '4a4c2e1b-3b4c-4d4e-8f9a-0b1c2d3e4f5a' does not exist or has expired",
+		"result: {res:?}"
+	);
+	// Prepared statements are not shared between connections
+	let other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	let res =
+		other.send_versioned_request(Some(2), "execute_prepared", json!([handle])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Verify the data was created
+	let res =
+		socket.send_message_query("SELECT VALUE value FROM tester ORDER BY value").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["bar", "foo"]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn prepared_statements_batch(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send PREPARE command
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"prepare",
+			json!(["CREATE ONLY type::thing('tester', $id) SET value = $value"]),
+		)
+		.await
+		.unwrap();
+	let handle = res["result"].as_str().unwrap().to_owned();
+	// Send EXECUTE_PREPARED_BATCH command
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"execute_prepared_batch",
+			json!([handle, [{ "id": 1, "value": "foo" }, { "id": 2, "value": "bar" }]]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([
+			[{ "id": "tester:1", "value": "foo" }],
+			[{ "id": "tester:2", "value": "bar" }],
+		]),
+		"result: {res:?}"
+	);
+	// Send EXECUTE_PREPARED_BATCH command which fails part way through
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"execute_prepared_batch",
+			json!([handle, [{ "id": 3, "value": "baz" }, { "id": 1, "value": "qux" }]]),
+		)
+		.await
+		.unwrap();
+	let message = res["error"]["message"].as_str().unwrap();
+	assert!(
+		message.starts_with("The batch was rolled back, as the parameters at index 1 failed"),
+		"result: {res:?}"
+	);
+	// Verify the whole failed batch was rolled back
+	let res =
+		socket.send_message_query("SELECT VALUE value FROM tester ORDER BY value").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["bar", "foo"]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn table_scan(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test records
+	socket.send_message_query("CREATE |tester:1..5| SET value = record::id(id)").await.unwrap();
+	// Send SCAN_OPEN command
+	let res =
+		socket.send_versioned_request(Some(2), "scan_open", json!(["tester", 2])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let handle = res["result"].as_str().unwrap().to_owned();
+	// Send SCAN_NEXT commands until the scan is complete
+	let mut values = Vec::new();
+	let mut batches = 0;
+	loop {
+		let res =
+			socket.send_versioned_request(Some(2), "scan_next", json!([handle])).await.unwrap();
+		assert!(res["result"].is_object(), "result: {res:?}");
+		let batch = res["result"]["result"].as_array().unwrap();
+		assert!(batch.len() <= 2, "result: {res:?}");
+		values.extend(batch.iter().map(|v| v["value"].clone()));
+		batches += 1;
+		if res["result"]["done"] == true {
+			break;
+		}
+	}
+	assert_eq!(batches, 3);
+	assert_eq!(values, vec![json!(1), json!(2), json!(3), json!(4), json!(5)]);
+	// The scan is closed once it is complete
+	let res = socket.send_versioned_request(Some(2), "scan_next", json!([handle])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Send SCAN_CLOSE command for an open scan
+	let res =
+		socket.send_versioned_request(Some(2), "scan_open", json!(["tester", 2])).await.unwrap();
+	let handle = res["result"].as_str().unwrap().to_owned();
+	let res = socket.send_versioned_request(Some(2), "scan_close", json!([handle])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "scan_next", json!([handle])).await.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		format!("The table scan '{handle}' does not exist or has expired"),
+		"result: {res:?}"
+	);
+	// Send SCAN_OPEN command with an invalid batch size
+	let res =
+		socket.send_versioned_request(Some(2), "scan_open", json!(["tester", 0])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn key_range(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test records
+	socket.send_message_query("CREATE |tester:1..5| SET value = record::id(id)").await.unwrap();
+	// Send KEY_RANGE command for the whole table
+	let res = socket.send_versioned_request(Some(2), "key_range", json!(["tester"])).await.unwrap();
+	assert_eq!(res["result"]["table"], "tester", "result: {res:?}");
+	assert_eq!(res["result"]["ranges"].as_array().unwrap().len(), 1, "result: {res:?}");
+	// Send KEY_RANGE command splitting the table into partitions
+	let res = socket
+		.send_versioned_request(Some(2), "key_range", json!(["tester", { "partitions": 2 }]))
+		.await
+		.unwrap();
+	let ranges = res["result"]["ranges"].as_array().unwrap().clone();
+	assert_eq!(ranges.len(), 2, "result: {res:?}");
+	// Scan each of the partitions separately
+	let mut partitions = Vec::new();
+	for range in ranges {
+		let res = socket
+			.send_versioned_request(Some(2), "scan_open", json!(["tester", 10, range]))
+			.await
+			.unwrap();
+		let handle = res["result"].as_str().unwrap().to_owned();
+		let res =
+			socket.send_versioned_request(Some(2), "scan_next", json!([handle])).await.unwrap();
+		let batch = res["result"]["result"].as_array().unwrap();
+		partitions.push(batch.iter().map(|v| v["value"].clone()).collect::<Vec<_>>());
+	}
+	assert_eq!(partitions, vec![vec![json!(1), json!(2), json!(3)], vec![json!(4), json!(5)]]);
+	// Send SCAN_OPEN command with a key which is not in the table
+	let res = socket
+		.send_versioned_request(Some(2), "scan_open", json!(["tester", 10, { "start": "00" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send KEY_RANGE command for a table which does not exist
+	let res =
+		socket.send_versioned_request(Some(2), "key_range", json!(["missing"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Check a root user without the owner role is not allowed
+	socket
+		.send_message_query("DEFINE USER viewer ON ROOT PASSWORD 'secret' ROLES VIEWER")
+		.await
+		.unwrap();
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin("viewer", "secret", None, None, None).await.unwrap();
+	other.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "key_range", json!(["tester"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn query_log(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// The query log is not started by default
+	let res = socket.send_versioned_request(Some(2), "query_log", json!([])).await.unwrap();
+	assert_eq!(
+		res["error"]["message"], "The query log has not been started on this connection",
+		"result: {res:?}"
+	);
+	// Send QUERY_LOG_START command keeping two queries
+	let res = socket
+		.send_versioned_request(Some(2), "query_log_start", json!([{ "size": 2, "redact": true }]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	// Run a few queries, the first of which is discarded
+	for sql in [
+		"CREATE tester:1",
+		"CREATE |tester:2..4|; SELECT * FROM tester",
+		"DEFINE USER test ON DATABASE PASSWORD 'secret' ROLES VIEWER; THROW 'failed'",
+	] {
+		socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	}
+	// Send QUERY_LOG command and clear the log
+	let res = socket
+		.send_versioned_request(Some(2), "query_log", json!([{ "clear": true }]))
+		.await
+		.unwrap();
+	let entries = res["result"].as_array().unwrap();
+	assert_eq!(entries.len(), 2, "result: {res:?}");
+	assert_eq!(entries[0]["query"], "CREATE |tester:2..4|; SELECT * FROM tester");
+	assert_eq!(entries[0]["rows"], 7, "result: {res:?}");
+	assert!(entries[0]["error"].is_null(), "result: {res:?}");
+	assert!(entries[0]["duration"].is_string(), "result: {res:?}");
+	assert_eq!(
+		entries[1]["query"],
+		"DEFINE USER test ON DATABASE PASSWORD '[REDACTED]' ROLES VIEWER; THROW '[REDACTED]'"
+	);
+	assert_eq!(entries[1]["error"], "An error occurred: failed", "result: {res:?}");
+	// The log is empty once it has been cleared
+	let res = socket.send_versioned_request(Some(2), "query_log", json!([])).await.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// Send QUERY_LOG_START command with a size above the limit
+	let res = socket
+		.send_versioned_request(Some(2), "query_log_start", json!([{ "size": 1000000 }]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Send QUERY_LOG_STOP command
+	let res = socket.send_versioned_request(Some(2), "query_log_stop", json!([])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "query_log", json!([])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn webhooks(cfg_server: Option<Format>, cfg_format: Format) {
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+	// Setup a webhook endpoint which fails the first delivery
+	let mock = MockServer::start().await;
+	Mock::given(method("POST"))
+		.and(path("/hook"))
+		.respond_with(ResponseTemplate::new(500))
+		.up_to_n_times(1)
+		.mount(&mock)
+		.await;
+	Mock::given(method("POST"))
+		.and(path("/hook"))
+		.respond_with(ResponseTemplate::new(200))
+		.mount(&mock)
+		.await;
+	let url = format!("{}/hook", mock.uri());
+	// Setup database server with access to the network
+	let (addr, mut server) = common::start_server(StartServerArguments {
+		args: "--allow-net".to_string(),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define the tables, with and without a changefeed
+	let res = socket
+		.send_message_query("DEFINE TABLE tester CHANGEFEED 1h; DEFINE TABLE other;")
+		.await
+		.unwrap();
+	assert!(res.iter().all(|v| v["status"] == "OK"), "result: {res:?}");
+	// Check tables without a changefeed are rejected
+	let res = socket
+		.send_versioned_request(Some(2), "webhook_register", json!([url.clone(), "other"]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Send WEBHOOK_REGISTER command
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"webhook_register",
+			json!([url.clone(), "tester", { "cond": "name = 'hook'" }]),
+		)
+		.await
+		.unwrap();
+	let id = res["result"]["id"].clone();
+	assert!(id.is_string(), "result: {res:?}");
+	assert_eq!(res["result"]["url"], url, "result: {res:?}");
+	assert_eq!(res["result"]["table"], "tester", "result: {res:?}");
+	assert_eq!(res["result"]["cond"], "name = 'hook'", "result: {res:?}");
+	assert_eq!(res["result"]["failures"], 0, "result: {res:?}");
+	// Check URLs which are not HTTP are rejected
+	let res = socket
+		.send_versioned_request(Some(2), "webhook_register", json!(["ftp://127.0.0.1", "tester"]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Check writes succeed while the webhook endpoint fails
+	let res = socket
+		.send_message_query("CREATE tester:1 SET name = 'hook'; CREATE tester:2 SET name = 'skip';")
+		.await
+		.unwrap();
+	assert!(res.iter().all(|v| v["status"] == "OK"), "result: {res:?}");
+	// Wait for the failed delivery to be retried
+	let mut delivered = Vec::new();
+	for _ in 0..100 {
+		delivered = mock.received_requests().await.unwrap_or_default();
+		if delivered.len() >= 2 {
+			break;
+		}
+		tokio::time::sleep(Duration::from_millis(100)).await;
+	}
+	assert_eq!(delivered.len(), 2, "requests: {delivered:?}");
+	// Check the same changes were delivered again, matching the condition
+	let first: serde_json::Value = delivered[0].body_json().unwrap();
+	let body: serde_json::Value = delivered[1].body_json().unwrap();
+	assert_eq!(first, body);
+	assert_eq!(body["webhook"], id, "body: {body:?}");
+	assert_eq!(body["table"], "tester", "body: {body:?}");
+	let changes = body["changes"].as_array().unwrap();
+	assert_eq!(changes.len(), 1, "body: {body:?}");
+	assert_eq!(changes[0]["update"]["name"], "hook", "body: {body:?}");
+	// Send WEBHOOKS command
+	let res = socket.send_versioned_request(Some(2), "webhooks", json!([])).await.unwrap();
+	let hooks = res["result"].as_array().unwrap();
+	assert_eq!(hooks.len(), 1, "result: {res:?}");
+	assert_eq!(hooks[0]["id"], id, "result: {res:?}");
+	assert_eq!(hooks[0]["failures"], 0, "result: {res:?}");
+	assert!(hooks[0]["error"].is_null(), "result: {res:?}");
+	// Send WEBHOOK_REMOVE command
+	let res = socket
+		.send_versioned_request(Some(2), "webhook_remove", json!([id.clone()]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], true, "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "webhook_remove", json!([id])).await.unwrap();
+	assert_eq!(res["result"], false, "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "webhooks", json!([])).await.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn batch(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Send BATCH command which changes the session, and then uses it
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"batch",
+			json!([[
+				{ "method": "use", "params": [NS, DB] },
+				{ "method": "let", "params": ["name", "foo"] },
+				{ "method": "create", "params": ["tester:one", { "name": "bar" }] },
+				{ "method": "missing", "params": [] },
+				{ "method": "select", "params": "tester:one" },
+				{ "method": "batch", "params": [[]] },
+				{ "method": "query", "params": ["RETURN [$name, tester:one.name]"] },
+			]]),
+		)
+		.await
+		.unwrap();
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 7, "result: {res:?}");
+	assert!(res[0]["error"].is_null(), "result: {res:?}");
+	assert!(res[1]["error"].is_null(), "result: {res:?}");
+	assert!(res[2]["error"].is_null(), "result: {res:?}");
+	// Failed calls are returned as errors without stopping the batch
+	assert_eq!(res[3]["error"]["code"], -32601, "result: {res:?}");
+	assert_eq!(res[3]["error"]["message"], "Method not found", "result: {res:?}");
+	assert_eq!(res[4]["error"]["code"], -32602, "result: {res:?}");
+	assert_eq!(res[5]["error"]["code"], -32600, "result: {res:?}");
+	assert_eq!(res[6]["result"][0]["result"], json!(["foo", "bar"]), "result: {res:?}");
+	// Send BATCH command without any calls
+	let res = socket.send_versioned_request(Some(2), "batch", json!([[]])).await.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn version(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Send version command
+	let res = socket.send_request("version", json!([])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let res = res["result"].as_str().unwrap();
+	assert!(res.starts_with("surrealdb-"), "result: {res}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+// Validate that the WebSocket is able to process multiple queries concurrently
+pub async fn concurrency(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send 5 long-running queries and verify they run concurrently
+
+	let mut futures = Vec::<Pin<Box<dyn Future<Output = _>>>>::new();
+	for i in 0..5 {
+		let future = socket.send_request("query", json!([format!("SLEEP 3s; RETURN {i};")]));
+		futures.push(Box::pin(future))
+	}
+
+	let res =
+		tokio::time::timeout(Duration::from_secs(5), futures::future::join_all(futures)).await;
+	let Ok(res) = res else {
+		panic!("future timed-out");
+	};
+
+	let res = res
+		.into_iter()
+		.try_fold(Vec::new(), |mut acc, x| -> Result<_, Box<dyn std::error::Error>> {
+			acc.push(x?);
+			Ok(acc)
+		})
+		.unwrap();
+
+	assert!(res.iter().all(|v| v["error"].is_null()), "Unexpected error received: {res:#?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn live_query(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send LIVE command
+	let res = socket.send_request("query", json!(["LIVE SELECT * FROM tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert!(res[0]["result"].is_string(), "result: {res:?}");
+	assert!(res[0]["type"].is_string(), "type: {res:?}");
+	let live1 = res[0]["result"].as_str().unwrap();
+	// Send LIVE command
+	let res = socket.send_request("query", json!(["LIVE SELECT * FROM tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert!(res[0]["result"].is_string(), "result: {res:?}");
+	assert!(res[0]["type"].is_string(), "type: {res:?}");
+	let live2 = res[0]["result"].as_str().unwrap();
+	// Create a new test record
+	let res =
+		socket.send_request("query", json!(["CREATE tester:id SET name = 'foo'"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	// Wait some time for all messages to arrive, and then search for the notification message
+	let msgs: Result<_, Box<dyn std::error::Error>> =
+		tokio::time::timeout(Duration::from_secs(1), async {
+			Ok(vec![
+				socket.receive_other_message().await.unwrap(),
+				socket.receive_other_message().await.unwrap(),
+			])
+		})
+		.await
+		.unwrap();
+	let msgs = msgs.unwrap();
+	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	// Check for first live query notifcation
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live1));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live1}: {msgs:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert!(res["action"].is_string(), "result: {res:?}");
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+	// Check for second live query notifcation
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live2));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+/// Same as live but uses the RPC for both methods.
+pub async fn live_rpc(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send LIVE command
+	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live1 = res["result"].as_str().unwrap();
+
+	// Send LIVE command
+	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live2 = res["result"].as_str().unwrap();
+
+	// Create a new test record
+	let res =
+		socket.send_request("query", json!(["CREATE tester:id SET name = 'foo'"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	// Wait some time for all messages to arrive, and then search for the notification message
+	let msgs: Result<_, Box<dyn std::error::Error>> =
+		tokio::time::timeout(Duration::from_secs(1), async {
+			Ok(vec![
+				socket.receive_other_message().await.unwrap(),
+				socket.receive_other_message().await.unwrap(),
+			])
+		})
+		.await
+		.unwrap();
+	let msgs = msgs.unwrap();
+	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	// Check for first live query notifcation
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live1));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live1}: {msgs:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert!(res["action"].is_string(), "result: {res:?}");
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+	// Check for second live query notifcation
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live2));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn is_live(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send LIVE command
+	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live = res["result"].as_str().unwrap();
+	// Check the live query is active
+	let res = socket.send_versioned_request(Some(2), "is_live", json!([live])).await.unwrap();
+	assert_eq!(res["result"], true, "result: {res:?}");
+	// Check an unknown live query is not active
+	let res = socket
+		.send_versioned_request(Some(2), "is_live", json!(["4a4c2e1b-3b4c-4d4e-8f9a-0b1c2d3e4f5a"]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], false, "result: {res:?}");
+	// Check the live query is not active on another connection
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "is_live", json!([live])).await.unwrap();
+	assert_eq!(res["result"], false, "result: {res:?}");
+	// Send KILL command
+	let res = socket.send_request("kill", json!([live])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	// Check the live query is no longer active
+	let res = socket.send_versioned_request(Some(2), "is_live", json!([live])).await.unwrap();
+	assert_eq!(res["result"], false, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn kill_orphans(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send LIVE command
+	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live = res["result"].as_str().unwrap();
+	// Define a root user without the owner role
+	let res = socket
+		.send_message_query("DEFINE USER viewer ON ROOT PASSWORD 'secret' ROLES VIEWER")
+		.await
+		.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Check the live queries of connected sessions are not killed
+	let res = socket.send_versioned_request(Some(2), "kill_orphans", json!([])).await.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "is_live", json!([live])).await.unwrap();
+	assert_eq!(res["result"], true, "result: {res:?}");
+	// Check a root user without the owner role is not allowed
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin("viewer", "secret", None, None, None).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "kill_orphans", json!([])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Check an anonymous connection is not allowed
+	let other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "kill_orphans", json!([])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Check the live query is still active
+	let res = socket.send_versioned_request(Some(2), "is_live", json!([live])).await.unwrap();
+	assert_eq!(res["result"], true, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn live_token(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Start a live query with a token
+	let res = socket
+		.send_versioned_request(Some(2), "live", json!(["tester", { "token": "sub-1" }]))
+		.await
+		.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live1 = res["result"].as_str().unwrap().to_owned();
+	// Start a live query without a token
+	let res = socket.send_versioned_request(Some(2), "live", json!(["tester"])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live2 = res["result"].as_str().unwrap().to_owned();
+	// Check an invalid token is rejected
+	let res = socket
+		.send_versioned_request(Some(2), "live", json!(["tester", { "token": 1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Create a new test record
+	socket.send_request("query", json!(["CREATE tester:id SET name = 'foo'"])).await.unwrap();
+	// Wait for the notifications of both live queries
+	let msgs = socket.receive_all_other_messages(2, Duration::from_secs(1)).await.unwrap();
+	// Check the token is echoed in the notification of the first live query
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, &live1));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live1}: {msgs:?}");
+	let res = &res.unwrap()["result"];
+	assert_eq!(res["token"], "sub-1", "result: {res:?}");
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert_eq!(res["result"]["id"], "tester:id", "result: {res:?}");
+	// Check no token is present in the notification of the second live query
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, &live2));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
+	let res = &res.unwrap()["result"];
+	assert!(res.get("token").is_none(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn live_aggregate(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the initial test records
+	socket
+		.send_message_query("CREATE tester:1 SET score = 2; CREATE tester:2 SET score = 3")
+		.await
+		.unwrap();
+	// Start a live aggregate counting the matching records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"live_aggregate",
+			json!(["tester", { "function": "count", "cond": "score > 1", "debounce": "100ms" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["value"], 2, "result: {res:?}");
+	let count = res["result"]["id"].as_str().unwrap().to_owned();
+	// Check the subscription was committed alongside the initial value
+	let res = socket.send_versioned_request(Some(2), "is_live", json!([count])).await.unwrap();
+	assert_eq!(res["result"], true, "result: {res:?}");
+	// Start a live aggregate summing a field
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"live_aggregate",
+			json!(["tester", { "function": "sum", "field": "score", "debounce": "100ms" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["value"], 5, "result: {res:?}");
+	let sum = res["result"]["id"].as_str().unwrap().to_owned();
+	// Create and update records within the debounce period
+	socket
+		.send_request(
+			"query",
+			json!(["CREATE tester:3 SET score = 4; UPDATE tester:1 SET score = 0"]),
+		)
+		.await
+		.unwrap();
+	// Wait for the recomputed aggregates
+	let msgs = socket.receive_all_other_messages(2, Duration::from_secs(2)).await.unwrap();
+	// Check the changes were coalesced into a single recomputed count
+	let res: Vec<_> = msgs.iter().filter(|v| common::is_notification_from_lq(v, &count)).collect();
+	assert_eq!(res.len(), 1, "result: {msgs:?}");
+	assert_eq!(res[0]["result"]["result"], 2, "result: {msgs:?}");
+	// Check the changes were coalesced into a single recomputed sum
+	let res: Vec<_> = msgs.iter().filter(|v| common::is_notification_from_lq(v, &sum)).collect();
+	assert_eq!(res.len(), 1, "result: {msgs:?}");
+	assert_eq!(res[0]["result"]["result"], 7, "result: {msgs:?}");
+	// Check a sum without a field is rejected
+	let res = socket
+		.send_versioned_request(Some(2), "live_aggregate", json!(["tester", { "function": "sum" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Check the live aggregates can be killed
+	let res = socket.send_versioned_request(Some(2), "kill", json!([count])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn live_debounce(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Start a live query with a debounce
+	let res = socket
+		.send_versioned_request(Some(2), "live", json!(["tester", { "debounce": "200ms" }]))
+		.await
+		.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live = res["result"].as_str().unwrap().to_owned();
+	// Make several changes within the window
+	socket
+		.send_request(
+			"query",
+			json!([
+				"CREATE tester:one SET score = 1; UPDATE tester:one SET score = 2; CREATE tester:two; DELETE tester:two"
+			]),
+		)
+		.await
+		.unwrap();
+	// Check only the latest state of the remaining record is sent
+	let msgs = socket.receive_all_other_messages(1, Duration::from_secs(2)).await.unwrap();
+	assert!(common::is_notification_from_lq(&msgs[0], &live), "result: {msgs:?}");
+	let res = &msgs[0]["result"];
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert_eq!(res["result"]["score"], 2, "result: {res:?}");
+	let res = socket.receive_all_other_messages(1, Duration::from_millis(500)).await;
+	assert!(res.is_err(), "result: {res:?}");
+	// Start a live query with diffs and a throttle
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"live",
+			json!(["tester", { "diff": true, "throttle": "200ms" }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let diff = res["result"].as_str().unwrap().to_owned();
+	// Kill the first live query
+	socket.send_versioned_request(Some(2), "kill", json!([live])).await.unwrap();
+	// Update the record twice within the window
+	socket
+		.send_request(
+			"query",
+			json!(["UPDATE tester:one SET score = 3; UPDATE tester:one SET name = 'one'"]),
+		)
+		.await
+		.unwrap();
+	// Check the patches are merged into a single notification
+	let msgs = socket.receive_all_other_messages(1, Duration::from_secs(2)).await.unwrap();
+	assert!(common::is_notification_from_lq(&msgs[0], &diff), "result: {msgs:?}");
+	let res = &msgs[0]["result"];
+	assert_eq!(res["action"], "UPDATE", "result: {res:?}");
+	assert_eq!(res["result"].as_array().unwrap().len(), 2, "result: {res:?}");
+	// Check a debounce and a throttle can not be combined
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"live",
+			json!(["tester", { "debounce": "200ms", "throttle": "200ms" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn live_stats(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Check there are no live queries initially
+	let res = socket.send_versioned_request(Some(2), "live_stats", json!([])).await.unwrap();
+	assert_eq!(res["result"]["count"], 0, "result: {res:?}");
+	assert!(res["result"]["buffered"].is_number(), "result: {res:?}");
+	// Start two live queries
+	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
+	let live = res["result"].as_str().unwrap().to_owned();
+	socket.send_request("live", json!(["other"])).await.unwrap();
+	// Check both live queries are counted
+	let res = socket.send_versioned_request(Some(2), "live_stats", json!([])).await.unwrap();
+	assert_eq!(res["result"]["count"], 2, "result: {res:?}");
+	// Check the live queries are not counted on another connection
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "live_stats", json!([])).await.unwrap();
+	assert_eq!(res["result"]["count"], 0, "result: {res:?}");
+	// Kill one of the live queries
+	let res = socket.send_versioned_request(Some(2), "kill", json!([live])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	// Check only the remaining live query is counted
+	let res = socket.send_versioned_request(Some(2), "live_stats", json!([])).await.unwrap();
+	assert_eq!(res["result"]["count"], 1, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn live_query_limit(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server with a limit of two live queries per connection
+	let (addr, mut server) = common::start_server(StartServerArguments {
+		vars: Some(HashMap::from([(
+			"SURREAL_WEBSOCKET_MAX_LIVE_QUERIES".to_string(),
+			"2".to_string(),
+		)])),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Start live queries up to the limit
+	let res = socket.send_versioned_request(Some(2), "live", json!(["tester"])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live = res["result"].as_str().unwrap().to_owned();
+	let res = socket.send_versioned_request(Some(2), "live", json!(["other"])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	// Check a live query over the limit is rejected
+	let res = socket.send_versioned_request(Some(2), "live", json!(["tester"])).await.unwrap();
+	assert_eq!(
+		res["error"]["message"], "The session has reached the maximum of 2 live queries",
+		"result: {res:?}"
+	);
+	// Check the limit applies to each connection separately
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	other.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	let res = other.send_versioned_request(Some(2), "live", json!(["tester"])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	// Kill one of the live queries
+	let res = socket.send_versioned_request(Some(2), "kill", json!([live])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	// Check a new live query can be started again
+	let res = socket.send_versioned_request(Some(2), "live", json!(["tester"])).await.unwrap();
+	assert!(res["result"].is_string(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn live_replay(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server with a limit of three replayed changes
+	let (addr, mut server) = common::start_server(StartServerArguments {
+		vars: Some(HashMap::from([(
+			"SURREAL_MAX_LIVE_REPLAY_CHANGES".to_string(),
+			"3".to_string(),
+		)])),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Make some changes to a table with a changefeed
+	socket
+		.send_message_query(
+			"DEFINE TABLE tester CHANGEFEED 1h; CREATE tester:one SET val = 1; \
+			DELETE tester:one; CREATE tester:two SET val = 2; CREATE tester:three SET val = 3;",
+		)
+		.await
+		.unwrap();
+	// Start a live query which replays the changes
+	let res = socket
+		.send_versioned_request(Some(2), "live", json!(["tester", { "since": 0 }]))
+		.await
+		.unwrap();
+	assert!(res["result"]["id"].is_string(), "result: {res:?}");
+	let live = res["result"]["id"].as_str().unwrap().to_owned();
+	let changes = res["result"]["changes"].as_array().unwrap();
+	assert_eq!(changes.len(), 3, "result: {res:?}");
+	assert_eq!(changes[0]["action"], "UPDATE", "result: {res:?}");
+	assert_eq!(changes[0]["record"], "tester:one", "result: {res:?}");
+	assert_eq!(changes[0]["result"]["val"], 1, "result: {res:?}");
+	assert_eq!(changes[1]["action"], "DELETE", "result: {res:?}");
+	assert_eq!(changes[1]["record"], "tester:one", "result: {res:?}");
+	assert_eq!(changes[2]["record"], "tester:two", "result: {res:?}");
+	// Resume the replay from the last replayed change
+	let since = changes[2]["versionstamp"].clone();
+	let res = socket
+		.send_versioned_request(Some(2), "live", json!(["tester", { "since": since }]))
+		.await
+		.unwrap();
+	let changes = res["result"]["changes"].as_array().unwrap();
+	assert_eq!(changes.len(), 2, "result: {res:?}");
+	assert_eq!(changes[0]["record"], "tester:two", "result: {res:?}");
+	assert_eq!(changes[1]["record"], "tester:three", "result: {res:?}");
+	// Check the live query receives new notifications after the replay
+	socket.send_message_query("CREATE tester:four").await.unwrap();
+	let msgs = socket.receive_all_other_messages(2, Duration::from_secs(1)).await.unwrap();
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, &live));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live}: {msgs:?}");
+	// Check changes can only be replayed for a table
+	let res = socket
+		.send_versioned_request(Some(2), "live", json!(["tester:one", { "since": 0 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn timezone(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// The query which splits datetimes into their components
+	let sql = "RETURN [
+		time::hour(d'2024-01-15T12:00:00Z'),
+		time::hour(d'2024-07-15T12:00:00Z'),
+		time::day(d'2024-01-15T03:00:00Z'),
+		d'2024-01-15T03:00:00Z'.wday(),
+		time::format(d'2024-01-15T03:00:00Z', '%Y-%m-%d %H:%M %z'),
+		<string> time::group(d'2024-01-15T03:00:00Z', 'day'),
+	]";
+	// Check the components in UTC by default
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_eq!(
+		res["result"][0]["result"],
+		json!([12, 12, 15, 1, "2024-01-15 03:00 +0000", "2024-01-15T00:00:00Z"]),
+		"result: {res:?}"
+	);
+	// Send TIMEZONE command with an invalid timezone
+	let res =
+		socket.send_versioned_request(Some(2), "timezone", json!(["Mars/Olympus"])).await.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send TIMEZONE command with a fixed offset
+	let res = socket.send_versioned_request(Some(2), "timezone", json!(["+05:30"])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res =
+		socket.send_message_query("RETURN time::hour(d'2024-01-15T12:00:00Z')").await.unwrap();
+	assert_eq!(res[0]["result"], 17, "result: {res:?}");
+	// Send TIMEZONE command with a region, which observes daylight saving time
+	let res = socket
+		.send_versioned_request(Some(2), "timezone", json!(["America/New_York"]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "timezone", json!([])).await.unwrap();
+	assert_eq!(res["result"], "America/New_York", "result: {res:?}");
+	let res = socket.send_message_query("RETURN time::timezone()").await.unwrap();
+	assert_eq!(res[0]["result"], "America/New_York", "result: {res:?}");
+	// Check the components in the session timezone
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_eq!(
+		res["result"][0]["result"],
+		json!([7, 8, 14, 7, "2024-01-14 22:00 -0500", "2024-01-14T05:00:00Z"]),
+		"result: {res:?}"
+	);
+	// Send TIMEZONE command to unset the timezone
+	let res = socket.send_versioned_request(Some(2), "timezone", json!([null])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "timezone", json!([])).await.unwrap();
+	assert!(res["result"].is_null(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn locale(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// The query which changes the case of strings
+	let sql =
+		"RETURN [string::lowercase('DİYARBAKIR'), string::uppercase('istanbul'), 'Iİ'.lowercase()]";
+	// Check the default casing rules
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_eq!(
+		res["result"][0]["result"],
+		json!(["di\u{307}yarbakir", "ISTANBUL", "ii\u{307}"]),
+		"result: {res:?}"
+	);
+	// Send LOCALE command with an invalid locale
+	let res =
+		socket.send_versioned_request(Some(2), "locale", json!(["not a locale"])).await.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send LOCALE command
+	let res = socket.send_versioned_request(Some(2), "locale", json!(["tr_tr"])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "locale", json!([])).await.unwrap();
+	assert_eq!(res["result"], "tr-TR", "result: {res:?}");
+	// Check the locale casing rules
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_eq!(
+		res["result"][0]["result"],
+		json!(["diyarbakır", "İSTANBUL", "ıi"]),
+		"result: {res:?}"
+	);
+	let res = socket.send_message_query("RETURN $session.lc").await.unwrap();
+	assert_eq!(res[0]["result"], "tr-TR", "result: {res:?}");
+	// The query which collates strings
+	let sql = "
+		SELECT VALUE name FROM [{ name: 'z' }, { name: 'ä' }, { name: 'a' }] ORDER BY name COLLATE;
+		RETURN array::sort_lexical(['z', 'ä', 'a']);
+	";
+	// Check the default collation rules
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_eq!(res["result"][0]["result"], json!(["a", "ä", "z"]), "result: {res:?}");
+	assert_eq!(res["result"][1]["result"], json!(["a", "ä", "z"]), "result: {res:?}");
+	// Check the locale collation rules
+	let res = socket.send_versioned_request(Some(2), "locale", json!(["sv"])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "query", json!([sql])).await.unwrap();
+	assert_eq!(res["result"][0]["result"], json!(["a", "z", "ä"]), "result: {res:?}");
+	assert_eq!(res["result"][1]["result"], json!(["a", "z", "ä"]), "result: {res:?}");
+	// Send LOCALE command to unset the locale
+	let res = socket.send_versioned_request(Some(2), "locale", json!([null])).await.unwrap();
+	assert!(res["error"].is_null(), "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "locale", json!([])).await.unwrap();
+	assert!(res["result"].is_null(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn kill(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send LIVE command
+	let res = socket.send_request("live", json!(["tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let live1 = res["result"].as_str().unwrap();
+	// Send QUERY command
+	let res = socket.send_request("query", json!(["LIVE SELECT * FROM tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert!(res[0]["result"].is_string(), "result: {res:?}");
+	assert!(res[0]["type"].is_string(), "type: {res:?}");
+	let live2 = res[0]["result"].as_str().unwrap();
+	// Create a new test record
+	let res =
+		socket.send_request("query", json!(["CREATE tester:one SET name = 'one'"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	// Wait some time for all messages to arrive, and then search for the notification message
+	let msgs = socket.receive_all_other_messages(2, Duration::from_secs(1)).await.unwrap();
+	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	// Check for first live query notifcation
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live1));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live1}: {msgs:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert!(res["action"].is_string(), "result: {res:?}");
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:one", "result: {res:?}");
+	// Check for second live query notifcation
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live2));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:one", "result: {res:?}");
+	// Send KILL command
+	let res = socket.send_request("kill", json!([live1])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_null(), "result: {res:?}");
+	// Create a new test record
+	let res =
+		socket.send_request("query", json!(["CREATE tester:two SET name = 'two'"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	// Wait some time for all messages to arrive, and then search for the notification message
+	let msgs = socket.receive_all_other_messages(1, Duration::from_secs(1)).await.unwrap();
+	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	// Check for second live query notifcation
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, live2));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {live2}: {msgs:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:two", "result: {res:?}");
+	// Send QUERY command
+	let res = socket.send_request("query", json!([format!("KILL u'{live2}'")])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	assert!(res[0]["result"].is_null(), "result: {res:?}");
+	assert!(res[0]["type"].is_string(), "type: {res:?}");
+	// Create a new test record
+	let res =
+		socket.send_request("query", json!(["CREATE tester:tre SET name = 'two'"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	// Wait some time for all messages to arrive, and then search for the notification message
+	let msgs = socket.receive_all_other_messages(0, Duration::from_secs(1)).await.unwrap();
+	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn live_second_connection(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket1 = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket1.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket1.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Send LIVE command
+	let res = socket1.send_request("live", json!(["tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let liveid = res["result"].as_str().unwrap();
+	// Connect to WebSocket
+	let mut socket2 = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket2.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket2.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a new test record
+	let res =
+		socket2.send_request("query", json!(["CREATE tester:id SET name = 'foo'"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	// Wait some time for all messages to arrive, and then search for the notification message
+	let msgs = socket1.receive_all_other_messages(1, Duration::from_secs(1)).await.unwrap();
+	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	// Check for live query notifcation
+	let res = msgs.iter().find(|v| common::is_notification_from_lq(v, liveid));
+	assert!(res.is_some(), "Expected to find a notification for LQ id {liveid}: {msgs:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert!(res["action"].is_string(), "result: {res:?}");
+	assert_eq!(res["action"], "CREATE", "result: {res:?}");
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = res["result"].as_object().unwrap();
+	assert_eq!(res["id"], "tester:id", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn variable_auth_live_query(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket_permanent = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket_permanent.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket_permanent.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a user record access method
+	socket_permanent
+		.send_message_query(
+			r#"
+			DEFINE ACCESS user ON DATABASE TYPE RECORD
+				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
+				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
+				DURATION FOR SESSION 1s, FOR TOKEN 24h
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Send SIGNUP command
+	let mut socket_expiring_auth = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+
+	let res = socket_expiring_auth
+		.send_request(
+			"signup",
+			json!([{
+				"ns": NS,
+				"db": DB,
+				"ac": "user",
+				"email": "email@email.com",
+				"pass": "pass",
+			}]),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	// Authenticate the connection
+	socket_expiring_auth.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Send LIVE command
+	let res = socket_expiring_auth.send_request("live", json!(["tester"])).await.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_string(), "result: {res:?}");
+	// Wait 2 seconds for auth to expire
+	tokio::time::sleep(Duration::from_secs(1)).await;
+	// Create a new test record
+	let res = socket_permanent
+		.send_request("query", json!(["CREATE tester:id SET name = 'foo'"]))
+		.await
+		.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 1, "result: {res:?}");
+	// Wait some time for all messages to arrive, and then search for the notification message
+	let msgs =
+		socket_expiring_auth.receive_all_other_messages(0, Duration::from_secs(1)).await.unwrap();
+	assert!(msgs.iter().all(|v| v["error"].is_null()), "Unexpected error received: {msgs:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn session_expiration(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a user record access method
+	socket
+		.send_message_query(
+			r#"
+			DEFINE ACCESS user ON DATABASE TYPE RECORD
+				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
+				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
+				DURATION FOR SESSION 1s, FOR TOKEN 1d
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Create resource that requires a session with the access method to query
+	socket
+		.send_message_query(
+			r#"
+			DEFINE TABLE test SCHEMALESS
+				PERMISSIONS FOR select, create, update, delete WHERE $access = "user"
+			;"#,
+		)
+		.await
+		.unwrap();
+	socket
+		.send_message_query(
+			r#"
+			CREATE test:1 SET working = "yes"
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Send SIGNUP command
+	let res = socket
+		.send_request(
+			"signup",
+			json!(
+				[{
+					"ns": NS,
+					"db": DB,
+					"ac": "user",
+					"email": "email@email.com",
+					"pass": "pass",
+				}]
+			),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	// Verify it returns a token
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let res = res["result"].as_str().unwrap();
+	assert!(res.starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzUxMiJ9"), "result: {res}");
+	// Authenticate using the token, which expires in a day
+	socket.send_request("authenticate", json!([res,])).await.unwrap();
+	// Check if the session is now authenticated
+	let res = socket.send_message_query("SELECT VALUE working FROM test:1").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["yes"]), "result: {res:?}");
+	// Wait two seconds for the session to expire
+	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+	// Check that the session has expired and queries fail
+	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert_eq!(
+		res["error"],
+		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
+	);
+	// Sign in again using the same session
+	let res = socket
+		.send_request(
+			"signin",
+			json!(
+				[{
+					"ns": NS,
+					"db": DB,
+					"ac": "user",
+					"email": "email@email.com",
+					"pass": "pass",
+				}]
+			),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	// Check that the session is now valid again and queries succeed
+	let res = socket.send_message_query("SELECT VALUE working FROM test:1").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["yes"]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn session_expiration_operations(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	// We store the root token to test reauthentication later
+	let root_token = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a user record access method
+	socket
+		.send_message_query(
+			r#"
+			DEFINE ACCESS user ON DATABASE TYPE RECORD
+				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
+				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
+				DURATION FOR SESSION 1s, FOR TOKEN 1d
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Create resource that requires a session with the access method to query
+	socket
+		.send_message_query(
+			r#"
+			DEFINE TABLE test SCHEMALESS
+				PERMISSIONS FOR select, create, update, delete WHERE $access = "user"
+			;"#,
+		)
+		.await
+		.unwrap();
+	socket
+		.send_message_query(
+			r#"
+			CREATE test:1 SET working = "yes"
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Send SIGNUP command
+	let res = socket
+		.send_request(
+			"signup",
+			json!(
+				[{
+					"ns": NS,
+					"db": DB,
+					"ac": "user",
+					"email": "email@email.com",
+					"pass": "pass",
+				}]
+			),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	// Verify it returns a token
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let res = res["result"].as_str().unwrap();
+	assert!(res.starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzUxMiJ9"), "result: {res}");
+	// Authenticate using the token, which expires in a day
+	socket.send_request("authenticate", json!([res,])).await.unwrap();
+	// Check if the session is now authenticated
+	let res = socket.send_message_query("SELECT VALUE working FROM test:1").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["yes"]), "result: {res:?}");
+	// Wait two seconds for the session to expire
+	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+	// Check if the session is now expired
+	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert_eq!(
+		res["error"],
+		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
+	);
+	// Test operations that SHOULD NOT work with an expired session
+	let operations_ko = vec![
+		socket.send_request("let", json!(["let_var", "let_value",])),
+		socket.send_request("set", json!(["set_var", "set_value",])),
+		socket.send_request("info", json!([])),
+		socket.send_request("select", json!(["tester",])),
+		socket.send_request(
+			"insert",
+			json!([
+				"tester",
+				{
+					"name": "foo",
+					"value": "bar",
+				}
+			]),
+		),
+		socket.send_request(
+			"create",
+			json!([
+				"tester",
+				{
+					"value": "bar",
+				}
+			]),
+		),
+		socket.send_request(
+			"update",
+			json!([
+				"tester",
+				{
+					"value": "bar",
+				}
+			]),
+		),
+		socket.send_request(
+			"merge",
+			json!([
+				"tester",
+				{
+					"value": "bar",
+				}
+			]),
+		),
+		socket.send_request(
+			"patch",
+			json!([
+				"tester:id",
+				[
+					{
+						"op": "add",
+						"path": "value",
+						"value": "bar"
+					},
+					{
+						"op": "remove",
+						"path": "name",
+					}
+				]
+			]),
+		),
+		socket.send_request("delete", json!(["tester"])),
+		socket.send_request("live", json!(["tester"])),
+		socket.send_request("kill", json!(["tester"])),
+	];
+	// Futures are executed sequentially as some operations rely on the previous state
+	for operation in operations_ko {
+		let res = operation.await;
+		assert!(res.is_ok(), "result: {res:?}");
+		let res = res.unwrap();
+		assert!(res.is_object(), "result: {res:?}");
+		let res = res.as_object().unwrap();
+		assert_eq!(
+			res["error"],
+			json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
+		);
+	}
+
+	// Test operations that SHOULD work with an expired session
+	let operations_ok = vec![
+		socket.send_request("use", json!([NS, DB])),
+		socket.send_request("ping", json!([])),
+		socket.send_request("version", json!([])),
+		socket.send_request("invalidate", json!([])),
+	];
+	// Futures are executed sequentially as some operations rely on the previous state
+	for operation in operations_ok {
+		let res = operation.await;
+		assert!(res.is_ok(), "result: {res:?}");
+		let res = res.unwrap();
+		assert!(res.is_object(), "result: {res:?}");
+		let res = res.as_object().unwrap();
+		// Verify response contains no error
+		assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	}
+
+	// Test operations that SHOULD work with an expired session
+	// These operations will refresh the session expiration
+	let res = socket
+		.send_request(
+			"signup",
+			json!([{
+				"ns": NS,
+				"db": DB,
+				"ac": "user",
+				"email": "another@email.com",
+				"pass": "pass",
+			}]),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	// Wait two seconds for the session to expire
+	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+	// The session must be expired now or we fail the test
+	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert_eq!(
+		res["error"],
+		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
+	);
+	let res = socket
+		.send_request(
+			"signin",
+			json!(
+				[{
+					"ns": NS,
+					"db": DB,
+					"ac": "user",
+					"email": "another@email.com",
+					"pass": "pass",
+				}]
+			),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	// Wait two seconds for the session to expire
+	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+	// The session must be expired now or we fail the test
+	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert_eq!(
+		res["error"],
+		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
+	);
+
+	// This needs to be last operation as the session will no longer expire afterwards
+	let res = socket.send_request("authenticate", json!([root_token,])).await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn session_reauthentication(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection and store the root level token
+	let root_token = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Check that we have root access
+	socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a user record access method
+	socket
+		.send_message_query(
+			r#"
+			DEFINE ACCESS user ON DATABASE TYPE RECORD
+				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
+				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
+				DURATION FOR SESSION 1s, FOR TOKEN 24h
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Create resource that requires a session with the access method to query
+	socket
+		.send_message_query(
+			r#"
+			DEFINE TABLE test SCHEMALESS
+				PERMISSIONS FOR select, create, update, delete WHERE $access = "user"
+			;"#,
+		)
+		.await
+		.unwrap();
+	socket
+		.send_message_query(
+			r#"
+			CREATE test:1 SET working = "yes"
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Send SIGNUP command
+	let res = socket
+		.send_request(
+			"signup",
+			json!(
+				[{
+					"ns": NS,
+					"db": DB,
+					"ac": "user",
+					"email": "email@email.com",
+					"pass": "pass",
+				}]
+			),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	// Verify it returns a token
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let res = res["result"].as_str().unwrap();
+	assert!(res.starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzUxMiJ9"), "result: {res}");
+	// Authenticate using the token
+	socket.send_request("authenticate", json!([res,])).await.unwrap();
+	// Check that we do not have root access
+	let res = socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	assert_eq!(res[0]["status"], "ERR", "result: {res:?}");
+	assert_eq!(
+		res[0]["result"], "IAM error: Not enough permissions to perform this action",
+		"result: {res:?}"
+	);
+	// Check if the session is authenticated
+	let res = socket.send_message_query("SELECT VALUE working FROM test:1").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["yes"]), "result: {res:?}");
+	// Authenticate using the root token
+	socket.send_request("authenticate", json!([root_token,])).await.unwrap();
+	// Check that we have root access again
+	let res = socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn session_reauthentication_expired(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection and store the root level token
+	let root_token = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Check that we have root access
+	socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define a user record access method
+	socket
+		.send_message_query(
+			r#"
+			DEFINE ACCESS user ON DATABASE TYPE RECORD
+				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
+				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
+				DURATION FOR SESSION 1s, FOR TOKEN 24h
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Create resource that requires a session with the access method to query
+	socket
+		.send_message_query(
+			r#"
+			DEFINE TABLE test SCHEMALESS
+				PERMISSIONS FOR select, create, update, delete WHERE $access = "user"
+			;"#,
+		)
+		.await
+		.unwrap();
+	socket
+		.send_message_query(
+			r#"
+			CREATE test:1 SET working = "yes"
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Send SIGNUP command
+	let res = socket
+		.send_request(
+			"signup",
+			json!(
+				[{
+					"ns": NS,
+					"db": DB,
+					"ac": "user",
+					"email": "email@email.com",
+					"pass": "pass",
+				}]
+			),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+	// Verify it returns a token
+	assert!(res["result"].is_string(), "result: {res:?}");
+	let res = res["result"].as_str().unwrap();
+	assert!(res.starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzUxMiJ9"), "result: {res}");
+	// Authenticate using the token, which will expire soon
+	socket.send_request("authenticate", json!([res,])).await.unwrap();
+	// Wait two seconds for token to expire
+	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+	// Verify that the session has expired
+	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
+	assert!(res.is_ok(), "result: {res:?}");
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {res:?}");
+	let res = res.as_object().unwrap();
+	assert_eq!(
 		res["error"],
 		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
 	);
-	// Test operations that SHOULD NOT work with an expired session
-	let operations_ko = vec![
-		socket.send_request("let", json!(["let_var", "let_value",])),
-		socket.send_request("set", json!(["set_var", "set_value",])),
-		socket.send_request("info", json!([])),
-		socket.send_request("select", json!(["tester",])),
-		socket.send_request(
-			"insert",
-			json!([
-				"tester",
-				{
-					"name": "foo",
-					"value": "bar",
-				}
-			]),
-		),
-		socket.send_request(
-			"create",
-			json!([
-				"tester",
-				{
-					"value": "bar",
-				}
-			]),
-		),
-		socket.send_request(
-			"update",
-			json!([
-				"tester",
-				{
-					"value": "bar",
-				}
-			]),
-		),
-		socket.send_request(
-			"merge",
-			json!([
-				"tester",
-				{
-					"value": "bar",
-				}
-			]),
-		),
-		socket.send_request(
-			"patch",
-			json!([
-				"tester:id",
-				[
-					{
-						"op": "add",
-						"path": "value",
-						"value": "bar"
-					},
-					{
-						"op": "remove",
-						"path": "name",
-					}
-				]
-			]),
-		),
-		socket.send_request("delete", json!(["tester"])),
-		socket.send_request("live", json!(["tester"])),
-		socket.send_request("kill", json!(["tester"])),
-	];
-	// Futures are executed sequentially as some operations rely on the previous state
-	for operation in operations_ko {
-		let res = operation.await;
-		assert!(res.is_ok(), "result: {res:?}");
-		let res = res.unwrap();
-		assert!(res.is_object(), "result: {res:?}");
-		let res = res.as_object().unwrap();
-		assert_eq!(
-			res["error"],
-			json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
-		);
-	}
+	// Authenticate using the root token, which has not expired yet
+	socket.send_request("authenticate", json!([root_token,])).await.unwrap();
+	// Check that we have root access and the session is not expired
+	let res = socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn session_failed_reauthentication(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server without authentication
+	let (addr, mut server) = common::start_server_without_auth().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Specify a namespace and database to use
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Check that we have are have a database and namespace selected
+	socket.send_message_query("INFO FOR DB").await.unwrap();
+	// Authenticate using an invalid token
+	socket.send_request("authenticate", json!(["invalid",])).await.unwrap();
+	// Check to see if we still have a namespace and database selected
+	let res = socket.send_message_query("INFO FOR DB").await.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn session_use_change_database(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection as a root level system user
+	let _ = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Check that we have root access
+	socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some("original")).await.unwrap();
+	// Define a scope on the original database
+	socket
+		.send_message_query(
+			r#"
+                       DEFINE USER user ON DATABASE PASSWORD "secret" ROLES VIEWER
+                       ;"#,
+		)
+		.await
+		.unwrap();
+	// Create resource that requires an authenticated record user to query
+	socket
+		.send_message_query(
+			r#"
+                       DEFINE TABLE user SCHEMALESS
+                               PERMISSIONS FOR select, create, update, delete NONE
+                       ;"#,
+		)
+		.await
+		.unwrap();
+	socket
+               .send_message_query(
+                       r#"
+                       CREATE user:1 CONTENT { name: "original", pass: crypto::argon2::generate("original") }
+                       ;"#,
+               )
+               .await
+               .unwrap();
+	// Change to a different database
+	socket.send_message_use(Some(NS), Some("different")).await.unwrap();
+	// Create the same user table with a user record with the same identifier
+	socket
+		.send_message_query(
+			r#"
+                       DEFINE TABLE user SCHEMALESS
+                               PERMISSIONS FOR select, create, update, delete NONE
+                       ;"#,
+		)
+		.await
+		.unwrap();
+	socket
+               .send_message_query(
+                       r#"
+                       CREATE user:1 CONTENT { name: "different", pass: crypto::argon2::generate("different") }
+                       ;"#,
+               )
+               .await
+               .unwrap();
+	// Sign in to original database as user
+	let res = socket
+		.send_request(
+			"signin",
+			json!(
+					[{
+							"ns": NS,
+							"db": "original",
+							"user": "user",
+							"pass": "secret",
+					}]
+			),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {:?}", res);
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {:?}", res);
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {:?}", res);
+	// Verify that the authenticated session corresponds with the original user
+	let res = socket.send_message_query("SELECT VALUE name FROM user:1").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["original"]), "result: {:?}", res);
+	// Swtich to the different database without signing in again
+	socket.send_message_use(Some(NS), Some("different")).await.unwrap();
+	// Verify that the authenticated session is unable to query data
+	let res = socket.send_message_query("SELECT VALUE name FROM user:1").await.unwrap();
+	// The query succeeds but the results does not contain the value with permissions
+	assert_eq!(res[0]["status"], "OK", "result: {:?}", res);
+	assert_eq!(res[0]["result"], json!([]), "result: {:?}", res);
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn session_use_change_database_scope(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection as a root level system user
+	let _ = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Check that we have root access
+	socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some("original")).await.unwrap();
+	// Define a user record access method on the original database
+	socket
+		.send_message_query(
+			r#"
+			DEFINE ACCESS user ON DATABASE TYPE RECORD
+				SIGNIN ( SELECT * FROM user WHERE name = $name AND crypto::argon2::compare(pass, $pass) )
+				DURATION FOR SESSION 24h, FOR TOKEN 24h
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Create resource that requires an authenticated record user to query
+	socket
+		.send_message_query(
+			r#"
+			DEFINE TABLE user SCHEMALESS
+				PERMISSIONS FOR select, create, update, delete WHERE id = $auth
+			;"#,
+		)
+		.await
+		.unwrap();
+	socket
+		.send_message_query(
+			r#"
+			CREATE user:1 CONTENT { name: "original", pass: crypto::argon2::generate("original") }
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Change to a different database
+	socket.send_message_use(Some(NS), Some("different")).await.unwrap();
+	// Create the same user table with a user record with the same identifier
+	socket
+		.send_message_query(
+			r#"
+			DEFINE TABLE user SCHEMALESS
+				PERMISSIONS FOR select, create, update, delete WHERE id = $auth
+			;"#,
+		)
+		.await
+		.unwrap();
+	socket
+		.send_message_query(
+			r#"
+			CREATE user:1 CONTENT { name: "different", pass: crypto::argon2::generate("different") }
+			;"#,
+		)
+		.await
+		.unwrap();
+	// Sign in to original database as user
+	let res = socket
+		.send_request(
+			"signin",
+			json!(
+				[{
+					"ns": NS,
+					"db": "original",
+					"ac": "user",
+					"name": "original",
+					"pass": "original",
+				}]
+			),
+		)
+		.await;
+	assert!(res.is_ok(), "result: {:?}", res);
+	let res = res.unwrap();
+	assert!(res.is_object(), "result: {:?}", res);
+	let res = res.as_object().unwrap();
+	// Verify response contains no error
+	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {:?}", res);
+	// Verify that the authenticated session corresponds with the original user
+	let res = socket.send_message_query("SELECT VALUE name FROM $auth").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["original"]), "result: {:?}", res);
+	// Swtich to the different database without signing in again
+	socket.send_message_use(Some(NS), Some("different")).await.unwrap();
+	// Verify that the authenticated session is unable to query data
+	let res = socket.send_message_query("SELECT VALUE name FROM $auth").await.unwrap();
+	// The following statement would be true when the bug was present:
+	// assert_eq!(res[0]["result"], json!(["different"]), "result: {:?}", res);
+	assert_eq!(res[0]["status"], "ERR", "result: {:?}", res);
+	assert_eq!(
+		res[0]["result"], "You don't have permission to change to the different database",
+		"result: {:?}",
+		res
+	);
+	// Test passed
+	server.finish().unwrap();
+}
 
-	// Test operations that SHOULD work with an expired session
-	let operations_ok = vec![
-		socket.send_request("use", json!([NS, DB])),
-		socket.send_request("ping", json!([])),
-		socket.send_request("version", json!([])),
-		socket.send_request("invalidate", json!([])),
-	];
-	// Futures are executed sequentially as some operations rely on the previous state
-	for operation in operations_ok {
-		let res = operation.await;
-		assert!(res.is_ok(), "result: {res:?}");
-		let res = res.unwrap();
-		assert!(res.is_object(), "result: {res:?}");
-		let res = res.as_object().unwrap();
-		// Verify response contains no error
-		assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	}
+pub async fn run_functions(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Define function
+	socket
+		.send_message_query("DEFINE FUNCTION fn::foo() {RETURN 'fn::foo called';}")
+		.await
+		.unwrap();
+	socket
+		.send_message_query(
+			"DEFINE FUNCTION fn::bar($val: string) {RETURN 'fn::bar called with: ' + $val;}",
+		)
+		.await
+		.unwrap();
+	// call functions
+	let res = socket.send_message_run("fn::foo", None, vec![]).await.unwrap();
+	assert!(matches!(res, serde_json::Value::String(s) if &s == "fn::foo called"));
+	let res = socket.send_message_run("fn::bar", None, vec![]).await;
+	assert!(res.is_err());
+	let res = socket.send_message_run("fn::bar", None, vec![42.into()]).await;
+	assert!(res.is_err());
+	let res = socket.send_message_run("fn::bar", None, vec!["first".into(), "second".into()]).await;
+	assert!(res.is_err());
+	let res = socket.send_message_run("fn::bar", None, vec!["string_val".into()]).await.unwrap();
+	assert!(matches!(res, serde_json::Value::String(s) if &s == "fn::bar called with: string_val"));
 
-	// Test operations that SHOULD work with an expired session
-	// These operations will refresh the session expiration
+	// normal functions
+	let res = socket.send_message_run("math::abs", None, vec![42.into()]).await.unwrap();
+	assert!(matches!(res, serde_json::Value::Number(n) if n.as_u64() == Some(42)));
+	let res = socket
+		.send_message_run("math::max", None, vec![vec![1, 2, 3, 4, 5, 6].into()])
+		.await
+		.unwrap();
+	assert!(matches!(res, serde_json::Value::Number(n) if n.as_u64() == Some(6)));
+
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn eval(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create a record which should not be reachable
+	let res = socket.send_message_query("CREATE tester:1 SET name = 'one'").await.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Send EVAL command
+	let res = socket
+		.send_versioned_request(Some(2), "eval", json!(["math::sum([1, 2, $a])", { "a": 3 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"], 6, "result: {res:?}");
+	// Send EVAL command which reads a table
 	let res = socket
-		.send_request(
-			"signup",
-			json!([{
-				"ns": NS,
-				"db": DB,
-				"ac": "user",
-				"email": "another@email.com",
-				"pass": "pass",
-			}]),
-		)
-		.await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	// Wait two seconds for the session to expire
-	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-	// The session must be expired now or we fail the test
-	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
+		.send_versioned_request(Some(2), "eval", json!(["(SELECT * FROM tester)"]))
+		.await
+		.unwrap();
 	assert_eq!(
-		res["error"],
-		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
+		res["error"]["message"], "Evaluated expressions can not read or write any records",
+		"result: {res:?}"
 	);
+	// Send EVAL command which reads a record
+	let res =
+		socket.send_versioned_request(Some(2), "eval", json!(["tester:1.name"])).await.unwrap();
+	assert_eq!(
+		res["error"]["message"], "Evaluated expressions can not read or write any records",
+		"result: {res:?}"
+	);
+	// Send EVAL command which writes a record
 	let res = socket
-		.send_request(
-			"signin",
-			json!(
-				[{
-					"ns": NS,
-					"db": DB,
-					"ac": "user",
-					"email": "another@email.com",
-					"pass": "pass",
-				}]
-			),
-		)
-		.await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	// Wait two seconds for the session to expire
-	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-	// The session must be expired now or we fail the test
-	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
+		.send_versioned_request(Some(2), "eval", json!(["(CREATE tester:2 SET name = 'two')"]))
+		.await
+		.unwrap();
 	assert_eq!(
-		res["error"],
-		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
+		res["error"]["message"], "Evaluated expressions can not read or write any records",
+		"result: {res:?}"
 	);
+	// Check that the record was not written
+	let res = socket.send_message_query("SELECT VALUE name FROM tester").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["one"]), "result: {res:?}");
+	// Send EVAL command with invalid variables
+	let res =
+		socket.send_versioned_request(Some(2), "eval", json!(["$a", "invalid"])).await.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send EVAL command which reads the root definitions as an anonymous user
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	let res =
+		socket.send_versioned_request(Some(2), "eval", json!(["(INFO FOR ROOT)"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
 
-	// This needs to be last operation as the session will no longer expire afterwards
-	let res = socket.send_request("authenticate", json!([root_token,])).await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
+pub async fn check_permission(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// The permission expression to test
+	let expr = "published = true OR author = $auth.id";
+	let record = json!({ "title": "draft", "published": false, "author": "user:one" });
+	// Check the permission is granted for the author
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"check_permission",
+			json!([expr, record, { "id": "user:one" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!({ "allowed": true, "error": null }), "result: {res:?}");
+	// Check the permission is denied for another user
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"check_permission",
+			json!([expr, record, { "id": "user:two" }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"], json!({ "allowed": false, "error": null }), "result: {res:?}");
+	// Check the permission is denied without an auth
+	let res = socket
+		.send_versioned_request(Some(2), "check_permission", json!([expr, record]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["allowed"], false, "result: {res:?}");
+	// Check an expression which reads records reports an error
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"check_permission",
+			json!(["count(SELECT * FROM tester) > 0", record]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["allowed"], false, "result: {res:?}");
+	assert!(res["result"]["error"].is_string(), "result: {res:?}");
+	// Check a sample record must be an object
+	let res = socket
+		.send_versioned_request(Some(2), "check_permission", json!([expr, "tester:one"]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Check a user which can not edit definitions is not allowed
+	let res = socket
+		.send_message_query("DEFINE USER viewer ON ROOT PASSWORD 'secret' ROLES VIEWER")
+		.await
+		.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	let mut other = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	other.send_message_signin("viewer", "secret", None, None, None).await.unwrap();
+	let res = other
+		.send_versioned_request(Some(2), "check_permission", json!([expr, record]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn relate_rpc(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// create records and relate
+	socket.send_message_query("CREATE foo:a, foo:b").await.unwrap();
+	socket
+		.send_message_relate("foo:a".into(), "bar".into(), "foo:b".into(), Some(json!({"val": 42})))
+		.await
+		.unwrap();
+	// test
+
+	let mut res = socket.send_message_query("RETURN foo:a->bar.val").await.unwrap();
+	let expected = json!([42]);
+	assert_eq!(res.remove(0)["result"], expected);
+
+	let mut res = socket.send_message_query("RETURN foo:a->bar->foo").await.unwrap();
+	let expected = json!(["foo:b"]);
+	assert_eq!(res.remove(0)["result"], expected);
 
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn session_reauthentication(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn relate_fetch(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
 	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
-	// Authenticate the connection and store the root level token
-	let root_token = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
-	// Check that we have root access
-	socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Define a user record access method
+	// Create records to relate
 	socket
-		.send_message_query(
-			r#"
-			DEFINE ACCESS user ON DATABASE TYPE RECORD
-				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
-				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
-				DURATION FOR SESSION 1s, FOR TOKEN 24h
-			;"#,
+		.send_message_query("CREATE foo:a SET name = 'a'; CREATE foo:b SET name = 'b'")
+		.await
+		.unwrap();
+	// Send RELATE command without fetching the related records
+	let res = socket
+		.send_versioned_request(Some(2), "relate", json!(["foo:a", "bar", "foo:b", { "val": 1 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"][0]["in"], "foo:a", "result: {res:?}");
+	assert_eq!(res["result"][0]["out"], "foo:b", "result: {res:?}");
+	// Send RELATE command fetching the related records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"relate",
+			json!(["foo:a", "bar", "foo:b", { "val": 2 }, { "fetch": "in, out" }]),
 		)
 		.await
 		.unwrap();
-	// Create resource that requires a session with the access method to query
-	socket
-		.send_message_query(
-			r#"
-			DEFINE TABLE test SCHEMALESS
-				PERMISSIONS FOR select, create, update, delete WHERE $access = "user"
-			;"#,
+	assert_eq!(res["result"].as_array().unwrap().len(), 1, "result: {res:?}");
+	assert_eq!(res["result"][0]["in"], json!({ "id": "foo:a", "name": "a" }), "result: {res:?}");
+	assert_eq!(res["result"][0]["out"], json!({ "id": "foo:b", "name": "b" }), "result: {res:?}");
+	assert_eq!(res["result"][0]["val"], 2, "result: {res:?}");
+	// Send RELATE command fetching a single related record
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"relate",
+			json!(["foo:b", "bar", "foo:a", null, { "only": true, "fetch": "out" }]),
 		)
 		.await
 		.unwrap();
-	socket
-		.send_message_query(
-			r#"
-			CREATE test:1 SET working = "yes"
-			;"#,
+	assert_eq!(res["result"]["in"], "foo:b", "result: {res:?}");
+	assert_eq!(res["result"]["out"], json!({ "id": "foo:a", "name": "a" }), "result: {res:?}");
+	// Check that the edges were stored with record links
+	let res = socket.send_message_query("RETURN foo:a->bar.out").await.unwrap();
+	assert_eq!(res[0]["result"], json!(["foo:b", "foo:b"]), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn relate_upsert(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create records to relate
+	socket.send_message_query("CREATE foo:a, foo:b").await.unwrap();
+	// Send RELATE command creating the edge
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"relate",
+			json!(["foo:a", "bar", "foo:b", { "val": 1, "since": 2020 }, { "upsert": true }]),
 		)
 		.await
 		.unwrap();
-	// Send SIGNUP command
+	assert_eq!(res["result"]["status"], "created", "result: {res:?}");
+	assert_eq!(res["result"]["result"]["val"], 1, "result: {res:?}");
+	let id = res["result"]["result"]["id"].clone();
+	// Send RELATE command updating the existing edge
 	let res = socket
-		.send_request(
-			"signup",
-			json!(
-				[{
-					"ns": NS,
-					"db": DB,
-					"ac": "user",
-					"email": "email@email.com",
-					"pass": "pass",
-				}]
-			),
+		.send_versioned_request(
+			Some(2),
+			"relate",
+			json!(["foo:a", "bar", "foo:b", { "val": 2 }, { "upsert": true }]),
 		)
-		.await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	// Verify it returns a token
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let res = res["result"].as_str().unwrap();
-	assert!(res.starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzUxMiJ9"), "result: {res}");
-	// Authenticate using the token
-	socket.send_request("authenticate", json!([res,])).await.unwrap();
-	// Check that we do not have root access
-	let res = socket.send_message_query("INFO FOR ROOT").await.unwrap();
-	assert_eq!(res[0]["status"], "ERR", "result: {res:?}");
-	assert_eq!(
-		res[0]["result"], "IAM error: Not enough permissions to perform this action",
-		"result: {res:?}"
-	);
-	// Check if the session is authenticated
-	let res = socket.send_message_query("SELECT VALUE working FROM test:1").await.unwrap();
-	assert_eq!(res[0]["result"], json!(["yes"]), "result: {res:?}");
-	// Authenticate using the root token
-	socket.send_request("authenticate", json!([root_token,])).await.unwrap();
-	// Check that we have root access again
-	let res = socket.send_message_query("INFO FOR ROOT").await.unwrap();
-	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["status"], "updated", "result: {res:?}");
+	assert_eq!(res["result"]["result"]["id"], id, "result: {res:?}");
+	assert_eq!(res["result"]["result"]["val"], 2, "result: {res:?}");
+	assert_eq!(res["result"]["result"]["since"], 2020, "result: {res:?}");
+	// Verify no duplicate edge was created
+	let res = socket.send_message_query("RETURN foo:a->bar.val").await.unwrap();
+	assert_eq!(res[0]["result"], json!([2]), "result: {res:?}");
+	// Send RELATE command upserting between multiple records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"relate",
+			json!([["foo:a", "foo:b"], "bar", "foo:b", null, { "upsert": true }]),
+		)
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn bulk_relate(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create records to relate
+	socket.send_message_query("CREATE foo:a, foo:b, foo:c").await.unwrap();
+	// Send BULK_RELATE command
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"bulk_relate",
+			json!([[
+				{ "from": "foo:a", "kind": "bar", "to": "foo:b", "data": { "val": 1 } },
+				{ "from": "foo:b", "kind": "bar", "to": "foo:c", "data": { "val": 2 } },
+				{ "from": "foo:a", "kind": "baz", "to": "foo:c" },
+			]]),
+		)
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
+	let res = res["result"].as_array().unwrap();
+	assert_eq!(res.len(), 3, "result: {res:?}");
+	assert_eq!(res[0]["val"], 1, "result: {res:?}");
+	assert_eq!(res[1]["val"], 2, "result: {res:?}");
+	// Send BULK_RELATE command with a malformed edge
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"bulk_relate",
+			json!([[
+				{ "from": "foo:a", "kind": "qux", "to": "foo:b" },
+				{ "from": "foo:b", "kind": "qux" },
+			]]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		"The edge at index 1 must be an object with `from`, `kind`, and `to` fields",
+		"result: {res:?}"
+	);
+	// Verify the edges were created, and the malformed batch was not
+	let res = socket
+		.send_message_query("RETURN [foo:a->bar->foo->bar.val, foo:a->baz->foo, foo:a->qux->foo]")
+		.await
+		.unwrap();
+	assert_eq!(res[0]["result"], json!([[2], ["foo:c"], []]), "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn session_reauthentication_expired(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn relate_group_by_from(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
 	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
-	// Authenticate the connection and store the root level token
-	let root_token = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
-	// Check that we have root access
-	socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Define a user record access method
-	socket
-		.send_message_query(
-			r#"
-			DEFINE ACCESS user ON DATABASE TYPE RECORD
-				SIGNUP ( CREATE user SET email = $email, pass = crypto::argon2::generate($pass) )
-				SIGNIN ( SELECT * FROM user WHERE email = $email AND crypto::argon2::compare(pass, $pass) )
-				DURATION FOR SESSION 1s, FOR TOKEN 24h
-			;"#,
+	// Create records to relate
+	socket.send_message_query("CREATE foo:a, foo:b, foo:c").await.unwrap();
+	// Send BULK_RELATE command grouped by the source records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"bulk_relate",
+			json!([
+				[
+					{ "from": "foo:a", "kind": "bar", "to": "foo:b", "data": { "val": 1 } },
+					{ "from": "foo:b", "kind": "bar", "to": "foo:c", "data": { "val": 2 } },
+					{ "from": "foo:a", "kind": "bar", "to": "foo:c", "data": { "val": 3 } },
+				],
+				{ "group_by": "from" }
+			]),
 		)
 		.await
 		.unwrap();
-	// Create resource that requires a session with the access method to query
-	socket
-		.send_message_query(
-			r#"
-			DEFINE TABLE test SCHEMALESS
-				PERMISSIONS FOR select, create, update, delete WHERE $access = "user"
-			;"#,
+	assert!(res["result"].is_object(), "result: {res:?}");
+	let res = &res["result"];
+	assert_eq!(res.as_object().unwrap().len(), 2, "result: {res:?}");
+	assert_eq!(res["foo:a"].as_array().unwrap().len(), 2, "result: {res:?}");
+	assert_eq!(res["foo:a"][0]["val"], 1, "result: {res:?}");
+	assert_eq!(res["foo:a"][1]["val"], 3, "result: {res:?}");
+	assert_eq!(res["foo:b"].as_array().unwrap().len(), 1, "result: {res:?}");
+	assert_eq!(res["foo:b"][0]["out"], "foo:c", "result: {res:?}");
+	// Send RELATE command grouped by the source records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"relate",
+			json!(["foo:c", "baz", "foo:a", null, { "group_by": "from" }]),
 		)
 		.await
 		.unwrap();
-	socket
-		.send_message_query(
-			r#"
-			CREATE test:1 SET working = "yes"
-			;"#,
+	assert_eq!(res["result"]["foo:c"][0]["out"], "foo:a", "result: {res:?}");
+	// Send RELATE command without returning the edges
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"relate",
+			json!(["foo:c", "baz", "foo:b", null, { "return": "none", "group_by": "from" }]),
 		)
 		.await
 		.unwrap();
-	// Send SIGNUP command
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Send RELATE command without grouping
 	let res = socket
-		.send_request(
-			"signup",
-			json!(
-				[{
-					"ns": NS,
-					"db": DB,
-					"ac": "user",
-					"email": "email@email.com",
-					"pass": "pass",
-				}]
-			),
-		)
-		.await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {res:?}");
-	// Verify it returns a token
-	assert!(res["result"].is_string(), "result: {res:?}");
-	let res = res["result"].as_str().unwrap();
-	assert!(res.starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzUxMiJ9"), "result: {res}");
-	// Authenticate using the token, which will expire soon
-	socket.send_request("authenticate", json!([res,])).await.unwrap();
-	// Wait two seconds for token to expire
-	tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-	// Verify that the session has expired
-	let res = socket.send_request("query", json!(["SELECT VALUE working FROM test:1",])).await;
-	assert!(res.is_ok(), "result: {res:?}");
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {res:?}");
-	let res = res.as_object().unwrap();
-	assert_eq!(
-		res["error"],
-		json!({"code": -32000, "message": "There was a problem with the database: The session has expired"})
-	);
-	// Authenticate using the root token, which has not expired yet
-	socket.send_request("authenticate", json!([root_token,])).await.unwrap();
-	// Check that we have root access and the session is not expired
-	let res = socket.send_message_query("INFO FOR ROOT").await.unwrap();
-	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+		.send_versioned_request(Some(2), "relate", json!(["foo:c", "baz", "foo:b"]))
+		.await
+		.unwrap();
+	assert!(res["result"].is_array(), "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn session_failed_reauthentication(cfg_server: Option<Format>, cfg_format: Format) {
-	// Setup database server without authentication
-	let (addr, mut server) = common::start_server_without_auth().await.unwrap();
+pub async fn edges(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
 	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
-	// Specify a namespace and database to use
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Check that we have are have a database and namespace selected
-	socket.send_message_query("INFO FOR DB").await.unwrap();
-	// Authenticate using an invalid token
-	socket.send_request("authenticate", json!(["invalid",])).await.unwrap();
-	// Check to see if we still have a namespace and database selected
-	let res = socket.send_message_query("INFO FOR DB").await.unwrap();
-	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Create the test records and edges
+	socket
+		.send_message_query(
+			"CREATE person:one, person:two, person:three; \
+			RELATE person:one->knows->person:two; \
+			RELATE person:two->likes->person:three; \
+			RELATE person:three->knows->person:two",
+		)
+		.await
+		.unwrap();
+	// Send EDGES command for a connected record
+	let res = socket.send_versioned_request(Some(2), "edges", json!(["person:two"])).await.unwrap();
+	let res = &res["result"];
+	assert_eq!(res["in"]["knows"].as_array().unwrap().len(), 2, "result: {res:?}");
+	assert_eq!(res["in"]["knows"][0]["out"], "person:two", "result: {res:?}");
+	assert_eq!(res["out"]["likes"].as_array().unwrap().len(), 1, "result: {res:?}");
+	assert_eq!(res["out"]["likes"][0]["in"], "person:two", "result: {res:?}");
+	// Send EDGES command limited by direction and edge table
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"edges",
+			json!(["person:two", { "direction": "in", "tables": ["likes"] }]),
+		)
+		.await
+		.unwrap();
+	let res = &res["result"];
+	assert_eq!(res["in"], json!({}), "result: {res:?}");
+	assert!(res.get("out").is_none(), "result: {res:?}");
+	// Send EDGES command for a record which does not exist
+	let res =
+		socket.send_versioned_request(Some(2), "edges", json!(["person:none"])).await.unwrap();
+	assert_eq!(res["result"], json!({ "in": {}, "out": {} }), "result: {res:?}");
+	// Send EDGES command with an invalid direction
+	let res = socket
+		.send_versioned_request(Some(2), "edges", json!(["person:two", { "direction": "up" }]))
+		.await
+		.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn session_use_change_database(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn traverse(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
 	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
-	// Authenticate the connection as a root level system user
-	let _ = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
-	// Check that we have root access
-	socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
-	socket.send_message_use(Some(NS), Some("original")).await.unwrap();
-	// Define a scope on the original database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test graph
 	socket
 		.send_message_query(
-			r#"
-                       DEFINE USER user ON DATABASE PASSWORD "secret" ROLES VIEWER
-                       ;"#,
+			"CREATE person:1, person:2, person:3 SET active = true; \
+			CREATE person:4 SET active = false; \
+			RELATE person:1->knows->person:2; \
+			RELATE person:2->knows->[person:3, person:4]; \
+			RELATE person:1->knows->person:3;",
 		)
 		.await
 		.unwrap();
-	// Create resource that requires an authenticated record user to query
-	socket
-		.send_message_query(
-			r#"
-                       DEFINE TABLE user SCHEMALESS
-                               PERMISSIONS FOR select, create, update, delete NONE
-                       ;"#,
+	// Send TRAVERSE command for the active friends of friends
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"traverse",
+			json!(["person:1", [{ "edge": "knows" }, { "edge": "knows", "cond": "active = true" }]]),
 		)
 		.await
 		.unwrap();
-	socket
-               .send_message_query(
-                       r#"
-                       CREATE user:1 CONTENT { name: "original", pass: crypto::argon2::generate("original") }
-                       ;"#,
-               )
-               .await
-               .unwrap();
-	// Change to a different database
-	socket.send_message_use(Some(NS), Some("different")).await.unwrap();
-	// Create the same user table with a user record with the same identifier
-	socket
-		.send_message_query(
-			r#"
-                       DEFINE TABLE user SCHEMALESS
-                               PERMISSIONS FOR select, create, update, delete NONE
-                       ;"#,
+	assert_eq!(res["result"], json!(["person:3"]), "result: {res:?}");
+	// Send TRAVERSE command following the edges in reverse
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"traverse",
+			json!(["person:3", [{ "edge": "knows", "direction": "in" }]]),
 		)
 		.await
 		.unwrap();
-	socket
-               .send_message_query(
-                       r#"
-                       CREATE user:1 CONTENT { name: "different", pass: crypto::argon2::generate("different") }
-                       ;"#,
-               )
-               .await
-               .unwrap();
-	// Sign in to original database as user
+	let mut ids: Vec<_> = res["result"].as_array().unwrap().iter().cloned().collect();
+	ids.sort_by_key(|v| v.to_string());
+	assert_eq!(ids, vec![json!("person:1"), json!("person:2")], "result: {res:?}");
+	// Check malformed hops are rejected
 	let res = socket
-		.send_request(
-			"signin",
-			json!(
-					[{
-							"ns": NS,
-							"db": "original",
-							"user": "user",
-							"pass": "secret",
-					}]
-			),
+		.send_versioned_request(
+			Some(2),
+			"traverse",
+			json!(["person:1", [{ "edge": "knows; DELETE person" }]]),
 		)
-		.await;
-	assert!(res.is_ok(), "result: {:?}", res);
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {:?}", res);
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {:?}", res);
-	// Verify that the authenticated session corresponds with the original user
-	let res = socket.send_message_query("SELECT VALUE name FROM user:1").await.unwrap();
-	assert_eq!(res[0]["result"], json!(["original"]), "result: {:?}", res);
-	// Swtich to the different database without signing in again
-	socket.send_message_use(Some(NS), Some("different")).await.unwrap();
-	// Verify that the authenticated session is unable to query data
-	let res = socket.send_message_query("SELECT VALUE name FROM user:1").await.unwrap();
-	// The query succeeds but the results does not contain the value with permissions
-	assert_eq!(res[0]["status"], "OK", "result: {:?}", res);
-	assert_eq!(res[0]["result"], json!([]), "result: {:?}", res);
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"],
+		"The hop at index 0 must be an object with an `edge` table, and an optional `direction` of `in` or `out` and `cond`",
+		"result: {res:?}"
+	);
+	let hops: Vec<_> = (0..9).map(|_| json!({ "edge": "knows" })).collect();
+	let res = socket
+		.send_versioned_request(Some(2), "traverse", json!(["person:1", hops]))
+		.await
+		.unwrap();
+	assert_eq!(
+		res["error"]["message"], "The traversal exceeds the maximum of 8 hops",
+		"result: {res:?}"
+	);
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn session_use_change_database_scope(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn pipeline(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
 	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
-	// Authenticate the connection as a root level system user
-	let _ = socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
-	// Check that we have root access
-	socket.send_message_query("INFO FOR ROOT").await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
-	socket.send_message_use(Some(NS), Some("original")).await.unwrap();
-	// Define a user record access method on the original database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create the test records
 	socket
 		.send_message_query(
-			r#"
-			DEFINE ACCESS user ON DATABASE TYPE RECORD
-				SIGNIN ( SELECT * FROM user WHERE name = $name AND crypto::argon2::compare(pass, $pass) )
-				DURATION FOR SESSION 24h, FOR TOKEN 24h
-			;"#,
+			"CREATE sale SET region = 'eu', amount = 10; \
+			CREATE sale SET region = 'eu', amount = 20; \
+			CREATE sale SET region = 'us', amount = 5; \
+			CREATE sale SET region = 'us', amount = 1; \
+			CREATE sale SET region = 'asia', amount = 7;",
 		)
 		.await
 		.unwrap();
-	// Create resource that requires an authenticated record user to query
-	socket
-		.send_message_query(
-			r#"
-			DEFINE TABLE user SCHEMALESS
-				PERMISSIONS FOR select, create, update, delete WHERE id = $auth
-			;"#,
+	// Send PIPELINE command which filters, groups, sorts, and limits the records
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"pipeline",
+			json!([
+				"sale",
+				[
+					{ "stage": "filter", "cond": "amount > 1" },
+					{ "stage": "filter", "cond": "region != 'us'" },
+					{
+						"stage": "group",
+						"by": ["region"],
+						"aggregate": {
+							"total": { "function": "sum", "field": "amount" },
+							"sales": { "function": "count" },
+						},
+					},
+					{ "stage": "sort", "order": [{ "field": "total", "direction": "desc" }] },
+					{ "stage": "limit", "count": 1 },
+				]
+			]),
 		)
 		.await
 		.unwrap();
-	socket
-		.send_message_query(
-			r#"
-			CREATE user:1 CONTENT { name: "original", pass: crypto::argon2::generate("original") }
-			;"#,
+	assert_eq!(
+		res["result"],
+		json!([{ "region": "eu", "total": 30, "sales": 2 }]),
+		"result: {res:?}"
+	);
+	// Send PIPELINE command with only a limit
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"pipeline",
+			json!(["sale", [{ "stage": "limit", "count": 2, "start": 4 }]]),
 		)
 		.await
 		.unwrap();
-	// Change to a different database
-	socket.send_message_use(Some(NS), Some("different")).await.unwrap();
-	// Create the same user table with a user record with the same identifier
-	socket
-		.send_message_query(
-			r#"
-			DEFINE TABLE user SCHEMALESS
-				PERMISSIONS FOR select, create, update, delete WHERE id = $auth
-			;"#,
+	assert_eq!(res["result"].as_array().unwrap().len(), 1, "result: {res:?}");
+	// Check stages out of order are rejected
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"pipeline",
+			json!([
+				"sale",
+				[
+					{ "stage": "sort", "order": ["amount"] },
+					{ "stage": "filter", "cond": "amount > 1" },
+				]
+			]),
 		)
 		.await
 		.unwrap();
-	socket
-		.send_message_query(
-			r#"
-			CREATE user:1 CONTENT { name: "different", pass: crypto::argon2::generate("different") }
-			;"#,
+	assert_eq!(
+		res["error"]["message"],
+		"The pipeline stage at index 1 is invalid, as the stages must appear in the order `filter`, `group`, `sort`, and then `limit`",
+		"result: {res:?}"
+	);
+	// Check malformed stages are rejected
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"pipeline",
+			json!([
+				"sale",
+				[
+					{ "stage": "filter", "cond": "amount > 1" },
+					{ "stage": "group", "aggregate": { "total": { "function": "median" } } },
+				]
+			]),
 		)
 		.await
 		.unwrap();
-	// Sign in to original database as user
+	assert_eq!(
+		res["error"]["message"],
+		"The pipeline stage at index 1 is invalid, as each aggregate must have a `function` of `count`, `sum`, `avg`, `min`, or `max`, and a `field` unless counting",
+		"result: {res:?}"
+	);
 	let res = socket
-		.send_request(
-			"signin",
-			json!(
-				[{
-					"ns": NS,
-					"db": "original",
-					"ac": "user",
-					"name": "original",
-					"pass": "original",
-				}]
-			),
-		)
-		.await;
-	assert!(res.is_ok(), "result: {:?}", res);
-	let res = res.unwrap();
-	assert!(res.is_object(), "result: {:?}", res);
-	let res = res.as_object().unwrap();
-	// Verify response contains no error
-	assert!(res.keys().all(|k| ["id", "result"].contains(&k.as_str())), "result: {:?}", res);
-	// Verify that the authenticated session corresponds with the original user
-	let res = socket.send_message_query("SELECT VALUE name FROM $auth").await.unwrap();
-	assert_eq!(res[0]["result"], json!(["original"]), "result: {:?}", res);
-	// Swtich to the different database without signing in again
-	socket.send_message_use(Some(NS), Some("different")).await.unwrap();
-	// Verify that the authenticated session is unable to query data
-	let res = socket.send_message_query("SELECT VALUE name FROM $auth").await.unwrap();
-	// The following statement would be true when the bug was present:
-	// assert_eq!(res[0]["result"], json!(["different"]), "result: {:?}", res);
-	assert_eq!(res[0]["status"], "ERR", "result: {:?}", res);
+		.send_versioned_request(Some(2), "pipeline", json!(["sale", [{ "stage": "project" }]]))
+		.await
+		.unwrap();
 	assert_eq!(
-		res[0]["result"], "You don't have permission to change to the different database",
-		"result: {:?}",
-		res
+		res["error"]["message"],
+		"The pipeline stage at index 0 is invalid, as its `stage` must be one of `filter`, `group`, `sort`, or `limit`",
+		"result: {res:?}"
 	);
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn run_functions(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn duplicate(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -1800,43 +7094,100 @@ pub async fn run_functions(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// Define function
-	socket
-		.send_message_query("DEFINE FUNCTION fn::foo() {RETURN 'fn::foo called';}")
+	// Create the record to copy
+	let res = socket.send_message_query("CREATE foo:a SET name = 'a', val = 1").await.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	// Copy the record into the same table
+	let res = socket.send_versioned_request(Some(2), "duplicate", json!(["foo:a"])).await.unwrap();
+	let id = res["result"]["id"].as_str().unwrap();
+	assert!(id.starts_with("foo:") && id != "foo:a", "result: {res:?}");
+	assert_eq!(res["result"]["name"], "a", "result: {res:?}");
+	assert_eq!(res["result"]["val"], 1, "result: {res:?}");
+	// Copy the record into another table
+	let res =
+		socket.send_versioned_request(Some(2), "duplicate", json!(["foo:a", "bar"])).await.unwrap();
+	assert!(res["result"]["id"].as_str().unwrap().starts_with("bar:"), "result: {res:?}");
+	assert_eq!(res["result"]["name"], "a", "result: {res:?}");
+	// Copy the record to a record id, overriding some fields
+	let res = socket
+		.send_versioned_request(Some(2), "duplicate", json!(["foo:a", "bar:copy", { "val": 2 }]))
+		.await
+		.unwrap();
+	assert_eq!(res["result"]["id"], "bar:copy", "result: {res:?}");
+	assert_eq!(res["result"]["name"], "a", "result: {res:?}");
+	assert_eq!(res["result"]["val"], 2, "result: {res:?}");
+	// Copy the record to a record id which already exists
+	let res = socket
+		.send_versioned_request(Some(2), "duplicate", json!(["foo:a", "bar:copy"]))
 		.await
 		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Check that the original record was not changed
+	let res = socket.send_message_query("RETURN foo:a.*").await.unwrap();
+	assert_eq!(
+		res[0]["result"],
+		json!({ "id": "foo:a", "name": "a", "val": 1 }),
+		"result: {res:?}"
+	);
+	// Copy a record which does not exist
+	let res =
+		socket.send_versioned_request(Some(2), "duplicate", json!(["foo:missing"])).await.unwrap();
+	let msg = res["error"]["message"].as_str().unwrap();
+	assert!(msg.contains("The record 'foo:missing' does not exist"), "result: {res:?}");
+	let res = socket.send_message_query("SELECT * FROM foo").await.unwrap();
+	assert_eq!(res[0]["result"].as_array().unwrap().len(), 2, "result: {res:?}");
+	// Copy a value which is not a record
+	let res = socket.send_versioned_request(Some(2), "duplicate", json!([1])).await.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
+
+pub async fn move_record(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Create related records
 	socket
 		.send_message_query(
-			"DEFINE FUNCTION fn::bar($val: string) {RETURN 'fn::bar called with: ' + $val;}",
+			"CREATE foo:a SET val = 1; CREATE foo:b, baz:b; RELATE foo:a->bar:1->foo:b SET val = 2; RELATE foo:b->bar:2->foo:a",
 		)
 		.await
 		.unwrap();
-	// call functions
-	let res = socket.send_message_run("fn::foo", None, vec![]).await.unwrap();
-	assert!(matches!(res, serde_json::Value::String(s) if &s == "fn::foo called"));
-	let res = socket.send_message_run("fn::bar", None, vec![]).await;
-	assert!(res.is_err());
-	let res = socket.send_message_run("fn::bar", None, vec![42.into()]).await;
-	assert!(res.is_err());
-	let res = socket.send_message_run("fn::bar", None, vec!["first".into(), "second".into()]).await;
-	assert!(res.is_err());
-	let res = socket.send_message_run("fn::bar", None, vec!["string_val".into()]).await.unwrap();
-	assert!(matches!(res, serde_json::Value::String(s) if &s == "fn::bar called with: string_val"));
-
-	// normal functions
-	let res = socket.send_message_run("math::abs", None, vec![42.into()]).await.unwrap();
-	assert!(matches!(res, serde_json::Value::Number(n) if n.as_u64() == Some(42)));
+	// Move the record into a new table, keeping its id and edges
 	let res = socket
-		.send_message_run("math::max", None, vec![vec![1, 2, 3, 4, 5, 6].into()])
+		.send_versioned_request(Some(2), "move", json!(["foo:a", "baz", { "edges": true }]))
 		.await
 		.unwrap();
-	assert!(matches!(res, serde_json::Value::Number(n) if n.as_u64() == Some(6)));
-
+	assert_eq!(res["result"]["id"], "baz:a", "result: {res:?}");
+	assert_eq!(res["result"]["val"], 1, "result: {res:?}");
+	// Verify the original record was removed
+	let res = socket.send_message_query("RETURN foo:a.*").await.unwrap();
+	assert_eq!(res[0]["result"], json!(null), "result: {res:?}");
+	// Verify the edges point at the moved record
+	let res = socket.send_message_query("RETURN [baz:a->bar.val, baz:a<-bar.id]").await.unwrap();
+	assert_eq!(res[0]["result"], json!([[2], ["bar:2"]]), "result: {res:?}");
+	// Move the record to a conflicting record id
+	let res =
+		socket.send_versioned_request(Some(2), "move", json!(["baz:a", "baz:b"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
+	// Verify the record was not moved
+	let res = socket.send_message_query("RETURN baz:a.val").await.unwrap();
+	assert_eq!(res[0]["result"], 1, "result: {res:?}");
+	// Move a record which does not exist
+	let res =
+		socket.send_versioned_request(Some(2), "move", json!(["foo:c", "baz"])).await.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
 
-pub async fn relate_rpc(cfg_server: Option<Format>, cfg_format: Format) {
+pub async fn changed(cfg_server: Option<Format>, cfg_format: Format) {
 	// Setup database server
 	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
 	// Connect to WebSocket
@@ -1845,22 +7196,102 @@ pub async fn relate_rpc(cfg_server: Option<Format>, cfg_format: Format) {
 	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
 	// Specify a namespace and database
 	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
-	// create records and relate
-	socket.send_message_query("CREATE foo:a, foo:b").await.unwrap();
-	socket
-		.send_message_relate("foo:a".into(), "bar".into(), "foo:b".into(), Some(json!({"val": 42})))
+	// Change some records several times
+	let res = socket
+		.send_message_query(
+			"DEFINE TABLE tester CHANGEFEED 1h;
+			CREATE tester:1 SET v = 1;
+			CREATE tester:2 SET v = 1;
+			UPDATE tester:1 SET v = 2;
+			DELETE tester:2;
+			CREATE tester:3 SET v = 1;
+			DELETE tester:3;
+			CREATE tester:3 SET v = 3;",
+		)
 		.await
 		.unwrap();
-	// test
-
-	let mut res = socket.send_message_query("RETURN foo:a->bar.val").await.unwrap();
-	let expected = json!([42]);
-	assert_eq!(res.remove(0)["result"], expected);
-
-	let mut res = socket.send_message_query("RETURN foo:a->bar->foo").await.unwrap();
-	let expected = json!(["foo:b"]);
-	assert_eq!(res.remove(0)["result"], expected);
+	assert!(res.iter().all(|v| v["status"] == "OK"), "result: {res:?}");
+	// Send CHANGED command
+	let res =
+		socket.send_versioned_request(Some(2), "changed", json!(["tester", 0])).await.unwrap();
+	// Only the latest state of each record is returned, with tombstones for deleted records
+	assert_eq!(
+		res["result"],
+		json!([
+			{ "id": "tester:1", "v": 2 },
+			{ "id": "tester:2", "deleted": true },
+			{ "id": "tester:3", "v": 3 },
+		]),
+		"result: {res:?}"
+	);
+	// Send CHANGED command for a table without changes
+	let res = socket.send_message_query("DEFINE TABLE other CHANGEFEED 1h").await.unwrap();
+	assert_eq!(res[0]["status"], "OK", "result: {res:?}");
+	let res = socket.send_versioned_request(Some(2), "changed", json!(["other", 0])).await.unwrap();
+	assert_eq!(res["result"], json!([]), "result: {res:?}");
+	// Send CHANGED command with invalid arguments
+	let res =
+		socket.send_versioned_request(Some(2), "changed", json!(["tester", -1])).await.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	let res =
+		socket.send_versioned_request(Some(2), "changed", json!(["tester", 0, 0])).await.unwrap();
+	assert_eq!(res["error"]["code"], -32602, "result: {res:?}");
+	// Test passed
+	server.finish().unwrap();
+}
 
+pub async fn diff(cfg_server: Option<Format>, cfg_format: Format) {
+	// Setup database server
+	let (addr, mut server) = common::start_server_with_defaults().await.unwrap();
+	// Connect to WebSocket
+	let mut socket = Socket::connect(&addr, cfg_server, cfg_format).await.unwrap();
+	// Authenticate the connection
+	socket.send_message_signin(USER, PASS, None, None, None).await.unwrap();
+	// Specify a namespace and database
+	socket.send_message_use(Some(NS), Some(DB)).await.unwrap();
+	// Diff two objects
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"diff",
+			json!([{ "name": "Tobie", "age": 30 }, { "name": "Tobie", "age": 31, "admin": true }]),
+		)
+		.await
+		.unwrap();
+	let ops = res["result"].as_array().unwrap();
+	assert_eq!(ops.len(), 2, "result: {res:?}");
+	assert!(
+		ops.contains(&json!({ "op": "replace", "path": "/age", "value": 31 })),
+		"result: {res:?}"
+	);
+	assert!(
+		ops.contains(&json!({ "op": "add", "path": "/admin", "value": true })),
+		"result: {res:?}"
+	);
+	// Diff the current state of a record with an object
+	socket.send_message_query("CREATE tester:1 SET name = 'Tobie', age = 30").await.unwrap();
+	let res = socket
+		.send_versioned_request(
+			Some(2),
+			"diff",
+			json!(["tester:1", { "name": "Jaime", "age": 30 }]),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		res["result"],
+		json!([{ "op": "replace", "path": "/name", "value": "Jaime" }]),
+		"result: {res:?}"
+	);
+	// Verify the record was not changed
+	let res = socket.send_message_query("RETURN tester:1.name").await.unwrap();
+	assert_eq!(res[0]["result"], "Tobie", "result: {res:?}");
+	// Diff a record which does not exist
+	let res = socket
+		.send_versioned_request(Some(2), "diff", json!(["tester:2", { "name": "Jaime" }]))
+		.await
+		.unwrap();
+	assert!(res["error"].is_object(), "result: {res:?}");
 	// Test passed
 	server.finish().unwrap();
 }
@@ -2255,6 +7686,8 @@ define_include_tests! {
 	#[test_log::test(tokio::test)]
 	info,
 	#[test_log::test(tokio::test)]
+	footprint,
+	#[test_log::test(tokio::test)]
 	signup,
 	#[test_log::test(tokio::test)]
 	signin,
@@ -2267,22 +7700,166 @@ define_include_tests! {
 	#[test_log::test(tokio::test)]
 	unset,
 	#[test_log::test(tokio::test)]
+	vars,
+	#[test_log::test(tokio::test)]
 	select,
 	#[test_log::test(tokio::test)]
+	select_compact,
+	#[test_log::test(tokio::test)]
+	select_flatten,
+	#[test_log::test(tokio::test)]
+	select_with_total,
+	#[test_log::test(tokio::test)]
+	select_paginate,
+	#[test_log::test(tokio::test)]
+	select_max_results,
+	#[test_log::test(tokio::test)]
+	select_distinct,
+	#[test_log::test(tokio::test)]
+	select_columns,
+	#[test_log::test(tokio::test)]
+	select_omit,
+	#[test_log::test(tokio::test)]
+	select_order,
+	#[test_log::test(tokio::test)]
+	select_by_id,
+	#[test_log::test(tokio::test)]
+	select_partition_by,
+	#[test_log::test(tokio::test)]
+	select_with_checksum,
+	#[test_log::test(tokio::test)]
+	select_expect,
+	#[test_log::test(tokio::test)]
+	select_alias,
+	#[test_log::test(tokio::test)]
+	select_sensitive,
+	#[test_log::test(tokio::test)]
+	select_computed,
+	#[test_log::test(tokio::test)]
+	select_fetch_cycle,
+	#[test_log::test(tokio::test)]
+	exists_many,
+	#[test_log::test(tokio::test)]
+	search,
+	#[test_log::test(tokio::test)]
+	permissions,
+	#[test_log::test(tokio::test)]
+	field_types,
+	#[test_log::test(tokio::test)]
+	view,
+	#[test_log::test(tokio::test)]
+	maintain,
+	#[test_log::test(tokio::test)]
+	temp_table,
+	#[test_log::test(tokio::test)]
+	migrate,
+	#[test_log::test(tokio::test)]
+	schema_hash,
+	#[test_log::test(tokio::test)]
+	export_schema,
+	#[test_log::test(tokio::test)]
+	indexes,
+	#[test_log::test(tokio::test)]
+	count,
+	#[test_log::test(tokio::test)]
+	aggregate,
+	#[test_log::test(tokio::test)]
 	insert,
 	#[test_log::test(tokio::test)]
+	insert_v2,
+	#[test_log::test(tokio::test)]
 	create,
 	#[test_log::test(tokio::test)]
+	create_defaults,
+	#[test_log::test(tokio::test)]
+	create_get_existing,
+	#[test_log::test(tokio::test)]
+	insert_get_existing,
+	#[test_log::test(tokio::test)]
+	insert_row_status,
+	#[test_log::test(tokio::test)]
+	echo_sql,
+	#[test_log::test(tokio::test)]
+	write_versionstamp,
+	#[test_log::test(tokio::test)]
+	read_min_version,
+	#[test_log::test(tokio::test)]
+	query_as_of,
+	#[test_log::test(tokio::test)]
 	update,
 	#[test_log::test(tokio::test)]
+	append,
+	#[test_log::test(tokio::test)]
+	update_changeset,
+	#[test_log::test(tokio::test)]
+	update_return_value,
+	#[test_log::test(tokio::test)]
+	update_only_null,
+	#[test_log::test(tokio::test)]
+	swap,
+	#[test_log::test(tokio::test)]
+	upsert_split,
+	#[test_log::test(tokio::test)]
 	merge,
 	#[test_log::test(tokio::test)]
 	patch,
 	#[test_log::test(tokio::test)]
 	delete,
 	#[test_log::test(tokio::test)]
+	delete_if_match,
+	#[test_log::test(tokio::test)]
 	query,
 	#[test_log::test(tokio::test)]
+	query_preview,
+	#[test_log::test(tokio::test)]
+	query_statement_types,
+	#[test_log::test(tokio::test)]
+	query_max_depth,
+	#[test_log::test(tokio::test)]
+	query_non_finite,
+	#[test_log::test(tokio::test)]
+	record_id_format,
+	#[test_log::test(tokio::test)]
+	explicit_nulls,
+	#[test_log::test(tokio::test)]
+	with_types,
+	#[test_log::test(tokio::test)]
+	query_with,
+	#[test_log::test(tokio::test)]
+	query_parser_flags,
+	#[test_log::test(tokio::test)]
+	query_seed,
+	#[test_log::test(tokio::test)]
+	request_tag,
+	#[test_log::test(tokio::test)]
+	query_explain_if_slower_than,
+	#[test_log::test(tokio::test)]
+	query_fail_fast,
+	#[test_log::test(tokio::test)]
+	query_debug_vars,
+	#[test_log::test(tokio::test)]
+	query_timing,
+	#[test_log::test(tokio::test)]
+	query_stream,
+	#[test_log::test(tokio::test)]
+	race,
+	#[test_log::test(tokio::test)]
+	query_typed_params,
+	#[test_log::test(tokio::test)]
+	prepared_statements,
+	#[test_log::test(tokio::test)]
+	prepared_statements_batch,
+	#[test_log::test(tokio::test)]
+	table_scan,
+	#[test_log::test(tokio::test)]
+	key_range,
+	#[test_log::test(tokio::test)]
+	query_log,
+	#[test_log::test(tokio::test)]
+	webhooks,
+	#[test_log::test(tokio::test)]
+	batch,
+	#[test_log::test(tokio::test)]
 	version,
 	#[test_log::test(tokio::test)]
 	concurrency,
@@ -2291,8 +7868,28 @@ define_include_tests! {
 	#[test_log::test(tokio::test)]
 	live_rpc,
 	#[test_log::test(tokio::test)]
+	timezone,
+	#[test_log::test(tokio::test)]
+	locale,
+	#[test_log::test(tokio::test)]
 	kill,
 	#[test_log::test(tokio::test)]
+	is_live,
+	#[test_log::test(tokio::test)]
+	kill_orphans,
+	#[test_log::test(tokio::test)]
+	live_token,
+	#[test_log::test(tokio::test)]
+	live_aggregate,
+	#[test_log::test(tokio::test)]
+	live_debounce,
+	#[test_log::test(tokio::test)]
+	live_stats,
+	#[test_log::test(tokio::test)]
+	live_query_limit,
+	#[test_log::test(tokio::test)]
+	live_replay,
+	#[test_log::test(tokio::test)]
 	live_second_connection,
 	#[test_log::test(tokio::test)]
 	variable_auth_live_query,
@@ -2313,8 +7910,34 @@ define_include_tests! {
 	#[test_log::test(tokio::test)]
 	run_functions,
 	#[test_log::test(tokio::test)]
+	eval,
+	#[test_log::test(tokio::test)]
+	check_permission,
+	#[test_log::test(tokio::test)]
 	relate_rpc,
 	#[test_log::test(tokio::test)]
+	relate_upsert,
+	#[test_log::test(tokio::test)]
+	relate_fetch,
+	#[test_log::test(tokio::test)]
+	bulk_relate,
+	#[test_log::test(tokio::test)]
+	relate_group_by_from,
+	#[test_log::test(tokio::test)]
+	edges,
+	#[test_log::test(tokio::test)]
+	traverse,
+	#[test_log::test(tokio::test)]
+	pipeline,
+	#[test_log::test(tokio::test)]
+	duplicate,
+	#[test_log::test(tokio::test)]
+	move_record,
+	#[test_log::test(tokio::test)]
+	changed,
+	#[test_log::test(tokio::test)]
+	diff,
+	#[test_log::test(tokio::test)]
 	temporary_directory,
 	#[test_log::test(tokio::test)]
 	session_id_defined,